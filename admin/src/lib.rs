@@ -0,0 +1,3 @@
+// The `seed` module.
+// This module generates realistic forum data for local development and load testing.
+pub mod seed;
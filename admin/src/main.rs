@@ -0,0 +1,372 @@
+// Importing the necessary modules and functions.
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::Arc;
+use anyhow::{bail, Context};
+use axum::Extension;
+use prisma_client_rust::chrono::Utc;
+use prisma_client_rust::raw;
+use wforum_backend::cache::Cache;
+use wforum_backend::config::config::BeConfig;
+use wforum_backend::error::EError;
+use wforum_backend::extractor::validate::{Validate, Validator};
+use wforum_backend::prisma::prisma::{
+    platform_posts, post_comments, user_details, user_password, PrismaClient, Role,
+};
+use wforum_backend::repository::{PrismaRepository, TokenRepository, UserRepository};
+use wforum_backend::service::user::model::CreateUserPost;
+use wforum_backend::service::user::service::UsersService;
+use wforum_admin::seed::{self, SeedConfig};
+
+
+// The usage printed for `--help` and invalid command lines.
+const USAGE: &str = "\
+Usage: wforum-admin [--config <path>] <command>
+
+Commands:
+  create-admin <email> <username>   Create an administrator, reading the password from standard input
+  reset-password <username>         Set a user's password, reading it from standard input
+  suspend <username>                Stop a user from logging in and revoke their API tokens
+  unsuspend <username>              Allow a suspended user to log in again
+  delete-user <username>... --yes   Delete users with their posts, comments, likes and follows
+  rebuild-counters                  Recount the likes of every post
+  stats                             Print the number of users, posts, comments, likes and follows
+  seed [options]                    Fill the database with generated users, posts and activity
+
+Seed options, with their defaults:
+  --seed 1          The same seed generates the same data
+  --users 200       --posts 1000      --comments 5000   --likes 10000
+  --follows 2000    --blocks 50       --history 20000   --days 90
+  --password seed   The password of every generated user";
+
+
+// The `Admin` struct which holds what the commands need.
+struct Admin {
+    // The loaded configuration.
+    config: BeConfig,
+    // The Prisma client, wrapped like the backend's handlers receive it.
+    prisma: Extension<Arc<PrismaClient>>,
+    // The response cache, so changed users are not served stale.
+    cache: Cache,
+}
+
+
+// The main function of the command line.
+#[tokio::main]
+async fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.is_empty() || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    // `--config <path>` selects the config file, like it does for the backend.
+    if let Some(index) = args.iter().position(|arg| arg == "--config") {
+        if index + 1 >= args.len() {
+            usage("--config needs a path");
+        }
+        std::env::set_var("WFORUM_CONFIG", args.remove(index + 1));
+        args.remove(index);
+    }
+
+    let config = match BeConfig::load() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(error) = run(config, args).await {
+        match error.downcast_ref::<EError>() {
+            Some(EError::Validation(errors)) => {
+                eprintln!("Invalid input:");
+                for error in errors {
+                    eprintln!("  - {}: {}", error.field, error.message);
+                }
+            }
+            _ => eprintln!("Error: {:#}", error),
+        }
+        std::process::exit(1);
+    }
+}
+
+
+// Function to print a usage error and exit.
+fn usage(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    std::process::exit(2);
+}
+
+// Function to connect to the database and run a command.
+// It takes the configuration and the command line without the options as parameters.
+async fn run(config: BeConfig, args: Vec<String>) -> anyhow::Result<()> {
+    let (command, args) = args.split_first().unwrap_or_else(|| usage("missing command"));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let prisma = PrismaClient::_builder()
+        .with_url(config.database_url.clone())
+        .build()
+        .await
+        .context("failed to connect to the database")?;
+    let cache = Cache::new(&config.cache_config);
+    let admin = Admin { config, prisma: Extension(Arc::new(prisma)), cache };
+
+    match (command.as_str(), args.as_slice()) {
+        ("create-admin", [email, username]) => admin.create_admin(email, username).await,
+        ("reset-password", [username]) => admin.reset_password(username).await,
+        ("suspend", [username]) => admin.suspend(username, true).await,
+        ("unsuspend", [username]) => admin.suspend(username, false).await,
+        ("delete-user", args) if args.iter().any(|arg| *arg != "--yes") => {
+            let usernames: Vec<&str> = args.iter().copied().filter(|arg| *arg != "--yes").collect();
+            admin.delete_users(&usernames, args.contains(&"--yes")).await
+        }
+        ("rebuild-counters", []) => admin.rebuild_counters().await,
+        ("stats", []) => admin.stats().await,
+        ("seed", options) => {
+            let (config, password) = seed_options(options).unwrap_or_else(|message| usage(&message));
+            admin.seed(&config, &password).await
+        }
+        ("create-admin" | "reset-password" | "suspend" | "unsuspend" | "delete-user" | "rebuild-counters"
+            | "stats", _) => usage(&format!("wrong arguments for {}", command)),
+        _ => usage(&format!("unknown command {}", command)),
+    }
+}
+
+
+// Implementation of the `Admin` struct.
+impl Admin {
+
+    // Function to create a user with the administrator role.
+    // The password is read from standard input so it stays out of the shell history,
+    // and every field is validated like a signup.
+    async fn create_admin(&self, email: &str, username: &str) -> anyhow::Result<()> {
+        let input = CreateUserPost {
+            email: email.to_string(),
+            username: username.to_string(),
+            password: read_password()?,
+        };
+        let mut validator = Validator::new(&self.config.validation_config);
+        input.validate(&mut validator);
+        validator.finish()?;
+
+        let user_data = self.prisma
+            .user_details()
+            .create(input.email, input.username, vec![user_details::role::set(Role::Admin)])
+            .exec().await?;
+
+        self.prisma
+            .user_password()
+            .create(
+                UsersService::hash_password(&input.password)?,
+                user_details::user_id::equals(user_data.user_id),
+                vec![],
+            )
+            .exec().await?;
+
+        println!("Created administrator {} with ID {}", user_data.username, user_data.user_id);
+        Ok(())
+    }
+
+    // Function to set a user's password, read from standard input.
+    // Users who only signed in through an identity provider get a password this way too.
+    async fn reset_password(&self, username: &str) -> anyhow::Result<()> {
+        let user_data = self.user(username).await?;
+
+        let password = read_password()?;
+        let mut validator = Validator::new(&self.config.validation_config);
+        let limits = validator.limits();
+        validator.length("password", &password, limits.password_min, limits.password_max);
+        validator.finish()?;
+
+        let hash = UsersService::hash_password(&password)?;
+        self.prisma
+            .user_password()
+            .upsert(
+                user_password::user_id::equals(user_data.user_id),
+                user_password::create(hash.clone(), user_details::user_id::equals(user_data.user_id), vec![]),
+                vec![user_password::hash_password::set(hash)],
+            )
+            .exec().await?;
+
+        println!("Reset the password of {}", user_data.username);
+        Ok(())
+    }
+
+    // Function to suspend or unsuspend a user.
+    // Suspended users can't log in and lose their API tokens,
+    // and the JWTs already issued to them are refused from their next request.
+    async fn suspend(&self, username: &str, suspend: bool) -> anyhow::Result<()> {
+        let user_data = self.user(username).await?;
+        let repository = PrismaRepository::new(self.prisma.clone());
+
+        let suspended_at = suspend.then(|| Utc::now().fixed_offset());
+        repository.set_suspended(user_data.user_id, suspended_at).await?;
+
+        if suspend {
+            repository.delete_api_tokens(user_data.user_id).await?;
+            println!("Suspended {}", user_data.username);
+        } else {
+            println!("Unsuspended {}", user_data.username);
+        }
+        Ok(())
+    }
+
+    // Function to delete users with everything they created.
+    // Without `--yes` it only prints what would be deleted.
+    async fn delete_users(&self, usernames: &[&str], confirmed: bool) -> anyhow::Result<()> {
+        let mut users = Vec::new();
+        for username in usernames {
+            users.push(self.user(username).await?);
+        }
+
+        for user_data in users {
+            if !confirmed {
+                let posts = self.prisma
+                    .platform_posts()
+                    .count(vec![platform_posts::author_id::equals(user_data.user_id)])
+                    .exec().await?;
+                let comments = self.prisma
+                    .post_comments()
+                    .count(vec![post_comments::user_id::equals(user_data.user_id)])
+                    .exec().await?;
+                println!(
+                    "Would delete {} with {} posts and {} comments", user_data.username, posts, comments,
+                );
+                continue;
+            }
+
+            let (user_id, username) = (user_data.user_id, user_data.username.as_str());
+            let repository = PrismaRepository::new(self.prisma.clone());
+            UsersService::delete_account(&repository, &self.cache, user_id, username).await?;
+            println!("Deleted {}", user_data.username);
+        }
+
+        if !confirmed {
+            println!("Pass --yes to delete them");
+        }
+        Ok(())
+    }
+
+    // Function to recount the likes of every post from the likes table.
+    // Counts drift when likes are removed outside the API, such as by editing the database by hand.
+    async fn rebuild_counters(&self) -> anyhow::Result<()> {
+        let updated = self.prisma
+            ._execute_raw(raw!(
+                r#"UPDATE "PlatformPosts" p
+                   SET "likeCount" = likes.count
+                   FROM (
+                       SELECT p2."postId", COUNT(l."postId") AS count
+                       FROM "PlatformPosts" p2 LEFT JOIN "UserLikePosts" l ON l."postId" = p2."postId"
+                       GROUP BY p2."postId"
+                   ) likes
+                   WHERE likes."postId" = p."postId" AND p."likeCount" <> likes.count"#
+            ))
+            .exec().await?;
+
+        println!("Fixed the like count of {} posts", updated);
+        Ok(())
+    }
+
+    // Function to print how many rows the main tables hold.
+    async fn stats(&self) -> anyhow::Result<()> {
+        let prisma = &self.prisma;
+        let admins = vec![user_details::role::equals(Role::Admin)];
+        let suspended = vec![user_details::suspended_at::not(None)];
+        let rows = [
+            ("Users", prisma.user_details().count(vec![]).exec().await?),
+            ("Administrators", prisma.user_details().count(admins).exec().await?),
+            ("Suspended users", prisma.user_details().count(suspended).exec().await?),
+            ("Posts", prisma.platform_posts().count(vec![]).exec().await?),
+            ("Comments", prisma.post_comments().count(vec![]).exec().await?),
+            ("Likes", prisma.user_like_posts().count(vec![]).exec().await?),
+            ("Follows", prisma.user_follows().count(vec![]).exec().await?),
+            ("Blocks", prisma.user_blocks().count(vec![]).exec().await?),
+            ("API tokens", prisma.user_api_tokens().count(vec![]).exec().await?),
+        ];
+
+        for (name, count) in rows {
+            println!("{:<16} {}", name, count);
+        }
+        Ok(())
+    }
+
+    // Function to fill the database with generated data.
+    async fn seed(&self, config: &SeedConfig, password: &str) -> anyhow::Result<()> {
+        let plan = seed::plan(config);
+        seed::insert(&self.prisma, &plan, &UsersService::hash_password(password)?).await?;
+
+        println!(
+            "Seeded {} users, {} posts, {} comments, {} likes, {} follows, {} blocks and {} history entries",
+            plan.users.len(), plan.posts.len(), plan.comments.len(), plan.likes.len(),
+            plan.follows.len(), plan.blocks.len(), plan.history.len(),
+        );
+        Ok(())
+    }
+
+    // Function to look up a user by their username.
+    async fn user(&self, username: &str) -> anyhow::Result<user_details::Data> {
+        let user_data = self.prisma
+            .user_details()
+            .find_unique(user_details::username::equals(username.to_string()))
+            .exec().await?;
+
+        match user_data {
+            Some(user_data) => Ok(user_data),
+            None => bail!("no user is named {}", username),
+        }
+    }
+}
+
+
+// Function to read the options of the `seed` command.
+// It returns the seed configuration and the password, or a usage error.
+fn seed_options(options: &[&str]) -> Result<(SeedConfig, String), String> {
+    let mut config = SeedConfig {
+        seed: 1,
+        users: 200,
+        posts: 1000,
+        comments: 5000,
+        likes: 10000,
+        follows: 2000,
+        blocks: 50,
+        history: 20000,
+        days: 90,
+    };
+    let mut password = String::from("seed");
+
+    for option in options.chunks(2) {
+        let [name, value] = option else {
+            return Err(format!("{} needs a value", option[0]));
+        };
+        let number = || value.parse().map_err(|_| format!("{} needs a number, not {}", name, value));
+        match *name {
+            "--seed" => config.seed = number()?,
+            "--users" => config.users = number()? as usize,
+            "--posts" => config.posts = number()? as usize,
+            "--comments" => config.comments = number()? as usize,
+            "--likes" => config.likes = number()? as usize,
+            "--follows" => config.follows = number()? as usize,
+            "--blocks" => config.blocks = number()? as usize,
+            "--history" => config.history = number()? as usize,
+            "--days" => config.days = number()? as i64,
+            "--password" => password = value.to_string(),
+            _ => return Err(format!("unknown seed option {}", name)),
+        }
+    }
+
+    Ok((config, password))
+}
+
+// Function to read a password from the first line of standard input, prompting when it is a terminal.
+fn read_password() -> anyhow::Result<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Password: ");
+        std::io::stderr().flush()?;
+    }
+
+    let mut password = String::new();
+    stdin.lock().read_line(&mut password).context("failed to read the password")?;
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,409 @@
+// Importing the necessary modules and functions.
+use std::collections::HashSet;
+use anyhow::bail;
+use prisma_client_rust::chrono::{Duration, Utc};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use wforum_backend::prisma::prisma::{
+    platform_posts, post_comments, user_blocks, user_details, user_follows, user_history, user_like_posts,
+    user_password, PrismaClient,
+};
+
+
+// The exponent of the power law popularity follows; the `n`th most popular item gets `1 / n^ZIPF_EXPONENT`.
+const ZIPF_EXPONENT: f64 = 1.1;
+
+// The number of rows inserted by one statement, well below PostgreSQL's limit of bind parameters.
+const CHUNK: usize = 1000;
+
+// The words titles, intros, comments and posts are made of.
+const WORDS: [&str; 48] = [
+    "rust", "forum", "async", "query", "server", "client", "thread", "memory", "cache", "index",
+    "release", "feature", "bug", "patch", "review", "design", "test", "deploy", "config", "schema",
+    "token", "login", "profile", "comment", "post", "feed", "latency", "metric", "trace", "error",
+    "simple", "fast", "small", "new", "old", "better", "strange", "useful", "quick", "careful",
+    "why", "how", "when", "today", "finally", "again", "really", "maybe",
+];
+
+// The names usernames start with.
+const NAMES: [&str; 16] = [
+    "alice", "bob", "carol", "dave", "erin", "frank", "grace", "heidi",
+    "ivan", "judy", "mallory", "niaj", "olivia", "peggy", "rupert", "sybil",
+];
+
+
+// The `SeedConfig` struct which represents how much data to generate.
+#[derive(Debug, Clone)]
+pub struct SeedConfig {
+    // The seed of the random generator; the same seed gives the same data.
+    pub seed: u64,
+    // The number of users.
+    pub users: usize,
+    // The number of posts.
+    pub posts: usize,
+    // The number of comments.
+    pub comments: usize,
+    // The number of likes.
+    pub likes: usize,
+    // The number of follows.
+    pub follows: usize,
+    // The number of blocks.
+    pub blocks: usize,
+    // The number of history entries.
+    pub history: usize,
+    // The number of days the creation dates are spread over.
+    pub days: i64,
+}
+
+// The `Plan` struct which represents the generated data, referring to users and posts by their index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    // The users to create.
+    pub users: Vec<PlannedUser>,
+    // The posts to create.
+    pub posts: Vec<PlannedPost>,
+    // The comments to create.
+    pub comments: Vec<PlannedComment>,
+    // The likes to create, as user and post indices.
+    pub likes: Vec<(usize, usize)>,
+    // The follows to create, as follower and followed user indices.
+    pub follows: Vec<(usize, usize)>,
+    // The blocks to create, as blocker and blocked user indices.
+    pub blocks: Vec<(usize, usize)>,
+    // The history entries to create.
+    pub history: Vec<PlannedVisit>,
+}
+
+// The `PlannedUser` struct which represents a generated user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedUser {
+    // The username of the user.
+    pub username: String,
+    // The email of the user.
+    pub email: String,
+    // The intro of the user, if any.
+    pub intro: Option<String>,
+    // How many seconds before seeding the user signed up.
+    pub age: i64,
+}
+
+// The `PlannedPost` struct which represents a generated post.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedPost {
+    // The index of the author.
+    pub author: usize,
+    // The title of the post.
+    pub title: String,
+    // The description of the post.
+    pub description: String,
+    // The content of the post.
+    pub content: String,
+    // How many seconds before seeding the post was created.
+    pub age: i64,
+}
+
+// The `PlannedComment` struct which represents a generated comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedComment {
+    // The index of the commenter.
+    pub user: usize,
+    // The index of the post.
+    pub post: usize,
+    // The content of the comment.
+    pub content: String,
+    // How many seconds before seeding the comment was written.
+    pub age: i64,
+}
+
+// The `PlannedVisit` struct which represents a generated history entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedVisit {
+    // The index of the visitor.
+    pub user: usize,
+    // The index of the post.
+    pub post: usize,
+    // How many seconds before seeding the post was visited.
+    pub age: i64,
+}
+
+
+// Function to generate the data for a configuration.
+// A few users write most posts and comments and a few posts get most likes, comments and visits,
+// following a power law; follows favour popular users the same way, and nobody follows someone they block.
+// It returns the same plan for the same configuration.
+pub fn plan(config: &SeedConfig) -> Plan {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let span = config.days.max(1) * 24 * 60 * 60;
+
+    let users: Vec<PlannedUser> = (0..config.users)
+        .map(|index| {
+            let username = format!("{}_{}", NAMES[index % NAMES.len()], index);
+            PlannedUser {
+                email: format!("{}@seed.example", username),
+                username,
+                intro: rng.gen_bool(0.6).then(|| sentence(&mut rng, 4, 12)),
+                age: rng.gen_range(0..span),
+            }
+        })
+        .collect();
+
+    let mut plan = Plan {
+        users,
+        posts: Vec::new(),
+        comments: Vec::new(),
+        likes: Vec::new(),
+        follows: Vec::new(),
+        blocks: Vec::new(),
+        history: Vec::new(),
+    };
+    if plan.users.is_empty() {
+        return plan;
+    }
+
+    let active = Zipf::new(&mut rng, plan.users.len());
+    let popular = Zipf::new(&mut rng, plan.users.len());
+
+    for _ in 0..config.posts {
+        let author = active.sample(&mut rng);
+        let age = rng.gen_range(0..=plan.users[author].age);
+        let paragraphs: Vec<String> = (0..rng.gen_range(1..=4)).map(|_| paragraph(&mut rng)).collect();
+        plan.posts.push(PlannedPost {
+            author,
+            title: title(&sentence(&mut rng, 3, 8)),
+            description: sentence(&mut rng, 6, 16),
+            content: paragraphs.join("\n\n"),
+            age,
+        });
+    }
+
+    if !plan.posts.is_empty() {
+        let read = Zipf::new(&mut rng, plan.posts.len());
+
+        for _ in 0..config.comments {
+            let (user, post) = (active.sample(&mut rng), read.sample(&mut rng));
+            let age = rng.gen_range(0..=plan.posts[post].age.min(plan.users[user].age));
+            let content = sentence(&mut rng, 3, 30);
+            plan.comments.push(PlannedComment { user, post, content, age });
+        }
+
+        for _ in 0..config.history {
+            let (user, post) = (rng.gen_range(0..plan.users.len()), read.sample(&mut rng));
+            let age = rng.gen_range(0..=plan.posts[post].age.min(plan.users[user].age));
+            plan.history.push(PlannedVisit { user, post, age });
+        }
+
+        // Authors don't like their own posts, and each user likes a post at most once.
+        let possible = plan.users.len() * plan.posts.len() - plan.posts.len();
+        plan.likes = pairs(config.likes.min(possible), || {
+            let (user, post) = (rng.gen_range(0..plan.users.len()), read.sample(&mut rng));
+            (plan.posts[post].author != user).then_some((user, post))
+        });
+    }
+
+    let possible = plan.users.len() * (plan.users.len() - 1);
+    plan.blocks = pairs(config.blocks.min(possible / 2), || {
+        let (blocker, blocked) = (rng.gen_range(0..plan.users.len()), rng.gen_range(0..plan.users.len()));
+        (blocker != blocked).then_some((blocker, blocked))
+    });
+
+    let blocked: HashSet<(usize, usize)> = plan.blocks.iter().flat_map(|&(a, b)| [(a, b), (b, a)]).collect();
+    plan.follows = pairs(config.follows.min(possible - blocked.len()), || {
+        let (follower, followed) = (rng.gen_range(0..plan.users.len()), popular.sample(&mut rng));
+        (follower != followed && !blocked.contains(&(follower, followed))).then_some((follower, followed))
+    });
+
+    plan
+}
+
+// Function to insert a plan through the Prisma client.
+// Every user gets the same password hash, as hashing one per user would take minutes.
+// It returns an error if any of the usernames is taken, such as when a seed was already inserted.
+pub async fn insert(prisma: &PrismaClient, plan: &Plan, password_hash: &str) -> anyhow::Result<()> {
+    let now = Utc::now().fixed_offset();
+    let at = |age: i64| now - Duration::seconds(age);
+
+    let usernames: Vec<String> = plan.users.iter().map(|user| user.username.clone()).collect();
+    let taken = prisma
+        .user_details()
+        .count(vec![user_details::username::in_vec(usernames.clone())])
+        .exec().await?;
+    if taken > 0 {
+        bail!("{} of the seeded usernames are taken; seed an empty database or use another --seed", taken);
+    }
+
+    for users in plan.users.chunks(CHUNK) {
+        prisma.user_details().create_many(users.iter().map(|user| user_details::create_unchecked(
+            user.email.clone(),
+            user.username.clone(),
+            vec![user_details::intro::set(user.intro.clone()), user_details::created_at::set(at(user.age))],
+        )).collect()).exec().await?;
+    }
+
+    // Rows are looked up again to learn their IDs, users by name and posts in the order they were inserted.
+    let mut user_ids = Vec::with_capacity(plan.users.len());
+    for usernames in usernames.chunks(CHUNK) {
+        let found = prisma
+            .user_details()
+            .find_many(vec![user_details::username::in_vec(usernames.to_vec())])
+            .exec().await?;
+        for username in usernames {
+            match found.iter().find(|user| &user.username == username) {
+                Some(user) => user_ids.push(user.user_id),
+                None => bail!("the seeded user {} was not found after inserting it", username),
+            }
+        }
+    }
+
+    for ids in user_ids.chunks(CHUNK) {
+        prisma.user_password().create_many(ids.iter().map(|&user_id| user_password::create_unchecked(
+            user_id, password_hash.to_string(), vec![],
+        )).collect()).exec().await?;
+    }
+
+    let mut like_counts = vec![0; plan.posts.len()];
+    for &(_, post) in &plan.likes {
+        like_counts[post] += 1;
+    }
+
+    let mut post_ids = Vec::with_capacity(plan.posts.len());
+    for (chunk, posts) in plan.posts.chunks(CHUNK).enumerate() {
+        let last = prisma
+            .platform_posts()
+            .find_first(vec![])
+            .order_by(platform_posts::post_id::order(prisma_client_rust::Direction::Desc))
+            .exec().await?
+            .map_or(0, |post| post.post_id);
+
+        prisma.platform_posts().create_many(posts.iter().enumerate().map(|(index, post)| {
+            platform_posts::create_unchecked(
+                user_ids[post.author],
+                post.title.clone(),
+                post.description.clone(),
+                post.content.clone(),
+                vec![
+                    platform_posts::like_count::set(like_counts[chunk * CHUNK + index]),
+                    platform_posts::created_at::set(at(post.age)),
+                ],
+            )
+        }).collect()).exec().await?;
+
+        let created = prisma
+            .platform_posts()
+            .find_many(vec![platform_posts::post_id::gt(last)])
+            .order_by(platform_posts::post_id::order(prisma_client_rust::Direction::Asc))
+            .exec().await?;
+        if created.len() != posts.len() {
+            bail!("posts were created while seeding; seed a database nothing else writes to");
+        }
+        post_ids.extend(created.iter().map(|post| post.post_id));
+    }
+
+    for comments in plan.comments.chunks(CHUNK) {
+        prisma.post_comments().create_many(comments.iter().map(|comment| post_comments::create_unchecked(
+            user_ids[comment.user],
+            post_ids[comment.post],
+            comment.content.clone(),
+            vec![post_comments::created_at::set(at(comment.age))],
+        )).collect()).exec().await?;
+    }
+
+    for likes in plan.likes.chunks(CHUNK) {
+        prisma.user_like_posts().create_many(likes.iter().map(|&(user, post)| {
+            user_like_posts::create_unchecked(user_ids[user], post_ids[post], vec![])
+        }).collect()).exec().await?;
+    }
+
+    for follows in plan.follows.chunks(CHUNK) {
+        prisma.user_follows().create_many(follows.iter().map(|&(follower, followed)| {
+            user_follows::create_unchecked(user_ids[follower], user_ids[followed], vec![])
+        }).collect()).exec().await?;
+    }
+
+    for blocks in plan.blocks.chunks(CHUNK) {
+        prisma.user_blocks().create_many(blocks.iter().map(|&(blocker, blocked)| {
+            user_blocks::create_unchecked(user_ids[blocker], user_ids[blocked], vec![])
+        }).collect()).exec().await?;
+    }
+
+    for visits in plan.history.chunks(CHUNK) {
+        prisma.user_history().create_many(visits.iter().map(|visit| user_history::create_unchecked(
+            user_ids[visit.user],
+            post_ids[visit.post],
+            vec![user_history::time::set(at(visit.age))],
+        )).collect()).exec().await?;
+    }
+
+    Ok(())
+}
+
+
+// The `Zipf` struct which picks indices with power law popularity.
+// Ranks are shuffled, so the most popular item isn't always the first one.
+struct Zipf {
+    // The item at each popularity rank.
+    items: Vec<usize>,
+    // The distribution of the ranks.
+    ranks: WeightedIndex<f64>,
+}
+
+// Implementation of the `Zipf` struct.
+impl Zipf {
+
+    // Function to rank `count` items at random.
+    fn new(rng: &mut StdRng, count: usize) -> Self {
+        let mut items: Vec<usize> = (0..count).collect();
+        items.shuffle(rng);
+        let weights = (1..=count).map(|rank| 1.0 / (rank as f64).powf(ZIPF_EXPONENT));
+        Self { items, ranks: WeightedIndex::new(weights).expect("at least one item") }
+    }
+
+    // Function to pick an item.
+    fn sample(&self, rng: &mut StdRng) -> usize {
+        self.items[self.ranks.sample(rng)]
+    }
+}
+
+
+// Function to draw `count` distinct pairs, `draw` returning `None` for pairs which aren't allowed.
+// Drawing gives up after a number of attempts, so fewer pairs are returned when few are left to find.
+fn pairs(count: usize, mut draw: impl FnMut() -> Option<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut seen = HashSet::new();
+    let mut pairs = Vec::with_capacity(count);
+
+    for _ in 0..count.saturating_mul(20) {
+        if pairs.len() == count {
+            break;
+        }
+        if let Some(pair) = draw().filter(|pair| seen.insert(*pair)) {
+            pairs.push(pair);
+        }
+    }
+
+    pairs
+}
+
+// Function to generate a sentence of `min` to `max` words, ending with a full stop.
+fn sentence(rng: &mut StdRng, min: usize, max: usize) -> String {
+    let words: Vec<&str> = (0..rng.gen_range(min..=max)).map(|_| *WORDS.choose(rng).unwrap()).collect();
+    let mut sentence = title(&words.join(" "));
+    sentence.push('.');
+    sentence
+}
+
+// Function to generate a paragraph of a few sentences.
+fn paragraph(rng: &mut StdRng) -> String {
+    let sentences: Vec<String> = (0..rng.gen_range(2..=6)).map(|_| sentence(rng, 5, 20)).collect();
+    sentences.join(" ")
+}
+
+// Function to capitalize the first letter of a text, dropping a trailing full stop.
+fn title(text: &str) -> String {
+    let text = text.trim_end_matches('.');
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
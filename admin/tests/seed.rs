@@ -0,0 +1,85 @@
+// Tests for the generated seed data.
+use std::collections::HashSet;
+
+use wforum_admin::seed::{plan, SeedConfig};
+
+
+// Function to get a small configuration with the given seed.
+fn config(seed: u64) -> SeedConfig {
+    SeedConfig {
+        seed,
+        users: 200,
+        posts: 1000,
+        comments: 2000,
+        likes: 5000,
+        follows: 1000,
+        blocks: 50,
+        history: 3000,
+        days: 30,
+    }
+}
+
+
+#[test]
+fn the_same_seed_gives_the_same_data() {
+    assert_eq!(plan(&config(7)), plan(&config(7)));
+    assert_ne!(plan(&config(7)), plan(&config(8)));
+}
+
+#[test]
+fn relations_are_consistent() {
+    let plan = plan(&config(1));
+    assert_eq!((plan.users.len(), plan.posts.len(), plan.comments.len()), (200, 1000, 2000));
+    assert_eq!((plan.likes.len(), plan.follows.len(), plan.blocks.len()), (5000, 1000, 50));
+
+    let usernames: HashSet<&str> = plan.users.iter().map(|user| user.username.as_str()).collect();
+    assert_eq!(usernames.len(), plan.users.len());
+
+    for post in &plan.posts {
+        assert!(post.age <= plan.users[post.author].age, "a post is older than its author");
+    }
+    for comment in &plan.comments {
+        assert!(comment.age <= plan.posts[comment.post].age && comment.age <= plan.users[comment.user].age);
+    }
+
+    assert_eq!(plan.likes.iter().collect::<HashSet<_>>().len(), plan.likes.len());
+    assert!(plan.likes.iter().all(|&(user, post)| plan.posts[post].author != user));
+
+    assert_eq!(plan.follows.iter().collect::<HashSet<_>>().len(), plan.follows.len());
+    let blocked: HashSet<(usize, usize)> = plan.blocks.iter().flat_map(|&(a, b)| [(a, b), (b, a)]).collect();
+    for follow in &plan.follows {
+        assert!(follow.0 != follow.1 && !blocked.contains(follow), "{:?}", follow);
+    }
+}
+
+#[test]
+fn popularity_follows_a_power_law() {
+    let plan = plan(&config(3));
+
+    let mut posts_by_author = vec![0; plan.users.len()];
+    for post in &plan.posts {
+        posts_by_author[post.author] += 1;
+    }
+    posts_by_author.sort_unstable_by(|a, b| b.cmp(a));
+    let top_tenth: usize = posts_by_author[..plan.users.len() / 10].iter().sum();
+    assert!(top_tenth * 2 > plan.posts.len(), "the top tenth of users wrote {} posts", top_tenth);
+
+    let mut likes_by_post = vec![0; plan.posts.len()];
+    for &(_, post) in &plan.likes {
+        likes_by_post[post] += 1;
+    }
+    assert!(likes_by_post.iter().filter(|&&likes| likes == 0).count() > plan.posts.len() / 10);
+}
+
+#[test]
+fn impossible_counts_are_capped() {
+    let mut config = config(1);
+    config.users = 3;
+    config.posts = 0;
+    config.follows = 100;
+    config.blocks = 100;
+
+    let plan = plan(&config);
+    assert!(plan.posts.is_empty() && plan.comments.is_empty() && plan.likes.is_empty());
+    assert!(plan.blocks.len() <= 3 && plan.follows.len() <= 6);
+}
@@ -0,0 +1,32 @@
+// The build script which records the build information served at `/version`.
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+// The main function of the build script.
+fn main() {
+    // The git commit, from `WFORUM_GIT_SHA` when building without a git checkout, such as in Docker.
+    let git_sha = std::env::var("WFORUM_GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.is_empty())
+        .or_else(|| {
+            let output = Command::new("git").args(["rev-parse", "HEAD"]).output().ok()?;
+            output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .unwrap_or_else(|| String::from("unknown"));
+
+    // The build time in seconds since the epoch, fixed by `SOURCE_DATE_EPOCH` for reproducible builds.
+    let build_timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()));
+
+    println!("cargo:rustc-env=WFORUM_GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=WFORUM_BUILD_TIMESTAMP={}", build_timestamp);
+
+    // Rebuilding when the checked out commit changes.
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-env-changed=WFORUM_GIT_SHA");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
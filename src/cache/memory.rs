@@ -0,0 +1,43 @@
+// Importing the necessary modules and functions.
+use std::time::Duration;
+use moka::policy::EvictionPolicy;
+
+
+// The `MemoryCache` struct which keeps entries in the process.
+// Entries expire after the configured time, and the least recently used ones are evicted
+// once the capacity is reached.
+pub struct MemoryCache {
+    // The entries by key, as JSON.
+    entries: moka::future::Cache<String, String>,
+}
+
+
+// Implementation of the `MemoryCache` struct.
+impl MemoryCache {
+
+    // Function to create a cache holding up to `capacity` entries for `ttl` seconds each.
+    pub fn new(capacity: usize, ttl: u64) -> Self {
+        Self {
+            entries: moka::future::Cache::builder()
+                .max_capacity(capacity as u64)
+                .eviction_policy(EvictionPolicy::lru())
+                .time_to_live(Duration::from_secs(ttl))
+                .build(),
+        }
+    }
+
+    // Function to get the entry of a key.
+    pub async fn get(&self, key: &str) -> Option<String> {
+        self.entries.get(key).await
+    }
+
+    // Function to set the entry of a key.
+    pub async fn set(&self, key: &str, json: String) {
+        self.entries.insert(key.to_string(), json).await
+    }
+
+    // Function to remove the entry of a key.
+    pub async fn invalidate(&self, key: &str) {
+        self.entries.invalidate(key).await
+    }
+}
@@ -0,0 +1,103 @@
+// The `cache` module.
+// This module caches hot reads, such as the posts and profiles anonymous readers fetch.
+
+// The `memory` module.
+// This module contains the in-process cache evicting the least recently used entries.
+pub mod memory;
+
+// The `redis` module.
+// This module contains the cache shared by every backend instance through Redis.
+pub mod redis;
+
+
+// Importing the necessary modules and functions.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::config::CacheConfig;
+use self::memory::MemoryCache;
+use self::redis::RedisCache;
+
+
+// The `Cache` enum which represents the configured cache backend.
+// Values are stored as JSON, and a failing backend only costs a cache miss.
+pub enum Cache {
+    // Nothing is cached.
+    Disabled,
+    // Entries are kept in the process.
+    Memory(MemoryCache),
+    // Entries are kept in Redis.
+    Redis(RedisCache),
+}
+
+
+// Implementation of the `Cache` enum.
+impl Cache {
+
+    // Function to create the cache backend selected by the configuration.
+    pub fn new(config: &CacheConfig) -> Self {
+        match config.backend.as_str() {
+            "memory" => Self::Memory(MemoryCache::new(config.capacity, config.ttl)),
+            // The URL is checked when the configuration is loaded.
+            "redis" => match RedisCache::new(config.redis_url.as_deref().unwrap_or_default(), config.ttl) {
+                Ok(cache) => Self::Redis(cache),
+                Err(error) => {
+                    tracing::error!("Cache: invalid Redis URL, caching disabled: {}", error);
+                    Self::Disabled
+                }
+            },
+            _ => Self::Disabled,
+        }
+    }
+
+
+    // Function to get a cached value.
+    // It returns `None` if the key is missing, expired or unreadable.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let json = match self {
+            Self::Disabled => return None,
+            Self::Memory(cache) => cache.get(key).await,
+            Self::Redis(cache) => cache.get(key).await,
+        };
+
+        let value = json.and_then(|json| serde_json::from_str(&json).ok());
+        let result = if value.is_some() { "hit" } else { "miss" };
+        metrics::counter!("wforum_cache_requests_total", "result" => result).increment(1);
+        value
+    }
+
+
+    // Function to cache a value until it expires or is invalidated.
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T) {
+        let Ok(json) = serde_json::to_string(value) else {
+            return;
+        };
+
+        match self {
+            Self::Disabled => {}
+            Self::Memory(cache) => cache.set(key, json).await,
+            Self::Redis(cache) => cache.set(key, json).await,
+        }
+    }
+
+
+    // Function to drop a cached value after the data behind it changed.
+    pub async fn invalidate(&self, key: &str) {
+        match self {
+            Self::Disabled => {}
+            Self::Memory(cache) => cache.invalidate(key).await,
+            Self::Redis(cache) => cache.invalidate(key).await,
+        }
+    }
+}
+
+
+// Function to get the key of the post with the given ID, as anonymous readers see it.
+pub fn post_key(post_id: i32) -> String {
+    format!("post:{}", post_id)
+}
+
+// Function to get the key of the profile with the given username, as anonymous readers see it.
+pub fn profile_key(username: &str) -> String {
+    format!("profile:{}", username)
+}
@@ -0,0 +1,87 @@
+// Importing the necessary modules and functions.
+use std::future::Future;
+use std::time::Duration;
+use ::redis::aio::ConnectionManager;
+use ::redis::{AsyncCommands, Client, RedisResult};
+use tokio::sync::OnceCell;
+
+
+// The prefix of every key, so the cache can share a Redis database with other applications.
+const KEY_PREFIX: &str = "wforum:";
+// The longest a cache operation may take, past which it counts as a miss.
+const OPERATION_TIMEOUT: Duration = Duration::from_millis(500);
+
+
+// The `RedisCache` struct which keeps entries in Redis, shared by every backend instance.
+// Redis errors are logged and treated as misses, so an unavailable Redis only slows requests down.
+pub struct RedisCache {
+    // The client connecting to Redis.
+    client: Client,
+    // The connection, opened on first use and reconnecting by itself after failures.
+    connection: OnceCell<ConnectionManager>,
+    // The time entries expire after, in seconds.
+    ttl: u64,
+}
+
+
+// Implementation of the `RedisCache` struct.
+impl RedisCache {
+
+    // Function to create a cache in the Redis server at `url`, holding entries for `ttl` seconds.
+    // It returns a `Result` which is an error if the URL is invalid; nothing is connected yet.
+    pub fn new(url: &str, ttl: u64) -> RedisResult<Self> {
+        Ok(Self { client: Client::open(url)?, connection: OnceCell::new(), ttl })
+    }
+
+    // Function to get the entry of a key.
+    pub async fn get(&self, key: &str) -> Option<String> {
+        self.run("GET", |mut connection| async move {
+            connection.get::<_, Option<String>>(format!("{}{}", KEY_PREFIX, key)).await
+        }).await.flatten()
+    }
+
+    // Function to set the entry of a key.
+    pub async fn set(&self, key: &str, json: String) {
+        self.run("SET", |mut connection| async move {
+            connection.set_ex::<_, _, ()>(format!("{}{}", KEY_PREFIX, key), json, self.ttl).await
+        }).await;
+    }
+
+    // Function to remove the entry of a key.
+    // An entry which could not be removed still expires after the TTL.
+    pub async fn invalidate(&self, key: &str) {
+        self.run("DEL", |mut connection| async move {
+            connection.del::<_, ()>(format!("{}{}", KEY_PREFIX, key)).await
+        }).await;
+    }
+
+    // Function to run a command, connecting first if needed.
+    // It returns `None` if Redis cannot be reached, fails or does not answer in time.
+    async fn run<T, F, Fut>(&self, command: &str, operation: F) -> Option<T>
+    where
+        F: FnOnce(ConnectionManager) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        let result: Result<RedisResult<T>, _> = tokio::time::timeout(OPERATION_TIMEOUT, async {
+            // Retrying the first connection only once, so requests do not wait on an unavailable Redis.
+            let connection = self
+                .connection
+                .get_or_try_init(|| ConnectionManager::new_with_backoff(self.client.clone(), 2, 10, 1))
+                .await?
+                .clone();
+            operation(connection).await
+        }).await;
+
+        match result {
+            Ok(Ok(value)) => Some(value),
+            Ok(Err(error)) => {
+                tracing::warn!("Cache: Redis {} failed: {}", command, error);
+                None
+            }
+            Err(_) => {
+                tracing::warn!("Cache: Redis {} did not answer within {:?}", command, OPERATION_TIMEOUT);
+                None
+            }
+        }
+    }
+}
@@ -1,21 +1,93 @@
 // Importing the necessary modules and functions.
-use std::env;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+use std::time::Duration;
 use dotenv::dotenv;
 
+use crate::config::source::{ConfigError, Sources};
 use crate::service::utils::helper::Helper;
 
 
+// The config file read when `WFORUM_CONFIG` is not set, if it exists.
+pub const DEFAULT_CONFIG_FILE: &str = "wforum.toml";
+// The formats logs can be written in.
+const LOG_FORMATS: [&str; 2] = ["text", "json"];
+// The backends hot reads can be cached in.
+const CACHE_BACKENDS: [&str; 3] = ["memory", "redis", "none"];
+// The signing algorithms supported for JWTs.
+const JWT_ALGORITHMS: [&str; 3] = ["HS256", "RS256", "EdDSA"];
+
+
 // The `BeConfig` struct which contains the configuration for the backend.
 #[derive(Debug, Clone)]
 pub struct BeConfig {
     // The log level for the application.
     pub log_level: String,
+    // The format logs are written in, "text" or "json".
+    pub log_format: String,
     // The port on which the backend will run.
     pub backend_port: u16,
+    // The configuration for the HTTP server.
+    pub server_config: ServerConfig,
+    // The configuration for CORS.
+    pub cors_config: CorsConfig,
+    // The configuration for TLS, if the backend serves HTTPS itself.
+    pub tls_config: Option<TlsConfig>,
     // The configuration for JWT.
     pub jwt_config: JwtConfig,
     // The URL for the database.
     pub database_url: String,
+    // The configuration for login protection.
+    pub login_config: LoginConfig,
+    // The configuration for two-factor authentication.
+    pub two_factor_config: TwoFactorConfig,
+    // The configured OpenID Connect providers.
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    // The secret signing the state of OpenID Connect logins, required when any provider is configured.
+    pub oidc_state_secret: Option<String>,
+    // The limits enforced on request bodies.
+    pub validation_config: ValidationConfig,
+    // The configuration for caching hot reads.
+    pub cache_config: CacheConfig,
+}
+
+// The `ServerConfig` struct which contains the configuration for the HTTP server.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    // The address the backend listens on.
+    pub bind_address: IpAddr,
+    // The time a request may take before it fails with `408 Request Timeout`, in seconds.
+    pub request_timeout: u64,
+    // The largest request body accepted, in bytes.
+    pub body_limit: usize,
+    // The time in-flight requests are given to finish on shutdown, in seconds.
+    pub shutdown_timeout: u64,
+    // The deadline of the readiness check in seconds.
+    pub ready_timeout: u64,
+    // The header a trusted reverse proxy puts the client's address in, such as `X-Forwarded-For`.
+    pub client_ip_header: Option<String>,
+}
+
+// The `CorsConfig` struct which contains the configuration for CORS.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    // The origins allowed to call the API, or "*" for any origin.
+    pub allowed_origins: Vec<String>,
+    // Whether browsers may send cookies and `Authorization` headers cross-origin.
+    pub allow_credentials: bool,
+}
+
+// The `TlsConfig` struct which contains the configuration for serving HTTPS.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    // The path of the PEM encoded certificate chain.
+    pub cert_file: String,
+    // The path of the PEM encoded private key.
+    pub key_file: String,
+    // How often the certificate and key files are checked for changes, in seconds.
+    pub reload_interval: u64,
+    // The port of the plain HTTP listener redirecting to HTTPS, if any.
+    pub redirect_port: Option<u16>,
 }
 
 // The `JwtConfig` struct which contains the configuration for JWT.
@@ -25,39 +97,530 @@ pub struct JwtConfig {
     pub jwt_secret: String,
     // The expiration value for JWT.
     pub jwt_exp_value: i64,
+    // The signing algorithm for JWT, "HS256", "RS256" or "EdDSA".
+    pub jwt_algorithm: String,
+    // The key ID of the signing key.
+    pub jwt_kid: String,
+    // The path of the PEM encoded private signing key, for asymmetric algorithms.
+    pub jwt_signing_key_file: Option<String>,
+    // The key IDs and paths of the PEM encoded public keys accepted for verification.
+    pub jwt_verification_key_files: Vec<(String, String)>,
+}
+
+// The `LoginConfig` struct which contains the configuration for login protection.
+#[derive(Debug, Clone)]
+pub struct LoginConfig {
+    // The number of failed attempts allowed per account before lockout starts.
+    pub max_account_attempts: u32,
+    // The number of failed attempts allowed per IP address before lockout starts.
+    pub max_ip_attempts: u32,
+    // The base lockout duration in seconds, doubled on every further failure.
+    pub lockout_base: i64,
+    // The maximum lockout duration in seconds.
+    pub lockout_max: i64,
+}
+
+// The `TwoFactorConfig` struct which contains the configuration for two-factor authentication.
+#[derive(Debug, Clone)]
+pub struct TwoFactorConfig {
+    // The issuer shown in authenticator apps.
+    pub issuer: String,
+    // The lifetime of login challenge and setup tokens in seconds.
+    pub challenge_exp_value: i64,
+    // Whether moderators and admins must enroll before they can log in.
+    pub required_for_elevated: bool,
+}
+
+// The `OidcProviderConfig` struct which contains the configuration for an OpenID Connect provider.
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    // The name of the provider used in URLs.
+    pub name: String,
+    // The issuer URL of the provider.
+    pub issuer: String,
+    // The client ID registered with the provider.
+    pub client_id: String,
+    // The client secret registered with the provider.
+    pub client_secret: String,
+    // The scopes requested from the provider.
+    pub scopes: Vec<String>,
+    // The callback URL registered with the provider.
+    pub redirect_uri: String,
+}
+
+// The `CacheConfig` struct which contains the configuration for caching hot reads.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    // The backend entries are kept in, "memory", "redis" or "none".
+    pub backend: String,
+    // The most entries the in-memory backend holds.
+    pub capacity: usize,
+    // The time entries expire after, in seconds.
+    pub ttl: u64,
+    // The URL of the Redis server, required with the "redis" backend.
+    pub redis_url: Option<String>,
+}
+
+// The `ValidationConfig` struct which contains the limits enforced on request bodies.
+// Lengths are counted in characters.
+#[derive(Debug, Clone)]
+pub struct ValidationConfig {
+    // The minimum and maximum length of a username.
+    pub username_min: usize,
+    pub username_max: usize,
+    // The minimum and maximum length of a password.
+    pub password_min: usize,
+    pub password_max: usize,
+    // The maximum length of an email address.
+    pub email_max: usize,
+    // The maximum length of a user's introduction.
+    pub intro_max: usize,
+    // The maximum length of an avatar URL.
+    pub avatar_max: usize,
+    // The maximum length of a post's title.
+    pub title_max: usize,
+    // The maximum length of a post's description.
+    pub description_max: usize,
+    // The maximum length of a post's content.
+    pub content_max: usize,
+    // The maximum length of a comment.
+    pub comment_max: usize,
+    // The maximum number of items returned by one page of a list.
+    pub page_max: i64,
+    // The most days a user can be muted for, short of muting them for good.
+    pub mute_days_max: i64,
 }
 
 
 // Implementation of the `BeConfig` struct.
 impl BeConfig {
+
     // Function to initialize the `BeConfig` struct.
+    // It panics with every configuration problem, `main` reports them more gracefully before this runs.
     pub fn init() -> Self {
+        Self::load().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    // Function to load the configuration from the config file and the environment.
+    // The file is `WFORUM_CONFIG`, or `wforum.toml` if it exists; environment variables override it.
+    // It returns a `Result` with the configuration or every problem found.
+    pub fn load() -> Result<Self, ConfigError> {
+        // Load the environment variables from the .env file.
+        dotenv().ok();
+
+        let path = match std::env::var("WFORUM_CONFIG") {
+            Ok(path) => Some(path),
+            Err(_) => Some(DEFAULT_CONFIG_FILE.to_string()).filter(|path| Path::new(path).exists()),
+        };
+
+        let contents = match path.as_ref().map(|path| (path, std::fs::read_to_string(path))) {
+            Some((path, Err(error))) => {
+                return Err(ConfigError { errors: vec![format!("Failed to read {}: {}", path, error)] });
+            }
+            Some((path, Ok(contents))) => Some((path.clone(), contents)),
+            None => None,
+        };
+
+        Self::from_sources(
+            contents.as_ref().map(|(path, contents)| (path.as_str(), contents.as_str())),
+            |key| std::env::var(key).ok(),
+        )
+    }
+
+    // Function to build the configuration from the name and contents of a config file, if any,
+    // and a function looking up environment variables.
+    // It returns a `Result` with the configuration or every problem found.
+    pub fn from_sources(
+        file: Option<(&str, &str)>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+
+        let mut sources = Sources::new(file, env);
+
+        // Get the log format or default to "text".
+        let log_format = sources.or("log_format", "LOG_FORMAT", String::from("text"));
+        if !LOG_FORMATS.contains(&log_format.as_str()) {
+            sources.error(format!(
+                "`log_format` must be one of {}, not {:?}", LOG_FORMATS.join(", "), log_format,
+            ));
+        }
+
+        let server_config = ServerConfig::from_sources(&mut sources);
+        let cors_config = CorsConfig::from_sources(&mut sources);
+        let tls_config = TlsConfig::from_sources(&mut sources);
+        let jwt_config = JwtConfig::from_sources(&mut sources);
+        let login_config = LoginConfig::from_sources(&mut sources);
+        let two_factor_config = TwoFactorConfig::from_sources(&mut sources);
+        let validation_config = ValidationConfig::from_sources(&mut sources);
+        let cache_config = CacheConfig::from_sources(&mut sources);
+
+        // Providers are listed by `OIDC_PROVIDERS` or as `[oidc.<name>]` tables in the config file.
+        let mut provider_names: Vec<String> = sources.table_names("oidc");
+        for name in sources.optional::<Vec<String>>("oidc.providers", "OIDC_PROVIDERS").unwrap_or_default() {
+            if !provider_names.iter().any(|known| known.eq_ignore_ascii_case(&name)) {
+                provider_names.push(name);
+            }
+        }
+        let oidc_providers: Vec<OidcProviderConfig> = provider_names
+            .iter()
+            .map(|name| OidcProviderConfig::from_sources(&mut sources, name))
+            .collect();
+
+        // The login state has a secret of its own, as the JWT secret is not set with the asymmetric algorithms.
+        // A short secret is refused, even an empty one from the config file, rather than signing with a weak key.
+        let oidc_state_secret: Option<String> = sources.optional("oidc.state_secret", "OIDC_STATE_SECRET");
+        match &oidc_state_secret {
+            None if !oidc_providers.is_empty() => {
+                sources.error(String::from("`oidc.state_secret` is required (or set OIDC_STATE_SECRET)"));
+            }
+            Some(secret) if secret.len() < 32 => {
+                sources.error(String::from("`oidc.state_secret` must be at least 32 characters"));
+            }
+            _ => {}
+        }
+
+        // Get the backend port or default to 8000.
+        let backend_port = sources.or("server.port", "BACKEND_PORT", 8000);
+        if tls_config.as_ref().and_then(|tls| tls.redirect_port) == Some(backend_port) {
+            sources.error(String::from("`tls.redirect_port` must differ from `server.port`"));
+        }
+
+        let config = Self {
+            // Get the log level or default to "info".
+            log_level: sources.or("log_level", "RUST_LOG", String::from("info")),
+            log_format,
+            backend_port,
+            server_config,
+            cors_config,
+            tls_config,
+            jwt_config,
+            // Get the database URL.
+            database_url: sources.required("database.url", "DATABASE_URL"),
+            login_config,
+            two_factor_config,
+            oidc_providers,
+            oidc_state_secret,
+            validation_config,
+            cache_config,
+        };
+
+        sources.finish(config)
+    }
+}
+
+
+// Implementation of the `ServerConfig` struct.
+impl ServerConfig {
+    // Function to read the `[server]` settings, other than the port.
+    fn from_sources(sources: &mut Sources) -> Self {
         Self {
-            // Get the log level from the environment variable or default to "info".
-            log_level: get_env("RUST_LOG"),
-            // Get the backend port from the environment variable.
-            backend_port: get_env("BACKEND_PORT").parse().unwrap(),
-            // Initialize the `JwtConfig` struct.
-            jwt_config: JwtConfig {
-                // Get the JWT secret from the environment variable.
-                jwt_secret: get_env("JWT_SECRET"),
-                // Get the JWT expiration value from the environment variable and convert it to seconds.
-                jwt_exp_value: Helper::value_to_seconds(
-                    get_env("JWT_EXPIRATION_VALUE").parse().unwrap(),
-                    get_env("JWT_EXPIRATION_UNIT")
+            // Get the bind address or default to every IPv4 interface.
+            bind_address: sources
+                .or("server.bind_address", "BIND_ADDRESS", IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            // Get the request timeout or default to 30 seconds.
+            request_timeout: sources
+                .or("server.request_timeout", "REQUEST_TIMEOUT", Duration::from_secs(30))
+                .as_secs(),
+            // Get the body limit or default to 2 MiB.
+            body_limit: sources.or("server.body_limit", "BODY_LIMIT", 2 * 1024 * 1024),
+            // Get the shutdown timeout or default to 30 seconds.
+            shutdown_timeout: sources
+                .or("server.shutdown_timeout", "SHUTDOWN_TIMEOUT", Duration::from_secs(30))
+                .as_secs(),
+            // Get the readiness check deadline or default to 2 seconds.
+            ready_timeout: sources
+                .or("server.ready_timeout", "READY_TIMEOUT", Duration::from_secs(2))
+                .as_secs(),
+            // Get the client address header or default to using the peer address.
+            client_ip_header: sources.optional("server.client_ip_header", "CLIENT_IP_HEADER"),
+        }
+    }
+}
+
+
+// Implementation of the `CorsConfig` struct.
+impl CorsConfig {
+    // Function to read the `[cors]` settings.
+    fn from_sources(sources: &mut Sources) -> Self {
+
+        // Get the allowed origins or default to any origin.
+        let allowed_origins: Vec<String> = sources
+            .or("cors.allowed_origins", "CORS_ALLOWED_ORIGINS", vec![String::from("*")]);
+        // Get whether credentials are allowed or default to not allowing them.
+        let allow_credentials = sources.or("cors.allow_credentials", "CORS_ALLOW_CREDENTIALS", false);
+
+        let any = allowed_origins.iter().any(|origin| origin == "*");
+        if any && allowed_origins.len() > 1 {
+            sources.error(String::from(
+                "`cors.allowed_origins` cannot list \"*\" together with other origins",
+            ));
+        }
+        if any && allow_credentials {
+            sources.error(String::from(
+                "`cors.allow_credentials` requires `cors.allowed_origins` to list origins, not \"*\"",
+            ));
+        }
+
+        // An origin is a scheme, host and optional port, without a path or trailing slash.
+        for origin in allowed_origins.iter().filter(|origin| *origin != "*") {
+            let valid = reqwest::Url::parse(origin).is_ok_and(|url| {
+                matches!(url.scheme(), "http" | "https") && url.origin().ascii_serialization() == *origin
+            });
+            if !valid {
+                sources.error(format!(
+                    "`cors.allowed_origins`: {:?} is not an origin such as \"https://example.com\"", origin,
+                ));
+            }
+        }
+
+        Self { allowed_origins, allow_credentials }
+    }
+}
+
+
+// Implementation of the `TlsConfig` struct.
+impl TlsConfig {
+    // Function to read the `[tls]` settings.
+    // It returns `None` when no certificate is configured, in which case the backend serves plain HTTP.
+    fn from_sources(sources: &mut Sources) -> Option<Self> {
+
+        let cert_file: Option<String> = sources.optional("tls.cert_file", "TLS_CERT_FILE");
+        let key_file: Option<String> = sources.optional("tls.key_file", "TLS_KEY_FILE");
+        // Get the reload interval or default to 1 minute.
+        let reload_interval = sources
+            .or("tls.reload_interval", "TLS_RELOAD_INTERVAL", Duration::from_secs(60))
+            .as_secs();
+        let redirect_port: Option<u16> = sources.optional("tls.redirect_port", "TLS_REDIRECT_PORT");
+
+        if reload_interval == 0 {
+            sources.error(String::from("`tls.reload_interval` must be at least one second"));
+        }
+
+        match (cert_file, key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                Some(Self { cert_file, key_file, reload_interval, redirect_port })
+            }
+            (None, None) => {
+                if redirect_port.is_some() {
+                    sources.error(String::from(
+                        "`tls.redirect_port` requires `tls.cert_file` and `tls.key_file`",
+                    ));
+                }
+                None
+            }
+            _ => {
+                sources.error(String::from("`tls.cert_file` and `tls.key_file` must be set together"));
+                None
+            }
+        }
+    }
+}
+
+
+// Implementation of the `JwtConfig` struct.
+impl JwtConfig {
+    // Function to read the `[jwt]` settings.
+    fn from_sources(sources: &mut Sources) -> Self {
+
+        // Get the signing algorithm or default to "HS256".
+        let jwt_algorithm = sources.or("jwt.algorithm", "JWT_ALGORITHM", String::from("HS256"));
+        if !JWT_ALGORITHMS.contains(&jwt_algorithm.as_str()) {
+            sources.error(format!(
+                "`jwt.algorithm` must be one of {}, not {:?}", JWT_ALGORITHMS.join(", "), jwt_algorithm,
+            ));
+        }
+
+        // The shared secret is only needed to sign with "HS256",
+        // the private key file only with the asymmetric algorithms.
+        let jwt_secret = match jwt_algorithm.as_str() {
+            "HS256" => sources.required("jwt.secret", "JWT_SECRET"),
+            _ => sources.or("jwt.secret", "JWT_SECRET", String::new()),
+        };
+        let jwt_signing_key_file = sources.optional("jwt.signing_key_file", "JWT_SIGNING_KEY_FILE");
+        if jwt_algorithm != "HS256" && jwt_signing_key_file.is_none() {
+            sources.error(format!("`jwt.signing_key_file` is required for {} (or set JWT_SIGNING_KEY_FILE)",
+                                  jwt_algorithm));
+        }
+
+        // The older `JWT_EXPIRATION_VALUE` and `JWT_EXPIRATION_UNIT` pair is the default of `jwt.expiration`.
+        let legacy_expiration = sources.env("JWT_EXPIRATION_VALUE").map(|value| {
+            let unit = sources.env("JWT_EXPIRATION_UNIT").unwrap_or_else(|| String::from("seconds"));
+            let seconds = value.parse().ok()
+                .and_then(|value| Helper::value_to_seconds(value, &unit))
+                .filter(|seconds| *seconds > 0);
+
+            seconds.unwrap_or_else(|| {
+                sources.error(format!(
+                    "JWT_EXPIRATION_VALUE and JWT_EXPIRATION_UNIT: invalid value {:?} {:?}", value, unit,
+                ));
+                0
+            })
+        });
+        let jwt_exp_value = sources.or(
+            "jwt.expiration", "JWT_EXPIRATION",
+            Duration::from_secs(legacy_expiration.unwrap_or(86400) as u64),
+        );
+
+        Self {
+            jwt_secret,
+            jwt_exp_value: jwt_exp_value.as_secs() as i64,
+            jwt_algorithm,
+            // Get the key ID of the signing key or default to "default".
+            jwt_kid: sources.or("jwt.kid", "JWT_KID", String::from("default")),
+            jwt_signing_key_file,
+            // Get the `kid=path` pairs of the verification keys.
+            jwt_verification_key_files: sources
+                .or("jwt.verification_keys", "JWT_VERIFICATION_KEYS", Vec::new()),
+        }
+    }
+}
+
+
+// Implementation of the `LoginConfig` struct.
+impl LoginConfig {
+    // Function to read the `[login]` settings.
+    fn from_sources(sources: &mut Sources) -> Self {
+        Self {
+            // Get the allowed failures per account or default to 5.
+            max_account_attempts: sources.or("login.max_account_attempts", "LOGIN_MAX_ACCOUNT_ATTEMPTS", 5),
+            // Get the allowed failures per IP address or default to 20.
+            max_ip_attempts: sources.or("login.max_ip_attempts", "LOGIN_MAX_IP_ATTEMPTS", 20),
+            // Get the base lockout duration or default to 30 seconds.
+            lockout_base: sources
+                .or("login.lockout_base", "LOGIN_LOCKOUT_BASE", Duration::from_secs(30))
+                .as_secs() as i64,
+            // Get the maximum lockout duration or default to 1 hour.
+            lockout_max: sources
+                .or("login.lockout_max", "LOGIN_LOCKOUT_MAX", Duration::from_secs(3600))
+                .as_secs() as i64,
+        }
+    }
+}
+
+
+// Implementation of the `TwoFactorConfig` struct.
+impl TwoFactorConfig {
+    // Function to read the `[two_factor]` settings.
+    fn from_sources(sources: &mut Sources) -> Self {
+        Self {
+            // Get the TOTP issuer or default to "wForum".
+            issuer: sources.or("two_factor.issuer", "TOTP_ISSUER", String::from("wForum")),
+            // Get the challenge lifetime or default to 5 minutes.
+            challenge_exp_value: sources
+                .or(
+                    "two_factor.challenge_expiration", "TWO_FACTOR_CHALLENGE_EXPIRATION",
+                    Duration::from_secs(300),
                 )
-            },
-            // Get the database URL from the environment variable.
-            database_url: get_env("DATABASE_URL"),
+                .as_secs() as i64,
+            // Get the elevated role policy or default to requiring two-factor authentication.
+            required_for_elevated: sources
+                .or("two_factor.required_for_elevated", "TWO_FACTOR_REQUIRED_FOR_ELEVATED", true),
         }
     }
 }
 
 
-// Function to get the value of an environment variable.
-pub fn get_env(key: &str) -> String {
-    // Load the environment variables from the .env file.
-    dotenv().ok();
-    // Get the value of the environment variable or panic if it is not set.
-    env::var(key).unwrap_or_else(|_| panic!("{} must be set", key))
-}
\ No newline at end of file
+// Implementation of the `ValidationConfig` struct.
+impl ValidationConfig {
+    // Function to read the `[validation]` settings, or the `VALIDATION_*` environment variables.
+    fn from_sources(sources: &mut Sources) -> Self {
+        let mut limit = |name: &str, default: usize| -> usize {
+            sources.or(
+                &format!("validation.{}", name),
+                &format!("VALIDATION_{}", name.to_uppercase()),
+                default,
+            )
+        };
+
+        let config = Self {
+            username_min: limit("username_min", 3),
+            username_max: limit("username_max", 32),
+            password_min: limit("password_min", 8),
+            password_max: limit("password_max", 128),
+            email_max: limit("email_max", 254),
+            intro_max: limit("intro_max", 500),
+            avatar_max: limit("avatar_max", 2048),
+            title_max: limit("title_max", 200),
+            description_max: limit("description_max", 500),
+            content_max: limit("content_max", 50000),
+            comment_max: limit("comment_max", 5000),
+            page_max: limit("page_max", 100) as i64,
+            mute_days_max: limit("mute_days_max", 365) as i64,
+        };
+
+        for (name, min, max) in [
+            ("username", config.username_min, config.username_max),
+            ("password", config.password_min, config.password_max),
+        ] {
+            if min > max {
+                sources.error(format!("`validation.{}_min` is larger than `validation.{}_max`", name, name));
+            }
+        }
+
+        config
+    }
+}
+
+
+// Implementation of the `CacheConfig` struct.
+impl CacheConfig {
+    // Function to read the `[cache]` settings.
+    fn from_sources(sources: &mut Sources) -> Self {
+
+        // Get the backend or default to the in-memory one.
+        let backend = sources.or("cache.backend", "CACHE_BACKEND", String::from("memory"));
+        let redis_url: Option<String> = sources.optional("cache.redis_url", "CACHE_REDIS_URL");
+
+        if !CACHE_BACKENDS.contains(&backend.as_str()) {
+            sources.error(format!(
+                "`cache.backend` must be one of {}, not {:?}", CACHE_BACKENDS.join(", "), backend,
+            ));
+        }
+        if backend == "redis" {
+            match &redis_url {
+                None => sources.error(String::from(
+                    "`cache.redis_url` is required for the redis backend (or set CACHE_REDIS_URL)",
+                )),
+                Some(url) if redis::Client::open(url.as_str()).is_err() => sources.error(format!(
+                    "`cache.redis_url`: {:?} is not a URL such as \"redis://localhost:6379\"", url,
+                )),
+                Some(_) => {}
+            }
+        }
+
+        Self {
+            backend,
+            // Get the capacity or default to 10000 entries.
+            capacity: sources.or("cache.capacity", "CACHE_CAPACITY", 10_000),
+            // Get the TTL or default to 1 minute.
+            ttl: sources.or("cache.ttl", "CACHE_TTL", Duration::from_secs(60)).as_secs(),
+            redis_url,
+        }
+    }
+}
+
+
+// Implementation of the `OidcProviderConfig` struct.
+impl OidcProviderConfig {
+    // Function to read the `[oidc.<name>]` settings, or the `OIDC_<NAME>_*` environment variables.
+    fn from_sources(sources: &mut Sources, name: &str) -> Self {
+        let key = |setting: &str| format!("oidc.{}.{}", name, setting);
+        let env = |setting: &str| format!("OIDC_{}_{}", name.to_uppercase(), setting.to_uppercase());
+
+        Self {
+            name: name.to_lowercase(),
+            // Get the issuer without a trailing slash.
+            issuer: sources
+                .required::<String>(&key("issuer"), &env("issuer"))
+                .trim_end_matches('/')
+                .to_string(),
+            client_id: sources.required(&key("client_id"), &env("client_id")),
+            client_secret: sources.required(&key("client_secret"), &env("client_secret")),
+            // Get the scopes or default to the standard ones.
+            scopes: sources.or(&key("scopes"), &env("scopes"), vec![
+                String::from("openid"), String::from("email"), String::from("profile"),
+            ]),
+            redirect_uri: sources.required(&key("redirect_uri"), &env("redirect_uri")),
+        }
+    }
+}
@@ -1,8 +1,22 @@
 // The `config` module.
 pub mod config;
 
+// The `source` module.
+// This module reads settings from the config file and the environment.
+pub mod source;
+
 // Importing the `BeConfig` struct from the `config` module.
 use config::BeConfig;
+// Importing the `LoginGuard` struct for tracking failed logins.
+use crate::service::utils::guard::LoginGuard;
+// Importing the `OidcClient` struct for talking to OpenID Connect providers.
+use crate::service::auth::oidc::OidcClient;
+// Importing the `Cache` enum for caching hot reads.
+use crate::cache::Cache;
+// Importing the `JwtKeys` struct for signing and verifying JWTs.
+use crate::extractor::keys::JwtKeys;
+// Importing the `Repository` trait for storing records.
+use crate::repository::Repository;
 // Importing the `lazy_static` macro for single initialization of global variables.
 use lazy_static::lazy_static;
 
@@ -12,6 +26,40 @@ use lazy_static::lazy_static;
 pub struct BeContext {
     // The `config` field is an `Arc` (Atomic Reference Count) which ensures thread safety.
     pub config: std::sync::Arc<BeConfig>,
+    // The `login_guard` field tracks failed login attempts shared across requests.
+    pub login_guard: std::sync::Arc<LoginGuard>,
+    // The `oidc_client` field caches provider discovery documents across requests.
+    pub oidc_client: std::sync::Arc<OidcClient>,
+    // The `jwt_keys` field holds the keys loaded once at startup.
+    pub jwt_keys: std::sync::Arc<JwtKeys>,
+    // The `cache` field holds the hot reads shared across requests.
+    pub cache: std::sync::Arc<Cache>,
+    // The `repository` field stores every record the services read and write.
+    pub repository: std::sync::Arc<dyn Repository>,
+}
+
+
+// Implementation of the `BeContext` struct.
+impl BeContext {
+    // Function to create the context from the global configuration, storing records in the given repository.
+    // It panics if the JWT keys are misconfigured.
+    pub fn new(repository: std::sync::Arc<dyn Repository>) -> Self {
+        Self {
+            // Ensure safe concurrency by wrapping `CONFIG` in an `Arc`.
+            config: std::sync::Arc::new(CONFIG.clone()),
+            // Share a single `LoginGuard` between all requests.
+            login_guard: std::sync::Arc::new(LoginGuard::new(CONFIG.login_config.clone())),
+            // Share a single `OidcClient` between all requests.
+            oidc_client: std::sync::Arc::new(OidcClient::new()),
+            // Load the JWT keys or panic if they are misconfigured.
+            jwt_keys: std::sync::Arc::new(
+                JwtKeys::load(&CONFIG.jwt_config).expect("Failed to load JWT keys"),
+            ),
+            // Share a single cache between all requests.
+            cache: std::sync::Arc::new(Cache::new(&CONFIG.cache_config)),
+            repository,
+        }
+    }
 }
 
 
@@ -19,10 +67,4 @@ pub struct BeContext {
 lazy_static! {
     // The `CONFIG` variable is a global instance of `BeConfig` initialized once.
     pub static ref CONFIG: BeConfig = BeConfig::init();
-    // The `CONTEXT` variable is a global instance of `BeContext` initialized once.
-    // It contains a thread-safe reference-counted `BeConfig`.
-    pub static ref CONTEXT: BeContext = BeContext {
-        // Ensure safe concurrency by wrapping `CONFIG` in an `Arc`.
-        config: std::sync::Arc::new(CONFIG.clone()),
-    };
-}
\ No newline at end of file
+}
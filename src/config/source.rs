@@ -0,0 +1,365 @@
+// Importing the necessary modules and functions.
+use std::collections::HashSet;
+use std::fmt;
+use std::net::IpAddr;
+use std::time::Duration;
+
+
+// The `Sources` struct which reads settings from the config file and the environment.
+// Environment variables override the file, and every problem is collected instead of panicking
+// so they can all be reported at once.
+pub struct Sources<'a> {
+    // The name of the config file, used in error messages.
+    file_name: String,
+    // The parsed config file, empty if there is none.
+    file: toml::Table,
+    // The function looking up environment variables.
+    env: Box<dyn Fn(&str) -> Option<String> + 'a>,
+    // The keys of the config file which have been read.
+    used: HashSet<String>,
+    // The problems found so far.
+    errors: Vec<String>,
+}
+
+// The `ConfigError` struct which represents every problem found while loading the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    // The problems, one per setting.
+    pub errors: Vec<String>,
+}
+
+
+// The `Setting` trait which is implemented by the types a setting can have.
+pub trait Setting: Sized {
+    // A description of the expected format, used in error messages.
+    const EXPECTED: &'static str;
+
+    // Function to parse the setting from an environment variable.
+    fn parse(value: &str) -> Option<Self>;
+
+    // Function to read the setting from a config file value.
+    // Numbers and booleans are parsed from their text, like environment variables.
+    fn from_toml(value: &toml::Value) -> Option<Self> {
+        match value {
+            toml::Value::String(value) => Self::parse(value),
+            toml::Value::Integer(_) | toml::Value::Boolean(_) => Self::parse(&value.to_string()),
+            _ => None,
+        }
+    }
+}
+
+
+// Implementation of the `Sources` struct.
+impl<'a> Sources<'a> {
+
+    // Function to create the sources from the name and contents of the config file, if any,
+    // and a function looking up environment variables.
+    pub fn new(file: Option<(&str, &str)>, env: impl Fn(&str) -> Option<String> + 'a) -> Self {
+        let mut sources = Self {
+            file_name: String::new(),
+            file: toml::Table::new(),
+            env: Box::new(env),
+            used: HashSet::new(),
+            errors: Vec::new(),
+        };
+
+        if let Some((name, contents)) = file {
+            sources.file_name = name.to_string();
+            match contents.parse::<toml::Table>() {
+                Ok(file) => sources.file = file,
+                Err(error) => {
+                    let line = error.span().map_or(1, |span| contents[..span.start].matches('\n').count() + 1);
+                    sources.error(format!("{} line {} is not valid TOML: {}", name, line, error.message()));
+                }
+            }
+        }
+
+        sources
+    }
+
+    // Function to get an environment variable, empty values count as unset.
+    pub fn env(&self, name: &str) -> Option<String> {
+        (self.env)(name).filter(|value| !value.is_empty())
+    }
+
+    // Function to get a required setting from the `env` variable or the `key` of the config file.
+    // A missing or invalid setting is recorded and the type's default is returned in its place.
+    pub fn required<T: Setting + Default>(&mut self, key: &str, env: &str) -> T {
+        match self.lookup(key, env) {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                self.error(format!("`{}` is required (or set {})", key, env));
+                T::default()
+            }
+            Err(()) => T::default(),
+        }
+    }
+
+    // Function to get an optional setting from the `env` variable or the `key` of the config file.
+    pub fn optional<T: Setting>(&mut self, key: &str, env: &str) -> Option<T> {
+        self.lookup(key, env).ok().flatten()
+    }
+
+    // Function to get a setting from the `env` variable or the `key` of the config file, or `default`.
+    pub fn or<T: Setting>(&mut self, key: &str, env: &str, default: T) -> T {
+        self.optional(key, env).unwrap_or(default)
+    }
+
+    // Function to get the names of the sub-tables of a table in the config file, such as `[oidc.google]`.
+    pub fn table_names(&self, key: &str) -> Vec<String> {
+        match self.file_value(key) {
+            Some(toml::Value::Table(table)) => table
+                .iter()
+                .filter(|(_, value)| value.is_table())
+                .map(|(name, _)| name.clone())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Function to record a problem.
+    pub fn error(&mut self, message: String) {
+        self.errors.push(message);
+    }
+
+    // Function to finish loading, reporting settings in the config file which were never read.
+    // It returns a `Result` with the loaded value or every problem found.
+    pub fn finish<T>(mut self, value: T) -> Result<T, ConfigError> {
+        let mut unknown = Vec::new();
+        collect_unknown(&self.file, "", &self.used, &mut unknown);
+
+        for key in unknown {
+            self.error(format!("unknown setting `{}` in {}", key, self.file_name));
+        }
+
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(ConfigError { errors: self.errors })
+        }
+    }
+
+    // Function to look up a setting, the environment variable taking precedence.
+    // It returns `Ok(None)` if the setting is not set, and `Err` if it is set but invalid.
+    fn lookup<T: Setting>(&mut self, key: &str, env: &str) -> Result<Option<T>, ()> {
+        self.used.insert(key.to_string());
+
+        if let Some(raw) = self.env(env) {
+            return match T::parse(&raw) {
+                Some(value) => Ok(Some(value)),
+                None => {
+                    self.error(format!("{}: invalid value {:?}, expected {}", env, raw, T::EXPECTED));
+                    Err(())
+                }
+            };
+        }
+
+        match self.file_value(key).map(T::from_toml) {
+            Some(Some(value)) => Ok(Some(value)),
+            Some(None) => {
+                self.error(format!("`{}` in {}: expected {}", key, self.file_name, T::EXPECTED));
+                Err(())
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Function to get the value at a dotted key of the config file.
+    fn file_value(&self, key: &str) -> Option<&toml::Value> {
+        let (tables, last) = match key.rsplit_once('.') {
+            Some((tables, last)) => (tables.split('.').collect::<Vec<_>>(), last),
+            None => (Vec::new(), key),
+        };
+
+        let mut table = &self.file;
+        for name in tables {
+            table = table.get(name)?.as_table()?;
+        }
+
+        table.get(last)
+    }
+}
+
+
+// Function to collect the dotted keys of a table which were never read.
+fn collect_unknown(table: &toml::Table, prefix: &str, used: &HashSet<String>, unknown: &mut Vec<String>) {
+    for (name, value) in table.iter() {
+        let key = format!("{}{}", prefix, name);
+
+        if used.contains(&key) {
+            continue;
+        }
+
+        match value {
+            toml::Value::Table(table) => collect_unknown(table, &format!("{}.", key), used, unknown),
+            _ => unknown.push(key),
+        }
+    }
+}
+
+// Function to parse a duration such as "30s", "15m", "1h30m" or "7d".
+// A bare number is a number of seconds.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if value.is_empty() {
+        return None;
+    }
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let mut total: u64 = 0;
+    let mut rest = value;
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+
+        let number: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+
+        let unit_length = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = match &rest[..unit_length] {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            "w" => 604800,
+            _ => return None,
+        };
+        rest = &rest[unit_length..];
+
+        total = total.checked_add(number.checked_mul(unit)?)?;
+    }
+
+    Some(Duration::from_secs(total))
+}
+
+
+// Implementation of the `Display` trait for `ConfigError`.
+impl fmt::Display for ConfigError {
+    // Function to list every problem on its own line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for error in self.errors.iter() {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+// Implementation of the `Error` trait for `ConfigError`.
+impl std::error::Error for ConfigError {}
+
+
+// Implementation of the `Setting` trait for `String`.
+impl Setting for String {
+    const EXPECTED: &'static str = "a string";
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(value.to_string())
+    }
+}
+
+// Implementation of the `Setting` trait for `bool`.
+impl Setting for bool {
+    const EXPECTED: &'static str = "true or false";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+// Implementation of the `Setting` trait for `u16`.
+impl Setting for u16 {
+    const EXPECTED: &'static str = "an integer between 0 and 65535";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+// Implementation of the `Setting` trait for `u32`.
+impl Setting for u32 {
+    const EXPECTED: &'static str = "a non-negative integer";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+// Implementation of the `Setting` trait for `usize`.
+impl Setting for usize {
+    const EXPECTED: &'static str = "a non-negative integer";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+// Implementation of the `Setting` trait for `IpAddr`.
+impl Setting for IpAddr {
+    const EXPECTED: &'static str = "an IP address such as \"0.0.0.0\" or \"::1\"";
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+// Implementation of the `Setting` trait for `Duration`.
+impl Setting for Duration {
+    const EXPECTED: &'static str = "a duration such as \"30s\", \"15m\", \"1h30m\" or \"7d\"";
+
+    fn parse(value: &str) -> Option<Self> {
+        parse_duration(value)
+    }
+}
+
+// Implementation of the `Setting` trait for lists of strings.
+// Environment variables separate items with commas or whitespace.
+impl Setting for Vec<String> {
+    const EXPECTED: &'static str = "a list of strings";
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(
+            value
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect(),
+        )
+    }
+
+    fn from_toml(value: &toml::Value) -> Option<Self> {
+        value.as_array()?.iter().map(|item| item.as_str().map(String::from)).collect()
+    }
+}
+
+// Implementation of the `Setting` trait for lists of named strings.
+// Environment variables write them as comma separated `name=value` pairs, the config file as a table.
+impl Setting for Vec<(String, String)> {
+    const EXPECTED: &'static str = "`name=value` pairs";
+
+    fn parse(value: &str) -> Option<Self> {
+        value
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                Some((name.trim().to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+
+    fn from_toml(value: &toml::Value) -> Option<Self> {
+        value
+            .as_table()?
+            .iter()
+            .map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+            .collect()
+    }
+}
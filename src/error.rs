@@ -1,84 +1,321 @@
 // Importing the necessary modules and functions.
 use axum::http::StatusCode;
+use axum::http::header::RETRY_AFTER;
 use axum::response::{Response, IntoResponse};
+use axum::Json;
 use prisma_client_rust::QueryError;
 use prisma_client_rust::prisma_errors::query_engine::{RecordNotFound, UniqueKeyViolation};
+use serde::Serialize;
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+
+use crate::extractor::validate::FieldError;
+use crate::middleware::request_id;
 
 
 // The `EError` enum which represents different types of errors that can occur in the application.
+// Each variant decides the status code, the `ErrorCode` tells clients which error site it was.
 #[derive(thiserror::Error, Debug)]
 pub enum EError {
 
-    /// Represents a `400 Bad Request` error.
-
     /// Represents a `401 Unauthorized` error.
-    #[error("Unauthorized : {0}")]
-    Unauthorized(String),
+    #[error("Unauthorized : {1}")]
+    Unauthorized(ErrorCode, String),
     /// Represents a `403 Forbidden` error.
-    #[error("Forbidden : {0}")]
-    Forbidden(String),
+    #[error("Forbidden : {1}")]
+    Forbidden(ErrorCode, String),
 
     /// Represents a `404 Not Found` error.
-    #[error("Not found : {0}")]
-    NotFound(String),
+    #[error("Not found : {1}")]
+    NotFound(ErrorCode, String),
+
+    /// Represents a `409 Conflict` error.
+    #[error("Conflict : {1}")]
+    Conflict(ErrorCode, String),
 
     /// Represents a `500 Internal Server Error`.
-    #[error("Internal server error: {0}")]
-    InternalServerError(String),
+    #[error("Internal server error: {1}")]
+    InternalServerError(ErrorCode, String),
 
     /// Represents a Prisma error.
     #[error("Prisma error: {0}")]
     PrismaError(#[from] QueryError),
 
     /// Represents a `400 Bad Request` error.
-    #[error("Bad request : {0}")]
-    BadRequest(String),
+    #[error("Bad request : {1}")]
+    BadRequest(ErrorCode, String),
+
+    /// Represents a `400 Bad Request` error caused by fields failing validation.
+    #[error("Validation failed : {0:?}")]
+    Validation(Vec<FieldError>),
+
+    /// Represents a `413 Payload Too Large` error with the configured limit in bytes.
+    #[error("Request body is larger than the configured {0} byte limit")]
+    PayloadTooLarge(usize),
+
+    /// Represents a `408 Request Timeout` error with the configured timeout in seconds.
+    #[error("Request took longer than the configured {0} second timeout")]
+    RequestTimeout(u64),
+
+    /// Represents a `429 Too Many Requests` error with the seconds to wait.
+    #[error("Too many requests : try again in {0} seconds")]
+    TooManyRequests(i64),
 
     /// Represents a generic error.
     #[error("Internal server error: {0}")]
     Anyhow(#[from] anyhow::Error),
 }
 
+// The `ErrorCode` enum which represents the stable, machine-readable code of an error.
+// Codes are part of the API: never rename one, add a new code instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // The request has no `Authorization` header.
+    MissingAuthorization,
+    // The `Authorization` header is malformed.
+    InvalidAuthorization,
+    // The JWT or API token is invalid.
+    InvalidToken,
+    // The JWT or API token has expired.
+    TokenExpired,
+    // The JWT or API token cannot be used on this route.
+    TokenNotAllowed,
+    // The API token lacks the scope required by the route.
+    MissingScope,
+    // The email or password is wrong.
+    InvalidCredentials,
+    // The account has been suspended by an administrator.
+    AccountSuspended,
+    // The request requires logging in.
+    LoginRequired,
+    // Too many failed attempts, retry later.
+    TooManyRequests,
+    // The request took too long.
+    RequestTimeout,
+    // The request body is larger than the configured limit.
+    PayloadTooLarge,
+    // The request body is not valid JSON or does not match the expected shape.
+    InvalidBody,
+    // The request body failed validation.
+    ValidationFailed,
+    // The user does not exist.
+    UserNotFound,
+    // The post does not exist.
+    PostNotFound,
+    // The comment does not exist.
+    CommentNotFound,
+    // The API token does not exist.
+    ApiTokenNotFound,
+    // The login provider is not configured.
+    ProviderNotFound,
+    // A generic record does not exist.
+    NotFound,
+    // The path could not be decoded.
+    InvalidPath,
+    // The query string is malformed or has values of the wrong type.
+    InvalidQuery,
+    // The post ID is not a positive integer.
+    InvalidPostId,
+    // The comment ID is not a positive integer.
+    InvalidCommentId,
+    // The API token ID is not a positive integer.
+    InvalidTokenId,
+    // The username in the path is empty or contains slashes, whitespace or control characters.
+    InvalidUsername,
+    // The user is not the author of the post.
+    NotPostAuthor,
+    // The user is not the author of the comment.
+    NotCommentAuthor,
+    // The author of the post has blocked the user.
+    BlockedByAuthor,
+    // The post is already liked.
+    AlreadyLiked,
+    // The post is not liked.
+    NotLiked,
+    // The user tried to follow, unfollow, block, unblock, mute or unmute themselves.
+    CannotTargetSelf,
+    // The user already follows the profile.
+    AlreadyFollowing,
+    // The user does not follow the profile.
+    NotFollowing,
+    // The profile has blocked the user.
+    BlockedByUser,
+    // The user has blocked the profile.
+    BlockingUser,
+    // The user already blocks the profile.
+    AlreadyBlocked,
+    // The user does not block the profile.
+    NotBlocking,
+    // The user does not mute the profile.
+    NotMuting,
+    // The user already asked to follow the private profile.
+    AlreadyRequested,
+    // The profile has not asked to follow the user.
+    NotRequested,
+    // The profile is private and the user does not follow it.
+    PrivateProfile,
+    // Two-factor authentication is already enabled.
+    TwoFactorAlreadyEnabled,
+    // Two-factor enrollment has not been started.
+    TwoFactorNotStarted,
+    // Two-factor authentication is not enabled.
+    TwoFactorNotEnabled,
+    // Two-factor authentication is required by the admin policy.
+    TwoFactorRequired,
+    // The request needs a two-factor or recovery code.
+    TwoFactorCodeRequired,
+    // The two-factor or recovery code is wrong.
+    InvalidTwoFactorCode,
+    // The OpenID Connect callback is missing parameters or has an invalid state.
+    InvalidLoginState,
+    // The OpenID Connect provider refused or failed the login.
+    ExternalLoginFailed,
+    // The OpenID Connect provider did not share an email address.
+    EmailRequired,
+    // The OpenID Connect provider has not verified the email of an identity which is not linked to an account yet.
+    EmailNotVerified,
+    // The OpenID Connect provider is unreachable or misbehaving.
+    ProviderError,
+    // The record conflicts with an existing one.
+    Conflict,
+    // The database failed.
+    DatabaseError,
+    // Something unexpected went wrong.
+    InternalError,
+}
 
-// Implementation of the `IntoResponse` trait for the `EError` enum.
-impl IntoResponse for EError {
-    // Function to convert an `EError` into a `Response`.
-    fn into_response(self) -> Response {
-        // Determine the status code based on the type of error.
-        let status = match self {
+// The `ErrorBody` struct which represents the JSON body of every error response.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorBody {
+    // The stable code of the error.
+    pub code: ErrorCode,
+    // The human-readable message of the error.
+    pub message: String,
+    // Additional data about the error, such as field errors.
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+    // The ID of the request, also sent in the `x-request-id` header.
+    pub request_id: Option<String>,
+}
 
+
+// Implementation of the `EError` enum.
+impl EError {
+
+    // Function to get the name of the variant, used to count errors in the metrics.
+    pub fn variant(&self) -> &'static str {
+        match self {
+            EError::Unauthorized(..) => "Unauthorized",
+            EError::Forbidden(..) => "Forbidden",
+            EError::NotFound(..) => "NotFound",
+            EError::Conflict(..) => "Conflict",
+            EError::InternalServerError(..) => "InternalServerError",
+            EError::PrismaError(_) => "PrismaError",
+            EError::BadRequest(..) => "BadRequest",
+            EError::Validation(_) => "Validation",
+            EError::RequestTimeout(_) => "RequestTimeout",
+            EError::PayloadTooLarge(_) => "PayloadTooLarge",
+            EError::TooManyRequests(_) => "TooManyRequests",
+            EError::Anyhow(_) => "Anyhow",
+        }
+    }
+
+    // Function to get the status code, error code, client-facing message and details of the error.
+    // Internal and database errors are logged but never shown to the client.
+    fn parts(&self) -> (StatusCode, ErrorCode, String, Option<Value>) {
+        match self {
             // Handle Prisma errors
             // If the error is a `UniqueKeyViolation`, return a `409 Conflict` status.
-            EError::PrismaError(ref error)
-            if error.is_prisma_error::<UniqueKeyViolation>() => {
-                StatusCode::CONFLICT
-            }
+            EError::PrismaError(error) if error.is_prisma_error::<UniqueKeyViolation>() => (
+                StatusCode::CONFLICT, ErrorCode::Conflict,
+                String::from("A record with the same unique value already exists"), None,
+            ),
             // If the error is a `RecordNotFound`, return a `404 Not Found` status.
-            EError::PrismaError(ref error)
-            if error.is_prisma_error::<RecordNotFound>() => {
-                StatusCode::NOT_FOUND
-            }
+            EError::PrismaError(error) if error.is_prisma_error::<RecordNotFound>() => (
+                StatusCode::NOT_FOUND, ErrorCode::NotFound,
+                String::from("Record not found"), None,
+            ),
+            // For other Prisma errors, return a `500 Internal Server Error` status without the query.
+            EError::PrismaError(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::DatabaseError,
+                String::from("Database error"), None,
+            ),
 
-            // For other Prisma errors, return a `400 Bad Request` status.
-            EError::PrismaError(_) => StatusCode::BAD_REQUEST,
             // For `BadRequest` errors, return a `400 Bad Request` status.
-            EError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            EError::BadRequest(code, message) => (StatusCode::BAD_REQUEST, *code, message.clone(), None),
+            // For `Validation` errors, return a `400 Bad Request` status listing every invalid field.
+            EError::Validation(fields) => (
+                StatusCode::BAD_REQUEST, ErrorCode::ValidationFailed,
+                String::from("Request body failed validation"),
+                Some(json!({ "fields": fields })),
+            ),
             // For `Unauthorized` errors, return a `401 Unauthorized` status.
-            EError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            EError::Unauthorized(code, message) => (StatusCode::UNAUTHORIZED, *code, message.clone(), None),
             // For `Forbidden` errors, return a `403 Forbidden` status.
-            EError::Forbidden(_) => StatusCode::FORBIDDEN,
+            EError::Forbidden(code, message) => (StatusCode::FORBIDDEN, *code, message.clone(), None),
             // For `NotFound` errors, return a `404 Not Found` status.
-            EError::NotFound(_) => StatusCode::NOT_FOUND,
+            EError::NotFound(code, message) => (StatusCode::NOT_FOUND, *code, message.clone(), None),
+            // For `Conflict` errors, return a `409 Conflict` status.
+            EError::Conflict(code, message) => (StatusCode::CONFLICT, *code, message.clone(), None),
+            // For `RequestTimeout` errors, return a `408 Request Timeout` status.
+            EError::RequestTimeout(_) => (
+                StatusCode::REQUEST_TIMEOUT, ErrorCode::RequestTimeout, self.to_string(), None,
+            ),
+            // For `PayloadTooLarge` errors, return a `413 Payload Too Large` status with the limit.
+            EError::PayloadTooLarge(limit) => (
+                StatusCode::PAYLOAD_TOO_LARGE, ErrorCode::PayloadTooLarge, self.to_string(),
+                Some(json!({ "limit": limit })),
+            ),
+            // For `TooManyRequests` errors, return a `429 Too Many Requests` status.
+            EError::TooManyRequests(seconds) => (
+                StatusCode::TOO_MANY_REQUESTS, ErrorCode::TooManyRequests,
+                format!("Too many requests, try again in {} seconds", seconds),
+                Some(json!({ "retryAfter": seconds })),
+            ),
             // For `InternalServerError` errors, return a `500 Internal Server Error` status.
-            EError::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            EError::InternalServerError(code, _) => (
+                StatusCode::INTERNAL_SERVER_ERROR, *code, String::from("Internal server error"), None,
+            ),
             // For generic errors, return a `500 Internal Server Error` status.
-            EError::Anyhow(_) => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+            EError::Anyhow(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::InternalError,
+                String::from("Internal server error"), None,
+            ),
+        }
+    }
+}
 
-        // Log the error.
-        tracing::error!("{:?}", self);
 
-        // Convert the status code and error message into a `Response`.
-        (status, self.to_string()).into_response()
+// Implementation of the `IntoResponse` trait for the `EError` enum.
+impl IntoResponse for EError {
+    // Function to convert an `EError` into a `Response`.
+    fn into_response(self) -> Response {
+        // Determine the status code and body based on the type of error.
+        let (status, code, message, details) = self.parts();
+
+        // Log the error, with the internal details the client does not see.
+        if status.is_server_error() {
+            tracing::error!("{:?}", self);
+        } else {
+            tracing::info!("{:?}", self);
+        }
+        metrics::counter!("wforum_errors_total", "variant" => self.variant()).increment(1);
+
+        let body = Json(ErrorBody {
+            code,
+            message,
+            details,
+            request_id: request_id::current(),
+        });
+
+        // Tell the client when it may retry if the request was throttled.
+        if let EError::TooManyRequests(seconds) = &self {
+            return (status, [(RETRY_AFTER, seconds.to_string())], body).into_response();
+        }
+
+        // Convert the status code and error body into a `Response`.
+        (status, body).into_response()
     }
-}
\ No newline at end of file
+}
@@ -5,7 +5,10 @@ use axum::http::{header::AUTHORIZATION, request::Parts, HeaderValue};
 use prisma_client_rust::chrono;
 
 use crate::config::BeContext;
-use crate::error::EError;
+use crate::error::{EError, ErrorCode};
+use crate::middleware::request_id;
+use crate::service::token::service::{TokenService, API_TOKEN_PREFIX};
+use crate::service::utils::checker::Checker;
 
 
 // Constant for the authorization header scheme.
@@ -23,6 +26,43 @@ pub struct AuthUser {
 #[derive(Debug, Clone)]
 pub struct OptionalAuthUser(pub Option<AuthUser>);
 
+// The `SetupAuthUser` struct which represents a user allowed to enroll in two-factor authentication.
+// It accepts regular tokens as well as the setup tokens issued to users who must enroll before logging in.
+#[derive(Debug, Clone)]
+pub struct SetupAuthUser(pub AuthUser);
+
+// The `TokenKind` enum which represents what a JWT may be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    // A regular session token.
+    #[default]
+    Access,
+    // A token which only allows enrolling in two-factor authentication.
+    Setup,
+    // A token which only allows completing a two-factor login.
+    Challenge,
+}
+
+// The `ApiScope` enum which represents what a personal API token may be used for.
+// Routes declare the scope they require with `Extension(ApiScope::...)`;
+// API tokens are rejected on routes which declare none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+pub enum ApiScope {
+    // Reading posts, comments, profiles and notifications.
+    #[serde(rename = "read")]
+    Read,
+    // Creating, updating, deleting and liking posts.
+    #[serde(rename = "write:posts")]
+    WritePosts,
+    // Creating and deleting comments.
+    #[serde(rename = "write:comments")]
+    WriteComments,
+    // Following, blocking and muting profiles, and answering follow requests.
+    #[serde(rename = "write:profiles")]
+    WriteProfiles,
+}
+
 // The `AuthUserClaims` struct which represents the claims in a JWT.
 #[derive(serde::Serialize, serde::Deserialize)]
 struct AuthUserClaims {
@@ -30,6 +70,35 @@ struct AuthUserClaims {
     user_id: i32,
     // The expiration timestamp of the JWT.
     exp: i64,
+    // The kind of the JWT, tokens issued before kinds existed are access tokens.
+    #[serde(default)]
+    kind: TokenKind,
+}
+
+
+// Implementation of the `ApiScope` enum.
+impl ApiScope {
+
+    // Function to get the name of the scope as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::Read => "read",
+            ApiScope::WritePosts => "write:posts",
+            ApiScope::WriteComments => "write:comments",
+            ApiScope::WriteProfiles => "write:profiles",
+        }
+    }
+
+    // Function to parse the name of a scope, unknown names are ignored.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "read" => Some(ApiScope::Read),
+            "write:posts" => Some(ApiScope::WritePosts),
+            "write:comments" => Some(ApiScope::WriteComments),
+            "write:profiles" => Some(ApiScope::WriteProfiles),
+            _ => None,
+        }
+    }
 }
 
 
@@ -38,61 +107,120 @@ impl AuthUser {
 
     // Function to generate a JWT for the user.
     pub fn gen_jwt(&self, ctx: &BeContext) -> String {
-        let key = jsonwebtoken::EncodingKey::from_secret(ctx.config.jwt_config.jwt_secret.as_ref());
+        self.gen_token(ctx, TokenKind::Access, ctx.config.jwt_config.jwt_exp_value)
+    }
+
+    // Function to generate a short-lived JWT of a restricted kind for the user.
+    pub fn gen_restricted_jwt(&self, ctx: &BeContext, kind: TokenKind) -> String {
+        self.gen_token(ctx, kind, ctx.config.two_factor_config.challenge_exp_value)
+    }
+
+    // Function to generate a JWT of the given kind and lifetime.
+    fn gen_token(&self, ctx: &BeContext, kind: TokenKind, exp_value: i64) -> String {
         let claims = AuthUserClaims {
             user_id: self.user_id,
-            exp: chrono::Utc::now().timestamp() + ctx.config.jwt_config.jwt_exp_value,
+            exp: chrono::Utc::now().timestamp() + exp_value,
+            kind,
         };
 
-        jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &key)
-            .expect("Failed to generate JWT")
+        ctx.jwt_keys.encode(&claims).expect("Failed to generate JWT")
     }
 
     // Function to create an `AuthUser` from an authorization header.
-    fn from_authorization(ctx: &BeContext, auth_header: &HeaderValue) -> Result<Self, EError> {
+    // Only tokens of one of the allowed kinds are accepted.
+    fn from_authorization(
+        ctx: &BeContext,
+        auth_header: &HeaderValue,
+        kinds: &[TokenKind],
+    ) -> Result<Self, EError> {
+        Self::from_token(ctx, Self::bearer_token(auth_header)?, kinds)
+    }
+
+    // Function to create an `AuthUser` from an authorization header holding an access JWT
+    // or a personal API token carrying the scope required by the route.
+    // Suspended users are refused, whichever token they send.
+    async fn from_request_authorization(
+        ctx: &BeContext,
+        parts: &Parts,
+        auth_header: &HeaderValue,
+    ) -> Result<Self, EError> {
+
+        let token = Self::bearer_token(auth_header)?;
+
+        if !token.starts_with(API_TOKEN_PREFIX) {
+            let auth_user = Self::from_token(ctx, token, &[TokenKind::Access])?;
+            auth_user.check_active(ctx).await?;
+            return Ok(auth_user);
+        }
+
+        let scope = parts.extensions.get::<ApiScope>().copied().ok_or_else(|| {
+            tracing::info!("API token used on a route without a scope");
+            EError::Forbidden(ErrorCode::TokenNotAllowed, String::from("API tokens cannot be used here"))
+        })?;
+
+        let user_id = TokenService::authenticate(&*ctx.repository, token, scope).await?;
+        request_id::record_user(user_id);
+
+        let auth_user = Self { user_id };
+        auth_user.check_active(ctx).await?;
+        Ok(auth_user)
+    }
+
+    // Function to check that the user is not suspended, as their JWTs stay signed until they expire.
+    // Users who no longer exist are left to the handlers, which report them as not found.
+    async fn check_active(&self, ctx: &BeContext) -> Result<(), EError> {
+        if let Some(user_data) = ctx.repository.find_user(self.user_id).await? {
+            Checker::check_active(&user_data).await?;
+        }
+        Ok(())
+    }
+
+    // Function to get the token from an authorization header using the bearer scheme.
+    fn bearer_token(auth_header: &HeaderValue) -> Result<&str, EError> {
 
         let auth_header = auth_header.to_str().map_err(|_| {
             tracing::info!("Authorization header is not UTF-8");
-            EError::Unauthorized(String::from("Authorization header is not UTF-8"))
+            EError::Unauthorized(
+                ErrorCode::InvalidAuthorization, String::from("Authorization header is not UTF-8"),
+            )
         })?;
 
         if !auth_header.starts_with(AUTH_HEADER_SCHEME) {
             tracing::info!(
                 "Authorization header is using the wrong scheme: {:?}",auth_header
             );
-            return Err(EError::Unauthorized(String::from(
+            return Err(EError::Unauthorized(ErrorCode::InvalidAuthorization, String::from(
                 "Authorization header is using the wrong scheme",
             )));
         }
 
-        let token = &auth_header[AUTH_HEADER_SCHEME.len()..];
+        Ok(&auth_header[AUTH_HEADER_SCHEME.len()..])
+    }
+
+    // Function to create an `AuthUser` from a raw JWT.
+    // Only tokens of one of the allowed kinds are accepted.
+    pub fn from_token(ctx: &BeContext, token: &str, kinds: &[TokenKind]) -> Result<Self, EError> {
 
         tracing::debug!("Incoming token: {:?}", token);
 
-        let jwt = jsonwebtoken::decode::<AuthUserClaims>(
-            token,
-            &jsonwebtoken::DecodingKey::from_secret(ctx.config.jwt_config.jwt_secret.as_ref()),
-            &jsonwebtoken::Validation::default(),
-        )
-            .map_err(|e| {
-                tracing::info!("JWT validation failed: {:?}", e);
-                EError::Unauthorized(String::from("JWT validation failed"))
-            })?;
-
-        let jsonwebtoken::TokenData { header, claims } = jwt;
-
-        if header.alg != jsonwebtoken::Algorithm::HS256 {
-            tracing::info!("JWT is using the wrong algorithm: {:?}", header.alg);
-            return Err(EError::Unauthorized(String::from(
-                "JWT is using the wrong algorithm",
-            )));
-        }
+        // The signature, key ID and algorithm are checked against the configured keys.
+        let jsonwebtoken::TokenData { claims, .. } =
+            ctx.jwt_keys.decode::<AuthUserClaims>(token)?;
 
         if claims.exp < prisma_client_rust::chrono::Utc::now().timestamp() {
             tracing::info!("JWT is expired");
-            return Err(EError::Unauthorized(String::from("JWT is expired")));
+            return Err(EError::Unauthorized(ErrorCode::TokenExpired, String::from("JWT is expired")));
         }
 
+        if !kinds.contains(&claims.kind) {
+            tracing::info!("JWT is of the wrong kind: {:?}", claims.kind);
+            return Err(EError::Unauthorized(
+                ErrorCode::TokenNotAllowed, String::from("JWT cannot be used here"),
+            ));
+        }
+
+        request_id::record_user(claims.user_id);
+
         Ok(Self {
             user_id: claims.user_id,
         })
@@ -126,11 +254,11 @@ impl<S> FromRequestParts<S> for AuthUser
 
         let auth_header = parts
             .headers.get(AUTHORIZATION)
-            .ok_or(EError::Unauthorized(String::from(
+            .ok_or(EError::Unauthorized(ErrorCode::MissingAuthorization, String::from(
                 "Missing Authorization header",
             )))?;
 
-        Self::from_authorization(&ctx, auth_header)
+        Self::from_request_authorization(&ctx, parts, auth_header).await
     }
 }
 
@@ -148,11 +276,44 @@ impl<S> FromRequestParts<S> for OptionalAuthUser
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let ctx: BeContext = BeContext::from_ref(state);
 
-        Ok(Self(
-            parts
-                .headers.get(AUTHORIZATION)
-                .map(|auth_header| AuthUser::from_authorization(&ctx, auth_header).ok())
-                .flatten(),
-        ))
+        // Invalid tokens are ignored, but suspended users are not served as anonymous readers.
+        let auth_user = match parts.headers.get(AUTHORIZATION) {
+            Some(auth_header) => match AuthUser::from_request_authorization(&ctx, parts, auth_header).await {
+                Ok(auth_user) => Some(auth_user),
+                Err(error @ EError::Forbidden(ErrorCode::AccountSuspended, _)) => return Err(error),
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        Ok(Self(auth_user))
+    }
+}
+
+
+// Implementation of the `FromRequestParts` trait for `SetupAuthUser`.
+#[async_trait]
+impl<S> FromRequestParts<S> for SetupAuthUser
+    where
+        S: Send + Sync,
+        BeContext: FromRef<S>,
+{
+    type Rejection = EError;
+
+    // Function to create a `SetupAuthUser` from request parts.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+
+        let ctx: BeContext = BeContext::from_ref(state);
+
+        let auth_header = parts
+            .headers.get(AUTHORIZATION)
+            .ok_or(EError::Unauthorized(ErrorCode::MissingAuthorization, String::from(
+                "Missing Authorization header",
+            )))?;
+
+        let auth_user = AuthUser::from_authorization(&ctx, auth_header, &[TokenKind::Access, TokenKind::Setup])?;
+        auth_user.check_active(&ctx).await?;
+
+        Ok(Self(auth_user))
     }
 }
@@ -0,0 +1,226 @@
+// Importing the necessary modules and functions.
+use std::collections::HashMap;
+use std::str::FromStr;
+use anyhow::{anyhow, Context};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use rsa::RsaPublicKey;
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::traits::PublicKeyParts;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::config::config::JwtConfig;
+use crate::error::{EError, ErrorCode};
+
+
+// The `VerificationKey` struct which represents a key accepted for verifying JWTs.
+struct VerificationKey {
+    // The algorithm tokens signed with this key must use.
+    algorithm: Algorithm,
+    // The key used for verification.
+    decoding: DecodingKey,
+    // The public key published in the JWKS, if the key is asymmetric.
+    jwk: Option<Jwk>,
+}
+
+// The `JwtKeys` struct.
+// This struct holds the key used for signing JWTs and every key accepted for verifying them,
+// so keys can be rotated by publishing the new public key before switching the signing key.
+pub struct JwtKeys {
+    // The key ID of the signing key.
+    kid: String,
+    // The algorithm of the signing key.
+    algorithm: Algorithm,
+    // The key used for signing.
+    encoding: EncodingKey,
+    // The keys accepted for verification by key ID.
+    verification: HashMap<String, VerificationKey>,
+}
+
+
+// Implementation of the `JwtKeys` struct.
+impl JwtKeys {
+
+    // Function to load the keys described by the JWT configuration.
+    pub fn load(config: &JwtConfig) -> anyhow::Result<Self> {
+
+        let algorithm = Algorithm::from_str(&config.jwt_algorithm)
+            .map_err(|_| anyhow!("Unsupported JWT algorithm: {}", config.jwt_algorithm))?;
+
+        // Symmetric keys are never published, so the shared secret is the only key.
+        if algorithm == Algorithm::HS256 {
+            let verification = VerificationKey {
+                algorithm,
+                decoding: DecodingKey::from_secret(config.jwt_secret.as_ref()),
+                jwk: None,
+            };
+
+            return Ok(Self {
+                kid: config.jwt_kid.clone(),
+                algorithm,
+                encoding: EncodingKey::from_secret(config.jwt_secret.as_ref()),
+                verification: HashMap::from([(config.jwt_kid.clone(), verification)]),
+            });
+        }
+
+        let signing_key_file = config.jwt_signing_key_file.as_ref()
+            .ok_or(anyhow!("JWT_SIGNING_KEY_FILE must be set for {:?}", algorithm))?;
+        let signing_key = std::fs::read(signing_key_file)
+            .with_context(|| format!("Failed to read JWT signing key {}", signing_key_file))?;
+
+        let encoding = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&signing_key)?,
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(&signing_key)?,
+            _ => return Err(anyhow!("Unsupported JWT algorithm: {:?}", algorithm)),
+        };
+
+        let mut verification = HashMap::new();
+
+        for (kid, path) in config.jwt_verification_key_files.iter() {
+            let pem = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read JWT verification key {}", path))?;
+            let key = Self::load_public_key(kid, &pem)
+                .with_context(|| format!("Invalid JWT verification key {}", path))?;
+
+            verification.insert(kid.clone(), key);
+        }
+
+        if !verification.contains_key(&config.jwt_kid) {
+            return Err(anyhow!(
+                "JWT_VERIFICATION_KEYS must contain the public key of the signing key {}", config.jwt_kid,
+            ));
+        }
+
+        Ok(Self {
+            kid: config.jwt_kid.clone(),
+            algorithm,
+            encoding,
+            verification,
+        })
+    }
+
+    // Function to sign claims with the signing key.
+    pub fn encode<T: Serialize>(&self, claims: &T) -> jsonwebtoken::errors::Result<String> {
+
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.kid.clone());
+
+        jsonwebtoken::encode(&header, claims, &self.encoding)
+    }
+
+    // Function to verify a token with the key named by its `kid` header.
+    // Tokens without a `kid` are verified with the signing key, as issued before keys had IDs.
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<TokenData<T>, EError> {
+
+        let invalid = |e: jsonwebtoken::errors::Error| {
+            tracing::info!("JWT validation failed: {:?}", e);
+            EError::Unauthorized(ErrorCode::InvalidToken, String::from("JWT validation failed"))
+        };
+
+        let header = jsonwebtoken::decode_header(token).map_err(invalid)?;
+        let kid = header.kid.unwrap_or(self.kid.clone());
+
+        let key = self.verification.get(&kid).ok_or_else(|| {
+            tracing::info!("JWT is signed with an unknown key: {:?}", kid);
+            EError::Unauthorized(ErrorCode::InvalidToken, String::from("JWT is signed with an unknown key"))
+        })?;
+
+        // Only the algorithm of the key itself is accepted, so a public key can never be used as a secret.
+        if header.alg != key.algorithm {
+            tracing::info!("JWT is using the wrong algorithm: {:?}", header.alg);
+            return Err(EError::Unauthorized(
+                ErrorCode::InvalidToken, String::from("JWT is using the wrong algorithm"),
+            ));
+        }
+
+        jsonwebtoken::decode::<T>(token, &key.decoding, &Validation::new(key.algorithm))
+            .map_err(invalid)
+    }
+
+    // Function to get the public verification keys as a JWKS.
+    pub fn jwks(&self) -> JwkSet {
+
+        let mut keys: Vec<Jwk> = self.verification
+            .values()
+            .filter_map(|key| key.jwk.clone())
+            .collect();
+
+        keys.sort_by(|a, b| a.common.key_id.cmp(&b.common.key_id));
+
+        JwkSet { keys }
+    }
+
+    // Function to load a PEM encoded RSA or Ed25519 public key.
+    fn load_public_key(kid: &str, pem: &str) -> anyhow::Result<VerificationKey> {
+
+        let common = |algorithm: KeyAlgorithm| CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(algorithm),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        };
+
+        let jwk = if pem.contains("BEGIN RSA PUBLIC KEY") || Self::is_rsa_spki(pem) {
+            let key = RsaPublicKey::from_pkcs1_pem(pem)
+                .or_else(|_| RsaPublicKey::from_public_key_pem(pem))?;
+
+            Jwk {
+                common: common(KeyAlgorithm::RS256),
+                algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                }),
+            }
+        } else {
+            // An Ed25519 SubjectPublicKeyInfo ends with the 32 byte raw public key.
+            let der = Self::pem_to_der(pem)?;
+            if der.len() != 44 {
+                return Err(anyhow!("Expected an RSA or Ed25519 public key"));
+            }
+
+            Jwk {
+                common: common(KeyAlgorithm::EdDSA),
+                algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(&der[12..]),
+                }),
+            }
+        };
+
+        let algorithm = match jwk.algorithm {
+            AlgorithmParameters::RSA(_) => Algorithm::RS256,
+            _ => Algorithm::EdDSA,
+        };
+
+        Ok(VerificationKey {
+            algorithm,
+            decoding: DecodingKey::from_jwk(&jwk)?,
+            jwk: Some(jwk),
+        })
+    }
+
+    // Function to check whether a PEM encoded SubjectPublicKeyInfo holds an RSA key.
+    fn is_rsa_spki(pem: &str) -> bool {
+        RsaPublicKey::from_public_key_pem(pem).is_ok()
+    }
+
+    // Function to decode the body of a PEM file.
+    fn pem_to_der(pem: &str) -> anyhow::Result<Vec<u8>> {
+
+        let body: String = pem
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .map(str::trim)
+            .collect();
+
+        Ok(STANDARD.decode(body)?)
+    }
+}
@@ -1,3 +1,15 @@
 // The `extractor` module.
 // This module contains functionality for extracting data from HTTP requests.
-pub mod extractor;
\ No newline at end of file
+pub mod extractor;
+
+// The `keys` module.
+// This module contains the keys used for signing and verifying JWTs.
+pub mod keys;
+
+// The `path` module.
+// This module contains the typed extractors for path segments.
+pub mod path;
+
+// The `validate` module.
+// This module contains the validating JSON extractor and the validation rules.
+pub mod validate;
@@ -0,0 +1,127 @@
+// Importing the necessary modules and functions.
+use std::collections::HashMap;
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Path};
+use axum::http::request::Parts;
+
+use crate::error::{EError, ErrorCode};
+
+
+// The longest username accepted in a path, longer ones cannot exist.
+const USERNAME_MAX: usize = 255;
+
+
+// The `PostId` struct which represents the `:post_id` segment of a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostId(pub i32);
+
+// The `CommentId` struct which represents the `:comment_id` segment of a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommentId(pub i32);
+
+// The `TokenId` struct which represents the `:token_id` segment of a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenId(pub i32);
+
+// The `Username` struct which represents the `:username` segment of a path.
+// Only obviously malformed names are rejected here, so accounts created
+// before usernames were validated can still be looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Username(pub String);
+
+
+// Implementation of the `FromRequestParts` trait for `PostId`.
+#[async_trait]
+impl<S> FromRequestParts<S> for PostId
+where
+    S: Send + Sync,
+{
+    type Rejection = EError;
+
+    // Function to parse the post ID, rejecting anything but a positive integer.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let value = path_param(parts, state, "post_id").await?;
+        parse_id(&value)
+            .map(PostId)
+            .ok_or_else(|| EError::BadRequest(ErrorCode::InvalidPostId, String::from("Invalid post id")))
+    }
+}
+
+
+// Implementation of the `FromRequestParts` trait for `CommentId`.
+#[async_trait]
+impl<S> FromRequestParts<S> for CommentId
+where
+    S: Send + Sync,
+{
+    type Rejection = EError;
+
+    // Function to parse the comment ID, rejecting anything but a positive integer.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let value = path_param(parts, state, "comment_id").await?;
+        parse_id(&value)
+            .map(CommentId)
+            .ok_or_else(|| EError::BadRequest(ErrorCode::InvalidCommentId, String::from("Invalid comment id")))
+    }
+}
+
+
+// Implementation of the `FromRequestParts` trait for `TokenId`.
+#[async_trait]
+impl<S> FromRequestParts<S> for TokenId
+where
+    S: Send + Sync,
+{
+    type Rejection = EError;
+
+    // Function to parse the API token ID, rejecting anything but a positive integer.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let value = path_param(parts, state, "token_id").await?;
+        parse_id(&value)
+            .map(TokenId)
+            .ok_or_else(|| EError::BadRequest(ErrorCode::InvalidTokenId, String::from("Invalid API token id")))
+    }
+}
+
+
+// Implementation of the `FromRequestParts` trait for `Username`.
+#[async_trait]
+impl<S> FromRequestParts<S> for Username
+where
+    S: Send + Sync,
+{
+    type Rejection = EError;
+
+    // Function to get the username, rejecting empty names, slashes, whitespace and control characters.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let value = path_param(parts, state, "username").await?;
+
+        let valid = !value.is_empty()
+            && value.chars().count() <= USERNAME_MAX
+            && !value.chars().any(|c| c == '/' || c.is_whitespace() || c.is_control());
+
+        if !valid {
+            return Err(EError::BadRequest(ErrorCode::InvalidUsername, String::from("Invalid username")));
+        }
+
+        Ok(Username(value))
+    }
+}
+
+
+// Function to get a named segment of the matched path.
+// It returns a `Result` with the percent-decoded segment, or an error if the route does not capture it.
+async fn path_param<S: Send + Sync>(parts: &mut Parts, state: &S, name: &str) -> Result<String, EError> {
+    let Path(mut params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+        .await
+        .map_err(|rejection| EError::BadRequest(ErrorCode::InvalidPath, rejection.body_text()))?;
+
+    params.remove(name).ok_or_else(|| EError::InternalServerError(
+        ErrorCode::InternalError, format!("Route does not capture a `{}` path segment", name),
+    ))
+}
+
+// Function to parse a database ID, which is always a positive integer.
+fn parse_id(value: &str) -> Option<i32> {
+    value.parse().ok().filter(|id: &i32| *id > 0)
+}
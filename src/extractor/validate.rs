@@ -0,0 +1,232 @@
+// Importing the necessary modules and functions.
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Query, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::BeContext;
+use crate::config::config::ValidationConfig;
+use crate::error::{EError, ErrorCode};
+
+
+// The `ValidJson` struct which represents a JSON request body that passed validation.
+// It is used like `Json`, but rejects bodies which fail `Validate` with field-level errors.
+#[derive(Debug, Clone)]
+pub struct ValidJson<T>(pub T);
+
+// The `ValidQuery` struct which represents a query string that passed validation.
+// It is used like `Query`, but rejects malformed or out of range values with the usual JSON errors.
+#[derive(Debug, Clone)]
+pub struct ValidQuery<T>(pub T);
+
+// The `FieldError` struct which represents why a single field failed validation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    // The dotted path of the field, such as "post.title".
+    pub field: String,
+    // The stable code of the rule which failed, such as "too_long".
+    pub code: &'static str,
+    // The human-readable message of the error.
+    pub message: String,
+}
+
+// The `Validator` struct which collects the field errors of a request body.
+// Only the first error of each field is kept.
+pub struct Validator<'a> {
+    // The configured limits.
+    limits: &'a ValidationConfig,
+    // The names of the objects the validator is currently inside.
+    path: Vec<&'static str>,
+    // The errors found so far.
+    errors: Vec<FieldError>,
+}
+
+
+// The `Validate` trait which is implemented by request bodies.
+pub trait Validate {
+    // Function to check every field of the body, reporting failures to the validator.
+    fn validate(&self, validator: &mut Validator);
+}
+
+
+// Implementation of the `Validator` struct.
+impl<'a> Validator<'a> {
+
+    // Function to create a validator enforcing the given limits.
+    pub fn new(limits: &'a ValidationConfig) -> Self {
+        Self { limits, path: Vec::new(), errors: Vec::new() }
+    }
+
+    // Function to get the configured limits.
+    pub fn limits(&self) -> &'a ValidationConfig {
+        self.limits
+    }
+
+    // Function to validate a nested object, prefixing its field names with `name`.
+    pub fn nested(&mut self, name: &'static str, validate: impl FnOnce(&mut Self)) {
+        self.path.push(name);
+        validate(self);
+        self.path.pop();
+    }
+
+    // Function to check that a field is between `min` and `max` characters long.
+    // A `min` above zero also rejects blank values.
+    pub fn length(&mut self, field: &str, value: &str, min: usize, max: usize) -> &mut Self {
+        let length = value.chars().count();
+
+        if min > 0 && value.trim().is_empty() {
+            self.fail(field, "required", String::from("is required"));
+        } else if length < min {
+            self.fail(field, "too_short", format!("must be at least {} characters", min));
+        } else if length > max {
+            self.fail(field, "too_long", format!("must be at most {} characters", max));
+        }
+
+        self
+    }
+
+    // Function to check that a field is an email address of at most `max` characters.
+    pub fn email(&mut self, field: &str, value: &str, max: usize) -> &mut Self {
+        self.length(field, value, 1, max);
+
+        if !is_email(value) {
+            self.fail(field, "invalid_email", String::from("must be a valid email address"));
+        }
+
+        self
+    }
+
+    // Function to check that a field is a username of `min` to `max` letters, digits, `_` or `-`.
+    // Usernames appear in URLs, so anything else is rejected.
+    pub fn username(&mut self, field: &str, value: &str, min: usize, max: usize) -> &mut Self {
+        self.length(field, value, min, max);
+
+        if !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            self.fail(field, "invalid_username", String::from(
+                "may only contain letters, digits, underscores and hyphens",
+            ));
+        }
+
+        self
+    }
+
+    // Function to check that a field is an http or https URL of at most `max` characters.
+    pub fn url(&mut self, field: &str, value: &str, max: usize) -> &mut Self {
+        self.length(field, value, 1, max);
+
+        let valid = reqwest::Url::parse(value)
+            .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some());
+
+        if !valid {
+            self.fail(field, "invalid_url", String::from("must be an http or https URL"));
+        }
+
+        self
+    }
+
+    // Function to check that a number is between `min` and `max`.
+    pub fn range(&mut self, field: &str, value: i64, min: i64, max: i64) -> &mut Self {
+        if value < min || value > max {
+            self.fail(field, "out_of_range", format!("must be between {} and {}", min, max));
+        }
+
+        self
+    }
+
+    // Function to finish validation.
+    // It returns a `Result` with nothing, or an error listing every invalid field.
+    pub fn finish(self) -> Result<(), EError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(EError::Validation(self.errors))
+        }
+    }
+
+    // Function to record a failed rule, unless the field already failed another one.
+    fn fail(&mut self, field: &str, code: &'static str, message: String) {
+        let field = self.path.iter().copied().chain([field]).collect::<Vec<_>>().join(".");
+
+        if self.errors.iter().all(|error| error.field != field) {
+            self.errors.push(FieldError { field, code, message });
+        }
+    }
+}
+
+
+// Implementation of the `FromRequest` trait for `ValidJson`.
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+    BeContext: FromRef<S>,
+{
+    type Rejection = EError;
+
+    // Function to parse the JSON body and validate it against the configured limits.
+    // Bodies over the configured size limit are rejected with `413 Payload Too Large`.
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let ctx = BeContext::from_ref(state);
+
+        let Json(value) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(|rejection| match rejection.status() {
+                StatusCode::PAYLOAD_TOO_LARGE => EError::PayloadTooLarge(ctx.config.server_config.body_limit),
+                _ => EError::BadRequest(ErrorCode::InvalidBody, rejection.body_text()),
+            })?;
+
+        let mut validator = Validator::new(&ctx.config.validation_config);
+        value.validate(&mut validator);
+        validator.finish()?;
+
+        Ok(ValidJson(value))
+    }
+}
+
+
+// Implementation of the `FromRequestParts` trait for `ValidQuery`.
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ValidQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+    BeContext: FromRef<S>,
+{
+    type Rejection = EError;
+
+    // Function to parse the query string and validate it against the configured limits.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| EError::BadRequest(ErrorCode::InvalidQuery, rejection.body_text()))?;
+
+        let ctx = BeContext::from_ref(state);
+        let mut validator = Validator::new(&ctx.config.validation_config);
+        value.validate(&mut validator);
+        validator.finish()?;
+
+        Ok(ValidQuery(value))
+    }
+}
+
+
+// Function to check whether a value looks like an email address.
+// Only the shape is checked, deliverability is up to the mail server.
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+
+    let labels: Vec<&str> = domain.split('.').collect();
+
+    !local.is_empty()
+        && !local.chars().any(|c| c.is_whitespace() || c == '@')
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
@@ -1,3 +1,7 @@
+// The `cache` module.
+// This module contains the cache for hot reads.
+pub mod cache;
+
 // The `config` module.
 // This module contains the configuration settings for the application.
 pub mod config;
@@ -10,10 +14,22 @@ pub mod error;
 // This module contains functionality for extracting data from HTTP requests.
 pub mod extractor;
 
+// The `middleware` module.
+// This module contains the middleware wrapping every request.
+pub mod middleware;
+
 // The `prisma` module.
 // This module contains functionality for interacting with the Prisma ORM.
 pub mod prisma;
 
+// The `repository` module.
+// This module contains the storage the services read and write through, in the database or in memory.
+pub mod repository;
+
 // The `service` module.
 // This module contains the business logic for the application.
 pub mod service;
+
+// The `tls` module.
+// This module contains functionality for serving HTTPS and redirecting plain HTTP to it.
+pub mod tls;
@@ -1,15 +1,27 @@
 // Importing the necessary modules and services.
 use std::sync::Arc;
+use std::net::SocketAddr;
+use std::time::Duration;
 use anyhow::Context;
-use serde_json::json;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use wforum_backend::config::{CONFIG, CONTEXT};
+use wforum_backend::config::{BeContext, CONFIG};
+use wforum_backend::config::config::BeConfig;
+use wforum_backend::extractor::keys::JwtKeys;
+use wforum_backend::error::{EError, ErrorCode};
+use wforum_backend::middleware::conditional::conditional;
+use wforum_backend::middleware::cors;
+use wforum_backend::middleware::metrics::{self, PROMETHEUS};
+use wforum_backend::middleware::request_id::request_id;
 use wforum_backend::prisma::prisma::PrismaClient;
+use wforum_backend::repository::PrismaRepository;
+use wforum_backend::service::health::HealthRouter;
+use wforum_backend::service::metrics::MetricsRouter;
+use wforum_backend::tls;
 use tower::ServiceBuilder;
+use tower_http::compression::CompressionLayer;
 use tower_http::trace::TraceLayer;
-use tower_http::cors::{Any, CorsLayer};
-use axum::http::StatusCode;
-use axum::{ BoxError, Extension, Json};
+use axum::{ BoxError, Extension};
+use axum::extract::{DefaultBodyLimit, Request};
 use axum::error_handling::HandleErrorLayer;
 
 
@@ -17,65 +29,180 @@ use axum::error_handling::HandleErrorLayer;
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
 
-    // Setting up the tracing subscriber with the log level from the configuration.
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(&CONFIG.log_level))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Reading the command line: `--config <path>` selects the config file,
+    // `--check-config` validates the configuration and exits.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--config") {
+        match args.get(index + 1) {
+            Some(path) => std::env::set_var("WFORUM_CONFIG", path),
+            None => {
+                eprintln!("--config needs a path");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // Loading the configuration up front, so every problem is reported at once instead of panicking.
+    let config = match BeConfig::load() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    if args.iter().any(|arg| arg == "--check-config") {
+        if let Err(error) = JwtKeys::load(&config.jwt_config) {
+            eprintln!("Invalid configuration:\n  - {:#}", error);
+            std::process::exit(1);
+        }
+        println!("Configuration is valid");
+        return Ok(());
+    }
+
+    // Setting up the tracing subscriber with the log level and format from the configuration.
+    // JSON logs carry the fields of the request span, such as the request and user IDs, on every line.
+    let registry = tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(&CONFIG.log_level));
+    match CONFIG.log_format.as_str() {
+        "json" => registry
+            .with(tracing_subscriber::fmt::layer().json().with_current_span(true).with_span_list(false))
+            .init(),
+        _ => registry.with(tracing_subscriber::fmt::layer()).init(),
+    }
+
+    // Installing the Prometheus recorder before anything is recorded.
+    lazy_static::initialize(&PROMETHEUS);
 
     // Creating a new Prisma client.
     let prisma_client = Arc::new(PrismaClient::_builder().build().await?);
-
-    // Setting up CORS with the `CorsLayer`.
-    let cors = CorsLayer::new()
-        .allow_methods(Any).allow_headers(Any).allow_origin(Any);
+    // Creating the application context, storing the services' records in the database through it.
+    let ctx = BeContext::new(Arc::new(PrismaRepository::new(Extension(prisma_client.clone()))));
 
     // Creating the application with the defined routes and middleware.
     let app = wforum_backend::service::Router::new()
-        .layer(cors)
-        .layer(Extension(prisma_client))
+        .layer(DefaultBodyLimit::max(CONFIG.server_config.body_limit))
+        .layer(cors::layer(&CONFIG.cors_config))
+        // Compressing responses as the client accepts, then tagging them, so each encoding has its own tag.
+        .layer(CompressionLayer::new())
+        .layer(axum::middleware::from_fn(conditional))
         .layer(
             ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
+                // Logging inside the span opened by `request_id`, instead of a second one without the IDs.
+                .layer(TraceLayer::new_for_http().make_span_with(|_: &Request| tracing::Span::current()))
                 .layer(HandleErrorLayer::new(handle_timeout_error))
-                .timeout(std::time::Duration::from_secs(30)),
+                .timeout(Duration::from_secs(CONFIG.server_config.request_timeout)),
         )
-        .with_state(CONTEXT.clone());
+        // Counting requests by matched route, outside the timeout so timed out requests are counted too.
+        .layer(axum::middleware::from_fn(metrics::track))
+        // Merging the probes and the scrape endpoint after the tracing and metrics layers,
+        // so orchestrators and Prometheus polling them do not flood the logs.
+        .merge(HealthRouter::new().layer(Extension(prisma_client)))
+        .merge(MetricsRouter::new())
+        // Assigning an ID to every request, outermost so even timeouts carry it.
+        .layer(axum::middleware::from_fn(request_id))
+        .with_state(ctx.clone());
+
+    // Telling background tasks to stop once the server starts shutting down.
+    let (shutdown_sender, shutdown_receiver) = tokio::sync::watch::channel(false);
+
+    // Periodically dropping stale failed login records until shutdown.
+    let mut prune_shutdown = shutdown_receiver.clone();
+    let prune_task = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => ctx.login_guard.prune(),
+                _ = prune_shutdown.changed() => break,
+            }
+        }
+    });
+
+    // Stopping to accept connections on SIGTERM or SIGINT, and letting in-flight requests finish,
+    // but no longer than the shutdown timeout.
+    let handle = axum_server::Handle::new();
+    let shutdown_handle = handle.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = shutdown_sender.send(true);
+        shutdown_handle.graceful_shutdown(Some(Duration::from_secs(CONFIG.server_config.shutdown_timeout)));
+    });
+
+    // Serving with connection info so handlers can see the client's address.
+    let address = SocketAddr::new(CONFIG.server_config.bind_address, CONFIG.backend_port);
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    let (served, reload_task) = match &CONFIG.tls_config {
+        None => {
+            tracing::info!("starting server on http://{}", address);
+            (axum_server::bind(address).handle(handle).serve(app).await, None)
+        }
+        Some(tls_config) => {
+            // Loading the certificate up front, so a broken one stops the backend before it listens.
+            let rustls = tls::load(tls_config).await?;
+            let reload_task = tls::watch(tls_config.clone(), rustls.clone(), shutdown_receiver.clone());
 
-    // Logging the start of the server.
-    tracing::info!("starting server on port {}", CONFIG.backend_port);
+            // Redirecting plain HTTP to HTTPS on the redirect port, if one is configured.
+            if let Some(redirect_port) = tls_config.redirect_port {
+                let redirect_address = SocketAddr::new(CONFIG.server_config.bind_address, redirect_port);
+                let redirect = axum_server::bind(redirect_address)
+                    .handle(handle.clone())
+                    .serve(tls::redirect_router(CONFIG.backend_port).into_make_service());
+                tracing::info!("redirecting http://{} to HTTPS", redirect_address);
+                tokio::spawn(async move {
+                    if let Err(error) = redirect.await {
+                        tracing::error!("failed to serve the redirect on {}: {}", redirect_address, error);
+                    }
+                });
+            }
 
-    // Binding the server to the configured port and starting it.
-    let listener = tokio::net::TcpListener::bind(
-        &format!("0.0.0.0:{}", CONFIG.backend_port))
-        .await
-        .unwrap();
-    axum::serve(listener, app)
-        .await
-        .context("error while booting server")?;
+            tracing::info!("starting server on https://{}", address);
+            (axum_server::bind_rustls(address, rustls).handle(handle).serve(app).await, Some(reload_task))
+        }
+    };
+    served.with_context(|| format!("error while serving on {}", address))?;
+
+    let _ = prune_task.await;
+    if let Some(reload_task) = reload_task {
+        let _ = reload_task.await;
+    }
+    tracing::info!("server stopped");
 
     Ok(())
 }
 
 
+// Function to wait for SIGINT, or SIGTERM on Unix, which orchestrators send before killing the process.
+async fn shutdown_signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {},
+        _ = terminate => {},
+    }
+
+    tracing::info!("shutdown signal received, draining in-flight requests");
+}
+
+
 // Function to handle timeout errors.
-// It takes a boxed error as a parameter and returns a tuple with a status code and a JSON response.
-async fn handle_timeout_error(err: BoxError) -> (StatusCode, Json<serde_json::Value>) {
+// It takes a boxed error as a parameter and returns it as an `EError` with the usual JSON body.
+async fn handle_timeout_error(err: BoxError) -> EError {
     if err.is::<tower::timeout::error::Elapsed>() {
-        (
-            StatusCode::REQUEST_TIMEOUT,
-            Json(json!({
-                "error":
-                    format!(
-                        "request took longer than the configured {} second timeout",
-                        30
-                    )
-            })),
-        )
+        EError::RequestTimeout(CONFIG.server_config.request_timeout)
     } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": format!("unhandled internal error: {}", err) })),
-        )
+        EError::InternalServerError(ErrorCode::InternalError, format!("unhandled internal error: {}", err))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,163 @@
+// Importing the necessary modules and functions.
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::Request;
+use axum::http::header::{
+    AUTHORIZATION, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, VARY,
+};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, IntoResponseParts, Response, ResponseParts};
+use futures_util::{future, stream, StreamExt};
+use prisma_client_rust::chrono::{DateTime, FixedOffset, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::error::{EError, ErrorCode};
+
+
+// The headers a `304 Not Modified` response keeps from the response it replaces.
+const KEPT_HEADERS: [axum::http::HeaderName; 4] = [ETAG, LAST_MODIFIED, VARY, CACHE_CONTROL];
+// The largest body which is buffered to be tagged; larger responses are sent as they are.
+pub const MAX_TAGGED_BODY: usize = 1024 * 1024;
+
+
+// The `LastModified` struct which sets the `Last-Modified` header of a response.
+// Handlers return it next to their JSON body with the time the returned data last changed, if known.
+pub struct LastModified(pub Option<DateTime<FixedOffset>>);
+
+// Implementation of the `IntoResponseParts` trait for `LastModified`.
+impl IntoResponseParts for LastModified {
+    type Error = std::convert::Infallible;
+
+    fn into_response_parts(self, mut parts: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Some(time) = self.0 {
+            let date = time.with_timezone(&Utc).format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            if let Ok(value) = HeaderValue::from_str(&date) {
+                parts.headers_mut().insert(LAST_MODIFIED, value);
+            }
+        }
+        Ok(parts)
+    }
+}
+
+
+// Middleware to add a strong `ETag` to successful `GET` responses and answer `304 Not Modified`
+// when the client already has the same representation.
+// The tag is a hash of the body as sent, so every content encoding gets its own tag.
+// `If-None-Match` is checked first; `If-Modified-Since` only when it is absent, against `Last-Modified`.
+// Tagged responses vary by `Authorization`, and authenticated ones are private, so shared caches
+// never hand one user's representation to another.
+pub async fn conditional(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    let if_none_match = request.headers().get(IF_NONE_MATCH).cloned();
+    let authenticated = request.headers().contains_key(AUTHORIZATION);
+    // Authenticated responses carry per-user flags, such as whether a post is liked, which change
+    // without changing `Last-Modified`, so they are only validated by their tag.
+    let if_modified_since = match authenticated {
+        true => None,
+        false => request.headers().get(IF_MODIFIED_SINCE).cloned(),
+    };
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match read_body(body).await {
+        Ok(Ok(bytes)) => bytes,
+        Ok(Err(body)) => return Response::from_parts(parts, body),
+        Err(error) => {
+            return EError::InternalServerError(
+                ErrorCode::InternalError, format!("failed to read the response body: {}", error),
+            ).into_response();
+        }
+    };
+
+    let tag = etag(&bytes);
+    if let Ok(value) = HeaderValue::from_str(&tag) {
+        parts.headers.insert(ETAG, value);
+    }
+
+    parts.headers.append(VARY, HeaderValue::from_static("authorization"));
+    if authenticated && !parts.headers.contains_key(CACHE_CONTROL) {
+        parts.headers.insert(CACHE_CONTROL, HeaderValue::from_static("private"));
+    }
+
+    let not_modified = match if_none_match {
+        Some(if_none_match) => matches_etag(&if_none_match, &tag),
+        None => if_modified_since.is_some_and(|since| not_modified_since(&parts.headers, &since)),
+    };
+
+    if not_modified {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        for name in KEPT_HEADERS {
+            for value in parts.headers.get_all(&name) {
+                response.headers_mut().append(name.clone(), value.clone());
+            }
+        }
+        return response;
+    }
+
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+
+// Function to read a response body of at most `MAX_TAGGED_BODY` bytes.
+// It returns the bytes, or a body sending what was read followed by the rest if the body is larger.
+async fn read_body(body: Body) -> Result<Result<Bytes, Body>, axum::Error> {
+    if body.size_hint().lower() > MAX_TAGGED_BODY as u64 {
+        return Ok(Err(body));
+    }
+
+    let mut chunks = body.into_data_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+
+    while let Some(chunk) = chunks.next().await {
+        buffer.extend_from_slice(&chunk?);
+
+        if buffer.len() > MAX_TAGGED_BODY {
+            let read = stream::once(future::ready(Ok(Bytes::from(buffer))));
+            return Ok(Err(Body::from_stream(read.chain(chunks))));
+        }
+    }
+
+    Ok(Ok(Bytes::from(buffer)))
+}
+
+// Function to compute the strong entity tag of a response body.
+pub fn etag(body: &[u8]) -> String {
+    let hash: String = Sha256::digest(body)
+        .iter()
+        .take(16)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("\"{}\"", hash)
+}
+
+// Function to check whether an `If-None-Match` header lists the tag, or is "*".
+// Weak tags from the client match too, as RFC 9110 requires for `If-None-Match`.
+fn matches_etag(if_none_match: &HeaderValue, tag: &str) -> bool {
+    let Ok(if_none_match) = if_none_match.to_str() else {
+        return false;
+    };
+
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == tag)
+}
+
+// Function to check whether the response's `Last-Modified` is no later than `If-Modified-Since`.
+fn not_modified_since(headers: &HeaderMap, since: &HeaderValue) -> bool {
+    let parse = |value: &HeaderValue| {
+        value.to_str().ok().and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+    };
+
+    match (headers.get(LAST_MODIFIED).and_then(parse), parse(since)) {
+        (Some(last_modified), Some(since)) => last_modified <= since,
+        _ => false,
+    }
+}
@@ -0,0 +1,32 @@
+// Importing the necessary modules and functions.
+use axum::http::header::RETRY_AFTER;
+use axum::http::HeaderValue;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+use crate::config::config::CorsConfig;
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+
+
+// Function to build the CORS layer from the configuration.
+// Browsers refuse wildcards together with credentials, so the requested methods and headers
+// are mirrored instead when credentials are allowed.
+pub fn layer(config: &CorsConfig) -> CorsLayer {
+
+    let cors = CorsLayer::new().expose_headers([REQUEST_ID_HEADER.clone(), RETRY_AFTER]);
+
+    let cors = match config.allowed_origins.iter().any(|origin| origin == "*") {
+        true => cors.allow_origin(Any),
+        // Origins are checked when the configuration is loaded.
+        false => cors.allow_origin(AllowOrigin::list(
+            config.allowed_origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )),
+    };
+
+    match config.allow_credentials {
+        true => cors
+            .allow_credentials(true)
+            .allow_methods(AllowMethods::mirror_request())
+            .allow_headers(AllowHeaders::mirror_request()),
+        false => cors.allow_methods(Any).allow_headers(Any),
+    }
+}
@@ -0,0 +1,99 @@
+// Importing the necessary modules and functions.
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Instant;
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use lazy_static::lazy_static;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+
+// The buckets of every latency histogram, in seconds.
+const LATENCY_BUCKETS: [f64; 12] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0];
+// The route label of requests matching no route, so unknown paths cannot create new series.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+
+lazy_static! {
+    // The handle of the Prometheus recorder, installed globally the first time it is used.
+    pub static ref PROMETHEUS: PrometheusHandle = PrometheusBuilder::new()
+        .set_buckets(&LATENCY_BUCKETS)
+        .expect("Latency buckets are not empty")
+        .install_recorder()
+        .expect("Failed to install the metrics recorder");
+}
+
+
+// The `Event` enum which represents the domain events counted in the metrics.
+#[derive(Debug, Clone, Copy)]
+pub enum Event {
+    // A post was created.
+    PostCreated,
+    // A comment was created.
+    CommentCreated,
+    // An account was created, with "password" or "oidc".
+    Signup(&'static str),
+    // A user received a session token, after a "password", "oidc" or "two_factor" step.
+    Login(&'static str),
+}
+
+// The `Timed` trait which records how long Prisma queries take.
+pub trait Timed: Future + Sized {
+    // Function to record the duration of the query in `prisma_query_duration_seconds`.
+    fn timed<'a>(self) -> Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>
+    where
+        Self: Send + 'a;
+}
+
+
+// Implementation of the `Timed` trait for every future, such as the ones returned by `exec()`.
+impl<F: Future> Timed for F {
+    fn timed<'a>(self) -> Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>
+    where
+        Self: Send + 'a,
+    {
+        Box::pin(async move {
+            let start = Instant::now();
+            let output = self.await;
+            metrics::histogram!("prisma_query_duration_seconds").record(start.elapsed());
+            output
+        })
+    }
+}
+
+
+// Function to count a domain event.
+pub fn record(event: Event) {
+    match event {
+        Event::PostCreated => metrics::counter!("wforum_posts_created_total").increment(1),
+        Event::CommentCreated => metrics::counter!("wforum_comments_created_total").increment(1),
+        Event::Signup(method) => metrics::counter!("wforum_signups_total", "method" => method).increment(1),
+        Event::Login(method) => metrics::counter!("wforum_logins_total", "method" => method).increment(1),
+    }
+}
+
+// Function to count every request and record its latency, labelled by method, matched route and status.
+// The route is the pattern, such as `/api/posts/:post_id`, so IDs do not create new series.
+pub async fn track(request: Request, next: Next) -> Response {
+
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!("http_request_duration_seconds", &labels).record(start.elapsed());
+
+    response
+}
@@ -0,0 +1,18 @@
+// The `middleware` module.
+// This module contains the middleware wrapping every request.
+
+// The `conditional` module.
+// This module adds entity tags to responses and answers conditional requests.
+pub mod conditional;
+
+// The `cors` module.
+// This module builds the CORS layer from the configuration.
+pub mod cors;
+
+// The `metrics` module.
+// This module records the request, error, query and domain metrics.
+pub mod metrics;
+
+// The `request_id` module.
+// This module assigns an ID to every request.
+pub mod request_id;
@@ -0,0 +1,74 @@
+// Importing the necessary modules and functions.
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use rand::{distributions::Alphanumeric, Rng};
+use tracing::{field, Instrument, Span};
+
+
+// The header carrying the request ID, in requests and responses.
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+// The maximum length of a request ID accepted from a client.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+
+tokio::task_local! {
+    // The ID of the request being handled by the current task.
+    static REQUEST_ID: String;
+    // The span covering the request being handled by the current task.
+    static REQUEST_SPAN: Span;
+}
+
+
+// Function to get the ID of the request being handled, if any.
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+// Function to record the authenticated user on the span of the request being handled, if any.
+pub fn record_user(user_id: i32) {
+    let _ = REQUEST_SPAN.try_with(|span| {
+        span.record("user_id", user_id);
+    });
+}
+
+// Function to assign an ID to a request, keeping a well-formed one sent by the client or a proxy.
+// The ID is available through `current()` while the request is handled and is echoed in the response.
+// Everything logged while handling the request is inside a span carrying the ID,
+// and the user ID once `record_user` is called by the authentication extractors.
+pub async fn request_id(request: Request, next: Next) -> Response {
+
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH)
+        .filter(|id| id.bytes().all(|byte| byte.is_ascii_alphanumeric() || b"-_.".contains(&byte)))
+        .map(str::to_string)
+        .unwrap_or_else(generate);
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        user_id = field::Empty,
+    );
+
+    let mut response = REQUEST_ID
+        .scope(id.clone(), REQUEST_SPAN.scope(span.clone(), next.run(request)))
+        .instrument(span)
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    }
+
+    response
+}
+
+// Function to generate a new request ID.
+fn generate() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(20).map(char::from).collect()
+}
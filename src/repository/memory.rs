@@ -0,0 +1,907 @@
+// Importing the necessary modules and functions.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use axum::async_trait;
+use prisma_client_rust::chrono::{self, DateTime, FixedOffset};
+
+use crate::error::{EError, ErrorCode};
+use crate::prisma::prisma::Role;
+use crate::repository::{
+    ApiTokenRecord, AuthRepository, CommentRecord, CommentRepository, FollowRequestRecord, MuteRecord,
+    NotificationRecord, PostFilter, PostRecord, PostRepository, PostUpdate, ProfileCounts, RecoveryCodeRecord,
+    RelationRepository, TokenRepository, TwoFactorRecord, UserRecord, UserRepository, UserUpdate, VisitRecord,
+};
+
+
+// The `MemoryRepository` struct which keeps everything in the process, for tests and running without a database.
+// It follows the database's rules: unique emails and usernames, one like per user and post,
+// and missing records failing like they do in Prisma.
+#[derive(Default)]
+pub struct MemoryRepository {
+    // The stored records.
+    state: Mutex<MemoryState>,
+}
+
+// The `MemoryState` struct which holds the records of a `MemoryRepository`.
+#[derive(Default)]
+struct MemoryState {
+    // The last ID handed out, shared by every kind of record.
+    last_id: i32,
+    // The users by their ID.
+    users: BTreeMap<i32, UserRecord>,
+    // The password hashes by user ID.
+    passwords: HashMap<i32, String>,
+    // The notifications with the ID of the user they were sent to.
+    notifications: Vec<(i32, NotificationRecord)>,
+    // The posts by their ID, with the author as they were when last written.
+    posts: BTreeMap<i32, PostRecord>,
+    // The comments by their ID, with the author as they were when written.
+    comments: BTreeMap<i32, CommentRecord>,
+    // The likes, as (user ID, post ID).
+    likes: BTreeSet<(i32, i32)>,
+    // The visits, as (visit ID, user ID, post ID, time).
+    history: Vec<(i32, i32, i32, DateTime<FixedOffset>)>,
+    // The follows, as (follower ID, followed ID).
+    follows: BTreeSet<(i32, i32)>,
+    // The follow requests by (requester ID, requested ID), with when they were made.
+    requests: BTreeMap<(i32, i32), DateTime<FixedOffset>>,
+    // The blocks, as (blocker ID, blocked ID).
+    blocks: BTreeSet<(i32, i32)>,
+    // The mutes by (muter ID, muted ID), with when they expire and when they were made.
+    mutes: BTreeMap<(i32, i32), (Option<DateTime<FixedOffset>>, DateTime<FixedOffset>)>,
+    // The two-factor secrets by user ID.
+    two_factor: HashMap<i32, TwoFactorRecord>,
+    // The recovery codes by their ID, with the ID of their user and whether they were used.
+    recovery_codes: BTreeMap<i32, (i32, RecoveryCodeRecord, bool)>,
+    // The IDs of the users external identities are linked to, by (provider, subject).
+    identities: BTreeMap<(String, String), i32>,
+    // The API tokens by their ID, with the hash of their secret.
+    api_tokens: BTreeMap<i32, (String, ApiTokenRecord)>,
+}
+
+
+// Implementation of the `MemoryRepository` struct.
+impl MemoryRepository {
+    // Function to create an empty repository.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Function to lock the stored records.
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap()
+    }
+}
+
+
+// Implementation of the `MemoryState` struct.
+impl MemoryState {
+
+    // Function to hand out a new ID.
+    fn next_id(&mut self) -> i32 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    // Function to get a user, failing like Prisma does when it doesn't exist.
+    fn user(&self, user_id: i32) -> Result<UserRecord, EError> {
+        self.users.get(&user_id).cloned().ok_or_else(record_not_found)
+    }
+
+    // Function to get a post with its author as they are now.
+    fn post(&self, post_id: i32) -> Option<PostRecord> {
+        let mut post = self.posts.get(&post_id)?.clone();
+        if let Some(author) = self.users.get(&post.author_id) {
+            post.author = author.clone();
+        }
+        Some(post)
+    }
+
+    // Function to get a comment with its author as they are now.
+    fn comment(&self, comment_id: i32) -> Option<CommentRecord> {
+        let mut comment = self.comments.get(&comment_id)?.clone();
+        if let Some(user) = self.users.get(&comment.user_id) {
+            comment.user = user.clone();
+        }
+        Some(comment)
+    }
+
+    // Function to check whether a viewer may read the posts of an author: public ones, their own,
+    // and those of private authors they follow.
+    fn is_visible(&self, author: &UserRecord, viewer_id: Option<i32>) -> bool {
+        !author.private || viewer_id.is_some_and(|viewer_id| {
+            viewer_id == author.user_id || self.follows.contains(&(viewer_id, author.user_id))
+        })
+    }
+
+    // Function to check that no other user has the email or username.
+    fn check_unique(&self, user_id: i32, email: Option<&str>, username: Option<&str>) -> Result<(), EError> {
+        let taken = self.users.values().any(|user| user.user_id != user_id && (
+            email.is_some_and(|email| user.email == email)
+                || username.is_some_and(|username| user.username == username)
+        ));
+
+        match taken {
+            true => Err(EError::Conflict(ErrorCode::Conflict, String::from(
+                "A record with the same unique value already exists",
+            ))),
+            false => Ok(()),
+        }
+    }
+
+    // Function to list the mutes of a user which have not expired, as (muted ID, expiry, creation time).
+    fn active_mutes(&self, muter_id: i32) -> Vec<(i32, Option<DateTime<FixedOffset>>, DateTime<FixedOffset>)> {
+        let now = now();
+
+        self.mutes.iter()
+            .filter(|((muter, _), (expires_at, _))| {
+                *muter == muter_id && expires_at.map_or(true, |expires_at| expires_at > now)
+            })
+            .map(|((_, muted), (expires_at, created_at))| (*muted, *expires_at, *created_at))
+            .collect()
+    }
+
+    // Function to list a page of the users with one of the IDs, by username, and count all of them.
+    fn user_page(&self, user_ids: impl Iterator<Item = i32>, offset: i64, limit: i64)
+        -> (Vec<UserRecord>, i64) {
+
+        let mut users: Vec<UserRecord> = user_ids
+            .filter_map(|user_id| self.users.get(&user_id).cloned())
+            .collect();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+
+        let count = users.len() as i64;
+        let users = users.into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .collect();
+
+        (users, count)
+    }
+
+    // Function to store a new user, whose email and username have been checked.
+    fn insert_user(&mut self, email: String, username: String, avatar: Option<String>) -> UserRecord {
+        let now = now();
+        let user = UserRecord {
+            user_id: self.next_id(),
+            email, username, avatar,
+            intro: None,
+            role: Role::User,
+            created_at: now,
+            updated_at: now,
+            suspended_at: None,
+            private: false,
+        };
+
+        self.users.insert(user.user_id, user.clone());
+        user
+    }
+
+    // Function to change the like count of a post.
+    fn count_like(&mut self, post_id: i32, change: i32) -> Result<PostRecord, EError> {
+        let post = self.posts.get_mut(&post_id).ok_or_else(record_not_found)?;
+        post.like_count += change;
+        Ok(self.post(post_id).unwrap())
+    }
+}
+
+
+// Function to get the current time, as Prisma returns it.
+fn now() -> DateTime<FixedOffset> {
+    chrono::Utc::now().into()
+}
+
+// Function to build the error Prisma returns when a record to change doesn't exist.
+fn record_not_found() -> EError {
+    EError::NotFound(ErrorCode::NotFound, String::from("Record not found"))
+}
+
+
+// Implementation of the `UserRepository` trait for `MemoryRepository`.
+#[async_trait]
+impl UserRepository for MemoryRepository {
+
+    async fn find_user(&self, user_id: i32) -> Result<Option<UserRecord>, EError> {
+        Ok(self.state().users.get(&user_id).cloned())
+    }
+
+    async fn find_user_by_name(&self, username: &str) -> Result<Option<UserRecord>, EError> {
+        Ok(self.state().users.values().find(|user| user.username == username).cloned())
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<UserRecord>, EError> {
+        Ok(self.state().users.values().find(|user| user.email == email).cloned())
+    }
+
+    async fn create_user(&self, email: String, username: String, password_hash: String)
+        -> Result<UserRecord, EError> {
+
+        let mut state = self.state();
+        state.check_unique(0, Some(&email), Some(&username))?;
+
+        let user = state.insert_user(email, username, None);
+        state.passwords.insert(user.user_id, password_hash);
+
+        Ok(user)
+    }
+
+    async fn update_user(&self, user_id: i32, update: UserUpdate) -> Result<UserRecord, EError> {
+
+        let mut state = self.state();
+        state.user(user_id)?;
+        state.check_unique(user_id, update.email.as_deref(), update.username.as_deref())?;
+
+        let user = state.users.get_mut(&user_id).unwrap();
+        if let Some(email) = update.email {
+            user.email = email;
+        }
+        if let Some(username) = update.username {
+            user.username = username;
+        }
+        if let Some(intro) = update.intro {
+            user.intro = Some(intro);
+        }
+        if let Some(avatar) = update.avatar {
+            user.avatar = Some(avatar);
+        }
+        if let Some(private) = update.private {
+            user.private = private;
+        }
+        user.updated_at = now();
+
+        Ok(user.clone())
+    }
+
+    async fn set_suspended(&self, user_id: i32, suspended_at: Option<DateTime<FixedOffset>>)
+        -> Result<UserRecord, EError> {
+
+        let mut state = self.state();
+        let user = state.users.get_mut(&user_id).ok_or_else(record_not_found)?;
+        user.suspended_at = suspended_at;
+        Ok(user.clone())
+    }
+
+    async fn password_hash(&self, user_id: i32) -> Result<Option<String>, EError> {
+        Ok(self.state().passwords.get(&user_id).cloned())
+    }
+
+    async fn set_password_hash(&self, user_id: i32, password_hash: String) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(user_id)?;
+        state.passwords.insert(user_id, password_hash);
+        Ok(())
+    }
+
+    async fn notify(&self, user_id: i32, message: String) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(user_id)?;
+
+        let notification = NotificationRecord { notification_id: state.next_id(), message, created_at: now() };
+        state.notifications.push((user_id, notification));
+        Ok(())
+    }
+
+    async fn notifications(&self, user_id: i32) -> Result<Vec<NotificationRecord>, EError> {
+        Ok(self.state().notifications
+            .iter()
+            .rev()
+            .filter(|(recipient, _)| *recipient == user_id)
+            .map(|(_, notification)| notification.clone())
+            .collect())
+    }
+
+    async fn profile_counts(&self, user_id: i32) -> Result<ProfileCounts, EError> {
+        let state = self.state();
+
+        Ok(ProfileCounts {
+            followers: state.follows.iter().filter(|(_, followed)| *followed == user_id).count() as i64,
+            following: state.follows.iter().filter(|(follower, _)| *follower == user_id).count() as i64,
+            posts: state.posts.values().filter(|post| post.author_id == user_id).count() as i64,
+        })
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<Vec<i32>, EError> {
+
+        let mut state = self.state();
+        state.user(user_id)?;
+
+        let own_posts: BTreeSet<i32> = state.posts.values()
+            .filter(|post| post.author_id == user_id)
+            .map(|post| post.post_id)
+            .collect();
+
+        state.follows.retain(|(follower, followed)| *follower != user_id && *followed != user_id);
+        state.requests.retain(|(requester, requested), _| *requester != user_id && *requested != user_id);
+        state.blocks.retain(|(blocker, blocked)| *blocker != user_id && *blocked != user_id);
+        state.mutes.retain(|(muter, muted), _| *muter != user_id && *muted != user_id);
+        state.comments.retain(|_, comment| comment.user_id != user_id && !own_posts.contains(&comment.post_id));
+
+        let liked_posts: Vec<i32> = state.likes.iter()
+            .filter(|(liker, _)| *liker == user_id)
+            .map(|(_, post_id)| *post_id)
+            .collect();
+        for post_id in liked_posts.iter() {
+            state.count_like(*post_id, -1)?;
+        }
+        state.likes.retain(|(liker, post_id)| *liker != user_id && !own_posts.contains(post_id));
+
+        state.history.retain(|(_, viewer, post_id, _)| *viewer != user_id && !own_posts.contains(post_id));
+        state.notifications.retain(|(recipient, _)| *recipient != user_id);
+        state.posts.retain(|post_id, _| !own_posts.contains(post_id));
+        state.two_factor.remove(&user_id);
+        state.recovery_codes.retain(|_, (owner, _, _)| *owner != user_id);
+        state.identities.retain(|_, owner| *owner != user_id);
+        state.api_tokens.retain(|_, (_, api_token)| api_token.user_id != user_id);
+        state.passwords.remove(&user_id);
+        state.users.remove(&user_id);
+
+        Ok(liked_posts)
+    }
+}
+
+
+// Implementation of the `PostRepository` trait for `MemoryRepository`.
+#[async_trait]
+impl PostRepository for MemoryRepository {
+
+    async fn find_post(&self, post_id: i32) -> Result<Option<PostRecord>, EError> {
+        Ok(self.state().post(post_id))
+    }
+
+    async fn list_posts(&self, filter: &PostFilter) -> Result<(Vec<PostRecord>, i64), EError> {
+
+        let state = self.state();
+
+        let mut posts: Vec<PostRecord> = state.posts.keys()
+            .filter_map(|post_id| state.post(*post_id))
+            .filter(|post| filter.author.as_ref().map_or(true, |author| &post.author.username == author))
+            .filter(|post| filter.liked_by.as_ref().map_or(true, |liked_by| {
+                state.users.values()
+                    .find(|user| &user.username == liked_by)
+                    .is_some_and(|user| state.likes.contains(&(user.user_id, post.post_id)))
+            }))
+            .filter(|post| filter.author_ids.as_ref().map_or(true, |ids| ids.contains(&post.author_id)))
+            .filter(|post| !filter.excluded_author_ids.contains(&post.author_id))
+            .filter(|post| state.is_visible(&post.author, filter.viewer_id))
+            .collect();
+
+        // Newest first, and the latest created first among posts created at the same time.
+        posts.sort_by(|a, b| (b.created_at, b.post_id).cmp(&(a.created_at, a.post_id)));
+
+        let count = posts.len() as i64;
+        let posts = posts.into_iter()
+            .skip(filter.offset.max(0) as usize)
+            .take(filter.limit.max(0) as usize)
+            .collect();
+
+        Ok((posts, count))
+    }
+
+    async fn post_ids_by_author(&self, user_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().posts.values()
+            .filter(|post| post.author_id == user_id)
+            .map(|post| post.post_id)
+            .collect())
+    }
+
+    async fn create_post(&self, author_id: i32, title: String, description: String, content: String)
+        -> Result<PostRecord, EError> {
+
+        let mut state = self.state();
+        let author = state.user(author_id)?;
+
+        let now = now();
+        let post = PostRecord {
+            post_id: state.next_id(),
+            author_id, title, description, content,
+            like_count: 0,
+            created_at: now,
+            updated_at: now,
+            author,
+        };
+
+        state.posts.insert(post.post_id, post.clone());
+
+        Ok(post)
+    }
+
+    async fn update_post(&self, post_id: i32, update: PostUpdate) -> Result<PostRecord, EError> {
+
+        let mut state = self.state();
+        let post = state.posts.get_mut(&post_id).ok_or_else(record_not_found)?;
+
+        if let Some(title) = update.title {
+            post.title = title;
+        }
+        if let Some(description) = update.description {
+            post.description = description;
+        }
+        if let Some(content) = update.content {
+            post.content = content;
+        }
+        post.updated_at = now();
+
+        Ok(state.post(post_id).unwrap())
+    }
+
+    async fn delete_post(&self, post_id: i32) -> Result<(), EError> {
+
+        let mut state = self.state();
+        state.posts.remove(&post_id).ok_or_else(record_not_found)?;
+
+        state.comments.retain(|_, comment| comment.post_id != post_id);
+        state.history.retain(|(_, _, visited, _)| *visited != post_id);
+        state.likes.retain(|(_, liked)| *liked != post_id);
+
+        Ok(())
+    }
+
+    async fn is_liked(&self, user_id: i32, post_id: i32) -> Result<bool, EError> {
+        Ok(self.state().likes.contains(&(user_id, post_id)))
+    }
+
+    async fn like_post(&self, user_id: i32, post_id: i32) -> Result<PostRecord, EError> {
+
+        let mut state = self.state();
+        state.user(user_id)?;
+        state.post(post_id).ok_or_else(record_not_found)?;
+
+        if !state.likes.insert((user_id, post_id)) {
+            return Err(EError::Conflict(ErrorCode::Conflict, String::from(
+                "A record with the same unique value already exists",
+            )));
+        }
+
+        state.count_like(post_id, 1)
+    }
+
+    async fn unlike_post(&self, user_id: i32, post_id: i32) -> Result<PostRecord, EError> {
+
+        let mut state = self.state();
+
+        if !state.likes.remove(&(user_id, post_id)) {
+            return Err(record_not_found());
+        }
+
+        state.count_like(post_id, -1)
+    }
+
+    async fn record_visit(&self, user_id: i32, post_id: i32) -> Result<(), EError> {
+
+        let mut state = self.state();
+        state.user(user_id)?;
+        state.post(post_id).ok_or_else(record_not_found)?;
+
+        let visit_id = state.next_id();
+        state.history.push((visit_id, user_id, post_id, now()));
+
+        Ok(())
+    }
+
+    async fn history(&self, user_id: i32, excluded_author_ids: &[i32], offset: i64, limit: i64)
+        -> Result<(Vec<VisitRecord>, i64), EError> {
+
+        let state = self.state();
+
+        let visits: Vec<VisitRecord> = state.history.iter()
+            .rev()
+            .filter(|(_, viewer, _, _)| *viewer == user_id)
+            .filter_map(|(_, _, post_id, time)| Some(VisitRecord { post: state.post(*post_id)?, time: *time }))
+            .filter(|visit| !excluded_author_ids.contains(&visit.post.author_id))
+            .collect();
+
+        let count = visits.len() as i64;
+        let visits = visits.into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .collect();
+
+        Ok((visits, count))
+    }
+}
+
+
+// Implementation of the `CommentRepository` trait for `MemoryRepository`.
+#[async_trait]
+impl CommentRepository for MemoryRepository {
+
+    async fn comments(&self, post_id: i32, excluded_user_ids: &[i32], offset: i64, limit: i64)
+        -> Result<(Vec<CommentRecord>, i64), EError> {
+
+        let state = self.state();
+
+        let comments: Vec<CommentRecord> = state.comments.values()
+            .filter(|comment| comment.post_id == post_id && !excluded_user_ids.contains(&comment.user_id))
+            .filter_map(|comment| state.comment(comment.comment_id))
+            .collect();
+
+        let count = comments.len() as i64;
+        let comments = comments.into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .collect();
+
+        Ok((comments, count))
+    }
+
+    async fn find_comment(&self, comment_id: i32) -> Result<Option<CommentRecord>, EError> {
+        Ok(self.state().comment(comment_id))
+    }
+
+    async fn create_comment(&self, user_id: i32, post_id: i32, content: String)
+        -> Result<CommentRecord, EError> {
+
+        let mut state = self.state();
+        let user = state.user(user_id)?;
+        state.post(post_id).ok_or_else(record_not_found)?;
+
+        let comment = CommentRecord {
+            comment_id: state.next_id(),
+            post_id, user_id, content,
+            created_at: now(),
+            user,
+        };
+
+        state.comments.insert(comment.comment_id, comment.clone());
+
+        Ok(comment)
+    }
+
+    async fn delete_comment(&self, comment_id: i32) -> Result<(), EError> {
+        self.state().comments.remove(&comment_id).ok_or_else(record_not_found)?;
+        Ok(())
+    }
+}
+
+
+// Implementation of the `RelationRepository` trait for `MemoryRepository`.
+#[async_trait]
+impl RelationRepository for MemoryRepository {
+
+    async fn is_following(&self, follower_id: i32, followed_id: i32) -> Result<bool, EError> {
+        Ok(self.state().follows.contains(&(follower_id, followed_id)))
+    }
+
+    async fn is_blocked(&self, blocker_id: i32, blocked_id: i32) -> Result<bool, EError> {
+        Ok(self.state().blocks.contains(&(blocker_id, blocked_id)))
+    }
+
+    async fn followed_ids(&self, follower_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().follows.iter()
+            .filter(|(follower, _)| *follower == follower_id)
+            .map(|(_, followed)| *followed)
+            .collect())
+    }
+
+    async fn follower_ids(&self, followed_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().follows.iter()
+            .filter(|(_, followed)| *followed == followed_id)
+            .map(|(follower, _)| *follower)
+            .collect())
+    }
+
+    async fn blocked_ids(&self, blocker_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().blocks.iter()
+            .filter(|(blocker, _)| *blocker == blocker_id)
+            .map(|(_, blocked)| *blocked)
+            .collect())
+    }
+
+    async fn blocker_ids(&self, blocked_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().blocks.iter()
+            .filter(|(_, blocked)| *blocked == blocked_id)
+            .map(|(blocker, _)| *blocker)
+            .collect())
+    }
+
+    async fn followers(&self, followed_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError> {
+
+        let state = self.state();
+        let ids = state.follows.iter()
+            .filter(|(_, followed)| *followed == followed_id)
+            .map(|(follower, _)| *follower);
+
+        Ok(state.user_page(ids, offset, limit))
+    }
+
+    async fn following(&self, follower_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError> {
+
+        let state = self.state();
+        let ids = state.follows.iter()
+            .filter(|(follower, _)| *follower == follower_id)
+            .map(|(_, followed)| *followed);
+
+        Ok(state.user_page(ids, offset, limit))
+    }
+
+    async fn blocked_users(&self, blocker_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError> {
+
+        let state = self.state();
+        let ids = state.blocks.iter()
+            .filter(|(blocker, _)| *blocker == blocker_id)
+            .map(|(_, blocked)| *blocked);
+
+        Ok(state.user_page(ids, offset, limit))
+    }
+
+    async fn is_requested(&self, requester_id: i32, requested_id: i32) -> Result<bool, EError> {
+        Ok(self.state().requests.contains_key(&(requester_id, requested_id)))
+    }
+
+    async fn requested_ids(&self, requester_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().requests.keys()
+            .filter(|(requester, _)| *requester == requester_id)
+            .map(|(_, requested)| *requested)
+            .collect())
+    }
+
+    async fn requester_ids(&self, requested_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().requests.keys()
+            .filter(|(_, requested)| *requested == requested_id)
+            .map(|(requester, _)| *requester)
+            .collect())
+    }
+
+    async fn follow_requests(&self, requested_id: i32) -> Result<Vec<FollowRequestRecord>, EError> {
+        let state = self.state();
+
+        let mut requests: Vec<FollowRequestRecord> = state.requests.iter()
+            .filter(|((_, requested), _)| *requested == requested_id)
+            .filter_map(|((requester, _), created_at)| Some(FollowRequestRecord {
+                user: state.users.get(requester)?.clone(),
+                created_at: *created_at,
+            }))
+            .collect();
+
+        requests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        Ok(requests)
+    }
+
+    async fn request_follow(&self, requester_id: i32, requested_id: i32) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(requester_id)?;
+        state.user(requested_id)?;
+        state.requests.entry((requester_id, requested_id)).or_insert_with(now);
+        Ok(())
+    }
+
+    async fn remove_request(&self, requester_id: i32, requested_id: i32) -> Result<(), EError> {
+        self.state().requests.remove(&(requester_id, requested_id));
+        Ok(())
+    }
+
+    async fn follow(&self, follower_id: i32, followed_id: i32) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(follower_id)?;
+        state.user(followed_id)?;
+        state.follows.insert((follower_id, followed_id));
+        Ok(())
+    }
+
+    async fn unfollow(&self, follower_id: i32, followed_id: i32) -> Result<(), EError> {
+        self.state().follows.remove(&(follower_id, followed_id));
+        Ok(())
+    }
+
+    async fn block(&self, blocker_id: i32, blocked_id: i32) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(blocker_id)?;
+        state.user(blocked_id)?;
+        state.blocks.insert((blocker_id, blocked_id));
+        Ok(())
+    }
+
+    async fn unblock(&self, blocker_id: i32, blocked_id: i32) -> Result<(), EError> {
+        self.state().blocks.remove(&(blocker_id, blocked_id));
+        Ok(())
+    }
+
+    async fn is_muted(&self, muter_id: i32, muted_id: i32) -> Result<bool, EError> {
+        Ok(self.state().active_mutes(muter_id).iter().any(|(muted, _, _)| *muted == muted_id))
+    }
+
+    async fn muted_ids(&self, muter_id: i32) -> Result<Vec<i32>, EError> {
+        Ok(self.state().active_mutes(muter_id).into_iter().map(|(muted, _, _)| muted).collect())
+    }
+
+    async fn mutes(&self, muter_id: i32) -> Result<Vec<MuteRecord>, EError> {
+        let state = self.state();
+
+        let mut mutes: Vec<MuteRecord> = state.active_mutes(muter_id).into_iter()
+            .filter_map(|(muted, expires_at, created_at)| Some(MuteRecord {
+                user: state.users.get(&muted)?.clone(),
+                expires_at, created_at,
+            }))
+            .collect();
+
+        mutes.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        Ok(mutes)
+    }
+
+    async fn mute(&self, muter_id: i32, muted_id: i32, expires_at: Option<DateTime<FixedOffset>>)
+        -> Result<MuteRecord, EError> {
+
+        let mut state = self.state();
+        state.user(muter_id)?;
+        let user = state.user(muted_id)?;
+
+        let now = now();
+        let (expiry, created_at) = state.mutes.entry((muter_id, muted_id)).or_insert((expires_at, now));
+        *expiry = expires_at;
+
+        Ok(MuteRecord { user, expires_at, created_at: *created_at })
+    }
+
+    async fn unmute(&self, muter_id: i32, muted_id: i32) -> Result<(), EError> {
+        self.state().mutes.remove(&(muter_id, muted_id));
+        Ok(())
+    }
+}
+
+
+// Implementation of the `AuthRepository` trait for `MemoryRepository`.
+#[async_trait]
+impl AuthRepository for MemoryRepository {
+
+    async fn two_factor(&self, user_id: i32) -> Result<Option<TwoFactorRecord>, EError> {
+        Ok(self.state().two_factor.get(&user_id).cloned())
+    }
+
+    async fn start_two_factor(&self, user_id: i32, secret: String) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(user_id)?;
+        state.two_factor.insert(user_id, TwoFactorRecord { secret, enabled: false, last_used_step: 0 });
+        Ok(())
+    }
+
+    async fn enable_two_factor(&self, user_id: i32, last_used_step: i32) -> Result<(), EError> {
+        let mut state = self.state();
+        let two_factor = state.two_factor.get_mut(&user_id).ok_or_else(record_not_found)?;
+        two_factor.enabled = true;
+        two_factor.last_used_step = last_used_step;
+        Ok(())
+    }
+
+    async fn set_last_used_step(&self, user_id: i32, last_used_step: i32) -> Result<bool, EError> {
+        let mut state = self.state();
+        let two_factor = state.two_factor.get_mut(&user_id).ok_or_else(record_not_found)?;
+        if two_factor.last_used_step >= last_used_step {
+            return Ok(false);
+        }
+        two_factor.last_used_step = last_used_step;
+        Ok(true)
+    }
+
+    async fn delete_two_factor(&self, user_id: i32) -> Result<(), EError> {
+        let mut state = self.state();
+        state.recovery_codes.retain(|_, (owner, _, _)| *owner != user_id);
+        state.two_factor.remove(&user_id);
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(&self, user_id: i32, hash_codes: Vec<String>) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(user_id)?;
+        state.recovery_codes.retain(|_, (owner, _, _)| *owner != user_id);
+
+        for hash_code in hash_codes {
+            let code_id = state.next_id();
+            state.recovery_codes.insert(code_id, (user_id, RecoveryCodeRecord { code_id, hash_code }, false));
+        }
+        Ok(())
+    }
+
+    async fn find_recovery_code(&self, user_id: i32, hash_code: &str) -> Result<Option<RecoveryCodeRecord>, EError> {
+        Ok(self.state().recovery_codes
+            .values()
+            .find(|(owner, recovery_code, used)| *owner == user_id && !used && recovery_code.hash_code == hash_code)
+            .map(|(_, recovery_code, _)| recovery_code.clone()))
+    }
+
+    async fn use_recovery_code(&self, code_id: i32) -> Result<(), EError> {
+        let mut state = self.state();
+        let (_, _, used) = state.recovery_codes.get_mut(&code_id).ok_or_else(record_not_found)?;
+        *used = true;
+        Ok(())
+    }
+
+    async fn find_identity(&self, provider: &str, subject: &str) -> Result<Option<UserRecord>, EError> {
+        let state = self.state();
+        let user_id = state.identities.get(&(provider.to_string(), subject.to_string()));
+        Ok(user_id.and_then(|user_id| state.users.get(user_id).cloned()))
+    }
+
+    async fn link_identity(&self, provider: String, subject: String, user_id: i32) -> Result<(), EError> {
+        let mut state = self.state();
+        state.user(user_id)?;
+
+        if state.identities.contains_key(&(provider.clone(), subject.clone())) {
+            return Err(EError::Conflict(ErrorCode::Conflict, String::from(
+                "A record with the same unique value already exists",
+            )));
+        }
+
+        state.identities.insert((provider, subject), user_id);
+        Ok(())
+    }
+
+    async fn create_external_user(&self, email: String, username: String, avatar: Option<String>)
+        -> Result<UserRecord, EError> {
+
+        let mut state = self.state();
+        state.check_unique(0, Some(&email), Some(&username))?;
+        Ok(state.insert_user(email, username, avatar))
+    }
+}
+
+
+// Implementation of the `TokenRepository` trait for `MemoryRepository`.
+#[async_trait]
+impl TokenRepository for MemoryRepository {
+
+    async fn api_tokens(&self, user_id: i32) -> Result<Vec<ApiTokenRecord>, EError> {
+        Ok(self.state().api_tokens
+            .values()
+            .rev()
+            .filter(|(_, api_token)| api_token.user_id == user_id)
+            .map(|(_, api_token)| api_token.clone())
+            .collect())
+    }
+
+    async fn find_api_token(&self, hash_token: &str) -> Result<Option<ApiTokenRecord>, EError> {
+        Ok(self.state().api_tokens
+            .values()
+            .find(|(hash, _)| hash == hash_token)
+            .map(|(_, api_token)| api_token.clone()))
+    }
+
+    async fn create_api_token(
+        &self,
+        user_id: i32,
+        name: String,
+        hash_token: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<ApiTokenRecord, EError> {
+
+        let mut state = self.state();
+        state.user(user_id)?;
+
+        let api_token = ApiTokenRecord {
+            token_id: state.next_id(),
+            user_id, name, scopes, expires_at,
+            last_used_at: None,
+            created_at: now(),
+        };
+
+        state.api_tokens.insert(api_token.token_id, (hash_token, api_token.clone()));
+        Ok(api_token)
+    }
+
+    async fn touch_api_token(&self, token_id: i32, used_at: DateTime<FixedOffset>) -> Result<(), EError> {
+        let mut state = self.state();
+        let (_, api_token) = state.api_tokens.get_mut(&token_id).ok_or_else(record_not_found)?;
+        api_token.last_used_at = Some(used_at);
+        Ok(())
+    }
+
+    async fn revoke_api_token(&self, user_id: i32, token_id: i32) -> Result<bool, EError> {
+        let mut state = self.state();
+
+        let owned = state.api_tokens.get(&token_id).is_some_and(|(_, api_token)| api_token.user_id == user_id);
+        if owned {
+            state.api_tokens.remove(&token_id);
+        }
+        Ok(owned)
+    }
+
+    async fn delete_api_tokens(&self, user_id: i32) -> Result<(), EError> {
+        self.state().api_tokens.retain(|_, (_, api_token)| api_token.user_id != user_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,492 @@
+// Importing the necessary modules and functions.
+use axum::async_trait;
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::error::EError;
+use crate::prisma::prisma::Role;
+
+// The `memory` module.
+// This module contains the in-memory repository, for tests and running without a database.
+pub mod memory;
+
+// The `prisma` module.
+// This module contains the repository backed by the Prisma client.
+pub mod prisma;
+
+pub use memory::MemoryRepository;
+pub use prisma::PrismaRepository;
+
+
+// The `UserRecord` struct which represents a stored user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserRecord {
+    // The ID of the user.
+    pub user_id: i32,
+    // The email of the user.
+    pub email: String,
+    // The username of the user.
+    pub username: String,
+    // The introduction of the user.
+    pub intro: Option<String>,
+    // The avatar of the user.
+    pub avatar: Option<String>,
+    // The role of the user.
+    pub role: Role,
+    // When the user signed up.
+    pub created_at: DateTime<FixedOffset>,
+    // When the user's details last changed.
+    pub updated_at: DateTime<FixedOffset>,
+    // When the user was suspended, if they are.
+    pub suspended_at: Option<DateTime<FixedOffset>>,
+    // Whether only the user's approved followers can read their posts.
+    #[serde(default)]
+    pub private: bool,
+}
+
+// The `PostRecord` struct which represents a stored post with its author.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostRecord {
+    // The ID of the post.
+    pub post_id: i32,
+    // The ID of the author.
+    pub author_id: i32,
+    // The title of the post.
+    pub title: String,
+    // The description of the post.
+    pub description: String,
+    // The content of the post.
+    pub content: String,
+    // The number of likes of the post.
+    pub like_count: i32,
+    // When the post was created.
+    pub created_at: DateTime<FixedOffset>,
+    // When the post last changed.
+    pub updated_at: DateTime<FixedOffset>,
+    // The author of the post.
+    pub author: UserRecord,
+}
+
+// The `CommentRecord` struct which represents a stored comment with its author.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommentRecord {
+    // The ID of the comment.
+    pub comment_id: i32,
+    // The ID of the post the comment is on.
+    pub post_id: i32,
+    // The ID of the author.
+    pub user_id: i32,
+    // The content of the comment.
+    pub content: String,
+    // When the comment was written.
+    pub created_at: DateTime<FixedOffset>,
+    // The author of the comment.
+    pub user: UserRecord,
+}
+
+// The `NotificationRecord` struct which represents a notification sent to a user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRecord {
+    // The ID of the notification.
+    pub notification_id: i32,
+    // The message of the notification.
+    pub message: String,
+    // When the notification was sent.
+    pub created_at: DateTime<FixedOffset>,
+}
+
+// The `VisitRecord` struct which represents a post in a user's history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisitRecord {
+    // The visited post.
+    pub post: PostRecord,
+    // When the post was visited.
+    pub time: DateTime<FixedOffset>,
+}
+
+// The `MuteRecord` struct which represents a user muted by another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MuteRecord {
+    // The muted user.
+    pub user: UserRecord,
+    // When the mute ends, `None` if it doesn't.
+    pub expires_at: Option<DateTime<FixedOffset>>,
+    // When the user was muted.
+    pub created_at: DateTime<FixedOffset>,
+}
+
+// The `FollowRequestRecord` struct which represents a user asking to follow a private user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowRequestRecord {
+    // The user asking to follow.
+    pub user: UserRecord,
+    // When the user asked.
+    pub created_at: DateTime<FixedOffset>,
+}
+
+// The `TwoFactorRecord` struct which represents a user's two-factor secret.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwoFactorRecord {
+    // The base32 encoded secret.
+    pub secret: String,
+    // Whether the enrollment has been confirmed.
+    pub enabled: bool,
+    // The last time step a code was accepted for, so codes can't be used twice.
+    pub last_used_step: i32,
+}
+
+// The `RecoveryCodeRecord` struct which represents an unused recovery code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryCodeRecord {
+    // The ID of the recovery code.
+    pub code_id: i32,
+    // The hash of the code.
+    pub hash_code: String,
+}
+
+// The `ApiTokenRecord` struct which represents a stored personal API token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiTokenRecord {
+    // The ID of the token.
+    pub token_id: i32,
+    // The ID of the token's owner.
+    pub user_id: i32,
+    // The name of the token.
+    pub name: String,
+    // The names of the scopes granted to the token.
+    pub scopes: Vec<String>,
+    // When the token expires, `None` if it doesn't.
+    pub expires_at: Option<DateTime<FixedOffset>>,
+    // When the token was last used.
+    pub last_used_at: Option<DateTime<FixedOffset>>,
+    // When the token was created.
+    pub created_at: DateTime<FixedOffset>,
+}
+
+// The `ProfileCounts` struct which represents how many followers, followed users and posts a user has.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ProfileCounts {
+    // The number of users following the user.
+    pub followers: i64,
+    // The number of users the user follows.
+    pub following: i64,
+    // The number of posts by the user.
+    pub posts: i64,
+}
+
+// The `UserUpdate` struct which represents the details of a user to change, leaving `None` fields alone.
+#[derive(Debug, Clone, Default)]
+pub struct UserUpdate {
+    // The new email.
+    pub email: Option<String>,
+    // The new username.
+    pub username: Option<String>,
+    // The new introduction.
+    pub intro: Option<String>,
+    // The new avatar.
+    pub avatar: Option<String>,
+    // Whether the user becomes private or public.
+    pub private: Option<bool>,
+}
+
+// The `PostUpdate` struct which represents the fields of a post to change, leaving `None` fields alone.
+#[derive(Debug, Clone, Default)]
+pub struct PostUpdate {
+    // The new title.
+    pub title: Option<String>,
+    // The new description.
+    pub description: Option<String>,
+    // The new content.
+    pub content: Option<String>,
+}
+
+// The `PostFilter` struct which represents which page of posts to list, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct PostFilter {
+    // Only posts by the user with this username.
+    pub author: Option<String>,
+    // Only posts liked by the user with this username.
+    pub liked_by: Option<String>,
+    // Only posts by one of these users.
+    pub author_ids: Option<Vec<i32>>,
+    // No posts by any of these users.
+    pub excluded_author_ids: Vec<i32>,
+    // The user the posts are listed for, who only sees the posts of private users they follow,
+    // or their own; anonymous viewers, `None`, only see those of public users.
+    pub viewer_id: Option<i32>,
+    // The number of posts to skip.
+    pub offset: i64,
+    // The number of posts to return.
+    pub limit: i64,
+}
+
+
+// The `UserRepository` trait which stores users, their passwords and notifications.
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    // Function to find a user by their ID.
+    async fn find_user(&self, user_id: i32) -> Result<Option<UserRecord>, EError>;
+
+    // Function to find a user by their username.
+    async fn find_user_by_name(&self, username: &str) -> Result<Option<UserRecord>, EError>;
+
+    // Function to find a user by their email.
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<UserRecord>, EError>;
+
+    // Function to create a user with a password hash.
+    // It returns a conflict error if the email or username is taken.
+    async fn create_user(&self, email: String, username: String, password_hash: String)
+        -> Result<UserRecord, EError>;
+
+    // Function to change a user's details.
+    // It returns a conflict error if the new email or username is taken.
+    async fn update_user(&self, user_id: i32, update: UserUpdate) -> Result<UserRecord, EError>;
+
+    // Function to suspend a user from the given time, or to lift their suspension with `None`.
+    async fn set_suspended(&self, user_id: i32, suspended_at: Option<DateTime<FixedOffset>>)
+        -> Result<UserRecord, EError>;
+
+    // Function to get the password hash of a user, `None` if they have no password.
+    async fn password_hash(&self, user_id: i32) -> Result<Option<String>, EError>;
+
+    // Function to set the password hash of a user.
+    async fn set_password_hash(&self, user_id: i32, password_hash: String) -> Result<(), EError>;
+
+    // Function to send a notification to a user.
+    async fn notify(&self, user_id: i32, message: String) -> Result<(), EError>;
+
+    // Function to list a user's notifications, newest first.
+    async fn notifications(&self, user_id: i32) -> Result<Vec<NotificationRecord>, EError>;
+
+    // Function to count a user's followers, followed users and posts.
+    async fn profile_counts(&self, user_id: i32) -> Result<ProfileCounts, EError>;
+
+    // Function to delete a user with everything they created, followed, requested, blocked, muted, liked or viewed,
+    // and their two-factor secret, recovery codes, external identities and API tokens,
+    // keeping the like counts of the posts they liked right.
+    // It returns the IDs of the posts they liked, whose like count changed.
+    async fn delete_user(&self, user_id: i32) -> Result<Vec<i32>, EError>;
+}
+
+// The `PostRepository` trait which stores posts, likes and reading history.
+#[async_trait]
+pub trait PostRepository: Send + Sync {
+    // Function to find a post by its ID.
+    async fn find_post(&self, post_id: i32) -> Result<Option<PostRecord>, EError>;
+
+    // Function to list a page of posts and count every post matching the filter.
+    async fn list_posts(&self, filter: &PostFilter) -> Result<(Vec<PostRecord>, i64), EError>;
+
+    // Function to list the IDs of a user's posts.
+    async fn post_ids_by_author(&self, user_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to create a post.
+    async fn create_post(&self, author_id: i32, title: String, description: String, content: String)
+        -> Result<PostRecord, EError>;
+
+    // Function to change a post.
+    async fn update_post(&self, post_id: i32, update: PostUpdate) -> Result<PostRecord, EError>;
+
+    // Function to delete a post with its comments, likes and views.
+    async fn delete_post(&self, post_id: i32) -> Result<(), EError>;
+
+    // Function to check whether a user likes a post.
+    async fn is_liked(&self, user_id: i32, post_id: i32) -> Result<bool, EError>;
+
+    // Function to like a post, counting the like.
+    async fn like_post(&self, user_id: i32, post_id: i32) -> Result<PostRecord, EError>;
+
+    // Function to unlike a post, uncounting the like.
+    async fn unlike_post(&self, user_id: i32, post_id: i32) -> Result<PostRecord, EError>;
+
+    // Function to add a post to a user's reading history.
+    async fn record_visit(&self, user_id: i32, post_id: i32) -> Result<(), EError>;
+
+    // Function to list a page of a user's reading history, latest first, and count all of it,
+    // leaving out the posts by any of the excluded authors.
+    async fn history(&self, user_id: i32, excluded_author_ids: &[i32], offset: i64, limit: i64)
+        -> Result<(Vec<VisitRecord>, i64), EError>;
+}
+
+// The `CommentRepository` trait which stores comments.
+#[async_trait]
+pub trait CommentRepository: Send + Sync {
+    // Function to list a page of the comments on a post, oldest first, and count all of them,
+    // leaving out the comments by any of the excluded users.
+    async fn comments(&self, post_id: i32, excluded_user_ids: &[i32], offset: i64, limit: i64)
+        -> Result<(Vec<CommentRecord>, i64), EError>;
+
+    // Function to find a comment by its ID.
+    async fn find_comment(&self, comment_id: i32) -> Result<Option<CommentRecord>, EError>;
+
+    // Function to create a comment on a post.
+    async fn create_comment(&self, user_id: i32, post_id: i32, content: String)
+        -> Result<CommentRecord, EError>;
+
+    // Function to delete a comment.
+    async fn delete_comment(&self, comment_id: i32) -> Result<(), EError>;
+}
+
+// The `RelationRepository` trait which stores who follows, who blocks and who mutes whom.
+#[async_trait]
+pub trait RelationRepository: Send + Sync {
+    // Function to check whether a user follows another.
+    async fn is_following(&self, follower_id: i32, followed_id: i32) -> Result<bool, EError>;
+
+    // Function to check whether a user blocks another.
+    async fn is_blocked(&self, blocker_id: i32, blocked_id: i32) -> Result<bool, EError>;
+
+    // Function to list the IDs of the users a user follows.
+    async fn followed_ids(&self, follower_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to list the IDs of the users following a user.
+    async fn follower_ids(&self, followed_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to list the IDs of the users a user blocks.
+    async fn blocked_ids(&self, blocker_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to list the IDs of the users who block a user.
+    async fn blocker_ids(&self, blocked_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to list a page of the users following a user, by username, and count all of them.
+    async fn followers(&self, followed_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError>;
+
+    // Function to list a page of the users a user follows, by username, and count all of them.
+    async fn following(&self, follower_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError>;
+
+    // Function to list a page of the users a user blocks, by username, and count all of them.
+    async fn blocked_users(&self, blocker_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError>;
+
+    // Function to check whether a user asked to follow another.
+    async fn is_requested(&self, requester_id: i32, requested_id: i32) -> Result<bool, EError>;
+
+    // Function to list the IDs of the users a user asked to follow.
+    async fn requested_ids(&self, requester_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to list the IDs of the users asking to follow a user.
+    async fn requester_ids(&self, requested_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to list the users asking to follow a user, latest first.
+    async fn follow_requests(&self, requested_id: i32) -> Result<Vec<FollowRequestRecord>, EError>;
+
+    // Function to make a user ask to follow another; asking twice is not an error.
+    async fn request_follow(&self, requester_id: i32, requested_id: i32) -> Result<(), EError>;
+
+    // Function to drop a user's request to follow another; it is not an error if they didn't ask.
+    async fn remove_request(&self, requester_id: i32, requested_id: i32) -> Result<(), EError>;
+
+    // Function to make a user follow another; following twice is not an error.
+    async fn follow(&self, follower_id: i32, followed_id: i32) -> Result<(), EError>;
+
+    // Function to make a user stop following another; it is not an error if they didn't.
+    async fn unfollow(&self, follower_id: i32, followed_id: i32) -> Result<(), EError>;
+
+    // Function to make a user block another; blocking twice is not an error.
+    async fn block(&self, blocker_id: i32, blocked_id: i32) -> Result<(), EError>;
+
+    // Function to make a user stop blocking another; it is not an error if they didn't.
+    async fn unblock(&self, blocker_id: i32, blocked_id: i32) -> Result<(), EError>;
+
+    // Function to check whether a user mutes another, leaving out expired mutes.
+    async fn is_muted(&self, muter_id: i32, muted_id: i32) -> Result<bool, EError>;
+
+    // Function to list the IDs of the users a user mutes, leaving out expired mutes.
+    async fn muted_ids(&self, muter_id: i32) -> Result<Vec<i32>, EError>;
+
+    // Function to list the users a user mutes, latest first, leaving out expired mutes.
+    async fn mutes(&self, muter_id: i32) -> Result<Vec<MuteRecord>, EError>;
+
+    // Function to make a user mute another until `expires_at`, or for good;
+    // muting again replaces the expiry.
+    async fn mute(&self, muter_id: i32, muted_id: i32, expires_at: Option<DateTime<FixedOffset>>)
+        -> Result<MuteRecord, EError>;
+
+    // Function to make a user stop muting another; it is not an error if they didn't.
+    async fn unmute(&self, muter_id: i32, muted_id: i32) -> Result<(), EError>;
+}
+
+
+// The `AuthRepository` trait which stores two-factor secrets, recovery codes and external identities.
+#[async_trait]
+pub trait AuthRepository: Send + Sync {
+    // Function to get a user's two-factor secret, confirmed or not.
+    async fn two_factor(&self, user_id: i32) -> Result<Option<TwoFactorRecord>, EError>;
+
+    // Function to store an unconfirmed two-factor secret for a user, replacing any earlier one.
+    async fn start_two_factor(&self, user_id: i32, secret: String) -> Result<(), EError>;
+
+    // Function to confirm a user's two-factor secret with the time step of the first accepted code.
+    async fn enable_two_factor(&self, user_id: i32, last_used_step: i32) -> Result<(), EError>;
+
+    // Function to remember the time step of the last accepted two-factor code, if it is later than the stored one.
+    // It returns `false` if another request has already used this step or a later one.
+    async fn set_last_used_step(&self, user_id: i32, last_used_step: i32) -> Result<bool, EError>;
+
+    // Function to delete a user's two-factor secret and recovery codes; it is not an error if they have none.
+    async fn delete_two_factor(&self, user_id: i32) -> Result<(), EError>;
+
+    // Function to replace a user's recovery codes with new ones, given by their hashes.
+    async fn replace_recovery_codes(&self, user_id: i32, hash_codes: Vec<String>) -> Result<(), EError>;
+
+    // Function to find one of a user's unused recovery codes by its hash.
+    async fn find_recovery_code(&self, user_id: i32, hash_code: &str) -> Result<Option<RecoveryCodeRecord>, EError>;
+
+    // Function to mark a recovery code as used.
+    async fn use_recovery_code(&self, code_id: i32) -> Result<(), EError>;
+
+    // Function to find the user an external identity is linked to.
+    async fn find_identity(&self, provider: &str, subject: &str) -> Result<Option<UserRecord>, EError>;
+
+    // Function to link an external identity to a user.
+    async fn link_identity(&self, provider: String, subject: String, user_id: i32) -> Result<(), EError>;
+
+    // Function to create a user signing up through a login provider, without a password.
+    // It returns a conflict error if the email or username is taken.
+    async fn create_external_user(&self, email: String, username: String, avatar: Option<String>)
+        -> Result<UserRecord, EError>;
+}
+
+// The `TokenRepository` trait which stores personal API tokens.
+#[async_trait]
+pub trait TokenRepository: Send + Sync {
+    // Function to list a user's API tokens, newest first.
+    async fn api_tokens(&self, user_id: i32) -> Result<Vec<ApiTokenRecord>, EError>;
+
+    // Function to find an API token by the hash of its secret.
+    async fn find_api_token(&self, hash_token: &str) -> Result<Option<ApiTokenRecord>, EError>;
+
+    // Function to create an API token for a user.
+    async fn create_api_token(
+        &self,
+        user_id: i32,
+        name: String,
+        hash_token: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<ApiTokenRecord, EError>;
+
+    // Function to remember when an API token was last used.
+    async fn touch_api_token(&self, token_id: i32, used_at: DateTime<FixedOffset>) -> Result<(), EError>;
+
+    // Function to delete one of a user's API tokens.
+    // It returns whether the user had the token.
+    async fn revoke_api_token(&self, user_id: i32, token_id: i32) -> Result<bool, EError>;
+
+    // Function to delete every API token of a user.
+    async fn delete_api_tokens(&self, user_id: i32) -> Result<(), EError>;
+}
+
+
+// The `Repository` trait which combines every repository, as the services use them together.
+pub trait Repository:
+    UserRepository + PostRepository + CommentRepository + RelationRepository + AuthRepository + TokenRepository {}
+
+// Implementation of the `Repository` trait for everything implementing each repository.
+impl<T> Repository for T
+    where
+        T: UserRepository + PostRepository + CommentRepository + RelationRepository
+            + AuthRepository + TokenRepository,
+{}
@@ -0,0 +1,1331 @@
+// Importing the necessary modules and functions.
+use std::sync::Arc;
+use axum::async_trait;
+use prisma_client_rust::chrono::{self, DateTime, FixedOffset};
+use prisma_client_rust::{or, Direction};
+
+use crate::error::{EError, ErrorCode};
+use crate::middleware::metrics::Timed;
+use crate::repository::{
+    ApiTokenRecord, AuthRepository, CommentRecord, CommentRepository, FollowRequestRecord, MuteRecord,
+    NotificationRecord, PostFilter, PostRecord, PostRepository, PostUpdate, ProfileCounts, RecoveryCodeRecord,
+    RelationRepository, TokenRepository, TwoFactorRecord, UserRecord, UserRepository, UserUpdate, VisitRecord,
+};
+use crate::prisma::prisma::{
+    follow_requests, platform_posts, post_comments, user_api_tokens, user_blocks, user_details, user_follows,
+    user_history, user_identities, user_like_posts, user_mutes, user_notifications, user_password,
+    user_recovery_codes, user_two_factor, PrismaClient,
+};
+
+
+// Type alias for the Prisma client.
+type PRISMA = axum::Extension<Arc<PrismaClient>>;
+
+
+// The `PrismaRepository` struct which stores everything in the database through the Prisma client.
+#[derive(Clone)]
+pub struct PrismaRepository {
+    // The Prisma client.
+    prisma: PRISMA,
+}
+
+
+// Implementation of the `PrismaRepository` struct.
+impl PrismaRepository {
+    // Function to create a repository using the given Prisma client.
+    pub fn new(prisma: PRISMA) -> Self {
+        Self { prisma }
+    }
+
+    // Function to delete a user and everything referring to them, returning the IDs of the posts they liked.
+    async fn delete_user_data(&self, user_id: i32) -> Result<Vec<i32>, EError> {
+
+        let prisma = &self.prisma;
+
+        // Delete user's follows, in both directions
+        let _ = prisma
+            .user_follows()
+            .delete_many(vec![user_follows::follower_id::equals(user_id)])
+            .exec().timed().await?;
+        let _ = prisma
+            .user_follows()
+            .delete_many(vec![user_follows::followed_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user's follow requests, in both directions
+        let _ = prisma
+            .follow_requests()
+            .delete_many(vec![follow_requests::requester_id::equals(user_id)])
+            .exec().timed().await?;
+        let _ = prisma
+            .follow_requests()
+            .delete_many(vec![follow_requests::requested_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user's blocks, in both directions
+        let _ = prisma
+            .user_blocks()
+            .delete_many(vec![user_blocks::blocker_id::equals(user_id)])
+            .exec().timed().await?;
+        let _ = prisma
+            .user_blocks()
+            .delete_many(vec![user_blocks::blocked_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user's mutes, in both directions
+        let _ = prisma
+            .user_mutes()
+            .delete_many(vec![user_mutes::muter_id::equals(user_id)])
+            .exec().timed().await?;
+        let _ = prisma
+            .user_mutes()
+            .delete_many(vec![user_mutes::muted_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user's comments, and every comment on their posts
+        let _ = prisma
+            .post_comments()
+            .delete_many(vec![post_comments::user_id::equals(user_id)])
+            .exec().timed().await?;
+        let _ = prisma
+            .post_comments()
+            .delete_many(vec![post_comments::post::is(vec![platform_posts::author_id::equals(user_id)])])
+            .exec().timed().await?;
+
+        // Delete user's likes, keeping the like counts of the posts they liked right
+        let liked_posts: Vec<i32> = prisma
+            .user_like_posts()
+            .find_many(vec![user_like_posts::user_id::equals(user_id)])
+            .exec().timed().await?
+            .into_iter()
+            .map(|like| like.post_id)
+            .collect();
+        let _ = prisma
+            .user_like_posts()
+            .delete_many(vec![user_like_posts::user_id::equals(user_id)])
+            .exec().timed().await?;
+        let _ = prisma
+            .platform_posts()
+            .update_many(
+                vec![platform_posts::post_id::in_vec(liked_posts.clone())],
+                vec![platform_posts::like_count::decrement(1)],
+            )
+            .exec().timed().await?;
+        let _ = prisma
+            .user_like_posts()
+            .delete_many(vec![user_like_posts::post::is(vec![platform_posts::author_id::equals(user_id)])])
+            .exec().timed().await?;
+
+        // Delete user's history, and every view of their posts
+        let _ = prisma
+            .user_history()
+            .delete_many(vec![user_history::user_id::equals(user_id)])
+            .exec().timed().await?;
+        let _ = prisma
+            .user_history()
+            .delete_many(vec![user_history::post::is(vec![platform_posts::author_id::equals(user_id)])])
+            .exec().timed().await?;
+
+        // Delete user's notifications
+        let _ = prisma
+            .user_notifications()
+            .delete_many(vec![user_notifications::user_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user's posts
+        let _ = prisma
+            .platform_posts()
+            .delete_many(vec![platform_posts::author_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user's two-factor secret and recovery codes
+        self.delete_two_factor(user_id).await?;
+
+        // Delete user's external identities
+        let _ = prisma
+            .user_identities()
+            .delete_many(vec![user_identities::user_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user's API tokens
+        self.delete_api_tokens(user_id).await?;
+
+        // Delete user's password
+        let _ = prisma
+            .user_password()
+            .delete_many(vec![user_password::user_id::equals(user_id)])
+            .exec().timed().await?;
+
+        // Delete user
+        let _ = prisma
+            .user_details()
+            .delete(user_details::user_id::equals(user_id))
+            .exec().timed().await?;
+
+        Ok(liked_posts)
+    }
+
+    // Function to delete a post and everything referring to it.
+    async fn delete_post_data(&self, post_id: i32) -> Result<(), EError> {
+
+        let _ = self.prisma
+            .post_comments()
+            .delete_many(vec![post_comments::post_id::equals(post_id)])
+            .exec().timed().await?;
+
+        let _ = self.prisma
+            .user_history()
+            .delete_many(vec![user_history::post_id::equals(post_id)])
+            .exec().timed().await?;
+
+        let _ = self.prisma
+            .user_like_posts()
+            .delete_many(vec![user_like_posts::post_id::equals(post_id)])
+            .exec().timed().await?;
+
+        let _ = self.prisma
+            .platform_posts()
+            .delete(platform_posts::post_id::equals(post_id))
+            .exec().timed().await?;
+
+        Ok(())
+    }
+}
+
+
+// Implementation of the `From` trait for `UserRecord`.
+impl From<user_details::Data> for UserRecord {
+    // Function to convert `user_details::Data` into a `UserRecord`.
+    fn from(data: user_details::Data) -> Self {
+        Self {
+            user_id: data.user_id,
+            email: data.email,
+            username: data.username,
+            intro: data.intro,
+            avatar: data.avatar,
+            role: data.role,
+            created_at: data.created_at,
+            updated_at: data.updated_at,
+            suspended_at: data.suspended_at,
+            private: data.private,
+        }
+    }
+}
+
+// Implementation of the `From` trait for `TwoFactorRecord`.
+impl From<user_two_factor::Data> for TwoFactorRecord {
+    // Function to convert `user_two_factor::Data` into a `TwoFactorRecord`.
+    fn from(data: user_two_factor::Data) -> Self {
+        Self {
+            secret: data.secret,
+            enabled: data.enabled,
+            last_used_step: data.last_used_step,
+        }
+    }
+}
+
+// Implementation of the `From` trait for `RecoveryCodeRecord`.
+impl From<user_recovery_codes::Data> for RecoveryCodeRecord {
+    // Function to convert `user_recovery_codes::Data` into a `RecoveryCodeRecord`.
+    fn from(data: user_recovery_codes::Data) -> Self {
+        Self {
+            code_id: data.code_id,
+            hash_code: data.hash_code,
+        }
+    }
+}
+
+// Implementation of the `From` trait for `ApiTokenRecord`.
+impl From<user_api_tokens::Data> for ApiTokenRecord {
+    // Function to convert `user_api_tokens::Data` into an `ApiTokenRecord`.
+    fn from(data: user_api_tokens::Data) -> Self {
+        Self {
+            token_id: data.token_id,
+            user_id: data.user_id,
+            name: data.name,
+            scopes: data.scopes,
+            expires_at: data.expires_at,
+            last_used_at: data.last_used_at,
+            created_at: data.created_at,
+        }
+    }
+}
+
+// Implementation of the `TryFrom` trait for `FollowRequestRecord`.
+impl TryFrom<follow_requests::Data> for FollowRequestRecord {
+    type Error = EError;
+
+    // Function to convert `follow_requests::Data`, fetched with the requester, into a `FollowRequestRecord`.
+    fn try_from(data: follow_requests::Data) -> Result<Self, EError> {
+        let user = data.requester().map_err(|_| EError::InternalServerError(
+            ErrorCode::InternalError, String::from("Requester not fetched"),
+        ))?.clone();
+
+        Ok(Self {
+            user: user.into(),
+            created_at: data.created_at,
+        })
+    }
+}
+
+// Implementation of the `From` trait for `NotificationRecord`.
+impl From<user_notifications::Data> for NotificationRecord {
+    // Function to convert `user_notifications::Data` into a `NotificationRecord`.
+    fn from(data: user_notifications::Data) -> Self {
+        Self {
+            notification_id: data.notification_id,
+            message: data.message,
+            created_at: data.created_at,
+        }
+    }
+}
+
+// Implementation of the `TryFrom` trait for `PostRecord`.
+impl TryFrom<platform_posts::Data> for PostRecord {
+    type Error = EError;
+
+    // Function to convert `platform_posts::Data`, fetched with its author, into a `PostRecord`.
+    fn try_from(data: platform_posts::Data) -> Result<Self, EError> {
+        let author = data.author().map_err(|_| EError::InternalServerError(
+            ErrorCode::InternalError, String::from("Post author not fetched"),
+        ))?.clone();
+
+        Ok(Self {
+            post_id: data.post_id,
+            author_id: data.author_id,
+            title: data.title,
+            description: data.description,
+            content: data.content,
+            like_count: data.like_count,
+            created_at: data.created_at,
+            updated_at: data.updated_at,
+            author: author.into(),
+        })
+    }
+}
+
+// Implementation of the `TryFrom` trait for `CommentRecord`.
+impl TryFrom<post_comments::Data> for CommentRecord {
+    type Error = EError;
+
+    // Function to convert `post_comments::Data`, fetched with its author, into a `CommentRecord`.
+    fn try_from(data: post_comments::Data) -> Result<Self, EError> {
+        let user = data.user().map_err(|_| EError::InternalServerError(
+            ErrorCode::InternalError, String::from("Comment author not fetched"),
+        ))?.clone();
+
+        Ok(Self {
+            comment_id: data.comment_id,
+            post_id: data.post_id,
+            user_id: data.user_id,
+            content: data.content,
+            created_at: data.created_at,
+            user: user.into(),
+        })
+    }
+}
+
+// Implementation of the `TryFrom` trait for `MuteRecord`.
+impl TryFrom<user_mutes::Data> for MuteRecord {
+    type Error = EError;
+
+    // Function to convert `user_mutes::Data`, fetched with the muted user, into a `MuteRecord`.
+    fn try_from(data: user_mutes::Data) -> Result<Self, EError> {
+        let user = data.muted().map_err(|_| EError::InternalServerError(
+            ErrorCode::InternalError, String::from("Muted user not fetched"),
+        ))?.clone();
+
+        Ok(Self {
+            user: user.into(),
+            expires_at: data.expires_at,
+            created_at: data.created_at,
+        })
+    }
+}
+
+
+// Function to build the filter matching the mutes of a user which have not expired.
+fn active_mutes(muter_id: i32) -> Vec<user_mutes::WhereParam> {
+    let now: DateTime<FixedOffset> = chrono::Utc::now().into();
+
+    vec![
+        user_mutes::muter_id::equals(muter_id),
+        or![user_mutes::expires_at::equals(None), user_mutes::expires_at::gt(now)],
+    ]
+}
+
+// Function to build the filter matching the posts a viewer may read: those of public users, their own,
+// and those of private users they follow.
+fn visible_posts(viewer_id: Option<i32>) -> platform_posts::WhereParam {
+    let public = platform_posts::author::is(vec![user_details::private::equals(false)]);
+
+    match viewer_id {
+        Some(viewer_id) => or![
+            public,
+            platform_posts::author_id::equals(viewer_id),
+            platform_posts::author::is(vec![
+                user_details::followed_by::some(vec![user_follows::follower_id::equals(viewer_id)]),
+            ]),
+        ],
+        None => public,
+    }
+}
+
+
+// Implementation of the `PrismaRepository` struct.
+impl PrismaRepository {
+    // Function to list a page of the users matching a filter, by username, and count all of them.
+    async fn user_page(&self, filter: Vec<user_details::WhereParam>, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError> {
+
+        let data = self.prisma
+            .user_details()
+            .find_many(filter.clone())
+            .take(limit)
+            .skip(offset)
+            .order_by(user_details::username::order(Direction::Asc))
+            .exec().timed().await?;
+
+        let count = self.prisma.user_details().count(filter).exec().timed().await?;
+
+        Ok((data.into_iter().map(UserRecord::from).collect(), count))
+    }
+}
+
+
+// Implementation of the `UserRepository` trait for `PrismaRepository`.
+#[async_trait]
+impl UserRepository for PrismaRepository {
+
+    async fn find_user(&self, user_id: i32) -> Result<Option<UserRecord>, EError> {
+        let data = self.prisma
+            .user_details()
+            .find_unique(user_details::user_id::equals(user_id))
+            .exec().timed().await?;
+
+        Ok(data.map(UserRecord::from))
+    }
+
+    async fn find_user_by_name(&self, username: &str) -> Result<Option<UserRecord>, EError> {
+        let data = self.prisma
+            .user_details()
+            .find_unique(user_details::username::equals(username.to_string()))
+            .exec().timed().await?;
+
+        Ok(data.map(UserRecord::from))
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<UserRecord>, EError> {
+        let data = self.prisma
+            .user_details()
+            .find_unique(user_details::email::equals(email.to_string()))
+            .exec().timed().await?;
+
+        Ok(data.map(UserRecord::from))
+    }
+
+    async fn create_user(&self, email: String, username: String, password_hash: String)
+        -> Result<UserRecord, EError> {
+
+        let data = self.prisma
+            .user_details()
+            .create(email, username, vec![])
+            .exec().timed().await?;
+
+        let _ = self.prisma
+            .user_password()
+            .create(password_hash, user_details::user_id::equals(data.user_id), vec![])
+            .exec().timed().await?;
+
+        Ok(data.into())
+    }
+
+    async fn update_user(&self, user_id: i32, update: UserUpdate) -> Result<UserRecord, EError> {
+
+        let mut params = Vec::new();
+        if let Some(email) = update.email {
+            params.push(user_details::email::set(email));
+        }
+        if let Some(username) = update.username {
+            params.push(user_details::username::set(username));
+        }
+        if let Some(intro) = update.intro {
+            params.push(user_details::intro::set(Some(intro)));
+        }
+        if let Some(avatar) = update.avatar {
+            params.push(user_details::avatar::set(Some(avatar)));
+        }
+        if let Some(private) = update.private {
+            params.push(user_details::private::set(private));
+        }
+
+        let data = self.prisma
+            .user_details()
+            .update(user_details::user_id::equals(user_id), params)
+            .exec().timed().await?;
+
+        Ok(data.into())
+    }
+
+    async fn set_suspended(&self, user_id: i32, suspended_at: Option<DateTime<FixedOffset>>)
+        -> Result<UserRecord, EError> {
+
+        let data = self.prisma
+            .user_details()
+            .update(
+                user_details::user_id::equals(user_id),
+                vec![user_details::suspended_at::set(suspended_at)],
+            )
+            .exec().timed().await?;
+
+        Ok(data.into())
+    }
+
+    async fn password_hash(&self, user_id: i32) -> Result<Option<String>, EError> {
+        let data = self.prisma
+            .user_password()
+            .find_unique(user_password::user_id::equals(user_id))
+            .exec().timed().await?;
+
+        Ok(data.map(|data| data.hash_password))
+    }
+
+    async fn set_password_hash(&self, user_id: i32, password_hash: String) -> Result<(), EError> {
+
+        // Users who signed up through a login provider have no password yet.
+        let _ = self.prisma
+            .user_password()
+            .upsert(
+                user_password::user_id::equals(user_id),
+                user_password::create(password_hash.clone(), user_details::user_id::equals(user_id), vec![]),
+                vec![user_password::hash_password::set(password_hash)],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn notify(&self, user_id: i32, message: String) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_notifications()
+            .create(message, user_details::user_id::equals(user_id), vec![])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn notifications(&self, user_id: i32) -> Result<Vec<NotificationRecord>, EError> {
+        let data = self.prisma
+            .user_notifications()
+            .find_many(vec![user_notifications::user_id::equals(user_id)])
+            .order_by(user_notifications::created_at::order(Direction::Desc))
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(NotificationRecord::from).collect())
+    }
+
+    async fn profile_counts(&self, user_id: i32) -> Result<ProfileCounts, EError> {
+        let followers = self.prisma
+            .user_follows()
+            .count(vec![user_follows::followed_id::equals(user_id)])
+            .exec().timed().await?;
+
+        let following = self.prisma
+            .user_follows()
+            .count(vec![user_follows::follower_id::equals(user_id)])
+            .exec().timed().await?;
+
+        let posts = self.prisma
+            .platform_posts()
+            .count(vec![platform_posts::author_id::equals(user_id)])
+            .exec().timed().await?;
+
+        Ok(ProfileCounts { followers, following, posts })
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<Vec<i32>, EError> {
+        // Delete the user and everything referring to them in one transaction, so a failure can't leave them
+        // half deleted
+        self.prisma
+            ._transaction()
+            .run(|tx| async move {
+                PrismaRepository::new(axum::Extension(Arc::new(tx))).delete_user_data(user_id).await
+            })
+            .await
+    }
+}
+
+
+// Implementation of the `PostRepository` trait for `PrismaRepository`.
+#[async_trait]
+impl PostRepository for PrismaRepository {
+
+    async fn find_post(&self, post_id: i32) -> Result<Option<PostRecord>, EError> {
+        let data = self.prisma
+            .platform_posts()
+            .find_unique(platform_posts::post_id::equals(post_id))
+            .with(platform_posts::author::fetch())
+            .exec().timed().await?;
+
+        data.map(PostRecord::try_from).transpose()
+    }
+
+    async fn list_posts(&self, filter: &PostFilter) -> Result<(Vec<PostRecord>, i64), EError> {
+
+        let mut params: Vec<platform_posts::WhereParam> = Vec::new();
+
+        if let Some(author) = &filter.author {
+            params.push(platform_posts::author::is(vec![user_details::username::equals(author.clone())]));
+        }
+
+        if let Some(liked_by) = &filter.liked_by {
+            params.push(platform_posts::liked_by_users::some(vec![
+                user_like_posts::user::is(vec![user_details::username::equals(liked_by.clone())])]));
+        }
+
+        if let Some(author_ids) = &filter.author_ids {
+            params.push(platform_posts::author_id::in_vec(author_ids.clone()));
+        }
+
+        if !filter.excluded_author_ids.is_empty() {
+            params.push(platform_posts::author_id::not_in_vec(filter.excluded_author_ids.clone()));
+        }
+
+        params.push(visible_posts(filter.viewer_id));
+
+        let data = self.prisma
+            .platform_posts()
+            .find_many(params.clone())
+            .with(platform_posts::author::fetch())
+            .take(filter.limit)
+            .skip(filter.offset)
+            .order_by(platform_posts::created_at::order(Direction::Desc))
+            .exec().timed().await?;
+
+        let count = self.prisma.platform_posts().count(params).exec().timed().await?;
+
+        let posts = data.into_iter().map(PostRecord::try_from).collect::<Result<Vec<_>, _>>()?;
+
+        Ok((posts, count))
+    }
+
+    async fn post_ids_by_author(&self, user_id: i32) -> Result<Vec<i32>, EError> {
+        let posts = self.prisma
+            .platform_posts()
+            .find_many(vec![platform_posts::author_id::equals(user_id)])
+            .select(platform_posts::select!({ post_id }))
+            .exec().timed().await?;
+
+        Ok(posts.into_iter().map(|post| post.post_id).collect())
+    }
+
+    async fn create_post(&self, author_id: i32, title: String, description: String, content: String)
+        -> Result<PostRecord, EError> {
+
+        let data = self.prisma
+            .platform_posts()
+            .create(title, description, content, user_details::user_id::equals(author_id), vec![])
+            .with(platform_posts::author::fetch())
+            .exec().timed().await?;
+
+        data.try_into()
+    }
+
+    async fn update_post(&self, post_id: i32, update: PostUpdate) -> Result<PostRecord, EError> {
+
+        let mut params = Vec::new();
+        if let Some(title) = update.title {
+            params.push(platform_posts::title::set(title));
+        }
+        if let Some(description) = update.description {
+            params.push(platform_posts::description::set(description));
+        }
+        if let Some(content) = update.content {
+            params.push(platform_posts::content::set(content));
+        }
+
+        let data = self.prisma
+            .platform_posts()
+            .update(platform_posts::post_id::equals(post_id), params)
+            .with(platform_posts::author::fetch())
+            .exec().timed().await?;
+
+        data.try_into()
+    }
+
+    async fn delete_post(&self, post_id: i32) -> Result<(), EError> {
+        // Delete the post and everything referring to it in one transaction
+        self.prisma
+            ._transaction()
+            .run(|tx| async move {
+                PrismaRepository::new(axum::Extension(Arc::new(tx))).delete_post_data(post_id).await
+            })
+            .await
+    }
+
+    async fn is_liked(&self, user_id: i32, post_id: i32) -> Result<bool, EError> {
+        let data = self.prisma
+            .user_like_posts()
+            .find_unique(user_like_posts::user_id_post_id(user_id, post_id))
+            .exec().timed().await?;
+
+        Ok(data.is_some())
+    }
+
+    async fn like_post(&self, user_id: i32, post_id: i32) -> Result<PostRecord, EError> {
+
+        let _ = self.prisma
+            .user_like_posts()
+            .create(user_details::user_id::equals(user_id), platform_posts::post_id::equals(post_id), vec![])
+            .exec().timed().await?;
+
+        let data = self.prisma
+            .platform_posts()
+            .update(platform_posts::post_id::equals(post_id), vec![platform_posts::like_count::increment(1)])
+            .with(platform_posts::author::fetch())
+            .exec().timed().await?;
+
+        data.try_into()
+    }
+
+    async fn unlike_post(&self, user_id: i32, post_id: i32) -> Result<PostRecord, EError> {
+
+        let _ = self.prisma
+            .user_like_posts()
+            .delete(user_like_posts::user_id_post_id(user_id, post_id))
+            .exec().timed().await?;
+
+        let data = self.prisma
+            .platform_posts()
+            .update(platform_posts::post_id::equals(post_id), vec![platform_posts::like_count::decrement(1)])
+            .with(platform_posts::author::fetch())
+            .exec().timed().await?;
+
+        data.try_into()
+    }
+
+    async fn record_visit(&self, user_id: i32, post_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_history()
+            .create(user_details::user_id::equals(user_id), platform_posts::post_id::equals(post_id), vec![])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn history(&self, user_id: i32, excluded_author_ids: &[i32], offset: i64, limit: i64)
+        -> Result<(Vec<VisitRecord>, i64), EError> {
+
+        let mut filter = vec![user_history::user_id::equals(user_id)];
+
+        if !excluded_author_ids.is_empty() {
+            filter.push(user_history::post::is(vec![
+                platform_posts::author_id::not_in_vec(excluded_author_ids.to_vec())]));
+        }
+
+        let data = self.prisma
+            .user_history()
+            .find_many(filter.clone())
+            .with(user_history::post::fetch().with(platform_posts::author::fetch()))
+            .take(limit)
+            .skip(offset)
+            .order_by(user_history::time::order(Direction::Desc))
+            .exec().timed().await?;
+
+        let count = self.prisma.user_history().count(filter).exec().timed().await?;
+
+        let mut visits = Vec::new();
+        for visit in data {
+            let post = visit.post().map_err(|_| EError::InternalServerError(
+                ErrorCode::InternalError, String::from("Visited post not fetched"),
+            ))?.clone();
+
+            visits.push(VisitRecord { post: post.try_into()?, time: visit.time });
+        }
+
+        Ok((visits, count))
+    }
+}
+
+
+// Implementation of the `CommentRepository` trait for `PrismaRepository`.
+#[async_trait]
+impl CommentRepository for PrismaRepository {
+
+    async fn comments(&self, post_id: i32, excluded_user_ids: &[i32], offset: i64, limit: i64)
+        -> Result<(Vec<CommentRecord>, i64), EError> {
+
+        let mut filter = vec![post_comments::post_id::equals(post_id)];
+
+        if !excluded_user_ids.is_empty() {
+            filter.push(post_comments::user_id::not_in_vec(excluded_user_ids.to_vec()));
+        }
+
+        let data = self.prisma
+            .post_comments()
+            .find_many(filter.clone())
+            .with(post_comments::user::fetch())
+            .take(limit)
+            .skip(offset)
+            .order_by(post_comments::created_at::order(Direction::Asc))
+            .exec().timed().await?;
+
+        let count = self.prisma.post_comments().count(filter).exec().timed().await?;
+
+        let comments = data.into_iter().map(CommentRecord::try_from).collect::<Result<_, _>>()?;
+
+        Ok((comments, count))
+    }
+
+    async fn find_comment(&self, comment_id: i32) -> Result<Option<CommentRecord>, EError> {
+        let data = self.prisma
+            .post_comments()
+            .find_unique(post_comments::comment_id::equals(comment_id))
+            .with(post_comments::user::fetch())
+            .exec().timed().await?;
+
+        data.map(CommentRecord::try_from).transpose()
+    }
+
+    async fn create_comment(&self, user_id: i32, post_id: i32, content: String)
+        -> Result<CommentRecord, EError> {
+        let data = self.prisma
+            .post_comments()
+            .create(
+                content,
+                user_details::user_id::equals(user_id),
+                platform_posts::post_id::equals(post_id),
+                vec![],
+            )
+            .with(post_comments::user::fetch())
+            .exec().timed().await?;
+
+        data.try_into()
+    }
+
+    async fn delete_comment(&self, comment_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .post_comments()
+            .delete(post_comments::comment_id::equals(comment_id))
+            .exec().timed().await?;
+
+        Ok(())
+    }
+}
+
+
+// Implementation of the `RelationRepository` trait for `PrismaRepository`.
+#[async_trait]
+impl RelationRepository for PrismaRepository {
+
+    async fn is_following(&self, follower_id: i32, followed_id: i32) -> Result<bool, EError> {
+        let data = self.prisma
+            .user_follows()
+            .find_unique(user_follows::follower_id_followed_id(follower_id, followed_id))
+            .exec().timed().await?;
+
+        Ok(data.is_some())
+    }
+
+    async fn is_blocked(&self, blocker_id: i32, blocked_id: i32) -> Result<bool, EError> {
+        let data = self.prisma
+            .user_blocks()
+            .find_unique(user_blocks::blocker_id_blocked_id(blocker_id, blocked_id))
+            .exec().timed().await?;
+
+        Ok(data.is_some())
+    }
+
+    async fn followed_ids(&self, follower_id: i32) -> Result<Vec<i32>, EError> {
+        let data = self.prisma
+            .user_follows()
+            .find_many(vec![user_follows::follower_id::equals(follower_id)])
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(|follow| follow.followed_id).collect())
+    }
+
+    async fn follower_ids(&self, followed_id: i32) -> Result<Vec<i32>, EError> {
+        let data = self.prisma
+            .user_follows()
+            .find_many(vec![user_follows::followed_id::equals(followed_id)])
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(|follow| follow.follower_id).collect())
+    }
+
+    async fn blocked_ids(&self, blocker_id: i32) -> Result<Vec<i32>, EError> {
+        let data = self.prisma
+            .user_blocks()
+            .find_many(vec![user_blocks::blocker_id::equals(blocker_id)])
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(|block| block.blocked_id).collect())
+    }
+
+    async fn blocker_ids(&self, blocked_id: i32) -> Result<Vec<i32>, EError> {
+        let data = self.prisma
+            .user_blocks()
+            .find_many(vec![user_blocks::blocked_id::equals(blocked_id)])
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(|block| block.blocker_id).collect())
+    }
+
+    async fn followers(&self, followed_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError> {
+
+        // `followers` holds the follows a user made, as the follower.
+        let filter = vec![user_details::followers::some(vec![user_follows::followed_id::equals(followed_id)])];
+
+        self.user_page(filter, offset, limit).await
+    }
+
+    async fn following(&self, follower_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError> {
+
+        let filter = vec![
+            user_details::followed_by::some(vec![user_follows::follower_id::equals(follower_id)])
+        ];
+
+        self.user_page(filter, offset, limit).await
+    }
+
+    async fn blocked_users(&self, blocker_id: i32, offset: i64, limit: i64)
+        -> Result<(Vec<UserRecord>, i64), EError> {
+
+        let filter = vec![
+            user_details::blocked_by_users::some(vec![user_blocks::blocker_id::equals(blocker_id)])
+        ];
+
+        self.user_page(filter, offset, limit).await
+    }
+
+    async fn is_requested(&self, requester_id: i32, requested_id: i32) -> Result<bool, EError> {
+        let data = self.prisma
+            .follow_requests()
+            .find_unique(follow_requests::requester_id_requested_id(requester_id, requested_id))
+            .exec().timed().await?;
+
+        Ok(data.is_some())
+    }
+
+    async fn requested_ids(&self, requester_id: i32) -> Result<Vec<i32>, EError> {
+        let data = self.prisma
+            .follow_requests()
+            .find_many(vec![follow_requests::requester_id::equals(requester_id)])
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(|request| request.requested_id).collect())
+    }
+
+    async fn requester_ids(&self, requested_id: i32) -> Result<Vec<i32>, EError> {
+        let data = self.prisma
+            .follow_requests()
+            .find_many(vec![follow_requests::requested_id::equals(requested_id)])
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(|request| request.requester_id).collect())
+    }
+
+    async fn follow_requests(&self, requested_id: i32) -> Result<Vec<FollowRequestRecord>, EError> {
+        let data = self.prisma
+            .follow_requests()
+            .find_many(vec![follow_requests::requested_id::equals(requested_id)])
+            .with(follow_requests::requester::fetch())
+            .order_by(follow_requests::created_at::order(Direction::Desc))
+            .exec().timed().await?;
+
+        data.into_iter().map(FollowRequestRecord::try_from).collect()
+    }
+
+    async fn request_follow(&self, requester_id: i32, requested_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .follow_requests()
+            .upsert(
+                follow_requests::requester_id_requested_id(requester_id, requested_id),
+                follow_requests::create(
+                    user_details::user_id::equals(requester_id),
+                    user_details::user_id::equals(requested_id),
+                    vec![],
+                ),
+                vec![],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn remove_request(&self, requester_id: i32, requested_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .follow_requests()
+            .delete_many(vec![
+                follow_requests::requester_id::equals(requester_id),
+                follow_requests::requested_id::equals(requested_id),
+            ])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn follow(&self, follower_id: i32, followed_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_follows()
+            .upsert(
+                user_follows::follower_id_followed_id(follower_id, followed_id),
+                user_follows::create(
+                    user_details::user_id::equals(follower_id),
+                    user_details::user_id::equals(followed_id),
+                    vec![],
+                ),
+                vec![],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn unfollow(&self, follower_id: i32, followed_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_follows()
+            .delete_many(vec![
+                user_follows::follower_id::equals(follower_id),
+                user_follows::followed_id::equals(followed_id),
+            ])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn block(&self, blocker_id: i32, blocked_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_blocks()
+            .upsert(
+                user_blocks::blocker_id_blocked_id(blocker_id, blocked_id),
+                user_blocks::create(
+                    user_details::user_id::equals(blocker_id),
+                    user_details::user_id::equals(blocked_id),
+                    vec![],
+                ),
+                vec![],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn unblock(&self, blocker_id: i32, blocked_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_blocks()
+            .delete_many(vec![
+                user_blocks::blocker_id::equals(blocker_id),
+                user_blocks::blocked_id::equals(blocked_id),
+            ])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn is_muted(&self, muter_id: i32, muted_id: i32) -> Result<bool, EError> {
+        let mut filter = active_mutes(muter_id);
+        filter.push(user_mutes::muted_id::equals(muted_id));
+
+        let data = self.prisma
+            .user_mutes()
+            .find_first(filter)
+            .exec().timed().await?;
+
+        Ok(data.is_some())
+    }
+
+    async fn muted_ids(&self, muter_id: i32) -> Result<Vec<i32>, EError> {
+        let data = self.prisma
+            .user_mutes()
+            .find_many(active_mutes(muter_id))
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(|mute| mute.muted_id).collect())
+    }
+
+    async fn mutes(&self, muter_id: i32) -> Result<Vec<MuteRecord>, EError> {
+        let data = self.prisma
+            .user_mutes()
+            .find_many(active_mutes(muter_id))
+            .with(user_mutes::muted::fetch())
+            .order_by(user_mutes::created_at::order(Direction::Desc))
+            .exec().timed().await?;
+
+        data.into_iter().map(MuteRecord::try_from).collect()
+    }
+
+    async fn mute(&self, muter_id: i32, muted_id: i32, expires_at: Option<DateTime<FixedOffset>>)
+        -> Result<MuteRecord, EError> {
+
+        let data = self.prisma
+            .user_mutes()
+            .upsert(
+                user_mutes::muter_id_muted_id(muter_id, muted_id),
+                user_mutes::create(
+                    user_details::user_id::equals(muter_id),
+                    user_details::user_id::equals(muted_id),
+                    vec![user_mutes::expires_at::set(expires_at)],
+                ),
+                vec![user_mutes::expires_at::set(expires_at)],
+            )
+            .with(user_mutes::muted::fetch())
+            .exec().timed().await?;
+
+        data.try_into()
+    }
+
+    async fn unmute(&self, muter_id: i32, muted_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_mutes()
+            .delete_many(vec![
+                user_mutes::muter_id::equals(muter_id),
+                user_mutes::muted_id::equals(muted_id),
+            ])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+}
+
+
+// Implementation of the `AuthRepository` trait for `PrismaRepository`.
+#[async_trait]
+impl AuthRepository for PrismaRepository {
+
+    async fn two_factor(&self, user_id: i32) -> Result<Option<TwoFactorRecord>, EError> {
+        let data = self.prisma
+            .user_two_factor()
+            .find_unique(user_two_factor::user_id::equals(user_id))
+            .exec().timed().await?;
+
+        Ok(data.map(TwoFactorRecord::from))
+    }
+
+    async fn start_two_factor(&self, user_id: i32, secret: String) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_two_factor()
+            .upsert(
+                user_two_factor::user_id::equals(user_id),
+                user_two_factor::create(secret.clone(), user_details::user_id::equals(user_id), vec![]),
+                vec![
+                    user_two_factor::secret::set(secret),
+                    user_two_factor::enabled::set(false),
+                    user_two_factor::last_used_step::set(0),
+                ],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn enable_two_factor(&self, user_id: i32, last_used_step: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_two_factor()
+            .update(
+                user_two_factor::user_id::equals(user_id),
+                vec![
+                    user_two_factor::enabled::set(true),
+                    user_two_factor::last_used_step::set(last_used_step),
+                ],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn set_last_used_step(&self, user_id: i32, last_used_step: i32) -> Result<bool, EError> {
+        // The condition and the write are one statement, so concurrent requests can't both use the same step.
+        let updated = self.prisma
+            .user_two_factor()
+            .update_many(
+                vec![
+                    user_two_factor::user_id::equals(user_id),
+                    user_two_factor::last_used_step::lt(last_used_step),
+                ],
+                vec![user_two_factor::last_used_step::set(last_used_step)],
+            )
+            .exec().timed().await?;
+
+        Ok(updated > 0)
+    }
+
+    async fn delete_two_factor(&self, user_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_recovery_codes()
+            .delete_many(vec![user_recovery_codes::user_id::equals(user_id)])
+            .exec().timed().await?;
+
+        let _ = self.prisma
+            .user_two_factor()
+            .delete_many(vec![user_two_factor::user_id::equals(user_id)])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(&self, user_id: i32, hash_codes: Vec<String>) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_recovery_codes()
+            .delete_many(vec![user_recovery_codes::user_id::equals(user_id)])
+            .exec().timed().await?;
+
+        for hash_code in hash_codes {
+            let _ = self.prisma
+                .user_recovery_codes()
+                .create(hash_code, user_details::user_id::equals(user_id), vec![])
+                .exec().timed().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn find_recovery_code(&self, user_id: i32, hash_code: &str) -> Result<Option<RecoveryCodeRecord>, EError> {
+        let data = self.prisma
+            .user_recovery_codes()
+            .find_first(vec![
+                user_recovery_codes::user_id::equals(user_id),
+                user_recovery_codes::hash_code::equals(hash_code.to_string()),
+                user_recovery_codes::used::equals(false),
+            ])
+            .exec().timed().await?;
+
+        Ok(data.map(RecoveryCodeRecord::from))
+    }
+
+    async fn use_recovery_code(&self, code_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_recovery_codes()
+            .update(
+                user_recovery_codes::code_id::equals(code_id),
+                vec![user_recovery_codes::used::set(true)],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn find_identity(&self, provider: &str, subject: &str) -> Result<Option<UserRecord>, EError> {
+        let data = self.prisma
+            .user_identities()
+            .find_unique(user_identities::provider_subject(provider.to_string(), subject.to_string()))
+            .with(user_identities::user::fetch())
+            .exec().timed().await?;
+
+        let Some(data) = data else {
+            return Ok(None);
+        };
+
+        let user = data.user().map_err(|_| EError::InternalServerError(
+            ErrorCode::InternalError, String::from("Identity user not fetched"),
+        ))?.clone();
+
+        Ok(Some(user.into()))
+    }
+
+    async fn link_identity(&self, provider: String, subject: String, user_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_identities()
+            .create(provider, subject, user_details::user_id::equals(user_id), vec![])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn create_external_user(&self, email: String, username: String, avatar: Option<String>)
+        -> Result<UserRecord, EError> {
+
+        let data = self.prisma
+            .user_details()
+            .create(email, username, vec![user_details::avatar::set(avatar)])
+            .exec().timed().await?;
+
+        Ok(data.into())
+    }
+}
+
+
+// Implementation of the `TokenRepository` trait for `PrismaRepository`.
+#[async_trait]
+impl TokenRepository for PrismaRepository {
+
+    async fn api_tokens(&self, user_id: i32) -> Result<Vec<ApiTokenRecord>, EError> {
+        let data = self.prisma
+            .user_api_tokens()
+            .find_many(vec![user_api_tokens::user_id::equals(user_id)])
+            .order_by(user_api_tokens::created_at::order(Direction::Desc))
+            .exec().timed().await?;
+
+        Ok(data.into_iter().map(ApiTokenRecord::from).collect())
+    }
+
+    async fn find_api_token(&self, hash_token: &str) -> Result<Option<ApiTokenRecord>, EError> {
+        let data = self.prisma
+            .user_api_tokens()
+            .find_unique(user_api_tokens::hash_token::equals(hash_token.to_string()))
+            .exec().timed().await?;
+
+        Ok(data.map(ApiTokenRecord::from))
+    }
+
+    async fn create_api_token(
+        &self,
+        user_id: i32,
+        name: String,
+        hash_token: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<ApiTokenRecord, EError> {
+
+        let data = self.prisma
+            .user_api_tokens()
+            .create(
+                name,
+                hash_token,
+                user_details::user_id::equals(user_id),
+                vec![
+                    user_api_tokens::scopes::set(scopes),
+                    user_api_tokens::expires_at::set(expires_at),
+                ],
+            )
+            .exec().timed().await?;
+
+        Ok(data.into())
+    }
+
+    async fn touch_api_token(&self, token_id: i32, used_at: DateTime<FixedOffset>) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_api_tokens()
+            .update(
+                user_api_tokens::token_id::equals(token_id),
+                vec![user_api_tokens::last_used_at::set(Some(used_at))],
+            )
+            .exec().timed().await?;
+
+        Ok(())
+    }
+
+    async fn revoke_api_token(&self, user_id: i32, token_id: i32) -> Result<bool, EError> {
+        let deleted = self.prisma
+            .user_api_tokens()
+            .delete_many(vec![
+                user_api_tokens::token_id::equals(token_id),
+                user_api_tokens::user_id::equals(user_id),
+            ])
+            .exec().timed().await?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn delete_api_tokens(&self, user_id: i32) -> Result<(), EError> {
+        let _ = self.prisma
+            .user_api_tokens()
+            .delete_many(vec![user_api_tokens::user_id::equals(user_id)])
+            .exec().timed().await?;
+
+        Ok(())
+    }
+}
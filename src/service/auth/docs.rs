@@ -0,0 +1,98 @@
+// Importing the necessary modules and functions.
+use crate::service::auth::model::OidcCallbackQuery;
+
+
+// The OpenAPI descriptions of the routes in `AuthRouter`.
+// Each function only carries the `#[utoipa::path]` attribute of the handler of the same name,
+// the schemas are referenced by name and registered in `crate::service::openapi::ApiDoc`.
+
+
+/// Start two-factor enrollment
+#[utoipa::path(
+    post, path = "/api/auth/2fa/enroll", tag = "auth",
+    responses(
+        (status = 200, description = "The new secret and its otpauth URI", body = EnrollmentResponse),
+        (status = 400, description = "Two-factor authentication is already enabled", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn enroll_two_factor() {}
+
+/// Confirm two-factor enrollment with a first code
+#[utoipa::path(
+    post, path = "/api/auth/2fa/confirm", tag = "auth",
+    request_body = CodeRequest,
+    responses(
+        (status = 200, description = "The recovery codes, shown only once", body = RecoveryCodesResponse),
+        (status = 400, description = "No pending enrollment, or an invalid code", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn confirm_two_factor() {}
+
+/// Complete a login with a two-factor code
+#[utoipa::path(
+    post, path = "/api/auth/2fa/verify", tag = "auth",
+    request_body = VerifyRequest,
+    responses(
+        (status = 200, description = "The logged-in user", body = UserResponse),
+        (status = 400, description = "Missing code, or two-factor authentication is not enabled", body = ErrorBody),
+        (status = 401, description = "Invalid code or challenge token", body = ErrorBody),
+        (status = 429, description = "Too many failed codes", body = ErrorBody),
+    ),
+)]
+pub fn verify_two_factor() {}
+
+/// Disable two-factor authentication
+#[utoipa::path(
+    delete, path = "/api/auth/2fa", tag = "auth",
+    request_body = CodeRequest,
+    responses(
+        (status = 200, description = "Two-factor authentication disabled", body = String),
+        (status = 400, description = "Invalid code, or two-factor authentication is not enabled", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Two-factor authentication is required for the user's role", body = ErrorBody),
+        (status = 429, description = "Too many failed codes", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn disable_two_factor() {}
+
+/// Start a login at an OpenID Connect provider
+#[utoipa::path(
+    get, path = "/api/auth/oidc/{provider}", tag = "auth",
+    params(("provider" = String, Path, description = "The name of the configured provider")),
+    responses(
+        (status = 303, description = "Redirect to the provider's authorization endpoint, setting the login state cookie"),
+        (status = 404, description = "Unknown provider", body = ErrorBody),
+    ),
+)]
+pub fn oidc_login() {}
+
+/// Complete a login at an OpenID Connect provider
+#[utoipa::path(
+    get, path = "/api/auth/oidc/{provider}/callback", tag = "auth",
+    params(
+        ("provider" = String, Path, description = "The name of the configured provider"),
+        OidcCallbackQuery,
+    ),
+    responses(
+        (status = 200, description = "The logged-in user, or the two-factor challenge to complete", body = LoginResponse),
+        (status = 400, description = "Missing code, state or login state cookie, a state started in another browser, or no email shared by the provider", body = ErrorBody),
+        (status = 401, description = "Invalid state, code or ID token", body = ErrorBody),
+        (status = 403, description = "The provider's email is not verified, or the account is suspended", body = ErrorBody),
+        (status = 404, description = "Unknown provider", body = ErrorBody),
+    ),
+)]
+pub fn oidc_callback() {}
+
+/// Fetch the public keys used for verifying JWTs
+#[utoipa::path(
+    get, path = "/.well-known/jwks.json", tag = "auth",
+    responses(
+        (status = 200, description = "The JSON Web Key Set", content_type = "application/json"),
+    ),
+)]
+pub fn jwks() {}
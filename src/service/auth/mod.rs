@@ -0,0 +1,42 @@
+// The `auth` module.
+pub mod docs;
+pub mod model;
+pub mod oidc;
+pub mod service;
+
+
+// Importing the necessary modules and functions.
+use crate::config::BeContext;
+use crate::service::auth::service::AuthService;
+use crate::service::route::Route;
+
+
+// The `AuthRouter` struct which is responsible for routing HTTP requests to the appropriate handlers.
+pub struct AuthRouter;
+
+
+// Implementation of the `AuthRouter` struct.
+impl AuthRouter {
+    // Function to create a new `AuthRouter`.
+    pub fn new() -> axum::Router<BeContext> {
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the `AuthRouter`.
+    pub fn routes() -> Vec<Route> {
+        vec![
+            // Route for starting two-factor enrollment.
+            Route::post("/auth/2fa/enroll", AuthService::enroll_two_factor),
+            // Route for confirming two-factor enrollment with a first code.
+            Route::post("/auth/2fa/confirm", AuthService::confirm_two_factor),
+            // Route for completing a login with a two-factor code.
+            Route::post("/auth/2fa/verify", AuthService::verify_two_factor),
+            // Route for disabling two-factor authentication.
+            Route::delete("/auth/2fa", AuthService::disable_two_factor),
+            // Route for starting a login at an OpenID Connect provider.
+            Route::get("/auth/oidc/:provider", AuthService::oidc_login),
+            // Route for completing a login at an OpenID Connect provider.
+            Route::get("/auth/oidc/:provider/callback", AuthService::oidc_callback),
+        ]
+    }
+}
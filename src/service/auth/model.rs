@@ -0,0 +1,109 @@
+// Importing the necessary modules and functions.
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::service::user::model::UserResponse;
+
+
+// The `TwoFactorBody` struct which represents the body of a two-factor request or response.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    EnrollmentResponse = TwoFactorBody<Enrollment>,
+    CodeRequest = TwoFactorBody<CodeInput>,
+    VerifyRequest = TwoFactorBody<VerifyInput>,
+    RecoveryCodesResponse = TwoFactorBody<RecoveryCodes>,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorBody<T> {
+    // The two-factor data in the body.
+    pub two_factor: T
+}
+
+// The `ChallengeBody` struct which represents the body of a login challenge.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(ChallengeResponse = ChallengeBody<Challenge>)]
+pub struct ChallengeBody<T> {
+    // The challenge in the body.
+    pub challenge: T
+}
+
+// The `ChallengeKind` enum which represents what a client must do to finish logging in.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeKind {
+    // The user must submit a TOTP or recovery code.
+    Totp,
+    // The user must enroll in two-factor authentication first.
+    Setup,
+}
+
+// The `Challenge` struct which represents a pending login.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Challenge {
+    // The kind of the challenge.
+    pub kind: ChallengeKind,
+    // The short-lived token which must accompany the next step.
+    pub token: String,
+    // The number of seconds until the token expires.
+    pub expires_in: i64,
+}
+
+// The `LoginResponse` enum which represents the result of a successful password check.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    // The user is logged in.
+    User(UserResponse),
+    // The user must complete a challenge first.
+    Challenge(ChallengeResponse),
+}
+
+// The `Enrollment` struct which represents a pending two-factor enrollment.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Enrollment {
+    // The base32 encoded secret.
+    pub secret: String,
+    // The `otpauth://` URI to be rendered as a QR code.
+    pub otpauth_uri: String,
+}
+
+// The `CodeInput` struct which represents a submitted TOTP code.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CodeInput {
+    // The TOTP code.
+    pub code: String,
+}
+
+// The `VerifyInput` struct which represents the second step of a two-factor login.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyInput {
+    // The challenge token returned by the login.
+    pub token: String,
+    // The TOTP code.
+    pub code: Option<String>,
+    // A one-time recovery code, used instead of the TOTP code.
+    pub recovery_code: Option<String>,
+}
+
+// The `RecoveryCodes` struct which represents freshly generated recovery codes.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCodes {
+    // The recovery codes, shown to the user only once.
+    pub recovery_codes: Vec<String>,
+}
+
+// The `OidcCallbackQuery` struct which represents the query parameters of a provider's callback.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OidcCallbackQuery {
+    // The authorization code.
+    pub code: Option<String>,
+    // The signed state sent with the authorization request.
+    pub state: Option<String>,
+    // The error reported by the provider.
+    pub error: Option<String>,
+}
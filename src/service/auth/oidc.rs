@@ -0,0 +1,323 @@
+// Importing the necessary modules and functions.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::jwk::JwkSet;
+use prisma_client_rust::chrono;
+
+use crate::config::config::OidcProviderConfig;
+use crate::error::{EError, ErrorCode};
+
+
+// The lifetime of the `state` parameter of an authorization request in seconds.
+pub const STATE_EXP_VALUE: i64 = 600;
+
+
+// The `ProviderMetadata` struct which represents the discovery document of a provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    // The issuer of the provider.
+    pub issuer: String,
+    // The authorization endpoint of the provider.
+    pub authorization_endpoint: String,
+    // The token endpoint of the provider.
+    pub token_endpoint: String,
+    // The JWKS endpoint of the provider.
+    pub jwks_uri: String,
+}
+
+// The `TokenResponse` struct which represents the response of the token endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenResponse {
+    // The ID token of the user.
+    pub id_token: String,
+}
+
+// The `IdTokenClaims` struct which represents the claims of an ID token that wForum uses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    // The issuer of the token.
+    pub iss: String,
+    // The subject identifier of the user at the provider.
+    pub sub: String,
+    // The nonce sent in the authorization request.
+    pub nonce: Option<String>,
+    // The email of the user.
+    pub email: Option<String>,
+    // Whether the provider has verified the email.
+    pub email_verified: Option<bool>,
+    // The preferred username of the user.
+    pub preferred_username: Option<String>,
+    // The full name of the user.
+    pub name: Option<String>,
+    // The picture of the user.
+    pub picture: Option<String>,
+}
+
+// The `StateClaims` struct which represents the signed `state` parameter of an authorization request.
+#[derive(Debug, Serialize, Deserialize)]
+struct StateClaims {
+    // The name of the provider the request was sent to.
+    provider: String,
+    // The nonce the ID token must echo.
+    nonce: String,
+    // The value of the cookie set in the browser which started the login, which must send it back.
+    binding: String,
+    // The expiration timestamp of the state.
+    exp: i64,
+}
+
+// The `OidcClient` struct.
+// This struct talks to OpenID Connect providers and caches their discovery documents.
+#[derive(Debug, Default)]
+pub struct OidcClient {
+    // The HTTP client.
+    http: reqwest::Client,
+    // The discovery documents by issuer.
+    metadata: Mutex<HashMap<String, ProviderMetadata>>,
+}
+
+
+// Implementation of the `OidcClient` struct.
+impl OidcClient {
+
+    // Function to create a new `OidcClient`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Function to fetch the discovery document of a provider.
+    // It returns the cached document if the provider has been discovered before.
+    pub async fn discover(&self, provider: &OidcProviderConfig) -> Result<ProviderMetadata, EError> {
+
+        if let Some(metadata) = self.metadata.lock().unwrap().get(&provider.issuer) {
+            return Ok(metadata.clone());
+        }
+
+        let metadata: ProviderMetadata = self.http
+            .get(format!("{}/.well-known/openid-configuration", provider.issuer))
+            .send().await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| EError::InternalServerError(
+                ErrorCode::ProviderError, format!("OIDC discovery failed: {}", e),
+            ))?
+            .json().await
+            .map_err(|e| EError::InternalServerError(
+                ErrorCode::ProviderError, format!("Invalid OIDC discovery document: {}", e),
+            ))?;
+
+        if metadata.issuer.trim_end_matches('/') != provider.issuer {
+            return Err(EError::InternalServerError(ErrorCode::ProviderError, String::from(
+                "OIDC discovery document has a different issuer",
+            )));
+        }
+
+        self.metadata.lock().unwrap().insert(provider.issuer.clone(), metadata.clone());
+
+        Ok(metadata)
+    }
+
+    // Function to build the URL the user is redirected to for logging in at a provider.
+    // The request carries a signed `state` parameter, so no server-side session is needed,
+    // bound to the value of a cookie the browser starting the login is given.
+    pub fn authorization_url(
+        metadata: &ProviderMetadata,
+        provider: &OidcProviderConfig,
+        secret: &str,
+        binding: &str,
+    ) -> Result<String, EError> {
+
+        let claims = StateClaims {
+            provider: provider.name.clone(),
+            nonce: random_string(32),
+            binding: binding.to_string(),
+            exp: chrono::Utc::now().timestamp() + STATE_EXP_VALUE,
+        };
+
+        let state = jsonwebtoken::encode(
+            &Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref()),
+        ).map_err(|e| EError::InternalServerError(
+            ErrorCode::InternalError, format!("Failed to sign OIDC state: {}", e),
+        ))?;
+
+        let url = reqwest::Url::parse_with_params(&metadata.authorization_endpoint, &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("scope", provider.scopes.join(" ").as_str()),
+            ("state", state.as_str()),
+            ("nonce", claims.nonce.as_str()),
+        ]).map_err(|e| EError::InternalServerError(
+            ErrorCode::ProviderError, format!("Invalid authorization endpoint: {}", e),
+        ))?;
+
+        Ok(url.to_string())
+    }
+
+    // Function to verify the `state` parameter returned to the callback, with the cookie the browser sent.
+    // It returns the nonce the ID token must contain.
+    pub fn verify_state(
+        provider: &OidcProviderConfig,
+        state: &str,
+        secret: &str,
+        binding: &str,
+    ) -> Result<String, EError> {
+
+        let claims = jsonwebtoken::decode::<StateClaims>(
+            state, &DecodingKey::from_secret(secret.as_ref()), &Validation::default(),
+        ).map_err(|e| {
+            tracing::info!("OIDC state validation failed: {:?}", e);
+            EError::BadRequest(ErrorCode::InvalidLoginState, String::from("Invalid or expired login state"))
+        })?.claims;
+
+        if claims.provider != provider.name {
+            return Err(EError::BadRequest(
+                ErrorCode::InvalidLoginState, String::from("Login state belongs to another provider"),
+            ));
+        }
+
+        if claims.binding != binding {
+            tracing::info!("OIDC state was started in another browser");
+            return Err(EError::BadRequest(
+                ErrorCode::InvalidLoginState, String::from("Login state was started in another browser"),
+            ));
+        }
+
+        Ok(claims.nonce)
+    }
+
+    // Function to exchange an authorization code for the user's ID token claims.
+    // It verifies the ID token's signature, issuer, audience, expiry and nonce.
+    pub async fn exchange_code(
+        &self,
+        metadata: &ProviderMetadata,
+        provider: &OidcProviderConfig,
+        code: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, EError> {
+
+        let tokens: TokenResponse = self.http
+            .post(&metadata.token_endpoint)
+            .basic_auth(&provider.client_id, Some(&provider.client_secret))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", provider.redirect_uri.as_str()),
+            ])
+            .send().await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                tracing::info!("OIDC code exchange failed: {:?}", e);
+                EError::Unauthorized(
+                    ErrorCode::ExternalLoginFailed, String::from("Failed to exchange authorization code"),
+                )
+            })?
+            .json().await
+            .map_err(|e| EError::InternalServerError(
+                ErrorCode::ProviderError, format!("Invalid OIDC token response: {}", e),
+            ))?;
+
+        self.verify_id_token(metadata, provider, &tokens.id_token, nonce).await
+    }
+
+    // Function to verify an ID token.
+    // Tokens signed with a MAC are verified with the client secret, all others with the provider's JWKS.
+    pub async fn verify_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        provider: &OidcProviderConfig,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, EError> {
+
+        let invalid = |e: jsonwebtoken::errors::Error| {
+            tracing::info!("ID token validation failed: {:?}", e);
+            EError::Unauthorized(ErrorCode::ExternalLoginFailed, String::from("ID token validation failed"))
+        };
+
+        let header = jsonwebtoken::decode_header(id_token).map_err(invalid)?;
+
+        let key = match header.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 =>
+                DecodingKey::from_secret(provider.client_secret.as_ref()),
+            _ => {
+                let jwks: JwkSet = self.http
+                    .get(&metadata.jwks_uri)
+                    .send().await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| EError::InternalServerError(
+                        ErrorCode::ProviderError, format!("Failed to fetch JWKS: {}", e),
+                    ))?
+                    .json().await
+                    .map_err(|e| EError::InternalServerError(
+                        ErrorCode::ProviderError, format!("Invalid JWKS: {}", e),
+                    ))?;
+
+                let jwk = match &header.kid {
+                    Some(kid) => jwks.find(kid),
+                    None => jwks.keys.first(),
+                }.ok_or(EError::Unauthorized(
+                    ErrorCode::ExternalLoginFailed, String::from("ID token is signed with an unknown key"),
+                ))?;
+
+                DecodingKey::from_jwk(jwk).map_err(invalid)?
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&provider.client_id]);
+        validation.set_issuer(&[&provider.issuer, &metadata.issuer]);
+
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(invalid)?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(EError::Unauthorized(
+                ErrorCode::ExternalLoginFailed, String::from("ID token nonce does not match"),
+            ));
+        }
+
+        Ok(claims)
+    }
+
+    // Function to derive a username of `min` to `max` characters from the ID token claims.
+    // It keeps only characters allowed in usernames, turning dots and spaces into underscores,
+    // falls back to "user" and pads names which are too short with random digits.
+    pub fn username_candidate(claims: &IdTokenClaims, min: usize, max: usize) -> String {
+        use rand::{rngs::OsRng, Rng};
+
+        let source = claims.preferred_username.as_deref()
+            .or(claims.email.as_deref().and_then(|email| email.split('@').next()))
+            .or(claims.name.as_deref())
+            .unwrap_or("user");
+
+        let mut candidate: String = source
+            .chars()
+            .filter_map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => Some(c),
+                '.' | ' ' => Some('_'),
+                _ => None,
+            })
+            .collect();
+
+        if candidate.is_empty() {
+            candidate = String::from("user");
+        }
+
+        while candidate.len() < min {
+            candidate.push(char::from(b'0' + OsRng.gen_range(0..10)));
+        }
+
+        candidate.chars().take(max).collect()
+    }
+}
+
+
+// Function to generate a random alphanumeric string of the given length.
+pub fn random_string(length: usize) -> String {
+    use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+
+    OsRng.sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+}
@@ -0,0 +1,569 @@
+// Importing the necessary modules and services.
+use axum::{extract::{Path, Query, State}, response::Redirect, Json};
+use axum::http::{header::{COOKIE, SET_COOKIE}, HeaderMap, HeaderName};
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+use subtle::ConstantTimeEq;
+use totp_rs::{Algorithm, Secret, TOTP};
+use prisma_client_rust::chrono;
+
+// Importing the application's modules.
+use crate::error::{EError, ErrorCode};
+use crate::middleware::metrics::{self, Event};
+use crate::config::BeContext;
+use crate::config::config::{OidcProviderConfig, ValidationConfig};
+use crate::service::auth::model::*;
+use crate::service::auth::oidc::{random_string, IdTokenClaims, OidcClient, STATE_EXP_VALUE};
+use crate::service::user::model::{User, UserBody};
+use crate::service::token::service::TokenService;
+use crate::service::utils::checker::Checker;
+use crate::service::utils::guard::GuardKey;
+use crate::service::utils::helper::Helper;
+use crate::extractor::extractor::{AuthUser, SetupAuthUser, TokenKind};
+use crate::extractor::validate::Validator;
+use crate::repository::{Repository, UserRecord};
+use crate::prisma::prisma::Role;
+
+
+// The number of seconds a TOTP code is valid for.
+const TOTP_STEP: u64 = 30;
+// The number of recovery codes generated on enrollment.
+const RECOVERY_CODE_COUNT: usize = 10;
+// The number of characters in a recovery code, enough for a fast hash to be safe to store.
+const RECOVERY_CODE_LENGTH: usize = 20;
+// The cookie binding an OpenID Connect login to the browser which started it.
+const STATE_COOKIE: &str = "wforum_oidc_state";
+
+
+// The `AuthService` struct.
+// This struct contains methods for handling HTTP requests related to authentication.
+pub struct AuthService;
+
+
+// Implementation of the `AuthService` struct.
+impl AuthService {
+
+    // Function to start two-factor enrollment.
+    // It takes a user allowed to enroll and the application context as parameters.
+    // It returns a `Result` with a JSON response containing the new secret or an error.
+    pub async fn enroll_two_factor(
+        SetupAuthUser(auth_user): SetupAuthUser,
+        ctx: State<BeContext>,
+    ) -> Result<Json<TwoFactorBody<Enrollment>>, EError> {
+
+        tracing::info!("Enrolling two-factor: user_id: {}", auth_user.user_id);
+
+        let repo = &*ctx.repository;
+        let user_data = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+
+        let two_factor = repo.two_factor(auth_user.user_id).await?;
+
+        if two_factor.is_some_and(|two_factor| two_factor.enabled) {
+            return Err(EError::BadRequest(ErrorCode::TwoFactorAlreadyEnabled, String::from(
+                "Two-factor authentication is already enabled",
+            )));
+        }
+
+        let secret = match Secret::generate_secret().to_encoded() {
+            Secret::Encoded(secret) => secret,
+            Secret::Raw(_) => unreachable!(),
+        };
+
+        let totp = Self::build_totp(&ctx, &secret, &user_data.email)?;
+
+        // Replace any unconfirmed secret from an earlier enrollment.
+        repo.start_two_factor(auth_user.user_id, secret.clone()).await?;
+
+        Ok(Json::from(TwoFactorBody {
+            two_factor: Enrollment {
+                secret,
+                otpauth_uri: totp.get_url(),
+            },
+        }))
+    }
+
+
+    // Function to confirm two-factor enrollment.
+    // It takes a user allowed to enroll, the application context and the first code as parameters.
+    // It returns a `Result` with a JSON response containing the recovery codes or an error.
+    pub async fn confirm_two_factor(
+        SetupAuthUser(auth_user): SetupAuthUser,
+        ctx: State<BeContext>,
+        Json(input): Json<TwoFactorBody<CodeInput>>,
+    ) -> Result<Json<TwoFactorBody<RecoveryCodes>>, EError> {
+
+        tracing::info!("Confirming two-factor: user_id: {}", auth_user.user_id);
+
+        let TwoFactorBody { two_factor: CodeInput { code } } = input;
+
+        let repo = &*ctx.repository;
+        let user_data = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+
+        let two_factor = repo
+            .two_factor(auth_user.user_id)
+            .await?
+            .ok_or(EError::BadRequest(
+                ErrorCode::TwoFactorNotStarted, String::from("Two-factor enrollment has not been started"),
+            ))?;
+
+        if two_factor.enabled {
+            return Err(EError::BadRequest(ErrorCode::TwoFactorAlreadyEnabled, String::from(
+                "Two-factor authentication is already enabled",
+            )));
+        }
+
+        let totp = Self::build_totp(&ctx, &two_factor.secret, &user_data.email)?;
+
+        let step = Self::check_code(&totp, &code, two_factor.last_used_step)
+            .ok_or(EError::BadRequest(
+                ErrorCode::InvalidTwoFactorCode, String::from("Invalid two-factor code"),
+            ))?;
+
+        repo.enable_two_factor(auth_user.user_id, step).await?;
+
+        let recovery_codes = Self::replace_recovery_codes(repo, auth_user.user_id).await?;
+
+        Ok(Json::from(TwoFactorBody {
+            two_factor: RecoveryCodes { recovery_codes },
+        }))
+    }
+
+
+    // Function to complete a login with a two-factor code or a recovery code.
+    // It takes the application context and the challenge response as parameters.
+    // It returns a `Result` with a JSON response containing the logged-in user's details or an error.
+    pub async fn verify_two_factor(
+        ctx: State<BeContext>,
+        Json(input): Json<TwoFactorBody<VerifyInput>>,
+    ) -> Result<Json<UserBody<User>>, EError> {
+
+        let TwoFactorBody {
+            two_factor: VerifyInput { token, code, recovery_code },
+        } = input;
+
+        let auth_user = AuthUser::from_token(&ctx, &token, &[TokenKind::Challenge])?;
+
+        tracing::info!("Verifying two-factor: user_id: {}", auth_user.user_id);
+
+        let guard_key = GuardKey::TwoFactor(auth_user.user_id);
+        ctx.login_guard.check(&[guard_key.clone()])?;
+
+        let repo = &*ctx.repository;
+        let user_data = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+
+        let two_factor = repo
+            .two_factor(auth_user.user_id)
+            .await?
+            .filter(|two_factor| two_factor.enabled)
+            .ok_or(EError::BadRequest(
+                ErrorCode::TwoFactorNotEnabled, String::from("Two-factor authentication is not enabled"),
+            ))?;
+
+        let verified = match (code, recovery_code) {
+            (Some(code), _) => {
+                let totp = Self::build_totp(&ctx, &two_factor.secret, &user_data.email)?;
+
+                match Self::check_code(&totp, &code, two_factor.last_used_step) {
+                    // A concurrent request which already used the step makes this one a replay.
+                    Some(step) => repo.set_last_used_step(auth_user.user_id, step).await?,
+                    None => false,
+                }
+            }
+            (None, Some(recovery_code)) =>
+                Self::use_recovery_code(repo, auth_user.user_id, &recovery_code).await?,
+            (None, None) => return Err(EError::BadRequest(ErrorCode::TwoFactorCodeRequired, String::from(
+                "A two-factor code or recovery code is required",
+            ))),
+        };
+
+        if !verified {
+            ctx.login_guard.record_failure(&guard_key);
+            return Err(EError::Unauthorized(
+                ErrorCode::InvalidTwoFactorCode, String::from("Invalid two-factor code"),
+            ));
+        }
+
+        ctx.login_guard.record_success(&guard_key);
+        metrics::record(Event::Login("two_factor"));
+
+        let token = Self::issue_token(&ctx, &user_data).await?;
+
+        let mut user: User = user_data.into();
+        user.set_token(token);
+
+        Ok(Json::from(UserBody { user }))
+    }
+
+
+    // Function to disable two-factor authentication.
+    // It takes an authenticated user, the application context and a current code as parameters.
+    // It returns a `Result` with a JSON response containing a success message or an error.
+    pub async fn disable_two_factor(
+        auth_user: AuthUser,
+        ctx: State<BeContext>,
+        Json(input): Json<TwoFactorBody<CodeInput>>,
+    ) -> Result<Json<String>, EError> {
+
+        tracing::info!("Disabling two-factor: user_id: {}", auth_user.user_id);
+
+        let TwoFactorBody { two_factor: CodeInput { code } } = input;
+
+        // Share the verification's failures, so a stolen session can't guess codes here instead.
+        let guard_key = GuardKey::TwoFactor(auth_user.user_id);
+        ctx.login_guard.check(&[guard_key.clone()])?;
+
+        let repo = &*ctx.repository;
+        let user_data = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+
+        if Self::requires_two_factor(&ctx, &user_data) {
+            return Err(EError::Forbidden(ErrorCode::TwoFactorRequired, String::from(
+                "Two-factor authentication is required for your role",
+            )));
+        }
+
+        let two_factor = repo
+            .two_factor(auth_user.user_id)
+            .await?
+            .filter(|two_factor| two_factor.enabled)
+            .ok_or(EError::BadRequest(
+                ErrorCode::TwoFactorNotEnabled, String::from("Two-factor authentication is not enabled"),
+            ))?;
+
+        let totp = Self::build_totp(&ctx, &two_factor.secret, &user_data.email)?;
+
+        if Self::check_code(&totp, &code, two_factor.last_used_step).is_none() {
+            ctx.login_guard.record_failure(&guard_key);
+            return Err(EError::BadRequest(
+                ErrorCode::InvalidTwoFactorCode, String::from("Invalid two-factor code"),
+            ));
+        }
+
+        ctx.login_guard.record_success(&guard_key);
+        repo.delete_two_factor(auth_user.user_id).await?;
+
+        Ok(Json::from("Two-factor authentication disabled".to_string()))
+    }
+
+
+    // Function to publish the public keys used for verifying JWTs.
+    // It takes the application context as a parameter.
+    // It returns a JSON Web Key Set with every accepted asymmetric key.
+    pub async fn jwks(ctx: State<BeContext>) -> Json<jsonwebtoken::jwk::JwkSet> {
+        Json(ctx.jwt_keys.jwks())
+    }
+
+
+    // Function to start a login at an OpenID Connect provider.
+    // It takes the provider's name and the application context as parameters.
+    // It returns a `Result` with a redirect to the provider's authorization endpoint,
+    // setting the cookie the callback checks the state against, or an error.
+    pub async fn oidc_login(
+        Path(provider): Path<String>,
+        ctx: State<BeContext>,
+    ) -> Result<([(HeaderName, String); 1], Redirect), EError> {
+
+        tracing::info!("Starting OIDC login: provider: {}", provider);
+
+        let provider = Self::get_provider(&ctx, &provider)?;
+        let metadata = ctx.oidc_client.discover(provider).await?;
+
+        let binding = random_string(32);
+        let url = OidcClient::authorization_url(&metadata, provider, Self::state_secret(&ctx)?, &binding)?;
+
+        Ok(([(SET_COOKIE, Self::state_cookie(&binding, STATE_EXP_VALUE))], Redirect::to(&url)))
+    }
+
+
+    // Function to complete a login at an OpenID Connect provider.
+    // It takes the provider's name, the callback query, the request headers with the login state cookie
+    // and the application context as parameters.
+    // It returns a `Result` with a JSON response containing the logged-in user's details,
+    // or the two-factor challenge they must complete first, or an error.
+    pub async fn oidc_callback(
+        Path(provider): Path<String>,
+        Query(query): Query<OidcCallbackQuery>,
+        headers: HeaderMap,
+        ctx: State<BeContext>,
+    ) -> Result<([(HeaderName, String); 1], Json<LoginResponse>), EError> {
+
+        tracing::info!("Completing OIDC login: provider: {}", provider);
+
+        let provider = Self::get_provider(&ctx, &provider)?;
+
+        if let Some(error) = query.error {
+            return Err(EError::Unauthorized(
+                ErrorCode::ExternalLoginFailed, format!("Provider refused the login: {}", error),
+            ));
+        }
+
+        let (code, state) = match (query.code, query.state) {
+            (Some(code), Some(state)) => (code, state),
+            _ => return Err(EError::BadRequest(
+                ErrorCode::InvalidLoginState, String::from("Missing code or state"),
+            )),
+        };
+
+        // Only the browser which started the login has the cookie, so a state can't be replayed in another one.
+        let binding = Self::state_binding(&headers)
+            .ok_or(EError::BadRequest(
+                ErrorCode::InvalidLoginState, String::from("Missing login state cookie"),
+            ))?;
+
+        let nonce = OidcClient::verify_state(provider, &state, Self::state_secret(&ctx)?, binding)?;
+        let metadata = ctx.oidc_client.discover(provider).await?;
+        let claims = ctx.oidc_client.exchange_code(&metadata, provider, &code, &nonce).await?;
+
+        let user_data = Self::link_identity(&*ctx.repository, &ctx.config.validation_config, provider, &claims)
+            .await?;
+        let response = Self::finish_login(&ctx, user_data, "oidc").await?;
+
+        Ok(([(SET_COOKIE, Self::state_cookie("", 0))], Json::from(response)))
+    }
+
+
+    // Utility functions for the `AuthService` struct.
+
+    // Function to find the user an external identity belongs to.
+    // Unknown identities are linked to the account with the same email, or to a new account with a generated
+    // username if there is none, but only once the provider has verified the email.
+    async fn link_identity<R: Repository + ?Sized>(
+        repo: &R,
+        limits: &ValidationConfig,
+        provider: &OidcProviderConfig,
+        claims: &IdTokenClaims,
+    ) -> Result<UserRecord, EError> {
+
+        if let Some(user_data) = repo.find_identity(&provider.name, &claims.sub).await? {
+            return Ok(user_data);
+        }
+
+        let email = claims.email.clone()
+            .ok_or(EError::BadRequest(
+                ErrorCode::EmailRequired, String::from("The provider did not share an email address"),
+            ))?;
+
+        // Anyone could otherwise claim an email at a provider which doesn't check it.
+        if claims.email_verified != Some(true) {
+            tracing::info!("Refusing unverified {} identity", provider.name);
+            return Err(EError::Forbidden(ErrorCode::EmailNotVerified, String::from(
+                "The provider has not verified the email address",
+            )));
+        }
+
+        let user_data = match repo.find_user_by_email(&email).await? {
+            Some(user_data) => {
+                tracing::info!("Linking {} identity to user_id: {}", provider.name, user_data.user_id);
+                user_data
+            }
+            None => {
+                let username = Self::generate_username(repo, limits, claims).await?;
+
+                tracing::info!("Creating user from {} identity: username: {}", provider.name, username);
+
+                let user_data = repo.create_external_user(email, username, claims.picture.clone()).await?;
+
+                metrics::record(Event::Signup("oidc"));
+                user_data
+            }
+        };
+
+        repo.link_identity(provider.name.clone(), claims.sub.clone(), user_data.user_id).await?;
+
+        Ok(user_data)
+    }
+
+    // Function to generate an unused username from the ID token claims.
+    // Every name is checked like one chosen by a user, as the limits may leave no room for a suffix.
+    async fn generate_username<R: Repository + ?Sized>(
+        repo: &R,
+        limits: &ValidationConfig,
+        claims: &IdTokenClaims,
+    ) -> Result<String, EError> {
+
+        let (min, max) = (limits.username_min, limits.username_max);
+        let candidate = OidcClient::username_candidate(claims, min, max);
+        let mut username = candidate.clone();
+
+        for _ in 0..10 {
+            let mut validator = Validator::new(limits);
+            validator.username("username", &username, min, max);
+
+            if validator.finish().is_ok() && repo.find_user_by_name(&username).await?.is_none() {
+                return Ok(username);
+            }
+
+            let suffix = format!("_{}", OsRng.gen_range(1000..10000));
+            let prefix: String = candidate.chars().take(max.saturating_sub(suffix.len())).collect();
+            username = prefix + &suffix;
+        }
+
+        Err(EError::InternalServerError(
+            ErrorCode::InternalError, String::from("Failed to generate a unique username"),
+        ))
+    }
+
+    // Function to get the secret signing the state of OpenID Connect logins.
+    // The configuration requires it whenever a provider is configured.
+    fn state_secret(ctx: &BeContext) -> Result<&str, EError> {
+        ctx.config.oidc_state_secret.as_deref()
+            .ok_or(EError::InternalServerError(
+                ErrorCode::InternalError, String::from("The OIDC state secret is not configured"),
+            ))
+    }
+
+    // Function to build the cookie binding a login to the browser, which expires after `max_age` seconds.
+    // It is sent back on the provider's redirect, a top-level navigation, which `SameSite=Lax` allows.
+    fn state_cookie(binding: &str, max_age: i64) -> String {
+        format!(
+            "{}={}; Max-Age={}; Path=/api/auth/oidc; HttpOnly; Secure; SameSite=Lax",
+            STATE_COOKIE, binding, max_age,
+        )
+    }
+
+    // Function to get the value of the cookie binding a login to the browser from the request headers.
+    fn state_binding(headers: &HeaderMap) -> Option<&str> {
+        headers.get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, value)| *name == STATE_COOKIE && !value.is_empty())
+            .map(|(_, value)| value)
+    }
+
+    // Function to get a configured OpenID Connect provider by its name.
+    fn get_provider<'a>(ctx: &'a BeContext, name: &str) -> Result<&'a OidcProviderConfig, EError> {
+        ctx.config.oidc_providers
+            .iter()
+            .find(|provider| provider.name == name)
+            .ok_or(EError::NotFound(ErrorCode::ProviderNotFound, String::from("Login provider not found")))
+    }
+
+    // Function to finish a login once the user's first factor has been verified.
+    // It takes the application context, the user's details and the login method, "password" or "oidc", as parameters.
+    // It returns a `Result` with the logged-in user, or the challenge they must complete first.
+    pub async fn finish_login(
+        ctx: &BeContext,
+        user_data: UserRecord,
+        method: &'static str,
+    ) -> Result<LoginResponse, EError> {
+
+        Checker::check_active(&user_data).await?;
+
+        let auth_user = AuthUser { user_id: user_data.user_id };
+
+        let two_factor = ctx.repository.two_factor(user_data.user_id).await?;
+
+        let kind = if two_factor.is_some_and(|two_factor| two_factor.enabled) {
+            Some((ChallengeKind::Totp, TokenKind::Challenge))
+        } else if Self::requires_two_factor(ctx, &user_data) {
+            Some((ChallengeKind::Setup, TokenKind::Setup))
+        } else {
+            None
+        };
+
+        if let Some((kind, token_kind)) = kind {
+            return Ok(LoginResponse::Challenge(ChallengeBody {
+                challenge: Challenge {
+                    kind,
+                    token: auth_user.gen_restricted_jwt(ctx, token_kind),
+                    expires_in: ctx.config.two_factor_config.challenge_exp_value,
+                },
+            }));
+        }
+
+        metrics::record(Event::Login(method));
+
+        let token = Self::issue_token(ctx, &user_data).await?;
+
+        let mut user: User = user_data.into();
+        user.set_token(token);
+
+        Ok(LoginResponse::User(UserBody { user }))
+    }
+
+    // Function to issue an access JWT to a user.
+    // Every access JWT is issued through it, so suspended users can't log in or renew their session.
+    // It takes the application context and the user's details as parameters.
+    pub async fn issue_token(ctx: &BeContext, user_data: &UserRecord) -> Result<String, EError> {
+        Checker::check_active(user_data).await?;
+
+        Ok(AuthUser { user_id: user_data.user_id }.gen_jwt(ctx))
+    }
+
+    // Function to check whether the admin policy requires a user to use two-factor authentication.
+    fn requires_two_factor(ctx: &BeContext, user_data: &UserRecord) -> bool {
+        ctx.config.two_factor_config.required_for_elevated
+            && matches!(user_data.role, Role::Moderator | Role::Admin)
+    }
+
+    // Function to build a TOTP generator from a base32 encoded secret.
+    fn build_totp(ctx: &BeContext, secret: &str, account: &str) -> Result<TOTP, EError> {
+
+        let secret = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|_| EError::InternalServerError(
+                ErrorCode::InternalError, String::from("Invalid two-factor secret"),
+            ))?;
+
+        TOTP::new(
+            Algorithm::SHA1, 6, 1, TOTP_STEP, secret,
+            Some(ctx.config.two_factor_config.issuer.clone()),
+            account.to_string(),
+        ).map_err(|e| EError::InternalServerError(
+            ErrorCode::InternalError, format!("Failed to build TOTP: {}", e),
+        ))
+    }
+
+    // Function to check a TOTP code against the current time, allowing one step of clock skew.
+    // It returns the matched time step, or `None` if the code is wrong or has already been used.
+    fn check_code(totp: &TOTP, code: &str, last_used_step: i32) -> Option<i32> {
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        let code = code.trim();
+
+        [now - TOTP_STEP, now, now + TOTP_STEP]
+            .into_iter()
+            .filter(|time| bool::from(totp.generate(*time).as_bytes().ct_eq(code.as_bytes())))
+            .map(|time| (time / TOTP_STEP) as i32)
+            .find(|step| *step > last_used_step)
+    }
+
+    // Function to replace a user's recovery codes with freshly generated ones.
+    // It returns the plain codes, only their hashes are stored.
+    async fn replace_recovery_codes<R: Repository + ?Sized>(repo: &R, user_id: i32) -> Result<Vec<String>, EError> {
+
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| {
+                let code: Vec<char> = OsRng
+                    .sample_iter(&Alphanumeric)
+                    .take(RECOVERY_CODE_LENGTH)
+                    .map(|c| char::from(c).to_ascii_lowercase())
+                    .collect();
+                code.chunks(5).map(String::from_iter).collect::<Vec<String>>().join("-")
+            })
+            .collect();
+
+        let hash_codes = codes.iter().map(|code| TokenService::hash_token(code)).collect();
+
+        repo.replace_recovery_codes(user_id, hash_codes).await?;
+
+        Ok(codes)
+    }
+
+    // Function to redeem a recovery code.
+    // It returns whether the code matched one of the user's unused codes, which is then marked as used.
+    async fn use_recovery_code<R: Repository + ?Sized>(repo: &R, user_id: i32, code: &str) -> Result<bool, EError> {
+
+        let code = code.trim().to_lowercase();
+
+        let matched = repo.find_recovery_code(user_id, &TokenService::hash_token(&code)).await?;
+
+        match matched {
+            Some(recovery_code) => {
+                repo.use_recovery_code(recovery_code.code_id).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// The `health` module.
+pub mod model;
+pub mod service;
+
+
+// Importing the necessary modules and functions.
+use crate::config::BeContext;
+use crate::service::health::service::HealthService;
+use crate::service::route::Route;
+
+
+// The `HealthRouter` struct which is responsible for routing the probes used by orchestrators.
+pub struct HealthRouter;
+
+
+// Implementation of the `HealthRouter` struct.
+impl HealthRouter {
+    // Function to create a new `HealthRouter`.
+    // These routes are merged outside the tracing and timeout layers in `main`,
+    // so frequent probing does not flood the logs, and are not part of the OpenAPI document.
+    pub fn new() -> axum::Router<BeContext> {
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the `HealthRouter`.
+    pub fn routes() -> Vec<Route> {
+        vec![
+            // Route for the liveness probe.
+            Route::get("/healthz", HealthService::healthz).undocumented(),
+            // Route for the readiness probe.
+            Route::get("/readyz", HealthService::readyz).undocumented(),
+            // Route for the build information.
+            Route::get("/version", HealthService::version).undocumented(),
+        ]
+    }
+}
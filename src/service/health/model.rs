@@ -0,0 +1,66 @@
+// Importing the necessary modules and functions.
+use serde::{Deserialize, Serialize};
+
+
+// The `HealthBody` struct which represents the body of the liveness response.
+#[derive(Debug, Serialize)]
+pub struct HealthBody {
+    // The status of the process, always "ok".
+    pub status: &'static str,
+}
+
+// The `ReadinessBody` struct which represents the body of the readiness response.
+#[derive(Debug, Serialize)]
+pub struct ReadinessBody {
+    // The overall status, "ready" or "unavailable".
+    pub status: &'static str,
+    // The result of every check.
+    pub checks: Vec<ReadinessCheck>,
+}
+
+// The `ReadinessCheck` struct which represents the result of one readiness check.
+#[derive(Debug, Serialize)]
+pub struct ReadinessCheck {
+    // The name of the check, "database" or "schema".
+    pub name: &'static str,
+    // Whether the check passed.
+    pub ok: bool,
+    // Why the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+// The `VersionBody` struct which represents the body of the build information response.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionBody {
+    // The version of the crate.
+    pub version: &'static str,
+    // The git commit the backend was built from, "unknown" outside a git checkout.
+    pub git_sha: &'static str,
+    // The build time in RFC 3339 format.
+    pub build_time: String,
+}
+
+// The `TableName` struct which represents a row of the table listing query.
+#[derive(Debug, Deserialize)]
+pub struct TableName {
+    // The name of the table.
+    pub table_name: String,
+}
+
+// The `MigrationName` struct which represents a row of the unfinished migrations query.
+#[derive(Debug, Deserialize)]
+pub struct MigrationName {
+    // The name of the migration.
+    pub migration_name: String,
+}
+
+
+// Implementation of the `ReadinessCheck` struct.
+impl ReadinessCheck {
+    // Function to create the result of a check from its outcome.
+    pub fn new(name: &'static str, outcome: Result<(), String>) -> Self {
+        Self { name, ok: outcome.is_ok(), message: outcome.err() }
+    }
+}
@@ -0,0 +1,164 @@
+// Importing the necessary modules and services.
+use std::time::Duration;
+use axum::{extract::State, http::StatusCode, Json};
+use prisma_client_rust::chrono::{SecondsFormat, TimeZone, Utc};
+use prisma_client_rust::raw;
+
+// Importing the application's modules.
+use crate::config::BeContext;
+use crate::service::health::model::*;
+use crate::prisma::prisma::PrismaClient;
+
+
+// Type alias for the Prisma client.
+type PRISMA = axum::Extension<std::sync::Arc<PrismaClient>>;
+
+
+// The Prisma schema the client was generated from, used to tell which tables must exist.
+const SCHEMA: &str = include_str!("../../../prisma/schema.prisma");
+// The table Prisma Migrate records applied migrations in, absent when the schema is synced with `db push`.
+const MIGRATIONS_TABLE: &str = "_prisma_migrations";
+
+
+// The `HealthService` struct.
+// This struct contains methods for handling the probes used by orchestrators.
+pub struct HealthService;
+
+
+// Implementation of the `HealthService` struct.
+impl HealthService {
+
+    // Function to report that the process is alive.
+    // It returns a JSON response which is always "ok".
+    pub async fn healthz() -> Json<HealthBody> {
+        Json::from(HealthBody { status: "ok" })
+    }
+
+
+    // Function to report whether the backend can serve requests.
+    // It takes the application context and the Prisma client as parameters.
+    // It returns `200` if the database answers within the configured deadline and every table
+    // of the schema exists, or `503` with the failed checks.
+    pub async fn readyz(
+        ctx: State<BeContext>,
+        prisma: PRISMA,
+    ) -> (StatusCode, Json<ReadinessBody>) {
+
+        let deadline = Duration::from_secs(ctx.config.server_config.ready_timeout);
+        let checks = match tokio::time::timeout(deadline, Self::check_database(&prisma)).await {
+            Ok(checks) => checks,
+            Err(_) => {
+                tracing::warn!("Readiness check: database did not respond within {:?}", deadline);
+                vec![ReadinessCheck::new("database", Err(format!("no response within {:?}", deadline)))]
+            }
+        };
+
+        let (status, body) = match checks.iter().all(|check| check.ok) {
+            true => (StatusCode::OK, "ready"),
+            false => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+        };
+
+        (status, Json::from(ReadinessBody { status: body, checks }))
+    }
+
+
+    // Function to report which build is running.
+    // It returns a JSON response with the crate version, the git commit and the build time.
+    pub async fn version() -> Json<VersionBody> {
+        let build_time = env!("WFORUM_BUILD_TIMESTAMP")
+            .parse()
+            .ok()
+            .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single())
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .unwrap_or_default();
+
+        Json::from(VersionBody {
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: env!("WFORUM_GIT_SHA"),
+            build_time,
+        })
+    }
+
+
+    // Function to get the names of the tables the Prisma schema defines.
+    // A model is stored in a table of the same name unless it is renamed with `@@map`.
+    pub fn expected_tables() -> Vec<String> {
+        let mut tables: Vec<String> = Vec::new();
+
+        for line in SCHEMA.lines().map(str::trim) {
+            if let Some(model) = line.strip_prefix("model ") {
+                if let Some(name) = model.split_whitespace().next() {
+                    tables.push(name.to_string());
+                }
+            } else if let Some(mapped) = line.strip_prefix("@@map(") {
+                if let (Some(table), Some(name)) = (tables.last_mut(), mapped.split('"').nth(1)) {
+                    *table = name.to_string();
+                }
+            }
+        }
+
+        tables
+    }
+
+
+    // Function to check that the database answers and its schema is up to date.
+    // It returns the result of the "database" and "schema" checks.
+    async fn check_database(prisma: &PrismaClient) -> Vec<ReadinessCheck> {
+        let tables: Vec<TableName> = match prisma
+            ._query_raw(raw!(
+                "SELECT table_name::text AS table_name FROM information_schema.tables \
+                 WHERE table_schema = current_schema()"
+            ))
+            .exec().await
+        {
+            Ok(tables) => tables,
+            Err(error) => {
+                tracing::warn!("Readiness check: database query failed: {}", error);
+                return vec![ReadinessCheck::new("database", Err(String::from("database query failed")))];
+            }
+        };
+
+        vec![
+            ReadinessCheck::new("database", Ok(())),
+            ReadinessCheck::new("schema", Self::check_schema(prisma, &tables).await),
+        ]
+    }
+
+
+    // Function to check that every table of the schema exists and no migration is unfinished.
+    // It returns a `Result` which is an error describing what is missing.
+    async fn check_schema(prisma: &PrismaClient, tables: &[TableName]) -> Result<(), String> {
+        let exists = |name: &str| tables.iter().any(|table| table.table_name == name);
+
+        let missing: Vec<String> = Self::expected_tables()
+            .into_iter()
+            .filter(|table| !exists(table))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("missing tables: {}", missing.join(", ")));
+        }
+
+        if !exists(MIGRATIONS_TABLE) {
+            return Ok(());
+        }
+
+        // Migrations which neither finished nor were rolled back have failed or are still running.
+        let unfinished: Vec<MigrationName> = prisma
+            ._query_raw(raw!(
+                "SELECT migration_name FROM _prisma_migrations \
+                 WHERE finished_at IS NULL AND rolled_back_at IS NULL"
+            ))
+            .exec().await
+            .map_err(|error| {
+                tracing::warn!("Readiness check: migration query failed: {}", error);
+                String::from("migration query failed")
+            })?;
+
+        if unfinished.is_empty() {
+            return Ok(());
+        }
+
+        let names: Vec<String> = unfinished.into_iter().map(|migration| migration.migration_name).collect();
+        Err(format!("unfinished migrations: {}", names.join(", ")))
+    }
+}
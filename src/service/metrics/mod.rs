@@ -0,0 +1,31 @@
+// The `metrics` module.
+pub mod service;
+
+
+// Importing the necessary modules and functions.
+use crate::config::BeContext;
+use crate::service::metrics::service::MetricsService;
+use crate::service::route::Route;
+
+
+// The `MetricsRouter` struct which is responsible for routing the Prometheus scrape endpoint.
+pub struct MetricsRouter;
+
+
+// Implementation of the `MetricsRouter` struct.
+impl MetricsRouter {
+    // Function to create a new `MetricsRouter`.
+    // Like the probes, this route is merged outside the tracing and metrics layers in `main`,
+    // and is not part of the OpenAPI document.
+    pub fn new() -> axum::Router<BeContext> {
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the `MetricsRouter`.
+    pub fn routes() -> Vec<Route> {
+        vec![
+            // Route for the metrics in the Prometheus text format.
+            Route::get("/metrics", MetricsService::metrics).undocumented(),
+        ]
+    }
+}
@@ -0,0 +1,26 @@
+// Importing the necessary modules and services.
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+
+// Importing the application's modules.
+use crate::middleware::metrics::PROMETHEUS;
+
+
+// The content type of the Prometheus text format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+
+// The `MetricsService` struct.
+// This struct contains methods for handling the Prometheus scrape endpoint.
+pub struct MetricsService;
+
+
+// Implementation of the `MetricsService` struct.
+impl MetricsService {
+
+    // Function to render every metric recorded since the backend started.
+    // It returns a response in the Prometheus text format.
+    pub async fn metrics() -> impl IntoResponse {
+        ([(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], PROMETHEUS.render())
+    }
+}
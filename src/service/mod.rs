@@ -1,9 +1,18 @@
 // Importing the necessary modules and services.
+pub mod auth;
+pub mod health;
+pub mod metrics;
+pub mod openapi;
 pub mod post;
 pub mod profile;
+pub mod route;
+pub mod token;
 pub mod user;
 pub mod utils;
 
+use crate::config::BeContext;
+use crate::service::route::Route;
+
 // The `Router` struct.
 // This struct is responsible for routing HTTP requests to the appropriate handlers.
 pub struct Router;
@@ -12,21 +21,38 @@ pub struct Router;
 impl Router {
     // Function to create a new `Router`.
     // This function defines the routes for the application.
-    pub fn new() -> axum::Router<crate::config::BeContext> {
-        // Create a new `Router` and define the routes.
-        axum::Router::new()
+    pub fn new() -> axum::Router<BeContext> {
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the application.
+    pub fn routes() -> Vec<Route> {
+        let own = vec![
             // Route for the root path ("/").
             // This route returns the string "Hello Rust!" when accessed with a GET request.
-            .route("/", axum::routing::get("Hello Rust!"))
-            // Nested route for the "/api" path.
-            // This route forwards requests to the `PostRouter`.
-            .nest("/api", post::PostRouter::new())
-            // Nested route for the "/api" path.
-            // This route forwards requests to the `ProfilesRouter`.
-            .nest("/api", profile::ProfilesRouter::new())
-            // Nested route for the "/api" path.
-            // This route forwards requests to the `UsersRouter`.
-            .nest("/api", user::UsersRouter::new())
+            Route::get("/", "Hello Rust!").undocumented(),
+            // Route for the "/.well-known/jwks.json" path.
+            // This route publishes the public keys used for verifying JWTs.
+            Route::get("/.well-known/jwks.json", auth::service::AuthService::jwks),
+            // Route for the "/api/openapi.json" path.
+            // This route serves the OpenAPI document describing every route.
+            Route::get("/api/openapi.json", openapi::OpenApiService::openapi_json).undocumented(),
+            // Route for the "/api/docs" path.
+            // This route serves the Swagger UI for the OpenAPI document.
+            Route::get("/api/docs", openapi::OpenApiService::swagger_ui).undocumented(),
+        ];
+
+        // The routes of the services, nested under the "/api" path.
+        let services = [
+            post::PostRouter::routes(),
+            profile::ProfilesRouter::routes(),
+            user::UsersRouter::routes(),
+            auth::AuthRouter::routes(),
+            token::TokenRouter::routes(),
+        ];
 
+        own.into_iter()
+            .chain(services.into_iter().flat_map(|routes| Route::nest("/api", routes)))
+            .collect()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,169 @@
+// Importing the necessary modules and functions.
+use axum::{response::Html, Json};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::error::{ErrorBody, ErrorCode};
+use crate::extractor::extractor::ApiScope;
+use crate::service::{auth, post, profile, token, user};
+
+
+// The URL the OpenAPI document is served at.
+pub const OPENAPI_URL: &str = "/api/openapi.json";
+
+
+// The `ApiDoc` struct which describes every documented route and model.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "wForum", description = "An online forum backend using axum and prisma."),
+    paths(
+        user::docs::current_user,
+        user::docs::login_user,
+        user::docs::update_user,
+        user::docs::delete_user,
+        user::docs::fetch_notifications,
+        user::docs::fetch_mutes,
+        user::docs::fetch_blocks,
+        user::docs::fetch_follow_requests,
+        user::docs::accept_follow_request,
+        user::docs::reject_follow_request,
+        user::docs::create_user,
+        auth::docs::enroll_two_factor,
+        auth::docs::confirm_two_factor,
+        auth::docs::verify_two_factor,
+        auth::docs::disable_two_factor,
+        auth::docs::oidc_login,
+        auth::docs::oidc_callback,
+        auth::docs::jwks,
+        token::docs::fetch_tokens,
+        token::docs::create_token,
+        token::docs::revoke_token,
+        profile::docs::fetch_profile,
+        profile::docs::fetch_followers,
+        profile::docs::fetch_following,
+        profile::docs::follow_profile,
+        profile::docs::unfollow_profile,
+        profile::docs::block_profile,
+        profile::docs::unblock_profile,
+        profile::docs::mute_profile,
+        profile::docs::unmute_profile,
+        post::docs::fetch_posts,
+        post::docs::create_post,
+        post::docs::fetch_history,
+        post::docs::fetch_post,
+        post::docs::update_post,
+        post::docs::delete_post,
+        post::docs::like_post,
+        post::docs::unlike_post,
+        post::docs::create_comment,
+        post::docs::get_comments,
+        post::docs::delete_comment,
+    ),
+    components(schemas(
+        ErrorBody,
+        ErrorCode,
+        user::model::UserResponse,
+        user::model::CreateUserRequest,
+        user::model::LoginUserRequest,
+        user::model::UpdateUserRequest,
+        user::model::CreateUserPost,
+        user::model::LoginUserPost,
+        user::model::UpdateUserPost,
+        user::model::User,
+        user::model::NotificationsResponse,
+        user::model::Notification,
+        auth::model::EnrollmentResponse,
+        auth::model::CodeRequest,
+        auth::model::VerifyRequest,
+        auth::model::RecoveryCodesResponse,
+        auth::model::ChallengeResponse,
+        auth::model::ChallengeKind,
+        auth::model::Challenge,
+        auth::model::LoginResponse,
+        auth::model::Enrollment,
+        auth::model::CodeInput,
+        auth::model::VerifyInput,
+        auth::model::RecoveryCodes,
+        token::model::ApiTokenResponse,
+        token::model::CreateApiTokenRequest,
+        token::model::ApiTokensResponse,
+        token::model::CreateApiToken,
+        token::model::ApiToken,
+        ApiScope,
+        profile::model::ProfileResponse,
+        profile::model::Profile,
+        profile::model::ProfilesResponse,
+        profile::model::MuteResponse,
+        profile::model::MutesResponse,
+        profile::model::Mute,
+        profile::model::FollowRequestsResponse,
+        profile::model::FollowRequest,
+        post::model::PostResponse,
+        post::model::CreatePostRequest,
+        post::model::UpdatePostRequest,
+        post::model::PostsResponse,
+        post::model::HistoryResponse,
+        post::model::CommentResponse,
+        post::model::CreateCommentRequest,
+        post::model::CommentsResponse,
+        post::model::CreatePostPost,
+        post::model::UpdatePostPost,
+        post::model::CommentCreateInput,
+        post::model::Post,
+        post::model::Comment,
+    )),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "users", description = "Accounts and login"),
+        (name = "auth", description = "Two-factor authentication, external logins and signing keys"),
+        (name = "tokens", description = "Personal API tokens"),
+        (name = "profiles", description = "Following, blocking and muting users"),
+        (name = "posts", description = "Posts, likes and viewing history"),
+        (name = "comments", description = "Comments on posts"),
+    ),
+)]
+pub struct ApiDoc;
+
+// The `SecurityAddon` struct which registers the bearer token scheme.
+struct SecurityAddon;
+
+
+// Implementation of the `Modify` trait for `SecurityAddon`.
+impl Modify for SecurityAddon {
+    // Function to add the `token` security scheme, accepting a JWT or a personal API token.
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "token",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .description(Some("A JWT, or a personal API token carrying the listed scopes"))
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
+
+// The `OpenApiService` struct.
+// This struct contains methods for serving the API documentation.
+pub struct OpenApiService;
+
+
+// Implementation of the `OpenApiService` struct.
+impl OpenApiService {
+
+    // Function to serve the OpenAPI document.
+    // It returns a JSON response containing the document.
+    pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+        Json(ApiDoc::openapi())
+    }
+
+    // Function to serve the Swagger UI.
+    // It returns an HTML page rendering the OpenAPI document.
+    pub async fn swagger_ui() -> Html<String> {
+        Html(axum_swagger_ui::swagger_ui(OPENAPI_URL))
+    }
+}
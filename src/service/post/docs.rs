@@ -0,0 +1,169 @@
+// Importing the necessary modules and functions.
+use crate::service::post::model::{ListCommentQuery, ListPostQuery};
+
+
+// The OpenAPI descriptions of the routes in `PostRouter`.
+// Each function only carries the `#[utoipa::path]` attribute of the handler of the same name,
+// the schemas are referenced by name and registered in `crate::service::openapi::ApiDoc`.
+
+
+/// List posts
+#[utoipa::path(
+    get, path = "/api/posts", tag = "posts",
+    params(ListPostQuery),
+    responses(
+        (status = 200, description = "The matching posts, newest first, without those of muted or blocked users or of private users not followed", body = PostsResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` or `If-Modified-Since` validators"),
+        (status = 400, description = "Malformed query, or limit or offset out of range", body = ErrorBody),
+        (status = 401, description = "Filtering by followed authors without logging in", body = ErrorBody),
+    ),
+    security((), ("token" = ["read"])),
+)]
+pub fn fetch_posts() {}
+
+/// Create a post
+#[utoipa::path(
+    post, path = "/api/posts", tag = "posts",
+    request_body = CreatePostRequest,
+    responses(
+        (status = 200, description = "The created post", body = PostResponse),
+        (status = 400, description = "Malformed body or invalid fields", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = ["write:posts"])),
+)]
+pub fn create_post() {}
+
+/// List the current user's viewing history
+#[utoipa::path(
+    get, path = "/api/posts/history", tag = "posts",
+    params(ListPostQuery),
+    responses(
+        (status = 200, description = "The viewed posts with their viewing times, without those of blocked users", body = HistoryResponse),
+        (status = 400, description = "Malformed query, or limit or offset out of range", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = ["read"])),
+)]
+pub fn fetch_history() {}
+
+/// Fetch a post
+#[utoipa::path(
+    get, path = "/api/posts/{post_id}", tag = "posts",
+    params(("post_id" = i32, Path, description = "The ID of the post")),
+    responses(
+        (status = 200, description = "The post", body = PostResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` or `If-Modified-Since` validators"),
+        (status = 400, description = "Invalid post id", body = ErrorBody),
+        (status = 403, description = "Blocked by the author of the post, or the author is private and not followed", body = ErrorBody),
+        (status = 404, description = "Post not found", body = ErrorBody),
+    ),
+    security((), ("token" = ["read"])),
+)]
+pub fn fetch_post() {}
+
+/// Update a post
+#[utoipa::path(
+    put, path = "/api/posts/{post_id}", tag = "posts",
+    params(("post_id" = i32, Path, description = "The ID of the post")),
+    request_body = UpdatePostRequest,
+    responses(
+        (status = 200, description = "The updated post", body = PostResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 400, description = "Invalid post id or body, or not the author of the post", body = ErrorBody),
+        (status = 404, description = "Post not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:posts"])),
+)]
+pub fn update_post() {}
+
+/// Delete a post
+#[utoipa::path(
+    delete, path = "/api/posts/{post_id}", tag = "posts",
+    params(("post_id" = i32, Path, description = "The ID of the post")),
+    responses(
+        (status = 200, description = "Post deleted", body = String),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 400, description = "Not the author of the post, or invalid post id", body = ErrorBody),
+        (status = 404, description = "Post not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:posts"])),
+)]
+pub fn delete_post() {}
+
+/// Like a post
+#[utoipa::path(
+    post, path = "/api/posts/{post_id}/like", tag = "posts",
+    params(("post_id" = i32, Path, description = "The ID of the post")),
+    responses(
+        (status = 200, description = "The liked post", body = PostResponse),
+        (status = 400, description = "Already liked, or invalid post id", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Blocked by the author of the post, or the author is private and not followed", body = ErrorBody),
+        (status = 404, description = "Post not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:posts"])),
+)]
+pub fn like_post() {}
+
+/// Unlike a post
+#[utoipa::path(
+    delete, path = "/api/posts/{post_id}/like", tag = "posts",
+    params(("post_id" = i32, Path, description = "The ID of the post")),
+    responses(
+        (status = 200, description = "The unliked post", body = PostResponse),
+        (status = 400, description = "Not liked, or invalid post id", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Blocked by the author of the post", body = ErrorBody),
+        (status = 404, description = "Post not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:posts"])),
+)]
+pub fn unlike_post() {}
+
+/// Comment on a post
+#[utoipa::path(
+    post, path = "/api/posts/{post_id}/comments", tag = "comments",
+    params(("post_id" = i32, Path, description = "The ID of the post")),
+    request_body = CreateCommentRequest,
+    responses(
+        (status = 200, description = "The created comment", body = CommentResponse),
+        (status = 400, description = "Invalid post id or body", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Blocked by the author of the post, or the author is private and not followed", body = ErrorBody),
+        (status = 404, description = "Post not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:comments"])),
+)]
+pub fn create_comment() {}
+
+/// List the comments on a post
+#[utoipa::path(
+    get, path = "/api/posts/{post_id}/comments", tag = "comments",
+    params(("post_id" = i32, Path, description = "The ID of the post"), ListCommentQuery),
+    responses(
+        (status = 200, description = "A page of the comments, oldest first, without those of muted users and with those of blocked users collapsed", body = CommentsResponse),
+        (status = 400, description = "Invalid post id, or limit or offset out of range", body = ErrorBody),
+        (status = 403, description = "Blocked by the author of the post, or the author is private and not followed", body = ErrorBody),
+        (status = 404, description = "Post not found", body = ErrorBody),
+    ),
+    security((), ("token" = ["read"])),
+)]
+pub fn get_comments() {}
+
+/// Delete a comment
+#[utoipa::path(
+    delete, path = "/api/posts/{post_id}/comments/{comment_id}", tag = "comments",
+    params(
+        ("post_id" = i32, Path, description = "The ID of the post"),
+        ("comment_id" = i32, Path, description = "The ID of the comment"),
+    ),
+    responses(
+        (status = 200, description = "Comment deleted", body = String),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 400, description = "Not the author of the comment, or invalid post id or comment id", body = ErrorBody),
+        (status = 404, description = "Comment not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:comments"])),
+)]
+pub fn delete_comment() {}
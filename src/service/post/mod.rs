@@ -1,11 +1,14 @@
 // The `post` module.
+pub mod docs;
 pub mod model;
 pub mod service;
 
 
 // Importing the necessary modules and functions.
-use axum::routing::{delete, get, post, put};
-use crate::{config::BeContext, service::post::service::PostService};
+use crate::extractor::extractor::ApiScope;
+use crate::config::BeContext;
+use crate::service::post::service::PostService;
+use crate::service::route::Route;
 
 
 // The `PostRouter` struct which is responsible for routing HTTP requests to the appropriate handlers.
@@ -16,29 +19,34 @@ pub struct PostRouter;
 impl PostRouter {
     // Function to create a new `PostRouter`.
     pub fn new() -> axum::Router<BeContext> {
-        // Create a new `Router` and define the routes.
-        axum::Router::new()
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the `PostRouter`.
+    pub fn routes() -> Vec<Route> {
+        vec![
             // Route for fetching all posts.
-            .route("/posts", get(PostService::fetch_posts))
+            Route::get("/posts", PostService::fetch_posts).scope(ApiScope::Read),
             // Route for creating a new post.
-            .route("/posts", post(PostService::create_post))
+            Route::post("/posts", PostService::create_post).scope(ApiScope::WritePosts),
             // Route for fetching user history.
-            .route("/posts/history", get(PostService::fetch_history))
+            Route::get("/posts/history", PostService::fetch_history).scope(ApiScope::Read),
             // Route for fetching a specific post.
-            .route("/posts/:post_id", get(PostService::fetch_post))
+            Route::get("/posts/:post_id", PostService::fetch_post).scope(ApiScope::Read),
             // Route for updating a specific post.
-            .route("/posts/:post_id", put(PostService::update_post))
+            Route::put("/posts/:post_id", PostService::update_post).scope(ApiScope::WritePosts),
             // Route for deleting a specific post.
-            .route("/posts/:post_id", delete(PostService::delete_post))
+            Route::delete("/posts/:post_id", PostService::delete_post).scope(ApiScope::WritePosts),
             // Route for liking a specific post.
-            .route("/posts/:post_id/like", post(PostService::like_post))
+            Route::post("/posts/:post_id/like", PostService::like_post).scope(ApiScope::WritePosts),
             // Route for unliking a specific post.
-            .route("/posts/:post_id/like", delete(PostService::unlike_post))
+            Route::delete("/posts/:post_id/like", PostService::unlike_post).scope(ApiScope::WritePosts),
             // Route for creating a comment on a specific post.
-            .route("/posts/:post_id/comments", post(PostService::create_comment))
+            Route::post("/posts/:post_id/comments", PostService::create_comment).scope(ApiScope::WriteComments),
             // Route for fetching all comments on a specific post.
-            .route("/posts/:post_id/comments", get(PostService::get_comments))
+            Route::get("/posts/:post_id/comments", PostService::get_comments).scope(ApiScope::Read),
             // Route for deleting a specific comment on a specific post.
-            .route("/posts/:post_id/comments/:comment_id", delete(PostService::delete_comment))
+            Route::delete("/posts/:post_id/comments/:comment_id", PostService::delete_comment).scope(ApiScope::WriteComments),
+        ]
     }
 }
\ No newline at end of file
@@ -1,20 +1,28 @@
 // Importing the necessary modules and functions.
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use prisma_client_rust::chrono::{DateTime, FixedOffset};
 
+use crate::extractor::validate::{Validate, Validator};
 use crate::service::profile::model::Profile;
-use crate::prisma::prisma::{platform_posts, post_comments};
+use crate::repository::{CommentRecord, PostRecord};
 
 
 // The `PostContent` struct which represents the content of a post.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    PostResponse = PostContent<Post>,
+    CreatePostRequest = PostContent<CreatePostPost>,
+    UpdatePostRequest = PostContent<UpdatePostPost>,
+)]
 pub struct PostContent<T> {
     // The post content.
     pub post: T
 }
 
 // The `PostsBody` struct which represents the body of a post.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(PostsResponse = PostsBody<Post>)]
 #[serde(rename_all = "camelCase")]
 pub struct PostsBody<T> {
     // The posts in the body.
@@ -23,7 +31,8 @@ pub struct PostsBody<T> {
     pub post_count: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(HistoryResponse = HistoryBody<Post>)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryBody<T> {
     // The posts in the body.
@@ -35,21 +44,29 @@ pub struct HistoryBody<T> {
 }
 
 // The `CommentContent` struct which represents the content of a comment.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    CommentResponse = CommentContent<Comment>,
+    CreateCommentRequest = CommentContent<CommentCreateInput>,
+)]
 pub struct CommentContent<T> {
     // The comment content.
     pub comment: T
 }
 
 // The `CommentsContent` struct which represents the content of comments.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(CommentsResponse = CommentsContent<Comment>)]
+#[serde(rename_all = "camelCase")]
 pub struct CommentsContent<T> {
     // The comments content.
-    pub comments: Vec<T>
+    pub comments: Vec<T>,
+    // The number of comments in the whole list.
+    pub comment_count: i64,
 }
 
 // The `CreatePostPost` struct which represents the data for creating a post.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreatePostPost {
     // The title of the post.
     pub title: String,
@@ -60,7 +77,7 @@ pub struct CreatePostPost {
 }
 
 // The `UpdatePostPost` struct which represents the data for updating a post.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdatePostPost {
     // The new title of the post.
     pub title: Option<String>,
@@ -71,7 +88,8 @@ pub struct UpdatePostPost {
 }
 
 // The `ListPostQuery` struct which represents the query parameters for listing posts.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[serde(rename_all = "camelCase")]
 pub struct ListPostQuery {
     // The author of the posts.
@@ -86,15 +104,25 @@ pub struct ListPostQuery {
     pub following: Option<bool>,
 }
 
+// The `ListCommentQuery` struct which represents the query parameters for listing comments.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListCommentQuery {
+    // The limit of comments to list.
+    pub limit: Option<i64>,
+    // The offset for listing comments.
+    pub offset: Option<i64>,
+}
+
 // The `CommentCreateInput` struct which represents the input for creating a comment.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CommentCreateInput {
     // The content of the comment.
     pub content: String,
 }
 
 // The `Post` struct which represents a post.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Post {
     // The ID of the post.
@@ -116,7 +144,7 @@ pub struct Post {
 }
 
 // The `Comment` struct which represents a comment.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Comment {
     // The ID of the comment.
@@ -127,12 +155,103 @@ pub struct Comment {
     pub created_at: DateTime<FixedOffset>,
     // The user who made the comment.
     pub user: Profile,
+    // Whether the comment is hidden, as the current user blocks its author or is blocked by them.
+    pub collapsed: bool,
+}
+
+
+// Implementation of the `Validate` trait for `PostContent`.
+impl<T: Validate> Validate for PostContent<T> {
+    // Function to validate the post in the body.
+    fn validate(&self, validator: &mut Validator) {
+        validator.nested("post", |validator| self.post.validate(validator));
+    }
+}
+
+
+// Implementation of the `Validate` trait for `CommentContent`.
+impl<T: Validate> Validate for CommentContent<T> {
+    // Function to validate the comment in the body.
+    fn validate(&self, validator: &mut Validator) {
+        validator.nested("comment", |validator| self.comment.validate(validator));
+    }
+}
+
+
+// Implementation of the `Validate` trait for `CreatePostPost`.
+impl Validate for CreatePostPost {
+    // Function to validate the title, description and content of the new post.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        validator
+            .length("title", &self.title, 1, limits.title_max)
+            .length("description", &self.description, 0, limits.description_max)
+            .length("content", &self.content, 1, limits.content_max);
+    }
+}
+
+
+// Implementation of the `Validate` trait for `UpdatePostPost`.
+impl Validate for UpdatePostPost {
+    // Function to validate the fields being updated.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        if let Some(title) = &self.title {
+            validator.length("title", title, 1, limits.title_max);
+        }
+        if let Some(description) = &self.description {
+            validator.length("description", description, 0, limits.description_max);
+        }
+        if let Some(content) = &self.content {
+            validator.length("content", content, 1, limits.content_max);
+        }
+    }
 }
 
 
-// Implementation of the `platform_posts::Data` struct.
-impl platform_posts::Data {
-    // Function to convert `platform_posts::Data` into a `Post`.
+// Implementation of the `Validate` trait for `CommentCreateInput`.
+impl Validate for CommentCreateInput {
+    // Function to validate the content of the new comment.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        validator.length("content", &self.content, 1, limits.comment_max);
+    }
+}
+
+
+// Implementation of the `Validate` trait for `ListPostQuery`.
+impl Validate for ListPostQuery {
+    // Function to validate the page of posts being requested.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        if let Some(limit) = self.limit {
+            validator.range("limit", limit, 1, limits.page_max);
+        }
+        if let Some(offset) = self.offset {
+            validator.range("offset", offset, 0, i64::MAX);
+        }
+    }
+}
+
+
+// Implementation of the `Validate` trait for `ListCommentQuery`.
+impl Validate for ListCommentQuery {
+    // Function to validate the page of comments being requested.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        if let Some(limit) = self.limit {
+            validator.range("limit", limit, 1, limits.page_max);
+        }
+        if let Some(offset) = self.offset {
+            validator.range("offset", offset, 0, i64::MAX);
+        }
+    }
+}
+
+
+// Implementation of the `PostRecord` struct.
+impl PostRecord {
+    // Function to convert a `PostRecord` into a `Post`.
     pub fn to_post(self, like: bool, followed: bool, following: bool, blocked:bool, blocking:bool,) -> Post {
         Post {
             post_id: self.post_id,
@@ -141,21 +260,37 @@ impl platform_posts::Data {
             content: self.content,
             created_at: self.created_at,
             liked: like, liked_count: self.like_count,
-            author: self.author.unwrap().to_profile(followed, following, blocked, blocking),
+            author: self.author.to_profile(followed, following, blocked, blocking),
         }
     }
+
+    // Function to get when the post, or its author, last changed.
+    pub fn last_modified(&self) -> DateTime<FixedOffset> {
+        self.updated_at.max(self.author.updated_at)
+    }
 }
 
 
-// Implementation of the `post_comments::Data` struct.
-impl post_comments::Data {
-    // Function to convert `post_comments::Data` into a `Comment`.
+// Implementation of the `CommentRecord` struct.
+impl CommentRecord {
+    // Function to convert a `CommentRecord` into a `Comment`.
     pub fn to_comment(self, followed: bool, following: bool, blocked:bool, blocking:bool,) -> Comment {
         Comment {
             comment_id: self.comment_id,
             content: self.content,
             created_at: self.created_at,
-            user: self.user.unwrap().to_profile(followed, following, blocked, blocking),
+            user: self.user.to_profile(followed, following, blocked, blocking),
+            collapsed: false,
         }
     }
-}
\ No newline at end of file
+}
+
+
+// Implementation of the `Comment` struct.
+impl Comment {
+    // Function to hide the content of the comment, keeping who wrote it.
+    pub fn collapse(&mut self) {
+        self.content = String::new();
+        self.collapsed = true;
+    }
+}
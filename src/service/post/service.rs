@@ -1,21 +1,21 @@
 // Importing the necessary modules and services.
 use std::vec;
-use axum::Json;
-use axum::extract::{Path, Query};
+use axum::{extract::State, Json};
 use prisma_client_rust::chrono::{DateTime, FixedOffset};
 
 // Importing the application's modules.
-use crate::error::EError;
+use crate::cache;
+use crate::config::BeContext;
+use crate::error::{EError, ErrorCode};
+use crate::middleware::conditional::LastModified;
+use crate::middleware::metrics::{self, Event};
 use crate::service::post::model::*;
 use crate::service::utils::helper::Helper;
 use crate::service::utils::checker::Checker;
 use crate::extractor::extractor::{AuthUser, OptionalAuthUser};
-use crate::prisma::prisma::{
-    platform_posts, post_comments, user_history, user_details, user_follows, user_like_posts, PrismaClient
-};
-
-// Type alias for the Prisma client.
-type PRISMA = axum::Extension<std::sync::Arc<PrismaClient>>;
+use crate::extractor::path::{CommentId, PostId};
+use crate::extractor::validate::{ValidJson, ValidQuery};
+use crate::repository::{PostFilter, PostUpdate};
 
 // The `PostService` struct.
 // This struct contains methods for handling HTTP requests related to posts.
@@ -25,111 +25,125 @@ pub struct PostService;
 impl PostService {
 
     // Function to fetch a post by its ID.
-    // It takes an optional authenticated user, the Prisma client and the post's ID as parameters.
-    // It returns a `Result` with a JSON response containing the post's details or an error.
+    // It takes the application context, an optional authenticated user and the post's ID as parameters;
+    // anonymous reads are cached.
+    // It returns a `Result` with a JSON response containing the post's details,
+    // last modified when the post or its author was, or an error if the author blocks the current user
+    // or is private and not followed by them.
     pub async fn fetch_post(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
         maybe_user: OptionalAuthUser,
-        prisma: PRISMA,
-        Path(post_id): Path<String>,
-    ) -> Result<Json<PostContent<Post>>, EError> {
+    ) -> Result<(LastModified, Json<PostContent<Post>>), EError> {
 
         tracing::info!("Fetching Post: post_id: {}",post_id);
 
-        let post = Helper::fetch_post(&prisma, post_id).await?;
+        // Anonymous readers all see the same post, so it is served from the cache when possible.
+        let key = cache::post_key(post_id);
+        if maybe_user.0.is_none() {
+            if let Some((post, modified)) = ctx.cache.get::<(Post, DateTime<FixedOffset>)>(&key).await {
+                return Ok((LastModified(Some(modified)), Json::from(PostContent { post })));
+            }
+        }
+
+        let repo = &*ctx.repository;
+        let post = Helper::fetch_post(repo, post_id).await?;
+        let modified = post.last_modified();
+        let last_modified = LastModified(Some(modified));
 
         if let Some(auth_user) = maybe_user.0 {
 
-            let _ = prisma
-                .user_history()
-                .create(
-                    user_details::user_id::equals(auth_user.user_id),
-                    platform_posts::post_id::equals(post.post_id),
-                    vec![],
-                ).exec().await?;
+            let blocked =
+                Checker::check_blocked(repo, post.author_id, auth_user.user_id,).await?;
+            if blocked {
+                return Err(EError::Forbidden(ErrorCode::BlockedByAuthor, String::from(
+                    "You are blocked by the author of this post",
+                ))); }
+
+            Checker::check_readable(repo, &post.author, Some(auth_user.user_id)).await?;
 
-            let liked = Checker::check_liked(&prisma, auth_user.user_id, post.post_id).await?;
+            repo.record_visit(auth_user.user_id, post.post_id).await?;
+
+            let liked = Checker::check_liked(repo, auth_user.user_id, post.post_id).await?;
             let followed =
-                Checker::check_following(&prisma, post.author_id, auth_user.user_id,).await?;
+                Checker::check_following(repo, post.author_id, auth_user.user_id,).await?;
             let following =
-                Checker::check_following(&prisma, auth_user.user_id, post.author_id).await?;
-            let blocked =
-                Checker::check_blocked(&prisma, post.author_id, auth_user.user_id,).await?;
+                Checker::check_following(repo, auth_user.user_id, post.author_id).await?;
             let blocking =
-                Checker::check_blocked(&prisma, auth_user.user_id, post.author_id).await?;
+                Checker::check_blocked(repo, auth_user.user_id, post.author_id).await?;
 
-            return Ok(Json::from(PostContent {
+            return Ok((last_modified, Json::from(PostContent {
                 post: post.to_post(liked, followed, following, blocked, blocking),
-            }));
+            })));
         }
 
-        Ok(Json::from(PostContent {
-            post: post.to_post(false, false, false,
-                               false, false),
-        }))
+        // Posts of private users are never read anonymously, so they are never cached.
+        Checker::check_readable(repo, &post.author, None).await?;
+
+        let post = post.to_post(false, false, false,
+                                false, false);
+        ctx.cache.set(&key, &(&post, modified)).await;
+
+        Ok((last_modified, Json::from(PostContent { post })))
     }
 
 
     // Function to fetch multiple posts based on provided filters.
-    // It takes an optional authenticated user, the Prisma client and the query parameters as parameters.
+    // It takes the application context, an optional authenticated user and the query parameters as parameters;
+    // the posts of users the current user mutes, blocks or is blocked by are left out,
+    // as are those of private users they do not follow.
     // It returns a `Result` with a JSON response containing a list of posts or an error.
+    // No `Last-Modified` is given: deleting a post or changing the filter changes the listing
+    // without changing any post's time, so listings are only validated by their `ETag`.
     pub async fn fetch_posts(
+        ctx: State<BeContext>,
+        ValidQuery(query): ValidQuery<ListPostQuery>,
         user: OptionalAuthUser,
-        prisma: PRISMA,
-        Query(query): Query<ListPostQuery>,
     ) -> Result<Json<PostsBody<Post>>, EError> {
 
         tracing::info!("Fetching Posts");
 
-        let mut filter: Vec<platform_posts::WhereParam> = Vec::new();
+        let repo = &*ctx.repository;
 
-        if let Some(author) = query.author {
-            filter.push(platform_posts::author::is(
-                vec![user_details::username::equals(author)]))
-        }
-
-        if let Some(liked_by) = query.liked_by {
-            filter.push(platform_posts::liked_by_users::some(vec![
-                user_like_posts::user::is(vec![user_details::username::equals(liked_by)])]))
-        }
+        let mut filter = PostFilter {
+            author: query.author,
+            liked_by: query.liked_by,
+            author_ids: None,
+            excluded_author_ids: Vec::new(),
+            viewer_id: user.0.as_ref().map(|auth_user| auth_user.user_id),
+            offset: query.offset.unwrap_or(0),
+            limit: query.limit.unwrap_or(20),
+        };
 
         if let Some(true) = query.following {
             if let Some(auth_user) = user.clone().0 {
-                // Get all users id that the current user is following
-                let followed_users = prisma.user_follows()
-                    .find_many(vec![user_follows::follower_id::equals(auth_user.user_id)])
-                    .exec().await?.iter()
-                    .map(|follow| follow.followed_id)
-                    .collect();
-
-                // Add the posts of the followed users to the filter
-                filter.push(platform_posts::author_id::in_vec(followed_users));
+                // Only the posts of the users the current user is following
+                filter.author_ids = Some(repo.followed_ids(auth_user.user_id).await?);
             }else {
-                return Err(EError::Unauthorized(String::from("Login to filter following author's post")));
+                return Err(EError::Unauthorized(
+                    ErrorCode::LoginRequired, String::from("Login to filter following author's post"),
+                ));
             }
         }
 
-        let _posts = prisma
-            .platform_posts().find_many(filter.clone())
-            .with(platform_posts::author::fetch())
-            .take(query.limit.unwrap_or(20))
-            .skip(query.offset.unwrap_or(0))
-            .order_by(platform_posts::created_at::order(prisma_client_rust::Direction::Desc))
-            .exec().await
-            .map_err(|_| EError::InternalServerError(String::from("Failed to fetch posts")))?;
+        if let Some(auth_user) = &user.0 {
+            filter.excluded_author_ids = repo.muted_ids(auth_user.user_id).await?;
+            filter.excluded_author_ids.extend(Helper::get_block_ids(repo, auth_user.user_id).await?);
+        }
 
-        let count = prisma.platform_posts().count(filter).exec().await?;
+        let (_posts, count) = repo.list_posts(&filter).await?;
 
         let mut posts: Vec<Post> = Vec::new();
 
         if let Some(auth_user) = user.0 {
             for post in _posts.iter() {
-                Helper::push_post(&prisma, &mut posts, post, auth_user.user_id).await?;
+                Helper::push_post(repo, &mut posts, post, auth_user.user_id).await?;
             }
         } else {
             posts = _posts
-                .iter()
-                .map(|post| post.clone().to_post(false, false, false,
-                                                 false, false))
+                .into_iter()
+                .map(|post| post.to_post(false, false, false,
+                                         false, false))
                 .collect();
         }
 
@@ -141,49 +155,36 @@ impl PostService {
 
 
     // Function to read user history.
-    // It takes an authenticated user, the Prisma client and the query parameters as parameters.
+    // It takes the application context, an authenticated user and the query parameters as parameters;
+    // the posts of users the current user blocks or is blocked by are left out.
     // It returns a `Result` with a JSON response containing a list of posts or an error.
     pub async fn fetch_history(
+        ctx: State<BeContext>,
+        ValidQuery(query): ValidQuery<ListPostQuery>,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Query(query): Query<ListPostQuery>,
     ) -> Result<Json<HistoryBody<Post>>, EError> {
 
         tracing::info!("Reading history: user_id:{}", auth_user.user_id);
 
-        let filter = vec![user_history::user_id::equals(auth_user.user_id)];
+        let repo = &*ctx.repository;
 
-        let history = prisma
-            .user_history().find_many(filter.clone())
-            .take(query.limit.unwrap_or(20))
-            .skip(query.offset.unwrap_or(0))
-            .order_by(user_history::time::order(prisma_client_rust::Direction::Desc))
-            .exec().await.map_err(|_| EError::InternalServerError(String::from("Failed to fetch history")))?;
+        let excluded_author_ids = Helper::get_block_ids(repo, auth_user.user_id).await?;
 
-
-        let post_ids = history
-            .iter().map(|history| history.post_id).collect::<Vec<i32>>();
+        let (history, count) = repo
+            .history(
+                auth_user.user_id, &excluded_author_ids, query.offset.unwrap_or(0), query.limit.unwrap_or(20),
+            )
+            .await?;
 
         let time_vec = history
             .iter().map(|history| history.time).collect::<Vec<DateTime<FixedOffset>>>();
 
         let mut posts: Vec<Post> = Vec::new();
 
-        for id in post_ids.iter() {
-            let post = prisma
-                .platform_posts()
-                .find_unique(platform_posts::post_id::equals(*id))
-                .with(platform_posts::author::fetch())
-                .exec().await
-                .map_err(|_| EError::InternalServerError(String::from("Failed to fetch post")))?;
-
-            if let Some(post) = post {
-                Helper::push_post(&prisma, &mut posts, &post, auth_user.user_id).await?;
-            }
+        for visit in history.iter() {
+            Helper::push_post(repo, &mut posts, &visit.post, auth_user.user_id).await?;
         }
 
-        let count = prisma.user_history().count(filter.clone()).exec().await?;
-
         Ok(Json::from(HistoryBody {
             posts, time_vec,
             post_count: count as usize,
@@ -192,12 +193,12 @@ impl PostService {
 
 
     // Function to create a new post.
-    // It takes an authenticated user, the Prisma client and the post data as parameters.
+    // It takes the application context, an authenticated user and the post data as parameters.
     // It returns a `Result` with a JSON response containing the created post's details or an error.
     pub async fn create_post(
+        ctx: State<BeContext>,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Json(input): Json<PostContent<CreatePostPost>>,
+        ValidJson(input): ValidJson<PostContent<CreatePostPost>>,
     ) -> Result<Json<PostContent<Post>>, EError> {
 
         tracing::info!("Creating post: user_id: {}", auth_user.user_id);
@@ -211,17 +212,14 @@ impl PostService {
             },
         } = input;
 
-        let post_data = prisma
-            .platform_posts()
-            .create(
-                title,
-                description,
-                content,
-                user_details::user_id::equals(auth_user.user_id),
-                vec![],
-            )
-            .with(platform_posts::author::fetch())
-            .exec().await?;
+        let post_data = ctx.repository
+            .create_post(auth_user.user_id, title, description, content)
+            .await?;
+
+        metrics::record(Event::PostCreated);
+
+        // The author's cached profile counts their posts.
+        ctx.cache.invalidate(&cache::profile_key(&post_data.author.username)).await;
 
         Ok(Json::from(PostContent {
             post: post_data.to_post(false, false, false,
@@ -231,13 +229,13 @@ impl PostService {
 
 
     // Function to update a post.
-    // It takes an authenticated user, the Prisma client, the post's ID and the new post data as parameters.
+    // It takes the application context, an authenticated user, the post's ID and the new post data as parameters.
     // It returns a `Result` with a JSON response containing the updated post's details or an error.
     pub async fn update_post(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Path(post_id): Path<String>,
-        Json(input): Json<PostContent<UpdatePostPost>>,
+        ValidJson(input): ValidJson<PostContent<UpdatePostPost>>,
     ) -> Result<Json<PostContent<Post>>, EError> {
 
         tracing::info!("Updating Post: user_id: {}, post_id: {}", auth_user.user_id, post_id);
@@ -251,36 +249,16 @@ impl PostService {
             },
         } = input;
 
-        let post = prisma
-            .platform_posts()
-            .find_unique(platform_posts::post_id::equals(post_id.parse().unwrap()))
-            .with(platform_posts::author::fetch())
-            .exec().await?
-            .ok_or(EError::NotFound(String::from("Post not found")))?;
+        let repo = &*ctx.repository;
+        let post = Helper::fetch_post(repo, post_id).await?;
 
         Checker::check_author(auth_user.user_id, &post).await?;
 
-        let updated_post = prisma
-            .platform_posts()
-            .update(
-                platform_posts::post_id::equals(post_id.parse().unwrap()),
-                vec![
-                    match title {
-                        Some(title) => platform_posts::title::set(title),
-                        None => platform_posts::title::set(post.title),
-                    },
-                    match description {
-                        Some(description) => platform_posts::description::set(description),
-                        None => platform_posts::description::set(post.description),
-                    },
-                    match content {
-                        Some(content) => platform_posts::content::set(content),
-                        None => platform_posts::content::set(post.content),
-                    },
-                ],
-            )
-            .with(platform_posts::author::fetch())
-            .exec().await?;
+        let updated_post = repo
+            .update_post(post_id, PostUpdate { title, description, content })
+            .await?;
+
+        ctx.cache.invalidate(&cache::post_key(post_id)).await;
 
         Ok(Json::from(PostContent {
             post: updated_post.to_post(false, false, false,
@@ -290,91 +268,67 @@ impl PostService {
 
 
     // Function to delete a post.
-    // It takes an authenticated user, the Prisma client and the post's ID as parameters.
+    // It takes the application context, an authenticated user and the post's ID as parameters.
     // It returns a `Result` with a JSON response containing a success message or an error.
     pub async fn delete_post(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Path(post_id): Path<String>,
     ) -> Result<Json<String>, EError> {
 
         tracing::info!("Deleting post: user_id: {}, post_id: {}", auth_user.user_id, post_id);
 
-        let post = Helper::fetch_post(&prisma, post_id.parse().unwrap()).await?;
+        let repo = &*ctx.repository;
+        let post = Helper::fetch_post(repo, post_id).await?;
 
         Checker::check_author(auth_user.user_id, &post).await?;
 
-        let _ = prisma
-            .post_comments()
-            .delete_many(vec![post_comments::post_id::equals(post_id.parse().unwrap())])
-            .exec().await?;
+        repo.delete_post(post_id).await?;
 
-        let _ = prisma
-            .user_history()
-            .delete_many(vec![user_history::post_id::equals(post_id.parse().unwrap())])
-            .exec().await?;
-
-        let _ = prisma
-            .user_like_posts()
-            .delete_many(vec![user_like_posts::post_id::equals(post_id.parse().unwrap())])
-            .exec().await?;
-
-        let _ = prisma
-            .platform_posts()
-            .delete(platform_posts::post_id::equals(post_id.parse().unwrap()))
-            .exec().await?;
+        ctx.cache.invalidate(&cache::post_key(post_id)).await;
+        ctx.cache.invalidate(&cache::profile_key(&post.author.username)).await;
 
         Ok(Json::from("Post deleted".to_string()))
     }
 
 
     // Function to like a post.
-    // It takes an authenticated user, the Prisma client and the post's ID as parameters.
+    // It takes the application context, an authenticated user and the post's ID as parameters.
     // It returns a `Result` with a JSON response containing the liked post's details or an error.
     pub async fn like_post(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Path(post_id): Path<String>,
     ) -> Result<Json<PostContent<Post>>, EError> {
 
         tracing::info!("Liking Post: user_id: {}, post_id: {}", auth_user.user_id, post_id);
 
-        let post_data = Helper::fetch_post(&prisma, post_id.parse().unwrap()).await?;
+        let repo = &*ctx.repository;
+        let post_data = Helper::fetch_post(repo, post_id).await?;
 
-        if Checker::check_blocked(&prisma, post_data.author_id, auth_user.user_id).await? {
-            return Err(EError::Forbidden(String::from(
+        if Checker::check_blocked(repo, post_data.author_id, auth_user.user_id).await? {
+            return Err(EError::Forbidden(ErrorCode::BlockedByAuthor, String::from(
                 "You are blocked by the author of this post",
             ))); }
 
-        if Checker::check_liked(&prisma, auth_user.user_id, post_data.post_id).await? {
-            return Err(EError::BadRequest(String::from(
+        Checker::check_readable(repo, &post_data.author, Some(auth_user.user_id)).await?;
+
+        if Checker::check_liked(repo, auth_user.user_id, post_data.post_id).await? {
+            return Err(EError::BadRequest(ErrorCode::AlreadyLiked, String::from(
                 "You have already liked this post",
             ))); }
 
-        let _ = prisma
-            .user_like_posts()
-            .create(
-                user_details::user_id::equals(auth_user.user_id),
-                platform_posts::post_id::equals(post_data.post_id),
-                vec![],
-            )
-            .exec().await?;
+        let post = repo.like_post(auth_user.user_id, post_data.post_id).await?;
 
-        let post = prisma
-            .platform_posts()
-            .update(
-                platform_posts::post_id::equals(post_id.parse().unwrap()),
-                vec![platform_posts::like_count::increment(1)],
-            )
-            .with(platform_posts::author::fetch())
-            .exec().await?;
+        // The like count is part of the cached post.
+        ctx.cache.invalidate(&cache::post_key(post_id)).await;
 
         let followed =
-            Checker::check_following(&prisma, post.author_id, auth_user.user_id,).await?;
+            Checker::check_following(repo, post.author_id, auth_user.user_id,).await?;
         let following =
-            Checker::check_following(&prisma, auth_user.user_id, post.author_id).await?;
+            Checker::check_following(repo, auth_user.user_id, post.author_id).await?;
         let blocking =
-            Checker::check_blocked(&prisma, auth_user.user_id, post.author_id).await?;
+            Checker::check_blocked(repo, auth_user.user_id, post.author_id).await?;
 
         Ok(Json::from(PostContent {
             post: post.to_post(true, followed, following, false, blocking),
@@ -383,51 +337,40 @@ impl PostService {
 
 
     // Function to unlike a post.
-    // It takes an authenticated user, the Prisma client and the post's ID as parameters.
+    // It takes the application context, an authenticated user and the post's ID as parameters.
     // It returns a `Result` with a JSON response containing the unliked post's details or an error.
     pub async fn unlike_post(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Path(post_id): Path<String>,
     ) -> Result<Json<PostContent<Post>>, EError> {
 
         tracing::info!("Unliking Post: user_id: {}, post_id: {}", auth_user.user_id, post_id);
 
-        let post = Helper::fetch_post(&prisma, post_id.parse().unwrap()).await?;
+        let repo = &*ctx.repository;
+        let post = Helper::fetch_post(repo, post_id).await?;
 
-        if Checker::check_blocked(&prisma, post.author_id, auth_user.user_id).await? {
-            return Err(EError::Forbidden(String::from(
+        if Checker::check_blocked(repo, post.author_id, auth_user.user_id).await? {
+            return Err(EError::Forbidden(ErrorCode::BlockedByAuthor, String::from(
                 "You are blocked by the author of this post",
             ))); }
 
-        if !Checker::check_liked(&prisma, auth_user.user_id, post.post_id).await? {
-            return Err(EError::BadRequest(String::from(
+        if !Checker::check_liked(repo, auth_user.user_id, post.post_id).await? {
+            return Err(EError::BadRequest(ErrorCode::NotLiked, String::from(
                 "You have not liked this post",
             ))); }
 
-        let _ = prisma
-            .user_like_posts()
-            .delete(user_like_posts::user_id_post_id(
-                auth_user.user_id,
-                post.post_id,
-            ))
-            .exec().await?;
-
-        let post = prisma
-            .platform_posts()
-            .update(
-                platform_posts::post_id::equals(post_id.parse().unwrap()),
-                vec![platform_posts::like_count::decrement(1)],
-            )
-            .with(platform_posts::author::fetch())
-            .exec().await?;
+        let post = repo.unlike_post(auth_user.user_id, post.post_id).await?;
+
+        // The like count is part of the cached post.
+        ctx.cache.invalidate(&cache::post_key(post_id)).await;
 
         let followed =
-            Checker::check_following(&prisma, post.author_id, auth_user.user_id,).await?;
+            Checker::check_following(repo, post.author_id, auth_user.user_id,).await?;
         let following =
-            Checker::check_following(&prisma, auth_user.user_id, post.author_id).await?;
+            Checker::check_following(repo, auth_user.user_id, post.author_id).await?;
         let blocking =
-            Checker::check_blocked(&prisma, auth_user.user_id, post.author_id).await?;
+            Checker::check_blocked(repo, auth_user.user_id, post.author_id).await?;
 
         Ok(Json::from(PostContent {
             post: post.to_post(false, followed, following, false, blocking),
@@ -435,54 +378,78 @@ impl PostService {
     }
 
 
-    // Function to fetch all comments on a post.
-    // It takes an optional authenticated user, the Prisma client and the post's ID as parameters.
-    // It returns a `Result` with a JSON response containing a list of comments or an error.
+    // Function to fetch a page of the comments on a post.
+    // It takes the application context, an optional authenticated user, the post's ID and the query parameters
+    // as parameters; the comments of users the current user mutes are left out,
+    // and those of users they block or are blocked by are collapsed.
+    // It returns a `Result` with a JSON response containing a page of comments, oldest first,
+    // or an error if the author of the post blocks the current user or is private and not followed by them.
     pub async fn get_comments(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
         auth_user: OptionalAuthUser,
-        prisma: PRISMA,
-        Path(post_id): Path<String>,
+        ValidQuery(query): ValidQuery<ListCommentQuery>,
     ) -> Result<Json<CommentsContent<Comment>>, EError> {
 
         tracing::info!("Getting comments: post_id: {}", post_id);
 
-        let post = Helper::fetch_post(&prisma, post_id.parse().unwrap()).await?;
+        let repo = &*ctx.repository;
+        let post = Helper::fetch_post(repo, post_id).await?;
+
+        let offset = query.offset.unwrap_or(0);
+        let limit = query.limit.unwrap_or(20);
 
-        let comments = prisma
-            .post_comments()
-            .find_many(vec![
-                post_comments::post_id::equals(post.post_id),
-            ])
-            .with(post_comments::user::fetch())
-            .exec().await?;
+        let Some(user) = auth_user.0 else {
+            Checker::check_readable(repo, &post.author, None).await?;
 
-        let mut comments: Vec<Comment> = comments
-            .iter()
-            .map(|comment| comment.clone().to_comment(false, false,
-                                                      false, false))
-            .collect();
+            let (records, comment_count) = repo.comments(post.post_id, &[], offset, limit).await?;
+            let comments = records
+                .into_iter()
+                .map(|comment| comment.to_comment(false, false,
+                                                  false, false))
+                .collect();
 
-        if let Some(user) = auth_user.0 {
-            for comment in comments.iter_mut() {
-                let followed =
-                    Checker::check_following(&prisma, user.user_id, post.author_id).await?;
+            return Ok(Json::from(CommentsContent { comments, comment_count }));
+        };
 
-                comment.user.following = followed;
+        if Checker::check_blocked(repo, post.author_id, user.user_id).await? {
+            return Err(EError::Forbidden(ErrorCode::BlockedByAuthor, String::from(
+                "You are blocked by the author of this post",
+            ))); }
+
+        Checker::check_readable(repo, &post.author, Some(user.user_id)).await?;
+
+        let muted = repo.muted_ids(user.user_id).await?;
+        let (records, comment_count) = repo.comments(post.post_id, &muted, offset, limit).await?;
+
+        // The relations are fetched once for the whole page rather than for every commenter.
+        let relations = Helper::get_relations(repo, user.user_id).await?;
+
+        let mut comments: Vec<Comment> = Vec::new();
+        for record in records {
+            let profile = relations.profile(record.user.clone());
+
+            let mut comment = record.to_comment(false, false, false, false);
+            comment.user = profile;
+            if comment.user.blocked || comment.user.blocking {
+                comment.collapse();
             }
+
+            comments.push(comment);
         }
 
-        Ok(Json::from(CommentsContent { comments }))
+        Ok(Json::from(CommentsContent { comments, comment_count }))
     }
 
 
     // Function to create a new comment on a post.
-    // It takes an authenticated user, the Prisma client, the post's ID and the comment data as parameters.
+    // It takes the application context, an authenticated user, the post's ID and the comment data as parameters.
     // It returns a `Result` with a JSON response containing the created comment's details or an error.
     pub async fn create_comment(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Path(post_id): Path<String>,
-        Json(input): Json<CommentContent<CommentCreateInput>>,
+        ValidJson(input): ValidJson<CommentContent<CommentCreateInput>>,
     ) -> Result<Json<CommentContent<Comment>>, EError> {
 
         tracing::info!("Creating comment: user_id: {}, post_id: {}", auth_user.user_id, post_id);
@@ -491,26 +458,22 @@ impl PostService {
             comment: CommentCreateInput { content: body },
         } = input;
 
-        let post = Helper::fetch_post(&prisma, post_id.parse().unwrap()).await?;
+        let repo = &*ctx.repository;
+        let post = Helper::fetch_post(repo, post_id).await?;
 
-        if Checker::check_blocked(&prisma, post.author_id, auth_user.user_id).await? {
-            return Err(EError::Forbidden(String::from(
+        if Checker::check_blocked(repo, post.author_id, auth_user.user_id).await? {
+            return Err(EError::Forbidden(ErrorCode::BlockedByAuthor, String::from(
                 "You are blocked by the author of this post",
             )));}
 
-        let comment = prisma
-            .post_comments()
-            .create(
-                body,
-                user_details::user_id::equals(auth_user.user_id),
-                platform_posts::post_id::equals(post.post_id),
-                vec![],
-            )
-            .with(post_comments::user::fetch())
-            .exec().await?;
+        Checker::check_readable(repo, &post.author, Some(auth_user.user_id)).await?;
+
+        let comment = repo.create_comment(auth_user.user_id, post.post_id, body).await?;
+
+        metrics::record(Event::CommentCreated);
 
         let blocking =
-            Checker::check_blocked(&prisma, auth_user.user_id, post.author_id).await?;
+            Checker::check_blocked(repo, auth_user.user_id, post.author_id).await?;
 
         Ok(Json::from(CommentContent {
             comment: comment.to_comment(false, false, false, blocking),
@@ -519,35 +482,29 @@ impl PostService {
 
 
     // Function to delete a comment on a post.
-    // It takes an authenticated user, the Prisma client and the post's ID and comment's ID as parameters.
+    // It takes the application context, an authenticated user and the post's ID and comment's ID as parameters.
     // It returns a `Result` with a JSON response containing a success message or an error.
     pub async fn delete_comment(
+        ctx: State<BeContext>,
+        PostId(post_id): PostId,
+        CommentId(comment_id): CommentId,
         auth_user: AuthUser,
-        prisma: PRISMA,
-        Path((_post_id, comment_id)): Path<(String, i32)>,
     ) -> Result<Json<String>, EError> {
 
         tracing::info!("Deleting comment: user_id: {}, post_id: {}, comment_id: {}"
-            , auth_user.user_id, _post_id, comment_id);
+            , auth_user.user_id, post_id, comment_id);
 
-        let comment = prisma
-            .post_comments()
-            .find_unique(post_comments::comment_id::equals(comment_id))
-            .with(post_comments::user::fetch())
-            .exec().await?
-            .ok_or(EError::NotFound(String::from("Comment not found")))?;
+        let comment = ctx.repository
+            .find_comment(comment_id).await?
+            .filter(|comment| comment.post_id == post_id)
+            .ok_or(EError::NotFound(ErrorCode::CommentNotFound, String::from("Comment not found")))?;
 
         if comment.user_id != auth_user.user_id {
-            return Err(EError::BadRequest(String::from(
+            return Err(EError::BadRequest(ErrorCode::NotCommentAuthor, String::from(
                 "You are not the author of this comment",
             ))); }
 
-        let _ = prisma
-            .post_comments()
-            .delete(
-                post_comments::comment_id::equals(comment_id),
-            )
-            .exec().await?;
+        ctx.repository.delete_comment(comment_id).await?;
 
         Ok(Json::from("Comment deleted".to_string()))
     }
@@ -0,0 +1,136 @@
+// Importing the necessary modules and functions.
+use crate::service::profile::model::{ListProfileQuery, MuteQuery};
+
+
+// The OpenAPI descriptions of the routes in `ProfilesRouter`.
+// Each function only carries the `#[utoipa::path]` attribute of the handler of the same name,
+// the schemas are referenced by name and registered in `crate::service::openapi::ApiDoc`.
+
+
+/// Fetch a profile
+#[utoipa::path(
+    get, path = "/api/profiles/{username}", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile")),
+    responses(
+        (status = 200, description = "The profile with its counts, only its username if it blocks the current user", body = ProfileResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` validator"),
+        (status = 400, description = "Invalid username", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security((), ("token" = ["read"])),
+)]
+pub fn fetch_profile() {}
+
+/// List the users following a profile
+#[utoipa::path(
+    get, path = "/api/profiles/{username}/followers", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile"), ListProfileQuery),
+    responses(
+        (status = 200, description = "The followers by username, as the current user sees them", body = ProfilesResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` validator"),
+        (status = 400, description = "Malformed query, or limit or offset out of range, or invalid username", body = ErrorBody),
+        (status = 403, description = "Blocked by the profile, or the profile is private and not followed", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security((), ("token" = ["read"])),
+)]
+pub fn fetch_followers() {}
+
+/// List the users a profile follows
+#[utoipa::path(
+    get, path = "/api/profiles/{username}/following", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile"), ListProfileQuery),
+    responses(
+        (status = 200, description = "The followed users by username, as the current user sees them", body = ProfilesResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` validator"),
+        (status = 400, description = "Malformed query, or limit or offset out of range, or invalid username", body = ErrorBody),
+        (status = 403, description = "Blocked by the profile, or the profile is private and not followed", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security((), ("token" = ["read"])),
+)]
+pub fn fetch_following() {}
+
+/// Follow a profile
+#[utoipa::path(
+    post, path = "/api/profiles/{username}/follow", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile")),
+    responses(
+        (status = 200, description = "The followed profile, or `requesting` if it is private and has to accept", body = ProfileResponse),
+        (status = 400, description = "Cannot follow yourself, or already following or asking, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn follow_profile() {}
+
+/// Unfollow a profile
+#[utoipa::path(
+    delete, path = "/api/profiles/{username}/follow", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile")),
+    responses(
+        (status = 200, description = "The unfollowed profile, or the profile no longer asked to follow", body = ProfileResponse),
+        (status = 400, description = "Cannot unfollow yourself, or not following or asking, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn unfollow_profile() {}
+
+/// Block a profile
+#[utoipa::path(
+    post, path = "/api/profiles/{username}/block", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile")),
+    responses(
+        (status = 200, description = "The blocked profile", body = ProfileResponse),
+        (status = 400, description = "Cannot block yourself, or already blocking, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn block_profile() {}
+
+/// Unblock a profile
+#[utoipa::path(
+    delete, path = "/api/profiles/{username}/block", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile")),
+    responses(
+        (status = 200, description = "The unblocked profile", body = ProfileResponse),
+        (status = 400, description = "Cannot unblock yourself, or not blocking, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn unblock_profile() {}
+
+/// Mute a profile
+#[utoipa::path(
+    post, path = "/api/profiles/{username}/mute", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile"), MuteQuery),
+    responses(
+        (status = 200, description = "The mute, hiding the profile's posts and comments from the current user", body = MuteResponse),
+        (status = 400, description = "Cannot mute yourself, or expiry out of range, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn mute_profile() {}
+
+/// Unmute a profile
+#[utoipa::path(
+    delete, path = "/api/profiles/{username}/mute", tag = "profiles",
+    params(("username" = String, Path, description = "The username of the profile")),
+    responses(
+        (status = 200, description = "The unmuted profile", body = ProfileResponse),
+        (status = 400, description = "Cannot unmute yourself, or not muting, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn unmute_profile() {}
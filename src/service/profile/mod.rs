@@ -1,11 +1,14 @@
 // The `profile` module.
+pub mod docs;
 pub mod model;
 pub mod service;
 
 
 // Importing the necessary modules and functions.
-use axum::routing::{delete, get, post};
-use crate::{config::BeContext, service::profile::service::ProfilesService};
+use crate::extractor::extractor::ApiScope;
+use crate::config::BeContext;
+use crate::service::profile::service::ProfilesService;
+use crate::service::route::Route;
 
 
 // The `ProfilesRouter` struct which is responsible for routing HTTP requests to the appropriate handlers.
@@ -16,17 +19,30 @@ pub struct ProfilesRouter;
 impl ProfilesRouter {
     // Function to create a new `ProfilesRouter`.
     pub fn new() -> axum::Router<BeContext> {
-        // Create a new `Router` and define the routes.
-        axum::Router::new()
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the `ProfilesRouter`.
+    pub fn routes() -> Vec<Route> {
+        vec![
             // Route for fetching a specific profile.
-            .route("/profiles/:username", get(ProfilesService::fetch_profile))
+            Route::get("/profiles/:username", ProfilesService::fetch_profile).scope(ApiScope::Read),
+            // Route for fetching the users following a specific profile.
+            Route::get("/profiles/:username/followers", ProfilesService::fetch_followers).scope(ApiScope::Read),
+            // Route for fetching the users a specific profile follows.
+            Route::get("/profiles/:username/following", ProfilesService::fetch_following).scope(ApiScope::Read),
             // Route for following a specific profile.
-            .route("/profiles/:username/follow", post(ProfilesService::follow_profile))
+            Route::post("/profiles/:username/follow", ProfilesService::follow_profile).scope(ApiScope::WriteProfiles),
             // Route for unfollowing a specific profile.
-            .route("/profiles/:username/follow", delete(ProfilesService::unfollow_profile))
+            Route::delete("/profiles/:username/follow", ProfilesService::unfollow_profile).scope(ApiScope::WriteProfiles),
             // Route for blocking a specific profile.
-            .route("/profiles/:username/block", post(ProfilesService::block_profile))
+            Route::post("/profiles/:username/block", ProfilesService::block_profile).scope(ApiScope::WriteProfiles),
             // Route for unblocking a specific profile.
-            .route("/profiles/:username/block", delete(ProfilesService::unblock_profile))
+            Route::delete("/profiles/:username/block", ProfilesService::unblock_profile).scope(ApiScope::WriteProfiles),
+            // Route for muting a specific profile.
+            Route::post("/profiles/:username/mute", ProfilesService::mute_profile).scope(ApiScope::WriteProfiles),
+            // Route for unmuting a specific profile.
+            Route::delete("/profiles/:username/mute", ProfilesService::unmute_profile).scope(ApiScope::WriteProfiles),
+        ]
     }
 }
\ No newline at end of file
@@ -1,17 +1,34 @@
 // Importing the necessary modules and functions.
 use serde::{Serialize, Deserialize};
-use crate::prisma::prisma::user_details;
+use utoipa::{IntoParams, ToSchema};
+use prisma_client_rust::chrono::{DateTime, FixedOffset};
+
+use crate::extractor::validate::{Validate, Validator};
+use crate::repository::{ProfileCounts, UserRecord};
 
 
 // The `ProfileBody` struct which represents the body of a profile.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(ProfileResponse = ProfileBody<Profile>)]
 pub struct ProfileBody<T> {
     // The profile in the body.
     pub profile: T
 }
 
+// The `ProfilesBody` struct which represents the body of a page of profiles.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(ProfilesResponse = ProfilesBody<Profile>)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesBody<T> {
+    // The profiles in the body.
+    pub profiles: Vec<T>,
+    // The number of profiles in the whole list.
+    pub profile_count: i64,
+}
+
 // The `Profile` struct which represents a profile.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct Profile {
     // The username of the profile.
     pub username: String,
@@ -27,34 +44,157 @@ pub struct Profile {
     pub blocked: bool,
     // Whether the profile is blocking.
     pub blocking: bool,
+    // Whether only approved followers can read the profile's posts.
+    pub private: bool,
+    // Whether the profile asked to follow the current user, waiting for them to accept.
+    pub requested: bool,
+    // Whether the current user asked to follow the profile, waiting for it to accept.
+    pub requesting: bool,
+    // The number of users following the profile, only given when the profile itself is fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follower_count: Option<i64>,
+    // The number of users the profile follows, only given when the profile itself is fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub following_count: Option<i64>,
+    // The number of posts by the profile, only given when the profile itself is fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_count: Option<i64>,
+}
+
+// The `MuteBody` struct which represents the body of a mute.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(MuteResponse = MuteBody<Mute>)]
+pub struct MuteBody<T> {
+    // The mute in the body.
+    pub mute: T
+}
+
+// The `MutesBody` struct which represents the body of a list of mutes.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(MutesResponse = MutesBody<Mute>)]
+pub struct MutesBody<T> {
+    // The mutes in the body.
+    pub mutes: Vec<T>,
+}
+
+// The `FollowRequestsBody` struct which represents the body of a list of follow requests.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(FollowRequestsResponse = FollowRequestsBody<FollowRequest>)]
+pub struct FollowRequestsBody<T> {
+    // The follow requests in the body.
+    pub requests: Vec<T>,
+}
+
+// The `FollowRequest` struct which represents a profile asking to follow the current user.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowRequest {
+    // The profile asking to follow.
+    pub profile: Profile,
+    // The creation timestamp of the request.
+    pub created_at: DateTime<FixedOffset>,
+}
+
+// The `Mute` struct which represents a profile muted by the current user.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Mute {
+    // The muted profile.
+    pub profile: Profile,
+    // The expiration timestamp of the mute, it never expires if empty.
+    pub expires_at: Option<DateTime<FixedOffset>>,
+    // The creation timestamp of the mute.
+    pub created_at: DateTime<FixedOffset>,
+}
+
+// The `MuteQuery` struct which represents the query parameters for muting a profile.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct MuteQuery {
+    // The number of days until the mute expires, it never expires if omitted.
+    pub expires_in_days: Option<i64>,
+}
+
+// The `ListProfileQuery` struct which represents the query parameters for listing profiles.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListProfileQuery {
+    // The limit of profiles to list.
+    pub limit: Option<i64>,
+    // The offset for listing profiles.
+    pub offset: Option<i64>,
+}
+
+
+// Implementation of the `Validate` trait for `MuteQuery`.
+impl Validate for MuteQuery {
+    // Function to validate the length of the mute.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        if let Some(days) = self.expires_in_days {
+            validator.range("expiresInDays", days, 1, limits.mute_days_max);
+        }
+    }
+}
+
+
+// Implementation of the `Validate` trait for `ListProfileQuery`.
+impl Validate for ListProfileQuery {
+    // Function to validate the page of profiles being requested.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        if let Some(limit) = self.limit {
+            validator.range("limit", limit, 1, limits.page_max);
+        }
+        if let Some(offset) = self.offset {
+            validator.range("offset", offset, 0, i64::MAX);
+        }
+    }
+}
+
+
+// Implementation of the `Profile` struct.
+impl Profile {
+    // Function to add the follower, following and post counts to a profile.
+    pub fn with_counts(mut self, counts: ProfileCounts) -> Self {
+        self.follower_count = Some(counts.followers);
+        self.following_count = Some(counts.following);
+        self.post_count = Some(counts.posts);
+        self
+    }
 }
 
 
-// Implementation of the `user_details::Data` struct.
-impl user_details::Data {
-    // Function to convert `user_details::Data` into a `Profile`.
+// Implementation of the `UserRecord` struct.
+impl UserRecord {
+    // Function to convert a `UserRecord` into a `Profile`.
     pub fn to_profile(self, followed: bool, following: bool, blocked:bool, blocking:bool,) -> Profile {
         Profile {
             username: self.username,
             intro: self.intro,
             avatar: self.avatar,
             following, followed,
-            blocking, blocked
+            blocking, blocked,
+            private: self.private, requested: false, requesting: false,
+            follower_count: None, following_count: None, post_count: None,
         }
     }
 }
 
 
 // Implementation of the `From` trait for `Profile`.
-impl From<user_details::Data> for Profile {
-    // Function to convert `user_details::Data` into a `Profile`.
-    fn from(data: user_details::Data) -> Self {
+impl From<UserRecord> for Profile {
+    // Function to convert a `UserRecord` into a `Profile`.
+    fn from(data: UserRecord) -> Self {
         Self {
             username: data.username,
             intro: data.intro,
             avatar: data.avatar,
             following: false, followed: false,
             blocking: false, blocked: false,
+            private: data.private, requested: false, requesting: false,
+            follower_count: None, following_count: None, post_count: None,
         }
     }
 }
\ No newline at end of file
@@ -1,18 +1,20 @@
 // Importing the necessary modules and services.
-use axum::Json;
-use axum::extract::Path;
+use axum::{extract::State, Json};
+use prisma_client_rust::chrono::{self, DateTime, FixedOffset};
 
 // Importing the application's modules.
+use crate::cache;
+use crate::config::BeContext;
+use crate::repository::UserRecord;
 use crate::service::utils::helper::Helper;
 use crate::service::utils::checker::Checker;
-use crate::service::profile::model::{Profile, ProfileBody};
-use crate::error::EError;
+use crate::service::profile::model::{
+    ListProfileQuery, Mute, MuteBody, MuteQuery, Profile, ProfileBody, ProfilesBody,
+};
+use crate::error::{EError, ErrorCode};
 use crate::extractor::extractor::{AuthUser, OptionalAuthUser};
-use crate::prisma::prisma::{user_blocks, user_details, user_follows, PrismaClient};
-
-
-// Type alias for the Prisma client.
-type PRISMA = axum::Extension<std::sync::Arc<PrismaClient>>;
+use crate::extractor::path::Username;
+use crate::extractor::validate::ValidQuery;
 
 
 // The `ProfilesService` struct.
@@ -24,88 +26,162 @@ pub struct ProfilesService;
 impl ProfilesService {
 
     // Function to fetch a profile by its username.
-    // It takes an optional authenticated user, the Prisma client and the username as parameters.
-    // It returns a `Result` with a JSON response containing the profile's details or an error.
+    // It takes the application context, an optional authenticated user and the username as parameters;
+    // anonymous reads are cached.
+    // It returns a `Result` with a JSON response containing the profile's details and counts, or an error;
+    // users blocked by the profile only see its username.
+    // The counts change without the user changing, so no `Last-Modified` is given.
     pub async fn fetch_profile(
-        Path(username): Path<String>,
+        ctx: State<BeContext>,
+        Username(username): Username,
         auth_user: OptionalAuthUser,
-        prisma: PRISMA,
     ) -> Result<Json<ProfileBody<Profile>>, EError> {
 
         tracing::info!("Fetching profile: username: {}", username);
 
-        let visited_user = Helper::get_user_by_name(&prisma, username).await?;
+        let key = cache::profile_key(&username);
+        if auth_user.0.is_none() {
+            if let Some(profile) = ctx.cache.get::<Profile>(&key).await {
+                return Ok(Json::from(ProfileBody { profile }));
+            }
+        }
+
+        let repo = &*ctx.repository;
+        let visited_user = Helper::get_user_by_name(repo, &username).await?;
+        let counts = repo.profile_counts(visited_user.user_id).await?;
 
         return match auth_user.0 {
             Some(user) => {
-                let followed =
-                    Checker::check_following(&prisma, visited_user.user_id, user.user_id,).await?;
-                let following =
-                    Checker::check_following(&prisma, user.user_id, visited_user.user_id).await?;
-                let blocked =
-                    Checker::check_blocked(&prisma, visited_user.user_id, user.user_id).await?;
-                let blocking =
-                    Checker::check_blocked(&prisma, user.user_id, visited_user.user_id,).await?;
-                Ok(Json::from(ProfileBody {
-                    profile: visited_user.to_profile(followed, following, blocking, blocked),
-                }))
+                let profile = Helper::get_profile(repo, visited_user, user.user_id).await?;
+                Ok(Json::from(ProfileBody { profile: profile.with_counts(counts) }))
+            }
+            None => {
+                let profile = visited_user.to_profile(false, false,
+                                                      false, false).with_counts(counts);
+                ctx.cache.set(&key, &profile).await;
+                Ok(Json::from(ProfileBody { profile }))
             }
-            None => Ok(Json::from(ProfileBody {
-                profile: visited_user.to_profile(false, false,
-                                                 false, false),
-            })),
         };
     }
 
 
-    // Function to follow a profile.
-    // It takes an authenticated user, the Prisma client and the username as parameters.
-    // It returns a `Result` with a JSON response containing the followed profile's details or an error.
+    // Function to fetch the users following a profile.
+    // It takes the application context, an optional authenticated user, the username and the query parameters
+    // as parameters.
+    // It returns a `Result` with a JSON response containing a page of the followers, by username, or an error.
+    pub async fn fetch_followers(
+        ctx: State<BeContext>,
+        Username(username): Username,
+        auth_user: OptionalAuthUser,
+        ValidQuery(query): ValidQuery<ListProfileQuery>,
+    ) -> Result<Json<ProfilesBody<Profile>>, EError> {
+
+        tracing::info!("Fetching followers: username: {}", username);
+
+        let repo = &*ctx.repository;
+        let user = Self::get_listed_user(&ctx, &username, &auth_user).await?;
+
+        let (users, profile_count) = repo.followers(
+            user.user_id, query.offset.unwrap_or(0), query.limit.unwrap_or(20),
+        ).await?;
+
+        Ok(Json::from(ProfilesBody {
+            profiles: Helper::get_profiles(repo, users, auth_user.0.map(|user| user.user_id)).await?,
+            profile_count,
+        }))
+    }
+
+
+    // Function to fetch the users a profile follows.
+    // It takes the application context, an optional authenticated user, the username and the query parameters
+    // as parameters.
+    // It returns a `Result` with a JSON response containing a page of the followed users, by username, or an error.
+    pub async fn fetch_following(
+        ctx: State<BeContext>,
+        Username(username): Username,
+        auth_user: OptionalAuthUser,
+        ValidQuery(query): ValidQuery<ListProfileQuery>,
+    ) -> Result<Json<ProfilesBody<Profile>>, EError> {
+
+        tracing::info!("Fetching following: username: {}", username);
+
+        let repo = &*ctx.repository;
+        let user = Self::get_listed_user(&ctx, &username, &auth_user).await?;
+
+        let (users, profile_count) = repo.following(
+            user.user_id, query.offset.unwrap_or(0), query.limit.unwrap_or(20),
+        ).await?;
+
+        Ok(Json::from(ProfilesBody {
+            profiles: Helper::get_profiles(repo, users, auth_user.0.map(|user| user.user_id)).await?,
+            profile_count,
+        }))
+    }
+
+
+    // Function to follow a profile, or ask to follow it if it is private.
+    // It takes the application context, an authenticated user and the username as parameters.
+    // It returns a `Result` with a JSON response containing the followed profile's details,
+    // `requesting` until a private profile accepts, or an error.
     pub async fn follow_profile(
-        Path(username): Path<String>,
+        ctx: State<BeContext>,
+        Username(username): Username,
         auth_user: AuthUser,
-        prisma: PRISMA,
     ) -> Result<Json<ProfileBody<Profile>>, EError> {
 
-        let current_user = Helper::get_user_by_id(&prisma, auth_user.user_id).await?;
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
 
         if current_user.username == username {
-            return Err(EError::BadRequest(String::from("You cannot follow yourself")));
+            return Err(EError::BadRequest(
+                ErrorCode::CannotTargetSelf, String::from("You cannot follow yourself"),
+            ));
         }
 
-        let followed_user = Helper::get_user_by_name(&prisma, username).await?;
+        let followed_user = Helper::get_user_by_name(repo, &username).await?;
 
         tracing::info!("Following profile: username: {} to {}",
             current_user.username, followed_user.username);
 
-        if Checker::check_following(&prisma, auth_user.user_id, followed_user.user_id).await? {
-            return Err(EError::BadRequest(String::from("You are already following this user")));
+        if Checker::check_following(repo, auth_user.user_id, followed_user.user_id).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::AlreadyFollowing, String::from("You are already following this user"),
+            ));
         }
 
-        if Checker::check_blocked(&prisma, followed_user.user_id, auth_user.user_id,).await? {
-            return Err(EError::BadRequest(String::from("Current user has been blocked")));
+        if Checker::check_blocked(repo, followed_user.user_id, auth_user.user_id,).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::BlockedByUser, String::from("Current user has been blocked"),
+            ));
         }
 
-        if Checker::check_blocked(&prisma, auth_user.user_id, followed_user.user_id).await? {
-            return Err(EError::BadRequest(String::from("Current user are blocking this user")));
+        if Checker::check_blocked(repo, auth_user.user_id, followed_user.user_id).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::BlockingUser, String::from("Current user are blocking this user"),
+            ));
+        }
+
+        if followed_user.private {
+            if Checker::check_requested(repo, auth_user.user_id, followed_user.user_id).await? {
+                return Err(EError::BadRequest(
+                    ErrorCode::AlreadyRequested, String::from("You have already asked to follow this user"),
+                ));
+            }
+
+            repo.request_follow(current_user.user_id, followed_user.user_id).await?;
+            Helper::notify(repo, followed_user.user_id,
+                           format!("{} asked to follow you", current_user.username)).await?;
+
+            return Ok(Json::from(ProfileBody {
+                profile: Helper::get_profile(repo, followed_user, auth_user.user_id).await?,
+            }));
         }
 
-        prisma
-            .user_follows()
-            .upsert(
-                user_follows::follower_id_followed_id(current_user.user_id, followed_user.user_id),
-                user_follows::create(
-                    user_details::user_id::equals(current_user.user_id),
-                    user_details::user_id::equals(followed_user.user_id),
-                    vec![],
-                ),
-                vec![],
-            )
-            .exec()
-            .await?;
+        repo.follow(current_user.user_id, followed_user.user_id).await?;
+        Self::invalidate_profiles(&ctx, &current_user, &followed_user).await;
 
         let followed =
-            Checker::check_following(&prisma, followed_user.user_id, auth_user.user_id,).await?;
+            Checker::check_following(repo, followed_user.user_id, auth_user.user_id,).await?;
 
         Ok(Json::from(ProfileBody {
             profile: followed_user.to_profile(followed, true,
@@ -114,40 +190,48 @@ impl ProfilesService {
     }
 
 
-    // Function to unfollow a profile.
-    // It takes an authenticated user, the Prisma client and the username as parameters.
+    // Function to unfollow a profile, or withdraw the request to follow it.
+    // It takes the application context, an authenticated user and the username as parameters.
     // It returns a `Result` with a JSON response containing the unfollowed profile's details or an error.
     pub async fn unfollow_profile(
-        Path(username): Path<String>,
+        ctx: State<BeContext>,
+        Username(username): Username,
         auth_user: AuthUser,
-        prisma: PRISMA,
     ) -> Result<Json<ProfileBody<Profile>>, EError> {
 
-        let current_user = Helper::get_user_by_id(&prisma, auth_user.user_id).await?;
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
 
         if current_user.username == username {
-            return Err(EError::BadRequest(String::from("You cannot unfollow yourself")));
+            return Err(EError::BadRequest(
+                ErrorCode::CannotTargetSelf, String::from("You cannot unfollow yourself"),
+            ));
         }
 
-        let followed_user = Helper::get_user_by_name(&prisma, username).await?;
+        let followed_user = Helper::get_user_by_name(repo, &username).await?;
 
         tracing::info!("Unfollowing profile: username: {} to {}",
             current_user.username, followed_user.username);
 
-        if !Checker::check_following(&prisma, auth_user.user_id, followed_user.user_id).await? {
-            return Err(EError::BadRequest(String::from("Current user did not follow")));
+        if Checker::check_requested(repo, auth_user.user_id, followed_user.user_id).await? {
+            repo.remove_request(current_user.user_id, followed_user.user_id).await?;
+
+            return Ok(Json::from(ProfileBody {
+                profile: Helper::get_profile(repo, followed_user, auth_user.user_id).await?,
+            }));
+        }
+
+        if !Checker::check_following(repo, auth_user.user_id, followed_user.user_id).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::NotFollowing, String::from("Current user did not follow"),
+            ));
         }
 
-        let _ = prisma
-            .user_follows()
-            .delete(user_follows::follower_id_followed_id(
-                current_user.user_id,
-                followed_user.user_id,
-            ))
-            .exec().await.is_ok();
+        repo.unfollow(current_user.user_id, followed_user.user_id).await?;
+        Self::invalidate_profiles(&ctx, &current_user, &followed_user).await;
 
         let followed =
-            Checker::check_following(&prisma, followed_user.user_id, auth_user.user_id,).await?;
+            Checker::check_following(repo, followed_user.user_id, auth_user.user_id,).await?;
 
         Ok(Json::from(ProfileBody {
             profile: followed_user.to_profile(followed, false,
@@ -157,60 +241,44 @@ impl ProfilesService {
 
 
     // Function to block a profile.
-    // It takes an authenticated user, the Prisma client and the username as parameters.
+    // It takes the application context, an authenticated user and the username as parameters.
     // It returns a `Result` with a JSON response containing the blocked profile's details or an error.
     pub async fn block_profile(
-        Path(username): Path<String>,
+        ctx: State<BeContext>,
+        Username(username): Username,
         auth_user: AuthUser,
-        prisma: PRISMA,
     ) -> Result<Json<ProfileBody<Profile>>, EError> {
 
-        let current_user = Helper::get_user_by_id(&prisma, auth_user.user_id).await?;
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
 
         if current_user.username == username {
-            return Err(EError::BadRequest(String::from("You cannot block yourself", )));
+            return Err(EError::BadRequest(
+                ErrorCode::CannotTargetSelf, String::from("You cannot block yourself"),
+            ));
         }
 
-        let blocked_user = Helper::get_user_by_name(&prisma, username).await?;
+        let blocked_user = Helper::get_user_by_name(repo, &username).await?;
 
         tracing::info!("Blocking profile: username: {} to {}",
             current_user.username, blocked_user.username);
 
-        if Checker::check_blocked(&prisma, auth_user.user_id, blocked_user.user_id).await? {
-            return Err(EError::BadRequest(String::from("User has already been blocked")));
+        if Checker::check_blocked(repo, auth_user.user_id, blocked_user.user_id).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::AlreadyBlocked, String::from("User has already been blocked"),
+            ));
         }
 
-        let _ = prisma
-            .user_follows()
-            .delete(user_follows::follower_id_followed_id(
-                current_user.user_id,
-                blocked_user.user_id,
-            ))
-            .exec().await.is_ok();
-
-        let _ = prisma
-            .user_follows()
-            .delete(user_follows::follower_id_followed_id(
-                blocked_user.user_id,
-                current_user.user_id,
-            ))
-            .exec().await.is_ok();
-
-        let _ = prisma
-            .user_blocks()
-            .upsert(
-                user_blocks::blocker_id_blocked_id(current_user.user_id, blocked_user.user_id ),
-                user_blocks::create(
-                    user_details::user_id::equals(current_user.user_id),
-                    user_details::user_id::equals(blocked_user.user_id),
-                    vec![],
-                ),
-                vec![],
-            )
-            .exec().await?;
+        repo.unfollow(current_user.user_id, blocked_user.user_id).await?;
+        repo.unfollow(blocked_user.user_id, current_user.user_id).await?;
+        repo.remove_request(current_user.user_id, blocked_user.user_id).await?;
+        repo.remove_request(blocked_user.user_id, current_user.user_id).await?;
+
+        repo.block(current_user.user_id, blocked_user.user_id).await?;
+        Self::invalidate_profiles(&ctx, &current_user, &blocked_user).await;
 
         let blocked =
-            Checker::check_blocked(&prisma, blocked_user.user_id, auth_user.user_id,).await?;
+            Checker::check_blocked(repo, blocked_user.user_id, auth_user.user_id,).await?;
 
         Ok(Json::from(ProfileBody {
             profile: blocked_user.to_profile(false, false,
@@ -220,43 +288,155 @@ impl ProfilesService {
 
 
     // Function to unblock a profile.
-    // It takes an authenticated user, the Prisma client and the username as parameters.
+    // It takes the application context, an authenticated user and the username as parameters.
     // It returns a `Result` with a JSON response containing the unblocked profile's details or an error.
     pub async fn unblock_profile(
-        Path(username): Path<String>,
+        ctx: State<BeContext>,
+        Username(username): Username,
         auth_user: AuthUser,
-        prisma: PRISMA,
     ) -> Result<Json<ProfileBody<Profile>>, EError> {
 
-        let current_user = Helper::get_user_by_id(&prisma, auth_user.user_id).await?;
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
 
         if current_user.username == username {
-            return Err(EError::BadRequest(String::from("You cannot unblock yourself", )));
+            return Err(EError::BadRequest(
+                ErrorCode::CannotTargetSelf, String::from("You cannot unblock yourself"),
+            ));
         }
 
-        let blocked_user = Helper::get_user_by_name(&prisma, username).await?;
+        let blocked_user = Helper::get_user_by_name(repo, &username).await?;
 
         tracing::info!("Unblocking profile: username: {} to {}",
             current_user.username, blocked_user.username);
 
-        if !Checker::check_blocked(&prisma, auth_user.user_id, blocked_user.user_id).await? {
-            return Err(EError::BadRequest(String::from("Current user did not block")));
+        if !Checker::check_blocked(repo, auth_user.user_id, blocked_user.user_id).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::NotBlocking, String::from("Current user did not block"),
+            ));
         }
 
-        let _ = prisma
-            .user_blocks()
-            .delete(user_blocks::blocker_id_blocked_id(
-                current_user.user_id,
-                blocked_user.user_id,
-            ))
-            .exec().await.is_ok();
+        repo.unblock(current_user.user_id, blocked_user.user_id).await?;
 
         let blocked =
-            Checker::check_blocked(&prisma, blocked_user.user_id, auth_user.user_id,).await?;
+            Checker::check_blocked(repo, blocked_user.user_id, auth_user.user_id,).await?;
 
         Ok(Json::from(ProfileBody {
             profile: blocked_user.to_profile(false, false,
                                              blocked, false),
         }))
     }
+
+    // Function to mute a profile, hiding their posts and comments from the current user only.
+    // It takes the application context, an authenticated user, the username and the query parameters as parameters.
+    // It returns a `Result` with a JSON response containing the mute or an error.
+    // Muting again replaces the expiry; the muted user is not told.
+    pub async fn mute_profile(
+        ctx: State<BeContext>,
+        Username(username): Username,
+        auth_user: AuthUser,
+        ValidQuery(query): ValidQuery<MuteQuery>,
+    ) -> Result<Json<MuteBody<Mute>>, EError> {
+
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+
+        if current_user.username == username {
+            return Err(EError::BadRequest(
+                ErrorCode::CannotTargetSelf, String::from("You cannot mute yourself"),
+            ));
+        }
+
+        let muted_user = Helper::get_user_by_name(repo, &username).await?;
+
+        tracing::info!("Muting profile: username: {} to {}",
+            current_user.username, muted_user.username);
+
+        let expires_at = query.expires_in_days.map(|days| {
+            DateTime::<FixedOffset>::from(chrono::Utc::now()) + chrono::Duration::days(days)
+        });
+
+        let mute = repo.mute(current_user.user_id, muted_user.user_id, expires_at).await?;
+
+        Ok(Json::from(MuteBody {
+            mute: Mute {
+                profile: Helper::get_profile(repo, mute.user, auth_user.user_id).await?,
+                expires_at: mute.expires_at,
+                created_at: mute.created_at,
+            },
+        }))
+    }
+
+
+    // Function to unmute a profile.
+    // It takes the application context, an authenticated user and the username as parameters.
+    // It returns a `Result` with a JSON response containing the unmuted profile's details or an error.
+    pub async fn unmute_profile(
+        ctx: State<BeContext>,
+        Username(username): Username,
+        auth_user: AuthUser,
+    ) -> Result<Json<ProfileBody<Profile>>, EError> {
+
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+
+        if current_user.username == username {
+            return Err(EError::BadRequest(
+                ErrorCode::CannotTargetSelf, String::from("You cannot unmute yourself"),
+            ));
+        }
+
+        let muted_user = Helper::get_user_by_name(repo, &username).await?;
+
+        tracing::info!("Unmuting profile: username: {} to {}",
+            current_user.username, muted_user.username);
+
+        if !Checker::check_muted(repo, auth_user.user_id, muted_user.user_id).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::NotMuting, String::from("Current user did not mute"),
+            ));
+        }
+
+        repo.unmute(current_user.user_id, muted_user.user_id).await?;
+
+        Ok(Json::from(ProfileBody {
+            profile: Helper::get_profile(repo, muted_user, auth_user.user_id).await?,
+        }))
+    }
+
+
+    // Utility functions for the `ProfilesService` struct.
+
+    // Function to get the user whose followers or followed users are listed.
+    // It takes the application context, the username and an optional authenticated user as parameters.
+    // It returns a `Result` with the user, or an error if they do not exist, block the viewer,
+    // or are private and not followed by the viewer.
+    async fn get_listed_user(
+        ctx: &BeContext,
+        username: &str,
+        auth_user: &OptionalAuthUser,
+    ) -> Result<UserRecord, EError> {
+
+        let repo = &*ctx.repository;
+        let user = Helper::get_user_by_name(repo, username).await?;
+
+        if let Some(viewer) = &auth_user.0 {
+            if Checker::check_blocked(repo, user.user_id, viewer.user_id).await? {
+                return Err(EError::Forbidden(
+                    ErrorCode::BlockedByUser, String::from("Current user has been blocked"),
+                ));
+            }
+        }
+
+        Checker::check_readable(repo, &user, auth_user.0.as_ref().map(|viewer| viewer.user_id)).await?;
+
+        Ok(user)
+    }
+
+    // Function to drop the cached profiles of two users whose follower and following counts changed.
+    // It takes the application context and the two users as parameters.
+    async fn invalidate_profiles(ctx: &BeContext, user: &UserRecord, other_user: &UserRecord) {
+        ctx.cache.invalidate(&cache::profile_key(&user.username)).await;
+        ctx.cache.invalidate(&cache::profile_key(&other_user.username)).await;
+    }
 }
@@ -0,0 +1,86 @@
+// Importing the necessary modules and functions.
+use axum::handler::Handler;
+use axum::http::Method;
+use axum::routing::{on, MethodFilter, MethodRouter};
+use axum::Extension;
+
+use crate::config::BeContext;
+use crate::extractor::extractor::ApiScope;
+
+
+// The `Route` struct which represents one method of one path, with its handler.
+// Every router lists its routes as a table of these, which builds the router and is checked against the
+// OpenAPI document, so the two can't drift apart.
+pub struct Route {
+    // The method of the route.
+    pub method: Method,
+    // The path of the route, in axum's syntax, such as "/posts/:post_id".
+    pub path: String,
+    // Whether the route is described in the OpenAPI document.
+    pub documented: bool,
+    // The handler of the route, with its layers.
+    handler: MethodRouter<BeContext>,
+}
+
+
+// Implementation of the `Route` struct.
+impl Route {
+
+    // Function to create a `GET` route.
+    pub fn get<H: Handler<T, BeContext>, T: 'static>(path: &str, handler: H) -> Self {
+        Self::new(Method::GET, MethodFilter::GET, path, handler)
+    }
+
+    // Function to create a `POST` route.
+    pub fn post<H: Handler<T, BeContext>, T: 'static>(path: &str, handler: H) -> Self {
+        Self::new(Method::POST, MethodFilter::POST, path, handler)
+    }
+
+    // Function to create a `PUT` route.
+    pub fn put<H: Handler<T, BeContext>, T: 'static>(path: &str, handler: H) -> Self {
+        Self::new(Method::PUT, MethodFilter::PUT, path, handler)
+    }
+
+    // Function to create a `DELETE` route.
+    pub fn delete<H: Handler<T, BeContext>, T: 'static>(path: &str, handler: H) -> Self {
+        Self::new(Method::DELETE, MethodFilter::DELETE, path, handler)
+    }
+
+    // Function to limit the route to API tokens with the given scope.
+    pub fn scope(mut self, scope: ApiScope) -> Self {
+        self.handler = self.handler.layer(Extension(scope));
+        self
+    }
+
+    // Function to leave the route out of the OpenAPI document.
+    pub fn undocumented(mut self) -> Self {
+        self.documented = false;
+        self
+    }
+
+    // Function to prefix the paths of routes, like `axum::Router::nest`.
+    pub fn nest(prefix: &str, routes: Vec<Route>) -> Vec<Route> {
+        routes
+            .into_iter()
+            .map(|route| Route { path: format!("{}{}", prefix, route.path), ..route })
+            .collect()
+    }
+
+    // Function to build a router serving the routes.
+    // Routes of the same path are merged into one, as `axum::Router::route` does.
+    pub fn router(routes: Vec<Route>) -> axum::Router<BeContext> {
+        routes
+            .into_iter()
+            .fold(axum::Router::new(), |router, route| router.route(&route.path, route.handler))
+    }
+
+    // Function to create a route.
+    fn new<H: Handler<T, BeContext>, T: 'static>(
+        method: Method,
+        filter: MethodFilter,
+        path: &str,
+        handler: H,
+    ) -> Self {
+        Self { method, path: path.to_string(), documented: true, handler: on(filter, handler) }
+    }
+}
@@ -0,0 +1,42 @@
+// The OpenAPI descriptions of the routes in `TokenRouter`.
+// Each function only carries the `#[utoipa::path]` attribute of the handler of the same name,
+// the schemas are referenced by name and registered in `crate::service::openapi::ApiDoc`.
+
+
+/// List the current user's API tokens
+#[utoipa::path(
+    get, path = "/api/users/tokens", tag = "tokens",
+    responses(
+        (status = 200, description = "The API tokens, without their secrets", body = ApiTokensResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn fetch_tokens() {}
+
+/// Create an API token
+#[utoipa::path(
+    post, path = "/api/users/tokens", tag = "tokens",
+    request_body = CreateApiTokenRequest,
+    responses(
+        (status = 200, description = "The API token with its secret, shown only once", body = ApiTokenResponse),
+        (status = 400, description = "Missing name or scopes, or an expiry in the past", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn create_token() {}
+
+/// Revoke an API token
+#[utoipa::path(
+    delete, path = "/api/users/tokens/{token_id}", tag = "tokens",
+    params(("token_id" = i32, Path, description = "The ID of the API token")),
+    responses(
+        (status = 200, description = "API token revoked", body = String),
+        (status = 400, description = "Invalid API token id", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "API token not found", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn revoke_token() {}
@@ -0,0 +1,36 @@
+// The `token` module.
+pub mod docs;
+pub mod model;
+pub mod service;
+
+
+// Importing the necessary modules and functions.
+use crate::config::BeContext;
+use crate::service::token::service::TokenService;
+use crate::service::route::Route;
+
+
+// The `TokenRouter` struct which is responsible for routing HTTP requests to the appropriate handlers.
+pub struct TokenRouter;
+
+
+// Implementation of the `TokenRouter` struct.
+impl TokenRouter {
+    // Function to create a new `TokenRouter`.
+    pub fn new() -> axum::Router<BeContext> {
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the `TokenRouter`.
+    pub fn routes() -> Vec<Route> {
+        // These routes declare no `ApiScope`, so API tokens can never manage API tokens.
+        vec![
+            // Route for listing the current user's API tokens.
+            Route::get("/users/tokens", TokenService::fetch_tokens),
+            // Route for creating an API token.
+            Route::post("/users/tokens", TokenService::create_token),
+            // Route for revoking an API token.
+            Route::delete("/users/tokens/:token_id", TokenService::revoke_token),
+        ]
+    }
+}
@@ -0,0 +1,79 @@
+// Importing the necessary modules and functions.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use prisma_client_rust::chrono::FixedOffset;
+
+use crate::extractor::extractor::ApiScope;
+use crate::repository::ApiTokenRecord;
+
+
+// The `ApiTokenBody` struct which represents the body of an API token request or response.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    ApiTokenResponse = ApiTokenBody<ApiToken>,
+    CreateApiTokenRequest = ApiTokenBody<CreateApiToken>,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenBody<T> {
+    // The API token in the body.
+    pub api_token: T
+}
+
+// The `ApiTokensBody` struct which represents the body of a list of API tokens.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(ApiTokensResponse = ApiTokensBody<ApiToken>)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokensBody<T> {
+    // The API tokens in the body.
+    pub api_tokens: Vec<T>,
+}
+
+// The `CreateApiToken` struct which represents the data for creating an API token.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiToken {
+    // The name of the token.
+    pub name: String,
+    // The scopes granted to the token.
+    pub scopes: Vec<ApiScope>,
+    // The number of days until the token expires, it never expires if omitted.
+    pub expires_in_days: Option<i64>,
+}
+
+// The `ApiToken` struct which represents an API token.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    // The ID of the token.
+    pub token_id: i32,
+    // The name of the token.
+    pub name: String,
+    // The scopes granted to the token.
+    pub scopes: Vec<ApiScope>,
+    // The expiration timestamp of the token.
+    pub expires_at: Option<prisma_client_rust::chrono::DateTime<FixedOffset>>,
+    // The timestamp of the last request made with the token.
+    pub last_used_at: Option<prisma_client_rust::chrono::DateTime<FixedOffset>>,
+    // The creation timestamp of the token.
+    pub created_at: prisma_client_rust::chrono::DateTime<FixedOffset>,
+    // The secret token, only returned once on creation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+
+// Implementation of the `From` trait for `ApiToken`.
+impl From<ApiTokenRecord> for ApiToken {
+    // Function to convert an `ApiTokenRecord` into an `ApiToken`.
+    fn from(data: ApiTokenRecord) -> Self {
+        Self {
+            token_id: data.token_id,
+            name: data.name,
+            scopes: data.scopes.iter().filter_map(|scope| ApiScope::parse(scope)).collect(),
+            expires_at: data.expires_at,
+            last_used_at: data.last_used_at,
+            created_at: data.created_at,
+            token: None,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// Importing the necessary modules and services.
+use axum::{extract::State, Json};
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+use sha2::{Digest, Sha256};
+use prisma_client_rust::chrono::{self, DateTime, FixedOffset};
+
+// Importing the application's modules.
+use crate::error::{EError, ErrorCode};
+use crate::config::BeContext;
+use crate::extractor::extractor::{ApiScope, AuthUser};
+use crate::extractor::path::TokenId;
+use crate::service::token::model::*;
+use crate::repository::Repository;
+
+
+// The prefix of every API token, used to tell them apart from JWTs.
+pub const API_TOKEN_PREFIX: &str = "wfp_";
+// The number of random characters after the prefix.
+const API_TOKEN_LENGTH: usize = 40;
+
+
+// The `TokenService` struct.
+// This struct contains methods for handling HTTP requests related to personal API tokens.
+pub struct TokenService;
+
+
+// Implementation of the `TokenService` struct.
+impl TokenService {
+
+    // Function to fetch the current user's API tokens.
+    // It takes the application context and an authenticated user as parameters.
+    // It returns a `Result` with a JSON response containing the user's tokens, without their secrets, or an error.
+    pub async fn fetch_tokens(
+        ctx: State<BeContext>,
+        auth_user: AuthUser,
+    ) -> Result<Json<ApiTokensBody<ApiToken>>, EError> {
+
+        tracing::info!("Fetching API tokens: user_id: {}", auth_user.user_id);
+
+        let api_tokens = ctx.repository
+            .api_tokens(auth_user.user_id)
+            .await?
+            .into_iter()
+            .map(ApiToken::from)
+            .collect();
+
+        Ok(Json::from(ApiTokensBody { api_tokens }))
+    }
+
+
+    // Function to create an API token.
+    // It takes the application context, an authenticated user and the token's details as parameters.
+    // It returns a `Result` with a JSON response containing the token and its secret or an error.
+    pub async fn create_token(
+        ctx: State<BeContext>,
+        auth_user: AuthUser,
+        Json(input): Json<ApiTokenBody<CreateApiToken>>,
+    ) -> Result<Json<ApiTokenBody<ApiToken>>, EError> {
+
+        let CreateApiToken { name, scopes, expires_in_days } = input.api_token;
+
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(EError::BadRequest(
+                ErrorCode::ValidationFailed, String::from("Token name is required"),
+            ));
+        }
+
+        if scopes.is_empty() {
+            return Err(EError::BadRequest(
+                ErrorCode::ValidationFailed, String::from("At least one scope is required"),
+            ));
+        }
+
+        let expires_at = match expires_in_days {
+            Some(days) if days <= 0 => {
+                return Err(EError::BadRequest(
+                    ErrorCode::ValidationFailed, String::from("Token expiry must be in the future"),
+                ));
+            }
+            Some(days) => Some(Self::now() + chrono::Duration::days(days)),
+            None => None,
+        };
+
+        tracing::info!("Creating API token: user_id: {}, name: {}", auth_user.user_id, name);
+
+        let secret = format!(
+            "{}{}",
+            API_TOKEN_PREFIX,
+            OsRng.sample_iter(&Alphanumeric).take(API_TOKEN_LENGTH).map(char::from).collect::<String>(),
+        );
+
+        let mut scopes: Vec<String> = scopes.iter().map(|scope| scope.as_str().to_string()).collect();
+        scopes.sort();
+        scopes.dedup();
+
+        let data = ctx.repository
+            .create_api_token(auth_user.user_id, name, Self::hash_token(&secret), scopes, expires_at)
+            .await?;
+
+        let mut api_token = ApiToken::from(data);
+        api_token.token = Some(secret);
+
+        Ok(Json::from(ApiTokenBody { api_token }))
+    }
+
+
+    // Function to revoke an API token.
+    // It takes the token's ID, the application context and an authenticated user as parameters.
+    // It returns a `Result` with a message or an error.
+    pub async fn revoke_token(
+        TokenId(token_id): TokenId,
+        ctx: State<BeContext>,
+        auth_user: AuthUser,
+    ) -> Result<String, EError> {
+
+        tracing::info!("Revoking API token: user_id: {}, token_id: {}", auth_user.user_id, token_id);
+
+        let revoked = ctx.repository.revoke_api_token(auth_user.user_id, token_id).await?;
+
+        if !revoked {
+            return Err(EError::NotFound(ErrorCode::ApiTokenNotFound, String::from("API token not found")));
+        }
+
+        Ok("API token revoked".to_string())
+    }
+
+
+    // Utility functions for the `TokenService` struct.
+
+    // Function to authenticate a request made with an API token.
+    // It takes the repository, the token and the scope required by the route as parameters.
+    // It returns a `Result` with the ID of the token's owner or an error.
+    pub async fn authenticate<R: Repository + ?Sized>(
+        repo: &R,
+        token: &str,
+        scope: ApiScope,
+    ) -> Result<i32, EError> {
+
+        let data = repo
+            .find_api_token(&Self::hash_token(token))
+            .await?
+            .ok_or_else(|| {
+                tracing::info!("Unknown API token");
+                EError::Unauthorized(ErrorCode::InvalidToken, String::from("Invalid API token"))
+            })?;
+
+        let now = Self::now();
+
+        if data.expires_at.is_some_and(|expires_at| expires_at < now) {
+            tracing::info!("API token is expired: token_id: {}", data.token_id);
+            return Err(EError::Unauthorized(ErrorCode::TokenExpired, String::from("API token is expired")));
+        }
+
+        if !data.scopes.iter().any(|granted| granted == scope.as_str()) {
+            tracing::info!("API token is missing scope {}: token_id: {}", scope.as_str(), data.token_id);
+            return Err(EError::Forbidden(
+                ErrorCode::MissingScope, format!("API token is missing the {} scope", scope.as_str()),
+            ));
+        }
+
+        repo.touch_api_token(data.token_id, now).await?;
+
+        Ok(data.user_id)
+    }
+
+    // Function to hash an API token or a recovery code for storage.
+    // Both are long and random, so a fast hash is enough and allows looking them up directly.
+    pub(crate) fn hash_token(token: &str) -> String {
+        Sha256::digest(token.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    // Function to get the current time.
+    fn now() -> DateTime<FixedOffset> {
+        chrono::Utc::now().into()
+    }
+}
@@ -0,0 +1,143 @@
+// Importing the necessary modules and functions.
+use crate::service::profile::model::ListProfileQuery;
+
+
+// The OpenAPI descriptions of the routes in `UsersRouter`.
+// Each function only carries the `#[utoipa::path]` attribute of the handler of the same name,
+// the schemas are referenced by name and registered in `crate::service::openapi::ApiDoc`.
+
+
+/// Fetch the current user
+#[utoipa::path(
+    get, path = "/api/users", tag = "users",
+    responses(
+        (status = 200, description = "The current user with a fresh token", body = UserResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn current_user() {}
+
+/// Log in with email and password
+#[utoipa::path(
+    post, path = "/api/users", tag = "users",
+    request_body = LoginUserRequest,
+    responses(
+        (status = 200, description = "The logged-in user, or the two-factor challenge to complete", body = LoginResponse),
+        (status = 401, description = "Invalid email or password", body = ErrorBody),
+        (status = 403, description = "The account is suspended", body = ErrorBody),
+        (status = 429, description = "Too many failed logins", body = ErrorBody),
+    ),
+)]
+pub fn login_user() {}
+
+/// Update the current user
+#[utoipa::path(
+    put, path = "/api/users", tag = "users",
+    request_body = UpdateUserRequest,
+    responses(
+        (status = 200, description = "The updated user", body = UserResponse),
+        (status = 400, description = "Malformed body or invalid fields", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 409, description = "Email or username already taken", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn update_user() {}
+
+/// Delete the current user's account
+#[utoipa::path(
+    delete, path = "/api/users", tag = "users",
+    responses(
+        (status = 200, description = "User deleted", body = String),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = [])),
+)]
+pub fn delete_user() {}
+
+/// Fetch the current user's notifications
+#[utoipa::path(
+    get, path = "/api/users/notifications", tag = "users",
+    responses(
+        (status = 200, description = "The notifications, newest first", body = NotificationsResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = ["read"])),
+)]
+pub fn fetch_notifications() {}
+
+/// List the profiles the current user mutes
+#[utoipa::path(
+    get, path = "/api/users/mutes", tag = "users",
+    responses(
+        (status = 200, description = "The mutes which have not expired, latest first", body = MutesResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = ["read"])),
+)]
+pub fn fetch_mutes() {}
+
+/// List the profiles the current user blocks
+#[utoipa::path(
+    get, path = "/api/users/blocks", tag = "users",
+    params(ListProfileQuery),
+    responses(
+        (status = 200, description = "The blocked profiles by username", body = ProfilesResponse),
+        (status = 400, description = "Malformed query, or limit or offset out of range", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = ["read"])),
+)]
+pub fn fetch_blocks() {}
+
+/// List the profiles asking to follow the current user
+#[utoipa::path(
+    get, path = "/api/users/follow-requests", tag = "users",
+    responses(
+        (status = 200, description = "The follow requests waiting for an answer, latest first", body = FollowRequestsResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+    ),
+    security(("token" = ["read"])),
+)]
+pub fn fetch_follow_requests() {}
+
+/// Accept a request to follow the current user
+#[utoipa::path(
+    post, path = "/api/users/follow-requests/{username}", tag = "users",
+    params(("username" = String, Path, description = "The username of the profile asking to follow")),
+    responses(
+        (status = 200, description = "The profile, now following the current user", body = ProfileResponse),
+        (status = 400, description = "No request from this profile, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn accept_follow_request() {}
+
+/// Reject a request to follow the current user
+#[utoipa::path(
+    delete, path = "/api/users/follow-requests/{username}", tag = "users",
+    params(("username" = String, Path, description = "The username of the profile asking to follow")),
+    responses(
+        (status = 200, description = "The rejected profile, which is not told", body = ProfileResponse),
+        (status = 400, description = "No request from this profile, or invalid username", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ),
+    security(("token" = ["write:profiles"])),
+)]
+pub fn reject_follow_request() {}
+
+/// Register a new user
+#[utoipa::path(
+    post, path = "/api/users/create", tag = "users",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 200, description = "The created user with a token", body = UserResponse),
+        (status = 400, description = "Malformed body or invalid fields", body = ErrorBody),
+        (status = 409, description = "Email or username already taken", body = ErrorBody),
+    ),
+)]
+pub fn create_user() {}
@@ -1,11 +1,14 @@
 // The `user` module.
+pub mod docs;
 pub mod model;
 pub mod service;
 
 
 // Importing the necessary modules and functions.
-use axum::routing::{delete, get, post, put};
+use crate::config::BeContext;
+use crate::extractor::extractor::ApiScope;
 use crate::service::user::service::UsersService;
+use crate::service::route::Route;
 
 
 // The `UsersRouter` struct which is responsible for routing HTTP requests to the appropriate handlers.
@@ -15,18 +18,35 @@ pub struct UsersRouter;
 // Implementation of the `UsersRouter` struct.
 impl UsersRouter {
     // Function to create a new `UsersRouter`.
-    pub fn new() -> axum::Router<crate::config::BeContext> {
-        // Create a new `Router` and define the routes.
-        axum::Router::new()
+    pub fn new() -> axum::Router<BeContext> {
+        Route::router(Self::routes())
+    }
+
+    // Function to list the routes of the `UsersRouter`.
+    pub fn routes() -> Vec<Route> {
+        vec![
             // Route for fetching the current user's details.
-            .route("/users", get(UsersService::current_user))
+            Route::get("/users", UsersService::current_user),
             // Route for logging in a user.
-            .route("/users", post(UsersService::login_user))
+            Route::post("/users", UsersService::login_user),
             // Route for updating the current user's details.
-            .route("/users", put(UsersService::update_user))
+            Route::put("/users", UsersService::update_user),
             // Route for deleting the current user's account.
-            .route("/users", delete(UsersService::delete_user))
+            Route::delete("/users", UsersService::delete_user),
+            // Route for fetching the current user's notifications.
+            Route::get("/users/notifications", UsersService::fetch_notifications).scope(ApiScope::Read),
+            // Route for fetching the profiles the current user mutes.
+            Route::get("/users/mutes", UsersService::fetch_mutes).scope(ApiScope::Read),
+            // Route for fetching the profiles the current user blocks.
+            Route::get("/users/blocks", UsersService::fetch_blocks).scope(ApiScope::Read),
+            // Route for fetching the profiles asking to follow the current user.
+            Route::get("/users/follow-requests", UsersService::fetch_follow_requests).scope(ApiScope::Read),
+            // Route for accepting a request to follow the current user.
+            Route::post("/users/follow-requests/:username", UsersService::accept_follow_request).scope(ApiScope::WriteProfiles),
+            // Route for rejecting a request to follow the current user.
+            Route::delete("/users/follow-requests/:username", UsersService::reject_follow_request).scope(ApiScope::WriteProfiles),
             // Route for creating a new user.
-            .route("/users/create", post(UsersService::create_user))
+            Route::post("/users/create", UsersService::create_user),
+        ]
     }
 }
\ No newline at end of file
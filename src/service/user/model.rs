@@ -1,19 +1,28 @@
 // Importing the necessary modules and functions.
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use prisma_client_rust::chrono::FixedOffset;
 
-use crate::prisma::prisma::user_details;
+use crate::extractor::validate::{Validate, Validator};
+use crate::prisma::prisma::Role;
+use crate::repository::{NotificationRecord, UserRecord};
 
 
 // The `UserBody` struct which represents the body of a user.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    UserResponse = UserBody<User>,
+    CreateUserRequest = UserBody<CreateUserPost>,
+    LoginUserRequest = UserBody<LoginUserPost>,
+    UpdateUserRequest = UserBody<UpdateUserPost>,
+)]
 pub struct UserBody<T> {
     // The user in the body.
     pub user: T
 }
 
 // The `CreateUserPost` struct which represents the data for creating a user.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateUserPost {
     // The email of the user.
     pub email: String,
@@ -24,7 +33,7 @@ pub struct CreateUserPost {
 }
 
 // The `UpdateUserPost` struct which represents the data for updating a user.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUserPost {
     // The new email of the user.
     pub email: Option<String>,
@@ -36,10 +45,12 @@ pub struct UpdateUserPost {
     pub username: Option<String>,
     // The new password of the user.
     pub password: Option<String>,
+    // Whether only approved followers can read the user's posts.
+    pub private: Option<bool>,
 }
 
 // The `LoginUserPost` struct which represents the data for logging in a user.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginUserPost {
     // The email of the user.
     pub email: String,
@@ -48,7 +59,7 @@ pub struct LoginUserPost {
 }
 
 // The `User` struct which represents a user.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     // The ID of the user.
@@ -61,13 +72,39 @@ pub struct User {
     pub email: String,
     // The username of the user.
     pub username: String,
+    // The role of the user.
+    #[schema(value_type = String, example = "USER")]
+    pub role: Role,
     // The creation timestamp of the user.
     pub created_at: prisma_client_rust::chrono::DateTime<FixedOffset>,
+    // Whether only approved followers can read the user's posts.
+    pub private: bool,
     // The token of the user.
     pub token: Option<String>,
 }
 
 
+// The `NotificationsBody` struct which represents the body of a list of notifications.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(NotificationsResponse = NotificationsBody<Notification>)]
+pub struct NotificationsBody<T> {
+    // The notifications in the body.
+    pub notifications: Vec<T>,
+}
+
+// The `Notification` struct which represents a notification sent to a user.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    // The ID of the notification.
+    pub notification_id: i32,
+    // The message of the notification.
+    pub message: String,
+    // The creation timestamp of the notification.
+    pub created_at: prisma_client_rust::chrono::DateTime<FixedOffset>,
+}
+
+
 // Implementation of the `User` struct.
 impl User {
     // Function to set the token of the user.
@@ -77,19 +114,81 @@ impl User {
 }
 
 
+// Implementation of the `Validate` trait for `UserBody`.
+impl<T: Validate> Validate for UserBody<T> {
+    // Function to validate the user in the body.
+    fn validate(&self, validator: &mut Validator) {
+        validator.nested("user", |validator| self.user.validate(validator));
+    }
+}
+
+
+// Implementation of the `Validate` trait for `CreateUserPost`.
+impl Validate for CreateUserPost {
+    // Function to validate the email, username and password of the new user.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        validator
+            .email("email", &self.email, limits.email_max)
+            .username("username", &self.username, limits.username_min, limits.username_max)
+            .length("password", &self.password, limits.password_min, limits.password_max);
+    }
+}
+
+
+// Implementation of the `Validate` trait for `UpdateUserPost`.
+impl Validate for UpdateUserPost {
+    // Function to validate the fields being updated.
+    fn validate(&self, validator: &mut Validator) {
+        let limits = validator.limits();
+        if let Some(email) = &self.email {
+            validator.email("email", email, limits.email_max);
+        }
+        if let Some(intro) = &self.intro {
+            validator.length("intro", intro, 0, limits.intro_max);
+        }
+        // An empty avatar removes it.
+        if let Some(avatar) = self.avatar.as_ref().filter(|avatar| !avatar.is_empty()) {
+            validator.url("avatar", avatar, limits.avatar_max);
+        }
+        if let Some(username) = &self.username {
+            validator.username("username", username, limits.username_min, limits.username_max);
+        }
+        if let Some(password) = &self.password {
+            validator.length("password", password, limits.password_min, limits.password_max);
+        }
+    }
+}
+
+
 // Implementation of the `From` trait for `User`.
-impl From<user_details::Data> for User {
-    // Function to convert `user_details::Data` into a `User`.
-    fn from(data: user_details::Data) -> Self {
+impl From<UserRecord> for User {
+    // Function to convert a `UserRecord` into a `User`.
+    fn from(data: UserRecord) -> Self {
         Self {
             user_id: data.user_id,
             intro: data.intro,
             avatar: data.avatar,
             email: data.email,
             username: data.username,
+            role: data.role,
             // Convert the creation timestamp to the configured timezone.
             created_at: data.created_at,
+            private: data.private,
             token: None,
         }
     }
+}
+
+
+// Implementation of the `From` trait for `Notification`.
+impl From<NotificationRecord> for Notification {
+    // Function to convert a `NotificationRecord` into a `Notification`.
+    fn from(data: NotificationRecord) -> Self {
+        Self {
+            notification_id: data.notification_id,
+            message: data.message,
+            created_at: data.created_at,
+        }
+    }
 }
\ No newline at end of file
@@ -1,19 +1,36 @@
 use std::vec;
+use std::net::SocketAddr;
 // Importing the necessary modules and services.
 use rand::rngs::OsRng;
-use axum::{extract::State, Json};
+use lazy_static::lazy_static;
+use axum::{extract::{ConnectInfo, State}, http::HeaderMap, Json};
 use argon2::{password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 
 // Importing the application's modules.
-use crate::error::EError;
+use crate::cache::{self, Cache};
+use crate::error::{EError, ErrorCode};
+use crate::middleware::metrics::{self, Event};
 use crate::service::user::model::*;
+use crate::service::profile::model::{
+    FollowRequest, FollowRequestsBody, ListProfileQuery, Mute, MutesBody, Profile, ProfileBody, ProfilesBody,
+};
 use crate::config::BeContext;
 use crate::extractor::extractor::AuthUser;
-use crate::prisma::prisma::{platform_posts, post_comments, PrismaClient, user_blocks, user_details, user_follows, user_history, user_like_posts, user_password};
-
-
-// Type alias for the Prisma client.
-type PRISMA = axum::Extension<std::sync::Arc<PrismaClient>>;
+use crate::extractor::path::Username;
+use crate::extractor::validate::{ValidJson, ValidQuery};
+use crate::service::auth::model::LoginResponse;
+use crate::service::auth::service::AuthService;
+use crate::service::utils::guard::GuardKey;
+use crate::service::utils::helper::Helper;
+use crate::repository::{Repository, UserRecord, UserUpdate};
+use crate::service::utils::checker::Checker;
+
+
+// Using the `lazy_static` macro to hash the dummy password only once.
+lazy_static! {
+    // The `DUMMY_HASH` variable is verified against when the email is unknown.
+    static ref DUMMY_HASH: String = UsersService::hash_password("wforum-dummy-password").unwrap();
+}
 
 
 // The `UsersService` struct.
@@ -25,39 +42,36 @@ pub struct UsersService;
 impl UsersService {
 
     // Function to fetch a user by their ID.
-    // It takes an authenticated user, the application context, and the Prisma client as parameters.
+    // It takes an authenticated user and the application context as parameters.
     // It returns a `Result` with a JSON response containing the user's details or an error.
     pub async fn current_user(
         auth_user: AuthUser,
         ctx: State<BeContext>,
-        prisma: PRISMA,
     ) -> Result<Json<UserBody<User>>, EError> {
 
         tracing::info!("Current user: user_id:{}", auth_user.user_id);
 
-        let data = prisma
-            .user_details().find_unique(user_details::user_id::equals(auth_user.user_id))
-            .exec().await.unwrap();
+        let data = Helper::get_user_by_id(&*ctx.repository, auth_user.user_id).await?;
+        let token = AuthService::issue_token(&ctx, &data).await?;
 
-        match data {
-            Some(data) => {
-                let mut user: User = data.into();
-                user.set_token(auth_user.gen_jwt(&ctx));
-                Ok(Json::from(UserBody { user }))
-            }
-            None => Err(EError::NotFound(String::from("User not found"))),
-        }
+        let mut user: User = data.into();
+        user.set_token(token);
+        Ok(Json::from(UserBody { user }))
     }
 
 
     // Function to log in a user.
-    // It takes the Prisma client, the application context, and the user's login data as parameters.
-    // It returns a `Result` with a JSON response containing the logged-in user's details or an error.
+    // It takes the application context, the client's address, and the user's login data as parameters.
+    // It returns a `Result` with a JSON response containing the logged-in user's details,
+    // or the two-factor challenge they must complete first, or an error.
+    // Failures are counted per account and per IP address, and every failure returns the same error;
+    // the account's owner is notified whenever a new lockout starts.
     pub async fn login_user(
-        prisma: PRISMA,
         ctx: State<BeContext>,
+        connect_info: Option<ConnectInfo<SocketAddr>>,
+        headers: HeaderMap,
         Json(input): Json<UserBody<LoginUserPost>>,
-    ) -> Result<Json<UserBody<User>>, EError> {
+    ) -> Result<Json<LoginResponse>, EError> {
 
         let UserBody {
             user: LoginUserPost { email, password },
@@ -65,42 +79,75 @@ impl UsersService {
 
         tracing::info!("Logging in user: email: {}", email);
 
-        let user_data = prisma
-            .user_details().find_unique(user_details::email::equals(email))
-            .exec().await?;
+        let account_key = GuardKey::Account(email.trim().to_lowercase());
+        let ip_key = GuardKey::client(
+            &headers,
+            ctx.config.server_config.client_ip_header.as_deref(),
+            connect_info.map(|ConnectInfo(addr)| addr),
+        );
 
-        let user_data = match user_data {
-            Some(user_data) => user_data,
-            None => return Err(EError::NotFound(String::from("User not found"))),
-        };
+        ctx.login_guard.check(&[account_key.clone(), ip_key.clone()])?;
 
-        let password_data = prisma
-            .user_password().find_unique(user_password::user_id::equals(user_data.user_id))
-            .exec().await?.unwrap();
+        let repo = &*ctx.repository;
 
-        match Self::verify_password(password.as_str(),
-                                    password_data.hash_password.as_str()) {
-            Ok(_) => (),
-            Err(_) => return Err(EError::Unauthorized(String::from("Invalid password"))),
+        let user_data = repo.find_user_by_email(&email).await?;
+
+        let password_hash = match &user_data {
+            Some(user_data) => repo.password_hash(user_data.user_id).await?,
+            None => None,
         };
 
-        let mut user: User = user_data.into();
+        // Always run a full verification, against a dummy hash if needed,
+        // so unknown emails take as long as wrong passwords.
+        let verified = match &password_hash {
+            Some(password_hash) =>
+                Self::verify_password(password.as_str(), password_hash.as_str()).is_ok(),
+            None => {
+                let _ = Self::verify_password(password.as_str(), DUMMY_HASH.as_str());
+                false
+            }
+        };
 
-        let token = AuthUser { user_id: user.user_id }.gen_jwt(&ctx);
-        user.set_token(token);
+        let user_data = match user_data {
+            Some(user_data) if verified => user_data,
+            user_data => {
+                ctx.login_guard.record_failure(&ip_key);
+
+                if let Some(lockout) = ctx.login_guard.record_failure(&account_key) {
+                    if let Some(user_data) = user_data {
+                        tracing::warn!("Locking account: user_id: {} for {} seconds",
+                            user_data.user_id, lockout);
+
+                        // The failure is still reported as such if the notification cannot be stored.
+                        if let Err(error) = Helper::notify(repo, user_data.user_id, format!(
+                            "Your account was locked for {} seconds after repeated failed sign-in attempts.",
+                            lockout,
+                        )).await {
+                            tracing::error!("Failed to notify locked account: user_id: {}: {}",
+                                user_data.user_id, error);
+                        }
+                    }
+                }
+
+                return Err(EError::Unauthorized(
+                    ErrorCode::InvalidCredentials, String::from("Invalid email or password"),
+                ));
+            }
+        };
 
-        Ok(Json::from(UserBody { user }))
+        ctx.login_guard.record_success(&account_key);
+
+        Ok(Json::from(AuthService::finish_login(&ctx, user_data, "password").await?))
     }
 
 
     // Function to update a user's details.
-    // It takes the Prisma client, an authenticated user, the application context, and the new user data as parameters.
+    // It takes an authenticated user, the application context, and the new user data as parameters.
     // It returns a `Result` with a JSON response containing the updated user's details or an error.
     pub async fn update_user(
-        prisma: PRISMA,
         auth_user: AuthUser,
         ctx: State<BeContext>,
-        Json(input): Json<UserBody<UpdateUserPost>>,
+        ValidJson(input): ValidJson<UserBody<UpdateUserPost>>,
     ) -> Result<Json<UserBody<User>>, EError> {
 
         tracing::info!("Updating user: user_id: {}", auth_user.user_id);
@@ -113,63 +160,37 @@ impl UsersService {
                 avatar,
                 username,
                 password,
+                private,
             },
         } = input;
 
-        let user_data = prisma
-            .user_details().find_unique(user_details::user_id::equals(auth_user.user_id))
-            .exec().await?;
+        let repo = &*ctx.repository;
+        let user_data = Helper::get_user_by_id(repo, auth_user.user_id).await?;
 
-        let user_data = match user_data {
-            Some(user_data) => user_data,
-            None => return Err(EError::NotFound(String::from("User not found"))),
-        };
+        let old_username = user_data.username.clone();
+
+        let user_data = repo
+            .update_user(auth_user.user_id, UserUpdate { email, username, intro, avatar, private })
+            .await?;
+
+        // Users going public have no one left to approve, so everyone waiting follows them.
+        if private == Some(false) {
+            for request in repo.follow_requests(auth_user.user_id).await? {
+                Self::accept_request(&ctx, &request.user, &user_data).await?;
+            }
+        }
 
-        let user_data = prisma
-            .user_details()
-            .update(
-                user_details::user_id::equals(auth_user.user_id),
-                vec![
-                    match intro {
-                        Some(intro) => user_details::intro::set(Some(intro)),
-                        None => user_details::intro::set(user_data.intro),
-                    },
-                    match avatar {
-                        Some(avatar) => user_details::avatar::set(Some(avatar)),
-                        None => user_details::avatar::set(user_data.avatar),
-                    },
-                    match email {
-                        Some(email) => user_details::email::set(email),
-                        None => user_details::email::set(user_data.email),
-                    },
-                    match username {
-                        Some(username) => user_details::username::set(username),
-                        None => user_details::username::set(user_data.username),
-                    },
-                ],
-            )
-            .exec().await?;
+        // The cached profile and posts show the old details, under the old username.
+        Helper::invalidate_user(&ctx.cache, repo, auth_user.user_id, &old_username).await?;
 
         if let Some(password) = password {
-            let password_data = prisma
-                .user_password().find_unique(user_password::user_id::equals(auth_user.user_id))
-                .exec().await?.unwrap();
-
-            let _ = prisma
-                .user_password()
-                .update(
-                    user_password::user_id::equals(password_data.user_id),
-                    vec![
-                        user_password::hash_password::set(
-                            Self::hash_password(password.as_str()).unwrap(),
-                        )
-                    ]
-                ).exec().await?;
+            // Users who signed up through a login provider have no password yet.
+            repo.set_password_hash(auth_user.user_id, Self::hash_password(password.as_str())?).await?;
         }
 
-        let mut user: User = user_data.into();
+        let token = AuthService::issue_token(&ctx, &user_data).await?;
 
-        let token = AuthUser { user_id: user.user_id }.gen_jwt(&ctx);
+        let mut user: User = user_data.into();
         user.set_token(token);
 
         Ok(Json::from(UserBody { user }))
@@ -177,12 +198,11 @@ impl UsersService {
 
 
     // Function to create a new user.
-    // It takes the Prisma client, the application context, and the new user data as parameters.
+    // It takes the application context and the new user data as parameters.
     // It returns a `Result` with a JSON response containing the created user's details or an error.
     pub async fn create_user(
-        prisma: PRISMA,
         ctx: State<BeContext>,
-        Json(input): Json<UserBody<CreateUserPost>>,
+        ValidJson(input): ValidJson<UserBody<CreateUserPost>>,
     ) -> Result<Json<UserBody<User>>, EError> {
 
         let UserBody {
@@ -194,20 +214,13 @@ impl UsersService {
 
         tracing::info!("Creating user: email: {}", email);
 
-        let user_data = prisma
-            .user_details()
-            .create(
-                email, username, vec![],
-            ).exec().await?;
+        let user_data = ctx.repository
+            .create_user(email, username, Self::hash_password(password.as_str())?)
+            .await?;
 
-        let _ = prisma.user_password()
-            .create(
-            Self::hash_password(password.as_str()).unwrap(),
-            user_details::user_id::equals(user_data.user_id),
-            vec![]
-            ).exec().await?;
+        metrics::record(Event::Signup("password"));
 
-        let token = AuthUser { user_id: user_data.user_id }.gen_jwt(&ctx);
+        let token = AuthService::issue_token(&ctx, &user_data).await?;
 
         let mut user: User = user_data.into();
         user.set_token(token);
@@ -217,82 +230,230 @@ impl UsersService {
 
 
     // Function to delete a user.
-    // It takes the Prisma client and an authenticated user as parameters.
+    // It takes an authenticated user and the application context as parameters.
     // It returns a `Result` with a JSON response containing the deleted user's details or an error.
     pub async fn delete_user(
-        prisma: PRISMA,
         auth_user: AuthUser,
+        ctx: State<BeContext>,
     ) -> Result<String, EError> {
 
-        let user_data = prisma
-            .user_details().find_unique(user_details::user_id::equals(auth_user.user_id))
-            .exec().await?;
+        let user_data = Helper::get_user_by_id(&*ctx.repository, auth_user.user_id).await?;
 
-        let _ = match user_data {
-            Some(user_data) => user_data,
-            None => return Err(EError::NotFound(String::from("User not found"))),
-        };
-
-        tracing::info!("Deleting user: user_id: {}", auth_user.user_id);
-
-        // Delete user's follows
-        let _ = prisma
-            .user_follows()
-            .delete_many(vec![user_follows::follower_id::equals(auth_user.user_id)])
-            .exec().await?;
-
-        // Delete user's blocks
-        let _ = prisma
-            .user_blocks()
-            .delete_many(vec![user_blocks::blocker_id::equals(auth_user.user_id)])
-            .exec().await?;
-
-        // Delete user's comments
-        let _ = prisma
-            .post_comments()
-            .delete_many(vec![post_comments::user_id::equals(auth_user.user_id)])
-            .exec().await?;
-
-        // Delete user's likes
-        let _ = prisma
-            .user_like_posts()
-            .delete_many(vec![user_like_posts::user_id::equals(auth_user.user_id)])
-            .exec().await?;
-
-        // Delete user's history
-        let _ = prisma
-            .user_history()
-            .delete_many(vec![user_history::user_id::equals(auth_user.user_id)])
-            .exec().await?;
-
-        // Delete user's posts
-        let _ = prisma
-            .platform_posts()
-            .delete_many(vec![platform_posts::author_id::equals(auth_user.user_id)])
-            .exec().await?;
-
-        // Delete user's password
-        let _ = prisma
-            .user_password()
-            .delete(user_password::user_id::equals(auth_user.user_id))
-            .exec().await?;
-
-        // Delete user
-        let _ = prisma
-            .user_details()
-            .delete(user_details::user_id::equals(auth_user.user_id))
-            .exec().await?;
+        Self::delete_account(&*ctx.repository, &ctx.cache, user_data.user_id, &user_data.username).await?;
 
         Ok("User deleted".to_string())
     }
 
 
+    // Function to delete a user with everything they created, followed, blocked, liked or viewed.
+    // It takes the repository, the cache, the user's ID and their username as parameters.
+    // It is shared by `delete_user` and the `wforum-admin` command line.
+    pub async fn delete_account<R: Repository + ?Sized>(
+        repo: &R,
+        cache: &Cache,
+        user_id: i32,
+        username: &str,
+    ) -> Result<(), EError> {
+
+        // Dropping the cached profile and posts while the posts can still be listed.
+        Helper::invalidate_user(cache, repo, user_id, username).await?;
+
+        // The profiles they follow, or are followed by, are cached with their old counts.
+        let (followers, _) = repo.followers(user_id, 0, i64::MAX).await?;
+        let (following, _) = repo.following(user_id, 0, i64::MAX).await?;
+        for user in followers.iter().chain(&following) {
+            cache.invalidate(&cache::profile_key(&user.username)).await;
+        }
+
+        tracing::info!("Deleting user: user_id: {}", user_id);
+
+        // The posts they liked are cached with their old like counts.
+        for post_id in repo.delete_user(user_id).await? {
+            cache.invalidate(&cache::post_key(post_id)).await;
+        }
+
+        Ok(())
+    }
+
+
+    // Function to fetch the current user's notifications.
+    // It takes the application context and an authenticated user as parameters.
+    // It returns a `Result` with a JSON response containing the user's notifications or an error.
+    pub async fn fetch_notifications(
+        ctx: State<BeContext>,
+        auth_user: AuthUser,
+    ) -> Result<Json<NotificationsBody<Notification>>, EError> {
+
+        tracing::info!("Fetching notifications: user_id: {}", auth_user.user_id);
+
+        let notifications = ctx.repository
+            .notifications(auth_user.user_id).await?
+            .into_iter()
+            .map(Notification::from)
+            .collect();
+
+        Ok(Json::from(NotificationsBody { notifications }))
+    }
+
+
+    // Function to fetch the profiles the current user mutes, leaving out expired mutes.
+    // It takes the application context and an authenticated user as parameters.
+    // It returns a `Result` with a JSON response containing the mutes, latest first, or an error.
+    pub async fn fetch_mutes(
+        ctx: State<BeContext>,
+        auth_user: AuthUser,
+    ) -> Result<Json<MutesBody<Mute>>, EError> {
+
+        tracing::info!("Fetching mutes: user_id: {}", auth_user.user_id);
+
+        let repo = &*ctx.repository;
+
+        let mut mutes = Vec::new();
+        for mute in repo.mutes(auth_user.user_id).await? {
+            mutes.push(Mute {
+                profile: Helper::get_profile(repo, mute.user, auth_user.user_id).await?,
+                expires_at: mute.expires_at,
+                created_at: mute.created_at,
+            });
+        }
+
+        Ok(Json::from(MutesBody { mutes }))
+    }
+
+
+    // Function to fetch the profiles the current user blocks.
+    // It takes the application context, an authenticated user and the query parameters as parameters.
+    // It returns a `Result` with a JSON response containing a page of the blocked profiles, by username, or an error.
+    pub async fn fetch_blocks(
+        ctx: State<BeContext>,
+        auth_user: AuthUser,
+        ValidQuery(query): ValidQuery<ListProfileQuery>,
+    ) -> Result<Json<ProfilesBody<Profile>>, EError> {
+
+        tracing::info!("Fetching blocks: user_id: {}", auth_user.user_id);
+
+        let repo = &*ctx.repository;
+
+        let (users, profile_count) = repo.blocked_users(
+            auth_user.user_id, query.offset.unwrap_or(0), query.limit.unwrap_or(20),
+        ).await?;
+
+        Ok(Json::from(ProfilesBody {
+            profiles: Helper::get_profiles(repo, users, Some(auth_user.user_id)).await?,
+            profile_count,
+        }))
+    }
+
+
+    // Function to fetch the profiles asking to follow the current user.
+    // It takes the application context and an authenticated user as parameters.
+    // It returns a `Result` with a JSON response containing the follow requests, latest first, or an error.
+    pub async fn fetch_follow_requests(
+        ctx: State<BeContext>,
+        auth_user: AuthUser,
+    ) -> Result<Json<FollowRequestsBody<FollowRequest>>, EError> {
+
+        tracing::info!("Fetching follow requests: user_id: {}", auth_user.user_id);
+
+        let repo = &*ctx.repository;
+
+        let mut requests = Vec::new();
+        for request in repo.follow_requests(auth_user.user_id).await? {
+            requests.push(FollowRequest {
+                profile: Helper::get_profile(repo, request.user, auth_user.user_id).await?,
+                created_at: request.created_at,
+            });
+        }
+
+        Ok(Json::from(FollowRequestsBody { requests }))
+    }
+
+
+    // Function to accept a request to follow the current user.
+    // It takes the application context, an authenticated user and the requester's username as parameters.
+    // It returns a `Result` with a JSON response containing the profile now following the current user, or an error.
+    pub async fn accept_follow_request(
+        ctx: State<BeContext>,
+        Username(username): Username,
+        auth_user: AuthUser,
+    ) -> Result<Json<ProfileBody<Profile>>, EError> {
+
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+        let requester = Self::get_requester(&ctx, &username, auth_user.user_id).await?;
+
+        tracing::info!("Accepting follow request: username: {} to {}", requester.username, current_user.username);
+
+        Self::accept_request(&ctx, &requester, &current_user).await?;
+
+        Ok(Json::from(ProfileBody {
+            profile: Helper::get_profile(repo, requester, auth_user.user_id).await?,
+        }))
+    }
+
+
+    // Function to reject a request to follow the current user.
+    // It takes the application context, an authenticated user and the requester's username as parameters.
+    // It returns a `Result` with a JSON response containing the rejected profile's details or an error;
+    // the requester is not told.
+    pub async fn reject_follow_request(
+        ctx: State<BeContext>,
+        Username(username): Username,
+        auth_user: AuthUser,
+    ) -> Result<Json<ProfileBody<Profile>>, EError> {
+
+        let repo = &*ctx.repository;
+        let current_user = Helper::get_user_by_id(repo, auth_user.user_id).await?;
+        let requester = Self::get_requester(&ctx, &username, auth_user.user_id).await?;
+
+        tracing::info!("Rejecting follow request: username: {} to {}", requester.username, current_user.username);
+
+        repo.remove_request(requester.user_id, auth_user.user_id).await?;
+
+        Ok(Json::from(ProfileBody {
+            profile: Helper::get_profile(repo, requester, auth_user.user_id).await?,
+        }))
+    }
+
+
     // Utility functions for the `UsersService` struct.
 
+    // Function to get a user asking to follow the current user.
+    // It takes the application context, the requester's username and the current user's ID as parameters.
+    // It returns a `Result` with the requester, or an error if they do not exist or did not ask.
+    async fn get_requester(ctx: &BeContext, username: &str, user_id: i32) -> Result<UserRecord, EError> {
+        let repo = &*ctx.repository;
+        let requester = Helper::get_user_by_name(repo, username).await?;
+
+        if !Checker::check_requested(repo, requester.user_id, user_id).await? {
+            return Err(EError::BadRequest(
+                ErrorCode::NotRequested, String::from("This user did not ask to follow you"),
+            ));
+        }
+
+        Ok(requester)
+    }
+
+    // Function to turn a follow request into a follow and tell the requester.
+    // It takes the application context, the requester and the requested user as parameters.
+    // It returns an empty `Result` or an error.
+    async fn accept_request(ctx: &BeContext, requester: &UserRecord, user: &UserRecord) -> Result<(), EError> {
+        let repo = &*ctx.repository;
+
+        repo.remove_request(requester.user_id, user.user_id).await?;
+        repo.follow(requester.user_id, user.user_id).await?;
+
+        // Both cached profiles count their follows.
+        ctx.cache.invalidate(&cache::profile_key(&requester.username)).await;
+        ctx.cache.invalidate(&cache::profile_key(&user.username)).await;
+
+        Helper::notify(repo, requester.user_id, format!("{} accepted your follow request", user.username)).await
+    }
+
     // Function to hash a password.
     // It takes a password as a parameter.
     // It returns a `Result` with a `String` containing the hashed password or an error.
-    fn hash_password(password: &str) -> anyhow::Result<String> {
+    pub fn hash_password(password: &str) -> anyhow::Result<String> {
         let salt = SaltString::generate(&mut OsRng);
 
         // Argon2 with default params (Argon2id v19)
@@ -310,7 +471,7 @@ impl UsersService {
     // Function to verify a password against a hashed password.
     // It takes a password and a hashed password as parameters.
     // It returns a `Result` indicating whether the password is valid or an error.
-    fn verify_password(password: &str, password_hash: &str) -> anyhow::Result<()> {
+    pub(crate) fn verify_password(password: &str, password_hash: &str) -> anyhow::Result<()> {
         let argon2 = Argon2::default();
         // Parse password hash from PHC string
         let password_hash = PasswordHash::new(password_hash)
@@ -1,11 +1,6 @@
 // Importing the necessary modules and functions.
-use crate::error::EError;
-use crate::prisma::prisma;
-use crate::prisma::prisma::{PrismaClient, user_blocks, user_follows, user_like_posts};
-
-
-// Type alias for the Prisma client.
-type PRISMA = axum::Extension<std::sync::Arc<PrismaClient>>;
+use crate::error::{EError, ErrorCode};
+use crate::repository::{PostRecord, Repository, UserRecord};
 
 
 // The `Checker` struct.
@@ -17,45 +12,99 @@ pub struct Checker;
 impl Checker {
 
     // Function to check if a user is following another user.
-    // It takes the Prisma client, the ID of the follower and the ID of the followed user as parameters.
+    // It takes the repository, the ID of the follower and the ID of the followed user as parameters.
     // It returns a `Result` with a `bool` indicating whether the user is following the other user or not.
-    pub async fn check_following(
-        prisma: &PRISMA,
+    pub async fn check_following<R: Repository + ?Sized>(
+        repo: &R,
         follower_id: i32,
         followed_id: i32,
     ) -> Result<bool, EError> {
 
-        // Query the database to find a follow relationship between the two users.
-        let following = prisma
-            .user_follows()
-            .find_unique(user_follows::follower_id_followed_id(
-                follower_id, followed_id,
-            ))
-            .exec().await?;
-
-        // Return `true` if the follow relationship exists, `false` otherwise.
-        Ok(following.is_some())
+        // Look for a follow relationship between the two users.
+        repo.is_following(follower_id, followed_id).await
     }
 
     // Function to check if a user has blocked another user.
-    // It takes the Prisma client, the ID of the blocker and the ID of the blocked user as parameters.
+    // It takes the repository, the ID of the blocker and the ID of the blocked user as parameters.
     // It returns a `Result` with a `bool` indicating whether the user has blocked the other user or not.
-    pub async fn check_blocked(
-        prisma: &PRISMA,
+    pub async fn check_blocked<R: Repository + ?Sized>(
+        repo: &R,
         blocker_id: i32,
         blocked_id: i32,
     ) -> Result<bool, EError> {
 
-        // Query the database to find a block relationship between the two users.
-        let blocked = prisma
-            .user_blocks()
-            .find_unique(user_blocks::blocker_id_blocked_id(
-                blocker_id,blocked_id
-            ))
-            .exec().await?;
+        // Look for a block relationship between the two users.
+        repo.is_blocked(blocker_id, blocked_id).await
+    }
+
+    // Function to check if a user has muted another user.
+    // It takes the repository, the ID of the muter and the ID of the muted user as parameters.
+    // It returns a `Result` with a `bool` indicating whether the user has muted the other user or not.
+    pub async fn check_muted<R: Repository + ?Sized>(
+        repo: &R,
+        muter_id: i32,
+        muted_id: i32,
+    ) -> Result<bool, EError> {
+
+        // Look for a mute relationship between the two users which has not expired.
+        repo.is_muted(muter_id, muted_id).await
+    }
+
+    // Function to check if a user has asked to follow another user.
+    // It takes the repository, the ID of the requester and the ID of the requested user as parameters.
+    // It returns a `Result` with a `bool` indicating whether the user is waiting to follow the other user or not.
+    pub async fn check_requested<R: Repository + ?Sized>(
+        repo: &R,
+        requester_id: i32,
+        requested_id: i32,
+    ) -> Result<bool, EError> {
+
+        // Look for a pending follow request between the two users.
+        repo.is_requested(requester_id, requested_id).await
+    }
+
+    // Function to check if a user may read what another user wrote.
+    // It takes the repository, the author and the ID of the reader, if logged in, as parameters.
+    // It returns a `Result` with `true` if the author is public, is the reader or is followed by the reader,
+    // or an error if the author is private.
+    pub async fn check_readable<R: Repository + ?Sized>(
+        repo: &R,
+        author: &UserRecord,
+        reader_id: Option<i32>,
+    ) -> Result<bool, EError> {
+
+        if !author.private {
+            return Ok(true);
+        }
+
+        if let Some(reader_id) = reader_id {
+            if reader_id == author.user_id || repo.is_following(reader_id, author.user_id).await? {
+                return Ok(true);
+            }
+        }
+
+        // If the author is private and not followed by the reader, return an error.
+        Err(EError::Forbidden(ErrorCode::PrivateProfile, String::from(
+            "Only approved followers can see this user's posts",
+        )))
+    }
+
+    // Function to check if a user may still use their account, which suspended users may not.
+    // It takes a reference to the user as a parameter.
+    // It returns a `Result` with `true` if the user is not suspended, or an error if they are.
+    pub async fn check_active(
+        user: &UserRecord,
+    ) -> Result<bool, EError> {
+
+        if user.suspended_at.is_none() {
+            return Ok(true);
+        }
 
-        // Return `true` if the block relationship exists, `false` otherwise.
-        Ok(blocked.is_some())
+        // If the user is suspended, return an error.
+        tracing::info!("Refusing suspended user: user_id: {}", user.user_id);
+        Err(EError::Forbidden(ErrorCode::AccountSuspended, String::from(
+            "This account is suspended",
+        )))
     }
 
     // Function to check if a user is the author of an article.
@@ -63,7 +112,7 @@ impl Checker {
     // It returns a `Result` with a `bool` indicating whether the user is the author of the article or not.
     pub async fn check_author(
         user_id: i32,
-        article: &prisma::platform_posts::Data,
+        article: &PostRecord,
     ) -> Result<bool, EError> {
 
         // If the user is the author of the article, return `true`.
@@ -72,30 +121,21 @@ impl Checker {
         }
 
         // If the user is not the author of the article, return an error.
-        Err(EError::BadRequest(String::from(
+        Err(EError::BadRequest(ErrorCode::NotPostAuthor, String::from(
             "You are not the author of this article",
         )))
     }
 
     // Function to check if a user has liked an article.
-    // It takes the Prisma client, the ID of the reader and the ID of the article as parameters.
+    // It takes the repository, the ID of the reader and the ID of the article as parameters.
     // It returns a `Result` with a `bool` indicating whether the user has liked the article or not.
-    pub async fn check_liked(
-        prisma: &PRISMA,
+    pub async fn check_liked<R: Repository + ?Sized>(
+        repo: &R,
         reader_id: i32,
         article_id: i32,
     ) -> Result<bool, EError> {
 
-        // Query the database to find a like relationship between the user and the article.
-        let data = prisma
-            .user_like_posts()
-            .find_unique(
-                user_like_posts::UniqueWhereParam::UserIdPostIdEquals(
-                    reader_id, article_id,
-                ))
-            .exec().await?;
-
-        // Return `true` if the like relationship exists, `false` otherwise.
-        Ok(data.is_some())
+        // Look for a like relationship between the user and the article.
+        repo.is_liked(reader_id, article_id).await
     }
 }
\ No newline at end of file
@@ -0,0 +1,153 @@
+// Importing the necessary modules and functions.
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use axum::http::HeaderMap;
+use prisma_client_rust::chrono;
+
+use crate::config::config::LoginConfig;
+use crate::error::EError;
+
+
+// The `GuardKey` enum which represents what a failed login is counted against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GuardKey {
+    // Failures counted against an account, keyed by normalized email.
+    Account(String),
+    // Failures counted against a client IP address.
+    Ip(String),
+    // Failures counted against a user's two-factor codes.
+    TwoFactor(i32),
+}
+
+// Implementation of the `GuardKey` enum.
+impl GuardKey {
+    // Function to get the key of the client a request comes from.
+    // It takes the request's headers, the header a trusted reverse proxy puts the client's address in, if any,
+    // and the address of the peer as parameters.
+    // Behind a proxy every connection comes from the proxy, so the last address of the header, the one the proxy
+    // appended, is used instead; without a configured header it is ignored, since clients could forge it.
+    pub fn client(headers: &HeaderMap, client_ip_header: Option<&str>, peer: Option<SocketAddr>) -> Self {
+        let forwarded = client_ip_header
+            .and_then(|name| headers.get(name))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|address| address.trim().parse::<IpAddr>().ok());
+
+        GuardKey::Ip(match (forwarded, peer) {
+            (Some(address), _) => address.to_string(),
+            (None, Some(peer)) => peer.ip().to_string(),
+            (None, None) => String::from("unknown"),
+        })
+    }
+}
+
+// The `FailureRecord` struct which represents the failure state of a single key.
+#[derive(Debug, Clone, Default)]
+struct FailureRecord {
+    // The number of consecutive failures.
+    failures: u32,
+    // The timestamp until which the key is locked.
+    locked_until: i64,
+    // The timestamp of the last failure.
+    last_failure: i64,
+}
+
+// The `LoginGuard` struct.
+// This struct tracks failed login attempts per account and per IP address,
+// and locks keys out with an exponential backoff once their allowance is used up.
+#[derive(Debug)]
+pub struct LoginGuard {
+    // The configuration for login protection.
+    config: LoginConfig,
+    // The failure records for every tracked key.
+    records: Mutex<HashMap<GuardKey, FailureRecord>>,
+}
+
+
+// Implementation of the `LoginGuard` struct.
+impl LoginGuard {
+
+    // Function to create a new `LoginGuard`.
+    pub fn new(config: LoginConfig) -> Self {
+        Self {
+            config,
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Function to check whether any of the given keys is locked.
+    // It returns a `TooManyRequests` error with the longest remaining lockout if so.
+    pub fn check(&self, keys: &[GuardKey]) -> Result<(), EError> {
+
+        let now = chrono::Utc::now().timestamp();
+        let records = self.records.lock().unwrap();
+
+        let remaining = keys
+            .iter()
+            .filter_map(|key| records.get(key))
+            .map(|record| record.locked_until - now)
+            .max()
+            .unwrap_or(0);
+
+        if remaining > 0 {
+            return Err(EError::TooManyRequests(remaining));
+        }
+
+        Ok(())
+    }
+
+    // Function to record a failed login attempt for a key.
+    // It returns the lockout duration in seconds if this failure started a new lockout of the key, once the
+    // allowance is used up and no lockout is running; failures racing a running lockout extend it without
+    // returning it, so the owner is told once per lockout.
+    pub fn record_failure(&self, key: &GuardKey) -> Option<i64> {
+
+        let now = chrono::Utc::now().timestamp();
+        let allowance = self.allowance(key);
+        let mut records = self.records.lock().unwrap();
+
+        let record = records.entry(key.clone()).or_default();
+        let locked = record.locked_until > now;
+        record.failures += 1;
+        record.last_failure = now;
+
+        if record.failures < allowance {
+            return None;
+        }
+
+        // Double the lockout for every failure past the allowance, up to the maximum.
+        let exponent = (record.failures - allowance).min(32);
+        let lockout = self.config.lockout_base
+            .saturating_mul(1_i64 << exponent)
+            .min(self.config.lockout_max);
+
+        record.locked_until = now + lockout;
+
+        (!locked).then_some(lockout)
+    }
+
+    // Function to clear the failure record for a key after a successful login.
+    pub fn record_success(&self, key: &GuardKey) {
+        self.records.lock().unwrap().remove(key);
+    }
+
+    // Function to drop records which are no longer locked and have been idle for the maximum lockout.
+    pub fn prune(&self) {
+
+        let now = chrono::Utc::now().timestamp();
+        let idle = self.config.lockout_max;
+
+        self.records.lock().unwrap().retain(|_, record| {
+            record.locked_until > now || record.last_failure + idle > now
+        });
+    }
+
+    // Function to get the number of failures allowed for a key before lockout.
+    fn allowance(&self, key: &GuardKey) -> u32 {
+        match key {
+            GuardKey::Account(_) | GuardKey::TwoFactor(_) => self.config.max_account_attempts,
+            GuardKey::Ip(_) => self.config.max_ip_attempts,
+        }
+    }
+}
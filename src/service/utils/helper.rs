@@ -1,119 +1,269 @@
 // Importing the necessary modules and functions.
-use crate::error::EError;
+use std::collections::HashSet;
+
+use crate::cache::{self, Cache};
+use crate::error::{EError, ErrorCode};
+use crate::repository::{PostRecord, Repository, UserRecord};
 use crate::service::post::model::Post;
+use crate::service::profile::model::Profile;
 use crate::service::utils::checker::Checker;
-use crate::prisma::prisma::{platform_posts, user_details, PrismaClient};
-
-
-// Type alias for the Prisma client.
-type PRISMA = axum::Extension<std::sync::Arc<PrismaClient>>;
 
 
 // The `Helper` struct.
 // This struct contains helper methods used throughout the application.
 pub struct Helper;
 
+// The `Relations` struct which represents how a viewer relates to every other user,
+// fetched once so that a list of profiles doesn't query the repository for each of them.
+pub struct Relations {
+    // The IDs of the users following the viewer.
+    followers: HashSet<i32>,
+    // The IDs of the users the viewer follows.
+    followed: HashSet<i32>,
+    // The IDs of the users blocking the viewer.
+    blockers: HashSet<i32>,
+    // The IDs of the users the viewer blocks.
+    blocked: HashSet<i32>,
+    // The IDs of the users asking to follow the viewer.
+    requesters: HashSet<i32>,
+    // The IDs of the users the viewer asked to follow.
+    requested: HashSet<i32>,
+}
+
 // Implementation of the `Helper` struct.
 impl Helper {
 
     // Function to convert a value to seconds based on the provided unit.
-    // It takes a value and a unit as parameters and returns the value in seconds.
-    pub fn value_to_seconds(value: i64, unit: String) -> i64 {
-
-        match unit.as_str() {
-            "seconds" => value,
-            "minutes" => value * 60,
-            "hours" => value * 3600,
-            "days" => value * 86400,
-            "weeks" => value * 604800,
-            "months" => value * 2592000,
-            "years" => value * 31536000,
-            _ => panic!("Invalid unit"),
-        }
+    // It takes a value and a unit as parameters and returns the value in seconds, or `None` for an unknown unit.
+    pub fn value_to_seconds(value: i64, unit: &str) -> Option<i64> {
+
+        let seconds = match unit {
+            "seconds" => 1,
+            "minutes" => 60,
+            "hours" => 3600,
+            "days" => 86400,
+            "weeks" => 604800,
+            "months" => 2592000,
+            "years" => 31536000,
+            _ => return None,
+        };
+
+        value.checked_mul(seconds)
     }
 
     // Function to get a user by their ID.
-    // It takes the Prisma client and the user's ID as parameters.
+    // It takes the repository and the user's ID as parameters.
     // It returns a `Result` with the user's details or an error.
-    pub async fn get_user_by_id(
-        prisma: &PRISMA,
+    pub async fn get_user_by_id<R: Repository + ?Sized>(
+        repo: &R,
         user_id: i32,
-    ) -> Result<user_details::Data, EError> {
+    ) -> Result<UserRecord, EError> {
 
-        let data = prisma
-            .user_details()
-            .find_unique(user_details::user_id::equals(user_id))
-            .exec().await?;
-
-        match data {
+        match repo.find_user(user_id).await? {
             Some(data) => Ok(data),
-            None => Err(EError::NotFound(String::from("User not found"))),
+            None => Err(EError::NotFound(ErrorCode::UserNotFound, String::from("User not found"))),
         }
     }
 
     // Function to get a user by their username.
-    // It takes the Prisma client and the username as parameters.
+    // It takes the repository and the username as parameters.
     // It returns a `Result` with the user's details or an error.
-    pub async fn get_user_by_name(
-        prisma: &PRISMA,
-        username: String,
-    ) -> Result<user_details::Data, EError> {
-
-        let data = prisma
-            .user_details()
-            .find_unique(user_details::username::equals(username))
-            .exec().await?;
+    pub async fn get_user_by_name<R: Repository + ?Sized>(
+        repo: &R,
+        username: &str,
+    ) -> Result<UserRecord, EError> {
 
-        match data {
+        match repo.find_user_by_name(username).await? {
             Some(data) => Ok(data),
-            None => Err(EError::NotFound(String::from("User not found"))),
+            None => Err(EError::NotFound(ErrorCode::UserNotFound, String::from("User not found"))),
         }
     }
 
     // Function to fetch a post by its ID.
-    // It takes the Prisma client and the post's ID as parameters.
+    // It takes the repository and the post's ID as parameters.
     // It returns a `Result` with the post's details or an error.
-    pub async fn fetch_post(
-        prisma: &PRISMA,
-        post_id: String,
-    ) -> Result<platform_posts::Data, EError> {
+    pub async fn fetch_post<R: Repository + ?Sized>(
+        repo: &R,
+        post_id: i32,
+    ) -> Result<PostRecord, EError> {
 
-        let post_id: i32 = post_id.parse()
-            .map_err(|_| EError::BadRequest(String::from("Invalid post id")))?;
+        match repo.find_post(post_id).await? {
+            Some(data) => Ok(data),
+            None => Err(EError::NotFound(ErrorCode::PostNotFound, String::from("Post not found"))),
+        }
+    }
 
-        let data = prisma
-            .platform_posts()
-            .find_unique(platform_posts::post_id::equals(post_id))
-            .with(platform_posts::author::fetch())
-            .exec().await?;
+    // Function to drop the cached profile of a user and the cached posts showing it as their author.
+    // It takes the cache, the repository, the user's ID and their username before the change as parameters.
+    pub async fn invalidate_user<R: Repository + ?Sized>(
+        cache: &Cache,
+        repo: &R,
+        user_id: i32,
+        username: &str,
+    ) -> Result<(), EError> {
 
-        match data {
-            Some(data) => Ok(data),
-            None => Err(EError::NotFound(String::from("Post not found"))),
+        cache.invalidate(&cache::profile_key(username)).await;
+
+        for post_id in repo.post_ids_by_author(user_id).await? {
+            cache.invalidate(&cache::post_key(post_id)).await;
         }
+
+        Ok(())
     }
 
     // Function to add a post to a vector of posts.
-    // It takes the Prisma client, a mutable reference to a vector of posts, a reference to a post and a user ID as parameters.
+    // It takes the repository, a mutable reference to a vector of posts, a reference to a post and a user ID as parameters.
     // It checks if the user has liked the post and if the user is following the author of the post, then adds the post to the vector.
-    pub async fn push_post(
-        prisma: &PRISMA,
+    pub async fn push_post<R: Repository + ?Sized>(
+        repo: &R,
         posts: &mut Vec<Post>,
-        post: &platform_posts::Data,
+        post: &PostRecord,
         user_id: i32,
     ) -> Result<(), EError> {
 
-        let like = Checker::check_liked(&prisma, user_id, post.post_id).await?;
+        let like = Checker::check_liked(repo, user_id, post.post_id).await?;
         let followed =
-            Checker::check_following(&prisma, post.author_id, user_id,).await?;
+            Checker::check_following(repo, post.author_id, user_id,).await?;
         let following =
-            Checker::check_following(&prisma, user_id, post.author_id).await?;
+            Checker::check_following(repo, user_id, post.author_id).await?;
         let blocked =
-            Checker::check_blocked(&prisma, post.author_id, user_id,).await?;
+            Checker::check_blocked(repo, post.author_id, user_id,).await?;
         let blocking =
-            Checker::check_blocked(&prisma, user_id, post.author_id).await?;
+            Checker::check_blocked(repo, user_id, post.author_id).await?;
 
         Ok(posts.push(post.clone().to_post(like, followed, following, blocked, blocking)))
     }
 
-}
\ No newline at end of file
+    // Function to get the profile of a user as another user sees it.
+    // It takes the repository, the user and the viewer's ID as parameters.
+    // It returns a `Result` with the profile, following, requests and blocking as seen by the viewer, or an error;
+    // viewers blocked by the user only see their username.
+    pub async fn get_profile<R: Repository + ?Sized>(
+        repo: &R,
+        user: UserRecord,
+        viewer_id: i32,
+    ) -> Result<Profile, EError> {
+
+        let followed =
+            Checker::check_following(repo, user.user_id, viewer_id).await?;
+        let following =
+            Checker::check_following(repo, viewer_id, user.user_id).await?;
+        let blocked =
+            Checker::check_blocked(repo, user.user_id, viewer_id).await?;
+        let blocking =
+            Checker::check_blocked(repo, viewer_id, user.user_id).await?;
+
+        let requested =
+            Checker::check_requested(repo, user.user_id, viewer_id).await?;
+        let requesting =
+            Checker::check_requested(repo, viewer_id, user.user_id).await?;
+
+        Ok(Self::seen_profile(user, followed, following, blocked, blocking, requested, requesting))
+    }
+
+    // Function to get how a user relates to every other user.
+    // It takes the repository and the user's ID as parameters.
+    // It returns a `Result` with the relations, to build many profiles as the user sees them, or an error.
+    pub async fn get_relations<R: Repository + ?Sized>(
+        repo: &R,
+        viewer_id: i32,
+    ) -> Result<Relations, EError> {
+
+        Ok(Relations {
+            followers: repo.follower_ids(viewer_id).await?.into_iter().collect(),
+            followed: repo.followed_ids(viewer_id).await?.into_iter().collect(),
+            blockers: repo.blocker_ids(viewer_id).await?.into_iter().collect(),
+            blocked: repo.blocked_ids(viewer_id).await?.into_iter().collect(),
+            requesters: repo.requester_ids(viewer_id).await?.into_iter().collect(),
+            requested: repo.requested_ids(viewer_id).await?.into_iter().collect(),
+        })
+    }
+
+    // Function to build the profile of a user from how they and the viewer relate.
+    // It takes the user and the flags, each as the viewer sees it, as parameters.
+    // It returns the profile; viewers blocked by the user only see its username.
+    fn seen_profile(
+        user: UserRecord,
+        followed: bool,
+        following: bool,
+        blocked: bool,
+        blocking: bool,
+        requested: bool,
+        requesting: bool,
+    ) -> Profile {
+
+        let mut profile = user.to_profile(followed, following, blocked, blocking);
+        profile.requested = requested;
+        profile.requesting = requesting;
+        if blocked {
+            profile.intro = None;
+            profile.avatar = None;
+        }
+
+        profile
+    }
+
+    // Function to get the profiles of users as another user sees them.
+    // It takes the repository, the users and the viewer's ID, if any, as parameters.
+    // It returns a `Result` with the profiles in the same order, or an error.
+    pub async fn get_profiles<R: Repository + ?Sized>(
+        repo: &R,
+        users: Vec<UserRecord>,
+        viewer_id: Option<i32>,
+    ) -> Result<Vec<Profile>, EError> {
+
+        let Some(viewer_id) = viewer_id else {
+            return Ok(users.into_iter().map(Profile::from).collect());
+        };
+
+        let relations = Self::get_relations(repo, viewer_id).await?;
+
+        Ok(users.into_iter().map(|user| relations.profile(user)).collect())
+    }
+
+    // Function to get the IDs of the users a user blocks or is blocked by.
+    // It takes the repository and the user's ID as parameters.
+    // It returns a `Result` with the IDs, whose posts are left out of the user's listings, or an error.
+    pub async fn get_block_ids<R: Repository + ?Sized>(
+        repo: &R,
+        user_id: i32,
+    ) -> Result<Vec<i32>, EError> {
+
+        let mut ids = repo.blocked_ids(user_id).await?;
+        ids.extend(repo.blocker_ids(user_id).await?);
+
+        Ok(ids)
+    }
+
+    // Function to notify a user.
+    // It takes the repository, the user's ID and the message as parameters.
+    // It returns an empty `Result` or an error.
+    pub async fn notify<R: Repository + ?Sized>(
+        repo: &R,
+        user_id: i32,
+        message: String,
+    ) -> Result<(), EError> {
+
+        repo.notify(user_id, message).await
+    }
+
+}
+
+
+// Implementation of the `Relations` struct.
+impl Relations {
+    // Function to get the profile of a user as the viewer sees it.
+    pub fn profile(&self, user: UserRecord) -> Profile {
+        let user_id = user.user_id;
+
+        Helper::seen_profile(
+            user,
+            self.followers.contains(&user_id),
+            self.followed.contains(&user_id),
+            self.blockers.contains(&user_id),
+            self.blocked.contains(&user_id),
+            self.requesters.contains(&user_id),
+            self.requested.contains(&user_id),
+        )
+    }
+}
@@ -7,4 +7,8 @@ pub mod checker;
 
 // The `helper` module.
 // This module contains helper functions used for various tasks in the application.
-pub mod helper;
\ No newline at end of file
+pub mod helper;
+
+// The `guard` module.
+// This module contains the login guard used to slow down brute-force attempts.
+pub mod guard;
\ No newline at end of file
@@ -0,0 +1,103 @@
+// Importing the necessary modules and functions.
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use anyhow::Context;
+use axum::http::header::{HOST, LOCATION};
+use axum::http::uri::Authority;
+use axum::http::{HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum_server::tls_rustls::RustlsConfig;
+use tokio::sync::watch::Receiver;
+use tokio::task::JoinHandle;
+
+use crate::config::config::TlsConfig;
+
+
+// Function to load the certificate and key the backend serves HTTPS with.
+// HTTP/2 and HTTP/1.1 are both offered to clients through ALPN.
+// It returns a `Result` with the rustls configuration, or an error naming the files.
+pub async fn load(config: &TlsConfig) -> anyhow::Result<RustlsConfig> {
+    RustlsConfig::from_pem_file(&config.cert_file, &config.key_file)
+        .await
+        .with_context(|| {
+            format!("failed to load TLS certificate {} and key {}", config.cert_file, config.key_file)
+        })
+}
+
+
+// Function to reload the certificate and key whenever either file changes, until shutdown.
+// A renewed certificate is picked up by new connections without restarting the backend;
+// files which fail to load are logged and retried on the next check, the old certificate staying in use.
+// It returns the handle of the background task.
+pub fn watch(config: TlsConfig, rustls: RustlsConfig, mut shutdown: Receiver<bool>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut loaded = modified(&config);
+        let mut interval = tokio::time::interval(Duration::from_secs(config.reload_interval));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = shutdown.changed() => break,
+            }
+
+            let current = modified(&config);
+            if current == loaded {
+                continue;
+            }
+
+            match rustls.reload_from_pem_file(&config.cert_file, &config.key_file).await {
+                Ok(()) => {
+                    tracing::info!("reloaded TLS certificate {}", config.cert_file);
+                    loaded = current;
+                }
+                Err(error) => {
+                    tracing::warn!("failed to reload TLS certificate {}: {}", config.cert_file, error)
+                }
+            }
+        }
+    })
+}
+
+// Function to get when the certificate and key files were last modified, `None` for unreadable files.
+fn modified(config: &TlsConfig) -> (Option<SystemTime>, Option<SystemTime>) {
+    let modified = |path: &str| Path::new(path).metadata().and_then(|metadata| metadata.modified()).ok();
+    (modified(&config.cert_file), modified(&config.key_file))
+}
+
+
+// Function to build the router of the plain HTTP listener, which redirects every request to HTTPS.
+// It takes the port HTTPS is served on as a parameter.
+pub fn redirect_router(https_port: u16) -> axum::Router {
+    axum::Router::new().fallback(move |headers: HeaderMap, uri: Uri| async move {
+        redirect(&headers, &uri, https_port)
+    })
+}
+
+// Function to answer a plain HTTP request with a permanent redirect to the same URL over HTTPS.
+// `308` is used rather than `301` so clients repeat the method and body.
+fn redirect(headers: &HeaderMap, uri: &Uri, https_port: u16) -> Response {
+    let host = headers.get(HOST).and_then(|host| host.to_str().ok());
+
+    match host.and_then(|host| redirect_target(host, uri, https_port)) {
+        Some(target) => (StatusCode::PERMANENT_REDIRECT, [(LOCATION, target)]).into_response(),
+        None => (StatusCode::BAD_REQUEST, "missing or invalid Host header").into_response(),
+    }
+}
+
+// Function to build the HTTPS URL a plain HTTP request is redirected to.
+// It takes the `Host` header, the request URI and the port HTTPS is served on as parameters.
+// It returns the URL, or `None` if the host is invalid.
+pub fn redirect_target(host: &str, uri: &Uri, https_port: u16) -> Option<String> {
+    // The port of the plain HTTP listener is replaced by the HTTPS one, left out when it is the default.
+    let host = host.parse::<Authority>().ok()?;
+    if host.as_str().contains('@') {
+        return None;
+    }
+    let port = match https_port {
+        443 => String::new(),
+        port => format!(":{}", port),
+    };
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+
+    Some(format!("https://{}{}{}", host.host(), port, path))
+}
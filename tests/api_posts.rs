@@ -0,0 +1,188 @@
+// End-to-end tests for the routes of the `PostRouter`, against a real database.
+mod common;
+
+use serde_json::json;
+
+use common::TestApp;
+
+
+#[tokio::test]
+async fn posts_are_created_fetched_updated_and_deleted() {
+    let Some(app) = TestApp::spawn("api_posts").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+
+    let (status, body) = app.post("/posts", None, Some(json!({
+        "post": { "title": "Anonymous", "description": "", "content": "Nope" },
+    }))).await;
+    assert_eq!(status, 401, "{}", body);
+
+    let post_id = app.create_post(&alice, "First post").await;
+    let path = format!("/posts/{}", post_id);
+
+    let (status, body) = app.get(&path, None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["post"]["title"], "First post");
+    assert_eq!(body["post"]["author"]["username"], alice.username.as_str());
+
+    let (status, body) = app.put(&path, Some(&bob), json!({ "post": { "title": "Hijacked" } })).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "NOT_POST_AUTHOR");
+
+    let (status, body) = app.put(&path, Some(&alice), json!({ "post": { "title": "Edited" } })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["post"]["title"], "Edited");
+    assert_eq!(body["post"]["content"], "Hello from the tests");
+
+    assert_eq!(app.delete(&path, Some(&bob)).await.0, 400);
+    assert_eq!(app.delete(&path, Some(&alice)).await.0, 200);
+
+    let (status, body) = app.get(&path, None).await;
+    assert_eq!(status, 404);
+    assert_eq!(body["code"], "POST_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn posts_are_listed_by_author_likes_and_follows() {
+    let Some(app) = TestApp::spawn("api_posts").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+    let carol = app.signup("carol").await;
+
+    let alice_post = app.create_post(&alice, "By Alice").await;
+    let bob_post = app.create_post(&bob, "By Bob").await;
+    app.post(&format!("/posts/{}/like", bob_post), Some(&carol), None).await;
+    app.post(&format!("/profiles/{}/follow", alice.username), Some(&carol), None).await;
+
+    let titles = |body: &serde_json::Value| -> Vec<String> {
+        let posts = body["posts"].as_array().unwrap();
+        posts.iter().map(|post| post["title"].as_str().unwrap().to_string()).collect()
+    };
+
+    let (status, body) = app.get(&format!("/posts?author={}", alice.username), None).await;
+    assert_eq!(status, 200);
+    assert_eq!((titles(&body), &body["postCount"]), (vec![String::from("By Alice")], &json!(1)));
+
+    let (_, body) = app.get(&format!("/posts?likedBy={}", carol.username), None).await;
+    assert_eq!(titles(&body), ["By Bob"]);
+
+    let (_, body) = app.get("/posts?following=true", Some(&carol)).await;
+    assert_eq!(titles(&body), ["By Alice"]);
+
+    let (status, body) = app.get("/posts?following=true", None).await;
+    assert_eq!(status, 401);
+    assert_eq!(body["code"], "LOGIN_REQUIRED");
+
+    // Signed in readers see whether they like each post.
+    let (_, body) = app.get(&format!("/posts?author={}", bob.username), Some(&carol)).await;
+    assert_eq!(body["posts"][0]["liked"], true);
+    let (_, body) = app.get(&format!("/posts/{}", alice_post), Some(&carol)).await;
+    assert_eq!(body["post"]["liked"], false);
+}
+
+#[tokio::test]
+async fn reading_a_post_signed_in_records_history() {
+    let Some(app) = TestApp::spawn("api_posts").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+    let post_id = app.create_post(&alice, "Worth reading").await;
+
+    let (_, body) = app.get("/posts/history", Some(&bob)).await;
+    assert_eq!(body["postCount"], 0);
+
+    app.get(&format!("/posts/{}", post_id), None).await;
+    app.get(&format!("/posts/{}", post_id), Some(&bob)).await;
+
+    let (status, body) = app.get("/posts/history", Some(&bob)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["postCount"], 1);
+    assert_eq!(body["posts"][0]["postId"], post_id);
+    assert_eq!(body["timeVec"].as_array().unwrap().len(), 1);
+
+    assert_eq!(app.get("/posts/history", None).await.0, 401);
+}
+
+#[tokio::test]
+async fn posts_are_liked_and_unliked_once() {
+    let Some(app) = TestApp::spawn("api_posts").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+    let like = format!("/posts/{}/like", app.create_post(&alice, "Likeable").await);
+
+    let (status, body) = app.post(&like, Some(&bob), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!((&body["post"]["liked"], &body["post"]["likedCount"]), (&json!(true), &json!(1)));
+
+    let (status, body) = app.post(&like, Some(&bob), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "ALREADY_LIKED");
+
+    let (status, body) = app.delete(&like, Some(&bob)).await;
+    assert_eq!(status, 200);
+    assert_eq!((&body["post"]["liked"], &body["post"]["likedCount"]), (&json!(false), &json!(0)));
+
+    let (status, body) = app.delete(&like, Some(&bob)).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "NOT_LIKED");
+
+    assert_eq!(app.post("/posts/999999999/like", Some(&bob), None).await.0, 404);
+}
+
+#[tokio::test]
+async fn comments_are_created_listed_and_deleted() {
+    let Some(app) = TestApp::spawn("api_posts").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+    let comments = format!("/posts/{}/comments", app.create_post(&alice, "Discuss").await);
+
+    let first = json!({ "comment": { "content": "First!" } });
+    let (status, body) = app.post(&comments, Some(&bob), Some(first)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["comment"]["user"]["username"], bob.username.as_str());
+    let comment_id = body["comment"]["commentId"].as_i64().unwrap();
+
+    let (status, body) = app.post(&comments, Some(&bob), Some(json!({ "comment": { "content": "" } }))).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+
+    let (status, body) = app.get(&comments, None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["comments"][0]["content"], "First!");
+
+    let comment = format!("{}/{}", comments, comment_id);
+    let (status, body) = app.delete(&comment, Some(&alice)).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "NOT_COMMENT_AUTHOR");
+
+    assert_eq!(app.delete(&comment, Some(&bob)).await.0, 200);
+    let (status, body) = app.delete(&comment, Some(&bob)).await;
+    assert_eq!(status, 404);
+    assert_eq!(body["code"], "COMMENT_NOT_FOUND");
+
+    let (_, body) = app.get(&comments, None).await;
+    assert_eq!(body["comments"], json!([]));
+}
+
+#[tokio::test]
+async fn blocked_users_cannot_like_or_comment() {
+    let Some(app) = TestApp::spawn("api_posts").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+    let post_id = app.create_post(&alice, "Not for Bob").await;
+
+    app.post(&format!("/profiles/{}/block", bob.username), Some(&alice), None).await;
+
+    let (status, body) = app.post(&format!("/posts/{}/like", post_id), Some(&bob), None).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "BLOCKED_BY_AUTHOR");
+
+    let (status, body) = app.post(&format!("/posts/{}/comments", post_id), Some(&bob), Some(json!({
+        "comment": { "content": "Let me in" },
+    }))).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "BLOCKED_BY_AUTHOR");
+
+    // Once unblocked, Bob can take part again.
+    app.delete(&format!("/profiles/{}/block", bob.username), Some(&alice)).await;
+    assert_eq!(app.post(&format!("/posts/{}/like", post_id), Some(&bob), None).await.0, 200);
+}
@@ -0,0 +1,100 @@
+// End-to-end tests for the routes of the `ProfilesRouter`, against a real database.
+mod common;
+
+use common::TestApp;
+
+
+#[tokio::test]
+async fn profiles_are_fetched() {
+    let Some(app) = TestApp::spawn("api_profiles").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+
+    let (status, body) = app.get(&format!("/profiles/{}", alice.username), None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["profile"]["username"], alice.username.as_str());
+    assert_eq!(body["profile"]["following"], false);
+
+    let (status, body) = app.get(&format!("/profiles/{}", alice.username), Some(&bob)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["profile"]["followed"], false);
+
+    let (status, body) = app.get("/profiles/nobody_by_this_name", None).await;
+    assert_eq!(status, 404);
+    assert_eq!(body["code"], "USER_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn users_follow_and_unfollow() {
+    let Some(app) = TestApp::spawn("api_profiles").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+    let follow = format!("/profiles/{}/follow", alice.username);
+
+    assert_eq!(app.post(&follow, None, None).await.0, 401);
+
+    let (status, body) = app.post(&follow, Some(&bob), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["profile"]["following"], true);
+
+    let (_, body) = app.get(&format!("/profiles/{}", alice.username), Some(&bob)).await;
+    assert_eq!(body["profile"]["following"], true);
+    let (_, body) = app.get(&format!("/profiles/{}", bob.username), Some(&alice)).await;
+    assert_eq!(body["profile"]["followed"], true);
+
+    let (status, body) = app.post(&follow, Some(&bob), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "ALREADY_FOLLOWING");
+
+    let (status, body) = app.delete(&follow, Some(&bob)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["profile"]["following"], false);
+
+    let (status, body) = app.delete(&follow, Some(&bob)).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "NOT_FOLLOWING");
+
+    let (status, body) = app.post(&follow, Some(&alice), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "CANNOT_TARGET_SELF");
+}
+
+#[tokio::test]
+async fn blocking_removes_follows_and_prevents_new_ones() {
+    let Some(app) = TestApp::spawn("api_profiles").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+    let block = format!("/profiles/{}/block", bob.username);
+
+    app.post(&format!("/profiles/{}/follow", alice.username), Some(&bob), None).await;
+    app.post(&format!("/profiles/{}/follow", bob.username), Some(&alice), None).await;
+
+    let (status, body) = app.post(&block, Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["profile"]["blocking"], true);
+
+    // Blocking drops the follows both ways.
+    let (_, body) = app.get(&format!("/profiles/{}", bob.username), Some(&alice)).await;
+    assert_eq!((&body["profile"]["following"], &body["profile"]["followed"]), (&false.into(), &false.into()));
+
+    let (status, body) = app.post(&format!("/profiles/{}/follow", alice.username), Some(&bob), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "BLOCKED_BY_USER");
+
+    let (status, body) = app.post(&format!("/profiles/{}/follow", bob.username), Some(&alice), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "BLOCKING_USER");
+
+    let (status, body) = app.post(&block, Some(&alice), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "ALREADY_BLOCKED");
+
+    let (status, _) = app.delete(&block, Some(&alice)).await;
+    assert_eq!(status, 200);
+    let (status, body) = app.delete(&block, Some(&alice)).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "NOT_BLOCKING");
+
+    let (status, _) = app.post(&format!("/profiles/{}/follow", alice.username), Some(&bob), None).await;
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,29 @@
+// Tests for the names of personal API token scopes.
+use wforum_backend::extractor::extractor::ApiScope;
+
+
+// Every scope, in declaration order.
+const SCOPES: [ApiScope; 4] = [
+    ApiScope::Read,
+    ApiScope::WritePosts,
+    ApiScope::WriteComments,
+    ApiScope::WriteProfiles,
+];
+
+
+#[test]
+fn stored_names_round_trip() {
+    for scope in SCOPES {
+        assert_eq!(ApiScope::parse(scope.as_str()), Some(scope));
+    }
+    assert_eq!(ApiScope::parse("admin"), None);
+}
+
+#[test]
+fn json_names_match_stored_names() {
+    for scope in SCOPES {
+        let json = serde_json::to_value(scope).unwrap();
+        assert_eq!(json, scope.as_str());
+        assert_eq!(serde_json::from_value::<ApiScope>(json).unwrap(), scope);
+    }
+}
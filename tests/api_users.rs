@@ -0,0 +1,106 @@
+// End-to-end tests for the routes of the `UsersRouter`, against a real database.
+mod common;
+
+use serde_json::json;
+
+use common::{TestApp, PASSWORD};
+
+
+#[tokio::test]
+async fn users_sign_up_and_log_in() {
+    let Some(app) = TestApp::spawn("api_users").await else { return };
+    let alice = app.signup("alice").await;
+
+    let (status, body) = app.login(&alice.email, PASSWORD).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["user"]["username"], alice.username.as_str());
+    assert_eq!(body["user"]["role"], "USER");
+    assert!(body["user"]["token"].is_string());
+
+    let (status, body) = app.login(&alice.email, "wrong password").await;
+    assert_eq!(status, 401);
+    assert_eq!(body["code"], "INVALID_CREDENTIALS");
+
+    let (status, body) = app.post("/users/create", None, Some(json!({
+        "user": { "email": "another@test.example", "username": alice.username, "password": PASSWORD },
+    }))).await;
+    assert_eq!(status, 409);
+    assert_eq!(body["code"], "CONFLICT");
+
+    let (status, body) = app.post("/users/create", None, Some(json!({
+        "user": { "email": "not an email", "username": "x", "password": "" },
+    }))).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+}
+
+#[tokio::test]
+async fn the_current_user_is_returned_and_updated() {
+    let Some(app) = TestApp::spawn("api_users").await else { return };
+    let alice = app.signup("alice").await;
+
+    let (status, body) = app.get("/users", None).await;
+    assert_eq!(status, 401);
+    assert_eq!(body["code"], "MISSING_AUTHORIZATION");
+
+    let (status, body) = app.get("/users", Some(&alice)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["user"]["email"], alice.email.as_str());
+
+    let renamed = format!("{}_renamed", alice.username);
+    let (status, body) = app.put("/users", Some(&alice), json!({
+        "user": { "intro": "Hello there", "username": renamed, "password": "a new password" },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["user"]["intro"], "Hello there");
+    assert_eq!(body["user"]["username"], renamed.as_str());
+
+    assert_eq!(app.login(&alice.email, PASSWORD).await.0, 401);
+    assert_eq!(app.login(&alice.email, "a new password").await.0, 200);
+
+    let (status, body) = app.get(&format!("/profiles/{}", renamed), None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["profile"]["intro"], "Hello there");
+}
+
+#[tokio::test]
+async fn notifications_are_listed() {
+    let Some(app) = TestApp::spawn("api_users").await else { return };
+    let alice = app.signup("alice").await;
+
+    let (status, body) = app.get("/users/notifications", Some(&alice)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["notifications"], json!([]));
+    assert_eq!(app.get("/users/notifications", None).await.0, 401);
+}
+
+#[tokio::test]
+async fn deleting_a_user_removes_their_content() {
+    let Some(app) = TestApp::spawn("api_users").await else { return };
+    let alice = app.signup("alice").await;
+    let bob = app.signup("bob").await;
+
+    // Alice's posts, her comments and likes on Bob's post, and follows both ways all go with her.
+    let alice_post = app.create_post(&alice, "Alice's post").await;
+    let bob_post = app.create_post(&bob, "Bob's post").await;
+    app.post(&format!("/posts/{}/like", bob_post), Some(&alice), None).await;
+    app.post(&format!("/posts/{}/comments", bob_post), Some(&alice), Some(json!({
+        "comment": { "content": "Nice" },
+    }))).await;
+    app.post(&format!("/posts/{}/like", alice_post), Some(&bob), None).await;
+    app.post(&format!("/profiles/{}/follow", alice.username), Some(&bob), None).await;
+    app.post(&format!("/profiles/{}/follow", bob.username), Some(&alice), None).await;
+
+    let (status, _) = app.delete("/users", Some(&alice)).await;
+    assert_eq!(status, 200);
+
+    assert_eq!(app.get("/users", Some(&alice)).await.0, 404);
+    assert_eq!(app.get(&format!("/profiles/{}", alice.username), None).await.0, 404);
+    assert_eq!(app.get(&format!("/posts/{}", alice_post), None).await.0, 404);
+    assert_eq!(app.login(&alice.email, PASSWORD).await.0, 401);
+
+    let (_, body) = app.get(&format!("/posts/{}", bob_post), None).await;
+    assert_eq!(body["post"]["likedCount"], 0);
+    let (_, body) = app.get(&format!("/posts/{}/comments", bob_post), None).await;
+    assert_eq!(body["comments"], json!([]));
+}
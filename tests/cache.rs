@@ -0,0 +1,74 @@
+// Tests for the cache backends.
+// The Redis test runs against the server at `TEST_REDIS_URL`, and is skipped when it is not set.
+use std::time::Duration;
+use serde_json::{json, Value};
+
+use wforum_backend::cache::{self, Cache};
+use wforum_backend::config::config::CacheConfig;
+
+
+// Function to create a cache with the given backend, holding entries for `ttl` seconds.
+fn cache(backend: &str, ttl: u64, redis_url: Option<String>) -> Cache {
+    Cache::new(&CacheConfig { backend: String::from(backend), capacity: 100, ttl, redis_url })
+}
+
+// Function to check that values are read back until they are invalidated.
+async fn check_round_trip(cache: &Cache, key: &str) {
+    cache.invalidate(key).await;
+    assert_eq!(cache.get::<Value>(key).await, None);
+
+    let value = json!({ "post": { "postId": 1, "likedCount": 3 } });
+    cache.set(key, &value).await;
+    assert_eq!(cache.get::<Value>(key).await, Some(value));
+
+    // A value of another type is a miss rather than an error.
+    assert_eq!(cache.get::<String>(key).await, None);
+
+    cache.invalidate(key).await;
+    assert_eq!(cache.get::<Value>(key).await, None);
+}
+
+
+#[tokio::test]
+async fn memory_entries_are_invalidated() {
+    check_round_trip(&cache("memory", 60, None), &cache::post_key(1)).await;
+}
+
+#[tokio::test]
+async fn memory_entries_expire() {
+    let cache = cache("memory", 1, None);
+
+    cache.set(&cache::profile_key("ann"), &"ann").await;
+    assert_eq!(cache.get::<String>(&cache::profile_key("ann")).await.as_deref(), Some("ann"));
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert_eq!(cache.get::<String>(&cache::profile_key("ann")).await, None);
+}
+
+#[tokio::test]
+async fn disabled_cache_never_hits() {
+    let cache = cache("none", 60, None);
+
+    cache.set("key", &1).await;
+    assert_eq!(cache.get::<i32>("key").await, None);
+}
+
+#[tokio::test]
+async fn unreachable_redis_is_a_miss() {
+    // Nothing listens on port 1, so every request fails and is treated as a miss.
+    let cache = cache("redis", 60, Some(String::from("redis://127.0.0.1:1")));
+
+    cache.set("key", &1).await;
+    assert_eq!(cache.get::<i32>("key").await, None);
+}
+
+#[tokio::test]
+async fn redis_entries_are_invalidated() {
+    let Ok(url) = std::env::var("TEST_REDIS_URL") else {
+        eprintln!("TEST_REDIS_URL is not set, skipping the Redis test");
+        return;
+    };
+
+    let key = format!("test:{}", std::process::id());
+    check_round_trip(&cache("redis", 60, Some(url)), &key).await;
+}
@@ -0,0 +1,196 @@
+// The integration test harness, serving the API against a throwaway PostgreSQL schema.
+// Tests using it run against the database at `TEST_DATABASE_URL`, and are skipped when it is not set.
+// Each test binary pushes the Prisma schema to its own `wforum_test_<binary>` schema, reset on every run.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use axum::Extension;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use wforum_backend::config::BeContext;
+use wforum_backend::prisma::prisma::PrismaClient;
+use wforum_backend::repository::PrismaRepository;
+use wforum_backend::service::Router;
+
+
+// The password every test user signs up with.
+pub const PASSWORD: &str = "correct horse battery staple";
+
+
+// The `TestApp` struct which represents the API served against the test schema.
+pub struct TestApp {
+    // The base URL of the server.
+    pub url: String,
+    // The Prisma client the server uses, for checking the database directly.
+    pub prisma: Arc<PrismaClient>,
+    // The HTTP client.
+    client: reqwest::Client,
+}
+
+// The `TestUser` struct which represents a signed up user.
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    // The ID of the user.
+    pub user_id: i64,
+    // The username of the user.
+    pub username: String,
+    // The email of the user.
+    pub email: String,
+    // The JWT of the user, sent as a bearer token.
+    pub token: String,
+}
+
+
+// Implementation of the `TestApp` struct.
+impl TestApp {
+
+    // Function to serve the API against the test schema, pushing the schema first if needed.
+    // It returns `None`, after saying why, when `TEST_DATABASE_URL` is not set.
+    pub async fn spawn(binary: &str) -> Option<Self> {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL is not set, skipping the database test");
+            return None;
+        };
+
+        static SCHEMA: OnceLock<String> = OnceLock::new();
+        let url = SCHEMA.get_or_init(|| {
+            let url = schema_url(&database_url, &format!("wforum_test_{}", binary));
+            push_schema(&url);
+
+            // The global configuration is read on first use, so the environment is set before any request.
+            std::env::set_var("DATABASE_URL", &url);
+            std::env::set_var("JWT_SECRET", "integration-test-secret");
+            std::env::set_var("CACHE_BACKEND", "none");
+            url
+        });
+
+        // Every test has its own runtime, so it gets its own client and connection pool.
+        let prisma = Arc::new(PrismaClient::_builder().with_url(url.clone()).build().await.unwrap());
+        let repository = Arc::new(PrismaRepository::new(Extension(prisma.clone())));
+        let app = Router::new().with_state(BeContext::new(repository));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/api", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+            axum::serve(listener, app).await.unwrap()
+        });
+
+        Some(Self { url: base, prisma, client: reqwest::Client::new() })
+    }
+
+    // Function to send a request to the API, as `user` if given.
+    // It returns the status code and the JSON body, or the body as a string if it is not JSON.
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        user: Option<&TestUser>,
+        body: Option<Value>,
+    ) -> (u16, Value) {
+        let mut request = self.client.request(method, format!("{}{}", self.url, path));
+        if let Some(user) = user {
+            request = request.bearer_auth(&user.token);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request.send().await.unwrap();
+        let status = response.status().as_u16();
+        let text = response.text().await.unwrap();
+        let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+        (status, body)
+    }
+
+    // Function to send a `GET` request.
+    pub async fn get(&self, path: &str, user: Option<&TestUser>) -> (u16, Value) {
+        self.request(Method::GET, path, user, None).await
+    }
+
+    // Function to send a `POST` request.
+    pub async fn post(&self, path: &str, user: Option<&TestUser>, body: Option<Value>) -> (u16, Value) {
+        self.request(Method::POST, path, user, body).await
+    }
+
+    // Function to send a `PUT` request.
+    pub async fn put(&self, path: &str, user: Option<&TestUser>, body: Value) -> (u16, Value) {
+        self.request(Method::PUT, path, user, Some(body)).await
+    }
+
+    // Function to send a `DELETE` request.
+    pub async fn delete(&self, path: &str, user: Option<&TestUser>) -> (u16, Value) {
+        self.request(Method::DELETE, path, user, None).await
+    }
+
+    // Function to sign up a user whose name starts with `name`, made unique across tests and runs.
+    pub async fn signup(&self, name: &str) -> TestUser {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let username = format!("{}_{}_{}", name, std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+        let email = format!("{}@test.example", username);
+
+        let (status, body) = self.post("/users/create", None, Some(json!({
+            "user": { "email": email, "username": username, "password": PASSWORD },
+        }))).await;
+        assert_eq!(status, 200, "signing up {} failed: {}", username, body);
+
+        TestUser {
+            user_id: body["user"]["userId"].as_i64().unwrap(),
+            username,
+            email,
+            token: body["user"]["token"].as_str().unwrap().to_string(),
+        }
+    }
+
+    // Function to log in with an email and password.
+    // It returns the status code and the body of the response.
+    pub async fn login(&self, email: &str, password: &str) -> (u16, Value) {
+        self.post("/users", None, Some(json!({ "user": { "email": email, "password": password } }))).await
+    }
+
+    // Function to create a post as `user`.
+    // It returns the ID of the post.
+    pub async fn create_post(&self, user: &TestUser, title: &str) -> i64 {
+        let (status, body) = self.post("/posts", Some(user), Some(json!({
+            "post": { "title": title, "description": "A test post", "content": "Hello from the tests" },
+        }))).await;
+        assert_eq!(status, 200, "creating a post failed: {}", body);
+
+        body["post"]["postId"].as_i64().unwrap()
+    }
+}
+
+
+// Function to point a database URL at another schema, replacing the one it names, if any.
+fn schema_url(database_url: &str, schema: &str) -> String {
+    let (base, query) = database_url.split_once('?').unwrap_or((database_url, ""));
+    let mut parameters: Vec<&str> = query
+        .split('&')
+        .filter(|parameter| !parameter.is_empty() && !parameter.starts_with("schema="))
+        .collect();
+    let schema = format!("schema={}", schema);
+    parameters.push(&schema);
+
+    format!("{}?{}", base, parameters.join("&"))
+}
+
+// Function to reset the schema and create the tables of the Prisma schema in it, through the Prisma CLI.
+// The CLI is built in a target directory of its own, so it doesn't wait for the lock `cargo test` holds.
+fn push_schema(url: &str) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let status = std::process::Command::new(env!("CARGO"))
+        .args(["run", "--quiet", "-p", "prisma", "--", "db", "push"])
+        .args(["--force-reset", "--accept-data-loss", "--skip-generate"])
+        .arg("--schema")
+        .arg(root.join("prisma/schema.prisma"))
+        .current_dir(root)
+        .env("DATABASE_URL", url)
+        .env("CARGO_TARGET_DIR", Path::new(env!("CARGO_TARGET_TMPDIR")).join("prisma-cli"))
+        .status()
+        .expect("failed to run the Prisma CLI");
+
+    assert!(status.success(), "pushing the Prisma schema to the test database failed");
+}
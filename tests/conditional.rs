@@ -0,0 +1,143 @@
+// Tests for response compression, entity tags and conditional requests.
+use axum::routing::get;
+use axum::Json;
+use prisma_client_rust::chrono::DateTime;
+use serde_json::{json, Value};
+use tower_http::compression::CompressionLayer;
+
+use wforum_backend::middleware::conditional::{conditional, LastModified, MAX_TAGGED_BODY};
+
+
+// Function to start a server whose `/post` route returns a post last modified at a fixed time,
+// and whose `/large` route returns a body too large to be tagged.
+// It returns the URL of the post.
+async fn server() -> String {
+    let app = axum::Router::new()
+        .route("/post", get(|| async {
+            let modified = DateTime::parse_from_rfc3339("2024-03-01T12:00:00.250+08:00").unwrap();
+            let post = json!({ "post": { "title": "Hello", "content": "x".repeat(200) } });
+            (LastModified(Some(modified)), Json(post))
+        }))
+        .route("/large", get(|| async { "x".repeat(MAX_TAGGED_BODY + 1) }))
+        .layer(CompressionLayer::new())
+        .layer(axum::middleware::from_fn(conditional));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/post", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    url
+}
+
+
+#[tokio::test]
+async fn matching_tags_are_not_modified() {
+    let url = server().await;
+    let client = reqwest::Client::new();
+
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers()["last-modified"], "Fri, 01 Mar 2024 04:00:00 GMT");
+    let tag = response.headers()["etag"].to_str().unwrap().to_string();
+    assert!(tag.starts_with('"') && !tag.starts_with("W/"), "{}", tag);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["post"]["title"], "Hello");
+
+    let matching = [tag.clone(), format!("W/{}", tag), format!("\"other\", {}", tag), String::from("*")];
+    for if_none_match in matching {
+        let response = client.get(&url).header("if-none-match", &if_none_match).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 304, "{}", if_none_match);
+        assert_eq!(response.headers()["etag"], tag.as_str());
+        assert!(response.bytes().await.unwrap().is_empty());
+    }
+
+    let response = client.get(&url).header("if-none-match", "\"other\"").send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn unchanged_resources_are_not_modified_since() {
+    let url = server().await;
+    let client = reqwest::Client::new();
+    let since = |date: &str| client.get(&url).header("if-modified-since", date);
+
+    assert_eq!(since("Fri, 01 Mar 2024 04:00:00 GMT").send().await.unwrap().status().as_u16(), 304);
+    assert_eq!(since("Sat, 02 Mar 2024 00:00:00 GMT").send().await.unwrap().status().as_u16(), 304);
+    assert_eq!(since("Fri, 01 Mar 2024 03:59:59 GMT").send().await.unwrap().status().as_u16(), 200);
+    assert_eq!(since("not a date").send().await.unwrap().status().as_u16(), 200);
+
+    // Authenticated responses depend on more than `Last-Modified`, and `If-None-Match` takes precedence.
+    let authenticated = since("Sat, 02 Mar 2024 00:00:00 GMT").header("authorization", "Token abc");
+    assert_eq!(authenticated.send().await.unwrap().status().as_u16(), 200);
+    let tagged = since("Sat, 02 Mar 2024 00:00:00 GMT").header("if-none-match", "\"other\"");
+    assert_eq!(tagged.send().await.unwrap().status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn each_encoding_has_its_own_tag() {
+    let url = server().await;
+    let client = reqwest::Client::new();
+
+    let identity = client.get(&url).send().await.unwrap();
+    let gzip = client.get(&url).header("accept-encoding", "gzip").send().await.unwrap();
+    let brotli = client.get(&url).header("accept-encoding", "br").send().await.unwrap();
+
+    assert!(identity.headers().get("content-encoding").is_none());
+    assert_eq!(gzip.headers()["content-encoding"], "gzip");
+    assert_eq!(brotli.headers()["content-encoding"], "br");
+    assert_eq!(gzip.headers()["vary"], "accept-encoding");
+
+    let tags: Vec<&str> = [&identity, &gzip, &brotli]
+        .iter()
+        .map(|response| response.headers()["etag"].to_str().unwrap())
+        .collect();
+    assert_ne!(tags[0], tags[1]);
+    assert_ne!(tags[1], tags[2]);
+
+    // Compressing again gives the same bytes, so the tag is stable.
+    let again = client
+        .get(&url)
+        .header("accept-encoding", "gzip")
+        .header("if-none-match", tags[1])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(again.status().as_u16(), 304);
+}
+
+#[tokio::test]
+async fn tagged_responses_vary_by_authorization() {
+    let url = server().await;
+    let client = reqwest::Client::new();
+
+    let anonymous = client.get(&url).header("accept-encoding", "gzip").send().await.unwrap();
+    let vary: Vec<&str> = anonymous.headers().get_all("vary").iter().map(|value| value.to_str().unwrap()).collect();
+    assert_eq!(vary, ["accept-encoding", "authorization"]);
+    assert!(anonymous.headers().get("cache-control").is_none());
+
+    let authenticated = client.get(&url).header("authorization", "Token abc").send().await.unwrap();
+    assert_eq!(authenticated.headers()["cache-control"], "private");
+    let tag = authenticated.headers()["etag"].to_str().unwrap().to_string();
+
+    let not_modified = client
+        .get(&url)
+        .header("authorization", "Token abc")
+        .header("if-none-match", &tag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(not_modified.status().as_u16(), 304);
+    assert_eq!(not_modified.headers()["cache-control"], "private");
+    assert_eq!(not_modified.headers()["vary"], "authorization");
+}
+
+#[tokio::test]
+async fn large_responses_are_not_tagged() {
+    let url = server().await.replace("/post", "/large");
+    let client = reqwest::Client::new();
+
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().get("etag").is_none());
+    assert_eq!(response.text().await.unwrap().len(), MAX_TAGGED_BODY + 1);
+}
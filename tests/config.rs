@@ -0,0 +1,305 @@
+// Tests for loading the configuration from the config file and the environment.
+use std::collections::HashMap;
+use std::time::Duration;
+
+use wforum_backend::config::config::BeConfig;
+use wforum_backend::config::source::{parse_duration, ConfigError};
+
+
+// Function to load a configuration from a config file and environment variables.
+fn load(file: Option<&str>, env: &[(&str, &str)]) -> Result<BeConfig, ConfigError> {
+    let env: HashMap<String, String> = env
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    BeConfig::from_sources(file.map(|contents| ("wforum.toml", contents)), |key| env.get(key).cloned())
+}
+
+
+#[test]
+fn durations_accept_units_and_bare_seconds() {
+    assert_eq!(parse_duration("45"), Some(Duration::from_secs(45)));
+    assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+    assert_eq!(parse_duration("15m"), Some(Duration::from_secs(900)));
+    assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+    assert_eq!(parse_duration("7d"), Some(Duration::from_secs(604800)));
+    assert_eq!(parse_duration("2w"), Some(Duration::from_secs(1209600)));
+
+    for invalid in ["", "d", "1x", "1.5h", "-1s", "1 h", "99999999999999999999w"] {
+        assert_eq!(parse_duration(invalid), None, "{:?}", invalid);
+    }
+}
+
+#[test]
+fn optional_settings_have_defaults() {
+    let config = load(None, &[("DATABASE_URL", "postgresql://db"), ("JWT_SECRET", "secret")]).unwrap();
+
+    assert_eq!(config.log_level, "info");
+    assert_eq!(config.backend_port, 8000);
+    assert_eq!(config.jwt_config.jwt_algorithm, "HS256");
+    assert_eq!(config.jwt_config.jwt_exp_value, 86400);
+    assert_eq!(config.login_config.lockout_base, 30);
+    assert_eq!(config.server_config.client_ip_header, None);
+    assert_eq!(config.two_factor_config.challenge_exp_value, 300);
+    assert!(config.two_factor_config.required_for_elevated);
+    assert_eq!(config.validation_config.title_max, 200);
+    assert!(config.oidc_providers.is_empty());
+}
+
+#[test]
+fn environment_overrides_the_file() {
+    let file = r#"
+        [server]
+        port = 9000
+        client_ip_header = "X-Forwarded-For"
+
+        [database]
+        url = "postgresql://file"
+
+        [jwt]
+        secret = "file secret"
+        expiration = "12h"
+
+        [login]
+        lockout_max = "2h"
+    "#;
+
+    let config = load(Some(file), &[("BACKEND_PORT", "9100"), ("JWT_EXPIRATION", "30m")]).unwrap();
+
+    assert_eq!(config.backend_port, 9100);
+    assert_eq!(config.database_url, "postgresql://file");
+    assert_eq!(config.jwt_config.jwt_secret, "file secret");
+    assert_eq!(config.jwt_config.jwt_exp_value, 1800);
+    assert_eq!(config.login_config.lockout_max, 7200);
+    assert_eq!(config.server_config.client_ip_header.as_deref(), Some("X-Forwarded-For"));
+}
+
+#[test]
+fn legacy_expiration_variables_still_work() {
+    let config = load(None, &[
+        ("DATABASE_URL", "postgresql://db"),
+        ("JWT_SECRET", "secret"),
+        ("JWT_EXPIRATION_VALUE", "2"),
+        ("JWT_EXPIRATION_UNIT", "hours"),
+    ]).unwrap();
+    assert_eq!(config.jwt_config.jwt_exp_value, 7200);
+
+    let error = load(None, &[
+        ("DATABASE_URL", "postgresql://db"),
+        ("JWT_SECRET", "secret"),
+        ("JWT_EXPIRATION_VALUE", "2"),
+        ("JWT_EXPIRATION_UNIT", "fortnights"),
+    ]).unwrap_err();
+    assert_eq!(error.errors.len(), 1);
+    assert!(error.errors[0].contains("fortnights"));
+}
+
+#[test]
+fn every_problem_is_reported_at_once() {
+    let file = r#"
+        [server]
+        prot = 8000
+
+        [jwt]
+        algorithm = "RS512"
+
+        [login]
+        lockout_base = "soon"
+
+        [validation]
+        username_min = 40
+    "#;
+
+    let error = load(Some(file), &[("BACKEND_PORT", "80a")]).unwrap_err();
+    let message = error.to_string();
+
+    for expected in [
+        "BACKEND_PORT: invalid value \"80a\"",
+        "`database.url` is required (or set DATABASE_URL)",
+        "`jwt.algorithm` must be one of HS256, RS256, EdDSA",
+        "`jwt.signing_key_file` is required for RS512",
+        "`login.lockout_base` in wforum.toml: expected a duration",
+        "`validation.username_min` is larger than `validation.username_max`",
+        "unknown setting `server.prot` in wforum.toml",
+    ] {
+        assert!(message.contains(expected), "{:?} is missing from:\n{}", expected, message);
+    }
+    assert_eq!(error.errors.len(), 7, "{}", message);
+    assert!(message.starts_with("Invalid configuration:\n  - "));
+}
+
+#[test]
+fn invalid_toml_reports_the_line() {
+    let error = load(Some("[server]\nport = = 8000\n"), &[]).unwrap_err();
+    assert!(error.errors[0].starts_with("wforum.toml line 2 is not valid TOML"), "{:?}", error.errors);
+}
+
+#[test]
+fn oidc_providers_come_from_tables_or_the_environment() {
+    let file = r#"
+        [database]
+        url = "postgresql://db"
+
+        [jwt]
+        secret = "secret"
+
+        [oidc]
+        state_secret = "a-state-secret-of-at-least-32-characters"
+
+        [oidc.google]
+        issuer = "https://accounts.google.com/"
+        client_id = "id"
+        client_secret = "secret"
+        redirect_uri = "http://localhost/api/auth/oidc/google/callback"
+    "#;
+
+    let config = load(Some(file), &[
+        ("OIDC_PROVIDERS", "github"),
+        ("OIDC_GITHUB_ISSUER", "https://github.example"),
+        ("OIDC_GITHUB_CLIENT_ID", "id"),
+        ("OIDC_GITHUB_CLIENT_SECRET", "secret"),
+        ("OIDC_GITHUB_REDIRECT_URI", "http://localhost/api/auth/oidc/github/callback"),
+        ("OIDC_GITHUB_SCOPES", "openid email"),
+    ]).unwrap();
+
+    let names: Vec<&str> = config.oidc_providers.iter().map(|provider| provider.name.as_str()).collect();
+    assert_eq!(names, ["google", "github"]);
+    assert_eq!(config.oidc_providers[0].issuer, "https://accounts.google.com");
+    assert_eq!(config.oidc_providers[0].scopes, ["openid", "email", "profile"]);
+    assert_eq!(config.oidc_providers[1].scopes, ["openid", "email"]);
+
+    let error = load(Some(file), &[("OIDC_PROVIDERS", "gitlab")]).unwrap_err();
+    assert!(error.to_string().contains("`oidc.gitlab.issuer` is required (or set OIDC_GITLAB_ISSUER)"));
+}
+
+#[test]
+fn oidc_providers_require_a_state_secret() {
+    let provider = [
+        ("DATABASE_URL", "postgresql://db"),
+        ("JWT_SECRET", "secret"),
+        ("OIDC_PROVIDERS", "github"),
+        ("OIDC_GITHUB_ISSUER", "https://github.example"),
+        ("OIDC_GITHUB_CLIENT_ID", "id"),
+        ("OIDC_GITHUB_CLIENT_SECRET", "secret"),
+        ("OIDC_GITHUB_REDIRECT_URI", "http://localhost/api/auth/oidc/github/callback"),
+    ];
+
+    let error = load(None, &provider).unwrap_err();
+    assert!(error.to_string().contains("`oidc.state_secret` is required (or set OIDC_STATE_SECRET)"));
+
+    let error = load(None, &[provider.as_slice(), &[("OIDC_STATE_SECRET", "short")]].concat()).unwrap_err();
+    assert!(error.to_string().contains("`oidc.state_secret` must be at least 32 characters"));
+
+    let error = load(Some("[oidc]\nstate_secret = \"\"\n"), &provider).unwrap_err();
+    assert!(error.to_string().contains("`oidc.state_secret` must be at least 32 characters"));
+
+    let secret = [("OIDC_STATE_SECRET", "a-state-secret-of-at-least-32-characters")];
+    let config = load(None, &[provider.as_slice(), &secret].concat()).unwrap();
+    assert_eq!(config.oidc_state_secret.as_deref(), Some(secret[0].1));
+}
+
+#[test]
+fn log_format_must_be_text_or_json() {
+    let env = [("DATABASE_URL", "postgresql://db"), ("JWT_SECRET", "secret"), ("LOG_FORMAT", "json")];
+    assert_eq!(load(None, &env).unwrap().log_format, "json");
+
+    let error = load(Some("log_format = \"xml\""), &env[..2]).unwrap_err();
+    assert!(error.to_string().contains("`log_format` must be one of text, json"), "{}", error);
+}
+
+#[test]
+fn server_settings_have_defaults_and_overrides() {
+    let env = [("DATABASE_URL", "postgresql://db"), ("JWT_SECRET", "secret")];
+
+    let config = load(None, &env).unwrap();
+    assert_eq!(config.server_config.bind_address.to_string(), "0.0.0.0");
+    assert_eq!(config.server_config.request_timeout, 30);
+    assert_eq!(config.server_config.body_limit, 2 * 1024 * 1024);
+    assert_eq!(config.cors_config.allowed_origins, ["*"]);
+    assert!(!config.cors_config.allow_credentials);
+
+    let file = r#"
+        [server]
+        bind_address = "::1"
+        request_timeout = "1m"
+        body_limit = 1024
+
+        [cors]
+        allowed_origins = ["https://forum.example", "http://localhost:3000"]
+        allow_credentials = true
+    "#;
+    let config = load(Some(file), &env).unwrap();
+    assert_eq!(config.server_config.bind_address.to_string(), "::1");
+    assert_eq!(config.server_config.request_timeout, 60);
+    assert_eq!(config.server_config.body_limit, 1024);
+    assert_eq!(config.cors_config.allowed_origins, ["https://forum.example", "http://localhost:3000"]);
+    assert!(config.cors_config.allow_credentials);
+}
+
+#[test]
+fn cors_origins_are_checked() {
+    let env = [("DATABASE_URL", "postgresql://db"), ("JWT_SECRET", "secret")];
+
+    let file = r#"
+        [cors]
+        allowed_origins = ["*"]
+        allow_credentials = true
+    "#;
+    let error = load(Some(file), &env).unwrap_err();
+    assert!(error.to_string().contains("`cors.allow_credentials` requires"), "{}", error);
+
+    for origin in ["https://forum.example/", "https://forum.example/app", "forum.example", "ftp://forum.example"] {
+        let file = format!("[cors]\nallowed_origins = [{:?}]", origin);
+        let error = load(Some(&file), &env).unwrap_err();
+        assert!(error.to_string().contains("is not an origin"), "{}: {}", origin, error);
+    }
+
+    let error = load(None, &[env[0], env[1], ("BIND_ADDRESS", "localhost")]).unwrap_err();
+    assert!(error.to_string().contains("BIND_ADDRESS: invalid value \"localhost\""), "{}", error);
+}
+
+#[test]
+fn tls_needs_both_files() {
+    let env = [("DATABASE_URL", "postgresql://db"), ("JWT_SECRET", "secret")];
+    assert!(load(None, &env).unwrap().tls_config.is_none());
+
+    let file = r#"
+        [tls]
+        cert_file = "certs/fullchain.pem"
+        key_file = "certs/privkey.pem"
+        redirect_port = 8080
+    "#;
+    let config = load(Some(file), &[env[0], env[1], ("TLS_RELOAD_INTERVAL", "5m")]).unwrap();
+    let tls_config = config.tls_config.unwrap();
+    assert_eq!(tls_config.cert_file, "certs/fullchain.pem");
+    assert_eq!(tls_config.reload_interval, 300);
+    assert_eq!(tls_config.redirect_port, Some(8080));
+
+    let error = load(None, &[env[0], env[1], ("TLS_CERT_FILE", "certs/fullchain.pem")]).unwrap_err();
+    assert!(error.to_string().contains("`tls.key_file` must be set together"), "{}", error);
+
+    let error = load(None, &[env[0], env[1], ("TLS_REDIRECT_PORT", "8080")]).unwrap_err();
+    assert!(error.to_string().contains("`tls.redirect_port` requires"), "{}", error);
+
+    let error = load(Some(file), &[env[0], env[1], ("BACKEND_PORT", "8080")]).unwrap_err();
+    assert!(error.to_string().contains("`tls.redirect_port` must differ from `server.port`"), "{}", error);
+}
+
+#[test]
+fn cache_backend_is_checked() {
+    let env = [("DATABASE_URL", "postgresql://db"), ("JWT_SECRET", "secret")];
+
+    let config = load(None, &env).unwrap();
+    assert_eq!(config.cache_config.backend, "memory");
+    assert_eq!(config.cache_config.ttl, 60);
+
+    let redis = [("CACHE_BACKEND", "redis"), ("CACHE_REDIS_URL", "redis://cache:6379")];
+    let config = load(None, &[env[0], env[1], redis[0], redis[1]]).unwrap();
+    assert_eq!(config.cache_config.redis_url.as_deref(), Some("redis://cache:6379"));
+
+    let error = load(None, &[env[0], env[1], ("CACHE_BACKEND", "redis")]).unwrap_err();
+    assert!(error.to_string().contains("`cache.redis_url` is required"), "{}", error);
+
+    let error = load(None, &[env[0], env[1], ("CACHE_BACKEND", "memcached")]).unwrap_err();
+    assert!(error.to_string().contains("`cache.backend` must be one of memory, redis, none"), "{}", error);
+}
@@ -0,0 +1,98 @@
+// Tests for the JSON error envelope and request IDs.
+use axum::routing::get;
+use serde_json::Value;
+
+use wforum_backend::error::{EError, ErrorCode};
+use wforum_backend::middleware::request_id::request_id;
+
+
+// Function to start a server whose routes fail with known errors.
+// It returns the server's base URL.
+async fn error_server() -> String {
+
+    let app = axum::Router::new()
+        .route("/not-found", get(|| async {
+            Err::<(), _>(EError::NotFound(ErrorCode::PostNotFound, String::from("Post not found")))
+        }))
+        .route("/throttled", get(|| async { Err::<(), _>(EError::TooManyRequests(42)) }))
+        .route("/internal", get(|| async {
+            Err::<(), _>(EError::InternalServerError(
+                ErrorCode::InternalError, String::from("SELECT secret FROM internals"),
+            ))
+        }))
+        .route("/ok", get(|| async { "ok" }))
+        .layer(axum::middleware::from_fn(request_id));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    url
+}
+
+// Function to get a path and return the status, the `x-request-id` header and the JSON body.
+async fn fetch(url: &str, request_id: Option<&str>) -> (u16, String, reqwest::header::HeaderMap, Value) {
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(request_id) = request_id {
+        request = request.header("x-request-id", request_id);
+    }
+
+    let response = request.send().await.unwrap();
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    let id = headers["x-request-id"].to_str().unwrap().to_string();
+    let body = response.json().await.unwrap_or(Value::Null);
+
+    (status, id, headers, body)
+}
+
+
+#[tokio::test]
+async fn errors_use_the_json_envelope_with_the_request_id() {
+    let url = error_server().await;
+    let (status, id, _, body) = fetch(&format!("{}/not-found", url), None).await;
+
+    assert_eq!(status, 404);
+    assert_eq!(body["code"], "POST_NOT_FOUND");
+    assert_eq!(body["message"], "Post not found");
+    assert!(body["details"].is_null());
+    assert_eq!(body["requestId"], id.as_str());
+    assert!(!id.is_empty());
+}
+
+#[tokio::test]
+async fn well_formed_request_ids_are_kept() {
+    let url = error_server().await;
+
+    let (_, id, _, body) = fetch(&format!("{}/not-found", url), Some("edge-1234.abc")).await;
+    assert_eq!(id, "edge-1234.abc");
+    assert_eq!(body["requestId"], "edge-1234.abc");
+
+    let (_, id, _, _) = fetch(&format!("{}/not-found", url), Some("bad id\"")).await;
+    assert_ne!(id, "bad id\"");
+
+    let (_, id, _, _) = fetch(&format!("{}/ok", url), Some("edge-5678")).await;
+    assert_eq!(id, "edge-5678");
+}
+
+#[tokio::test]
+async fn throttled_requests_carry_retry_after() {
+    let url = error_server().await;
+    let (status, _, headers, body) = fetch(&format!("{}/throttled", url), None).await;
+
+    assert_eq!(status, 429);
+    assert_eq!(headers["retry-after"], "42");
+    assert_eq!(body["code"], "TOO_MANY_REQUESTS");
+    assert_eq!(body["details"]["retryAfter"], 42);
+}
+
+#[tokio::test]
+async fn internal_errors_hide_their_details() {
+    let url = error_server().await;
+    let (status, _, _, body) = fetch(&format!("{}/internal", url), None).await;
+
+    assert_eq!(status, 500);
+    assert_eq!(body["code"], "INTERNAL_ERROR");
+    assert_eq!(body["message"], "Internal server error");
+    assert!(!body.to_string().contains("SELECT"));
+}
@@ -0,0 +1,70 @@
+// Tests for the liveness and build information probes, which need no database.
+use std::sync::{Arc, Once};
+use serde_json::Value;
+
+use wforum_backend::config::BeContext;
+use wforum_backend::repository::MemoryRepository;
+use wforum_backend::service::health::HealthRouter;
+use wforum_backend::service::health::service::HealthService;
+
+
+// Function to start the probes without a database.
+// It returns the server's base URL.
+async fn server() -> String {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| {
+        for (key, value) in [
+            ("JWT_SECRET", "test"),
+            ("DATABASE_URL", "postgresql://unused"),
+        ] {
+            std::env::set_var(key, value);
+        }
+    });
+
+    let app = HealthRouter::new().with_state(BeContext::new(Arc::new(MemoryRepository::new())));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    url
+}
+
+// Function to send a GET request and return the status and the JSON body.
+async fn get(url: &str) -> (u16, Value) {
+    let response = reqwest::get(url).await.unwrap();
+    (response.status().as_u16(), response.json().await.unwrap())
+}
+
+
+#[tokio::test]
+async fn healthz_reports_ok() {
+    let url = server().await;
+
+    let (status, body) = get(&format!("{}/healthz", url)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "ok");
+}
+
+#[tokio::test]
+async fn version_reports_the_build() {
+    let url = server().await;
+
+    let (status, body) = get(&format!("{}/version", url)).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(!body["gitSha"].as_str().unwrap().is_empty());
+    assert!(body["buildTime"].as_str().unwrap().ends_with('Z'), "{}", body);
+}
+
+#[test]
+fn every_model_of_the_schema_is_an_expected_table() {
+    let tables = HealthService::expected_tables();
+
+    let schema = std::fs::read_to_string(format!("{}/prisma/schema.prisma", env!("CARGO_MANIFEST_DIR"))).unwrap();
+    let models = schema.lines().filter(|line| line.trim_start().starts_with("model ")).count();
+
+    assert_eq!(tables.len(), models);
+    assert!(tables.contains(&String::from("UserDetails")));
+    assert!(tables.contains(&String::from("PostComments")));
+}
@@ -0,0 +1,98 @@
+// Tests for signing and verifying JWTs with rotated keys.
+use prisma_client_rust::chrono;
+use serde::{Deserialize, Serialize};
+
+use wforum_backend::config::config::JwtConfig;
+use wforum_backend::extractor::keys::JwtKeys;
+
+
+// The claims signed in the tests.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    exp: i64,
+}
+
+
+// Function to build a configuration signing with `kid` and verifying with the given keys.
+fn config(algorithm: &str, kid: &str, verification: &[&str]) -> JwtConfig {
+    let fixture = |name: &str| format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+
+    JwtConfig {
+        jwt_secret: String::from("secret"),
+        jwt_exp_value: 3600,
+        jwt_algorithm: algorithm.to_string(),
+        jwt_kid: kid.to_string(),
+        jwt_signing_key_file: Some(fixture(&format!("jwt-{}.pem", kid))),
+        jwt_verification_key_files: verification
+            .iter()
+            .map(|kid| (kid.to_string(), fixture(&format!("jwt-{}.pub.pem", kid))))
+            .collect(),
+    }
+}
+
+// Function to build the claims signed in the tests.
+fn claims() -> Claims {
+    Claims { sub: String::from("1"), exp: chrono::Utc::now().timestamp() + 60 }
+}
+
+
+#[test]
+fn eddsa_round_trip_sets_kid() {
+    let keys = JwtKeys::load(&config("EdDSA", "new", &["new"])).unwrap();
+    let token = keys.encode(&claims()).unwrap();
+
+    let header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("new"));
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::EdDSA);
+    assert_eq!(keys.decode::<Claims>(&token).unwrap().claims.sub, "1");
+}
+
+#[test]
+fn tokens_signed_with_the_previous_key_stay_valid_during_rotation() {
+    let old = JwtKeys::load(&config("EdDSA", "old", &["old"])).unwrap();
+    let token = old.encode(&claims()).unwrap();
+
+    let rotated = JwtKeys::load(&config("EdDSA", "new", &["new", "old"])).unwrap();
+    assert!(rotated.decode::<Claims>(&token).is_ok());
+
+    let retired = JwtKeys::load(&config("EdDSA", "new", &["new"])).unwrap();
+    assert!(retired.decode::<Claims>(&token).is_err());
+}
+
+#[test]
+fn jwks_lists_every_verification_key() {
+    let keys = JwtKeys::load(&config("EdDSA", "new", &["new", "old"])).unwrap();
+    let jwks = serde_json::to_value(keys.jwks()).unwrap();
+
+    let kids: Vec<&str> = jwks["keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|key| key["kid"].as_str().unwrap())
+        .collect();
+    assert_eq!(kids, vec!["new", "old"]);
+    assert_eq!(jwks["keys"][0]["crv"], "Ed25519");
+}
+
+#[test]
+fn rejects_missing_public_key_for_signing_key() {
+    assert!(JwtKeys::load(&config("EdDSA", "new", &["old"])).is_err());
+}
+
+#[test]
+fn hs256_publishes_no_keys_and_rejects_asymmetric_tokens() {
+    let hmac = JwtKeys::load(&JwtConfig {
+        jwt_algorithm: String::from("HS256"),
+        jwt_signing_key_file: None,
+        jwt_verification_key_files: vec![],
+        ..config("HS256", "new", &[])
+    }).unwrap();
+    assert!(hmac.jwks().keys.is_empty());
+
+    let token = hmac.encode(&claims()).unwrap();
+    assert!(hmac.decode::<Claims>(&token).is_ok());
+
+    let eddsa = JwtKeys::load(&config("EdDSA", "new", &["new"])).unwrap();
+    assert!(hmac.decode::<Claims>(&eddsa.encode(&claims()).unwrap()).is_err());
+}
@@ -0,0 +1,73 @@
+// Tests that logs written while handling a request carry its request ID and authenticated user.
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use axum::routing::get;
+use serde_json::Value;
+
+use wforum_backend::middleware::request_id::{self, request_id};
+
+
+// The `Buffer` struct which collects the JSON log lines written by the subscriber.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+// Implementation of the `Write` trait for `Buffer`.
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+
+#[tokio::test]
+async fn json_logs_carry_the_request_and_user_ids() {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(false)
+        .with_writer(move || writer.clone())
+        .init();
+
+    let app = axum::Router::new()
+        .route("/anonymous", get(|| async {
+            tracing::info!("anonymous request");
+        }))
+        .route("/authenticated", get(|| async {
+            request_id::record_user(7);
+            tracing::info!("authenticated request");
+        }))
+        .layer(axum::middleware::from_fn(request_id));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = reqwest::Client::new();
+    client.get(format!("{}/anonymous", url)).header("x-request-id", "first").send().await.unwrap();
+    client.get(format!("{}/authenticated", url)).header("x-request-id", "second").send().await.unwrap();
+
+    let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<Value> = logs.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let find = |message: &str| {
+        lines
+            .iter()
+            .find(|line| line["fields"]["message"] == message)
+            .unwrap_or_else(|| panic!("{:?} was not logged:\n{}", message, logs))
+    };
+
+    let anonymous = &find("anonymous request")["span"];
+    assert_eq!(anonymous["request_id"], "first");
+    assert_eq!(anonymous["path"], "/anonymous");
+    assert!(anonymous.get("user_id").is_none());
+
+    let authenticated = &find("authenticated request")["span"];
+    assert_eq!(authenticated["request_id"], "second");
+    assert_eq!(authenticated["user_id"], 7);
+}
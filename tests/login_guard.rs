@@ -0,0 +1,65 @@
+// Tests for the lockout of failed logins and the keys they are counted against.
+use std::net::SocketAddr;
+use axum::http::HeaderMap;
+
+use wforum_backend::config::config::LoginConfig;
+use wforum_backend::service::utils::guard::{GuardKey, LoginGuard};
+
+
+// Function to create a guard allowing three failures per account, with lockouts of the given length.
+fn guard(lockout: i64) -> LoginGuard {
+    LoginGuard::new(LoginConfig {
+        max_account_attempts: 3,
+        max_ip_attempts: 10,
+        lockout_base: lockout,
+        lockout_max: lockout,
+    })
+}
+
+
+#[test]
+fn every_failure_starting_a_lockout_reports_it() {
+    // Lockouts expire at once, so every failure past the allowance starts a new one.
+    let guard = guard(0);
+    let key = GuardKey::Account(String::from("alice@test.example"));
+
+    let reported: Vec<Option<i64>> = (0..5).map(|_| guard.record_failure(&key)).collect();
+    assert_eq!(reported, [None, None, Some(0), Some(0), Some(0)]);
+
+    // A successful login starts the count over.
+    guard.record_success(&key);
+    let reported: Vec<Option<i64>> = (0..3).map(|_| guard.record_failure(&key)).collect();
+    assert_eq!(reported, [None, None, Some(0)]);
+}
+
+#[test]
+fn failures_during_a_lockout_are_not_reported_again() {
+    let guard = guard(60);
+    let key = GuardKey::Account(String::from("alice@test.example"));
+
+    // Requests which passed the check before the lockout started still record their failures.
+    let reported: Vec<Option<i64>> = (0..5).map(|_| guard.record_failure(&key)).collect();
+    assert_eq!(reported, [None, None, Some(60), None, None]);
+    assert!(guard.check(&[key]).is_err());
+}
+
+#[test]
+fn clients_are_told_apart_by_the_trusted_header_only() {
+    let peer: SocketAddr = "10.0.0.2:4000".parse().unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("x-forwarded-for", "198.51.100.7, 203.0.113.9".parse().unwrap());
+
+    // The proxy appends the address it saw, so the last one is the client's.
+    assert_eq!(
+        GuardKey::client(&headers, Some("X-Forwarded-For"), Some(peer)),
+        GuardKey::Ip(String::from("203.0.113.9")),
+    );
+    assert_eq!(GuardKey::client(&headers, None, Some(peer)), GuardKey::Ip(String::from("10.0.0.2")));
+
+    headers.insert("x-forwarded-for", "not an address".parse().unwrap());
+    assert_eq!(
+        GuardKey::client(&headers, Some("X-Forwarded-For"), Some(peer)),
+        GuardKey::Ip(String::from("10.0.0.2")),
+    );
+    assert_eq!(GuardKey::client(&HeaderMap::new(), None, None), GuardKey::Ip(String::from("unknown")));
+}
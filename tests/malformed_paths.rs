@@ -0,0 +1,141 @@
+// Tests that every route rejects malformed path segments and query strings with a `400` JSON error.
+// Path and query extractors run before anything touching the database, so no database is needed.
+use std::sync::{Arc, Once};
+use serde_json::Value;
+use utoipa::OpenApi;
+
+use wforum_backend::config::BeContext;
+use wforum_backend::repository::MemoryRepository;
+use wforum_backend::service::openapi::ApiDoc;
+
+
+// The path segments which are plain strings and have nothing to reject.
+const UNTYPED: [&str; 1] = ["provider"];
+
+
+// Function to get a well-formed value of a typed path segment,
+// and the malformed values with the error codes they are rejected with.
+fn segment(name: &str) -> Option<(&'static str, Vec<(String, &'static str)>)> {
+    let ids = |code: &'static str| {
+        ["abc", "0", "-1", "1.5", "99999999999", "1%20", "%F0%9F%92%A9"]
+            .into_iter()
+            .map(|value| (value.to_string(), code))
+            .chain([(String::from("%FF"), "INVALID_PATH")])
+            .collect()
+    };
+
+    match name {
+        "post_id" => Some(("1", ids("INVALID_POST_ID"))),
+        "comment_id" => Some(("1", ids("INVALID_COMMENT_ID"))),
+        "token_id" => Some(("1", ids("INVALID_TOKEN_ID"))),
+        "username" => Some(("alice", vec![
+            (String::from("%20alice"), "INVALID_USERNAME"),
+            (String::from("al%20ice"), "INVALID_USERNAME"),
+            (String::from("alice%0A"), "INVALID_USERNAME"),
+            (String::from("a%2Fb"), "INVALID_USERNAME"),
+            ("a".repeat(256), "INVALID_USERNAME"),
+            (String::from("%FF"), "INVALID_PATH"),
+        ])),
+        _ => None,
+    }
+}
+
+// Function to start the application without a database.
+// It returns the server's base URL.
+async fn server() -> String {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| {
+        for (key, value) in [
+            ("RUST_LOG", "info"),
+            ("BACKEND_PORT", "8000"),
+            ("JWT_SECRET", "test"),
+            ("JWT_EXPIRATION_VALUE", "1"),
+            ("JWT_EXPIRATION_UNIT", "days"),
+            ("DATABASE_URL", "postgresql://unused"),
+        ] {
+            std::env::set_var(key, value);
+        }
+    });
+
+    let ctx = BeContext::new(Arc::new(MemoryRepository::new()));
+    let app = wforum_backend::service::Router::new().with_state(ctx);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    url
+}
+
+// Function to send a request and return the status and the error code of the JSON body.
+async fn send(method: &str, url: &str) -> (u16, String) {
+    let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+    let response = reqwest::Client::new().request(method, url).send().await.unwrap();
+    let status = response.status().as_u16();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+
+    (status, body["code"].as_str().unwrap_or_default().to_string())
+}
+
+
+#[tokio::test]
+async fn every_route_rejects_malformed_path_segments() {
+    let url = server().await;
+    let openapi = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut checked = 0;
+
+    for (path, item) in openapi["paths"].as_object().unwrap() {
+        let params: Vec<&str> = path
+            .split('/')
+            .filter_map(|part| part.strip_prefix('{')?.strip_suffix('}'))
+            .filter(|param| !UNTYPED.contains(param))
+            .collect();
+
+        for param in params.iter() {
+            let (_, malformed) = segment(param).unwrap_or_else(|| {
+                panic!("`{}` in {} needs a typed extractor and a case in `segment`", param, path)
+            });
+
+            for (value, code) in malformed {
+                // Fill in the other segments with well-formed values.
+                let mut target = path.clone();
+                for other in params.iter() {
+                    let replacement = match other == param {
+                        true => value.clone(),
+                        false => segment(other).unwrap().0.to_string(),
+                    };
+                    target = target.replace(&format!("{{{}}}", other), &replacement);
+                }
+
+                for method in item.as_object().unwrap().keys() {
+                    let (status, actual) = send(method, &format!("{}{}", url, target)).await;
+                    assert_eq!(
+                        (status, actual.as_str()), (400, code),
+                        "{} {} with {} = {:?}", method, path, param, value,
+                    );
+                    checked += 1;
+                }
+            }
+        }
+    }
+
+    assert!(checked > 0);
+}
+
+#[tokio::test]
+async fn list_routes_reject_malformed_queries() {
+    let url = server().await;
+
+    for path in ["/api/posts", "/api/posts/history"] {
+        for (query, code) in [
+            ("limit=abc", "INVALID_QUERY"),
+            ("following=maybe", "INVALID_QUERY"),
+            ("limit=0", "VALIDATION_FAILED"),
+            ("limit=100000", "VALIDATION_FAILED"),
+            ("offset=-1", "VALIDATION_FAILED"),
+        ] {
+            let (status, actual) = send("get", &format!("{}{}?{}", url, path, query)).await;
+            assert_eq!((status, actual.as_str()), (400, code), "{}?{}", path, query);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Tests for the Prometheus metrics, using requests which are rejected before touching the database.
+use std::sync::{Arc, Once};
+
+use wforum_backend::config::BeContext;
+use wforum_backend::repository::MemoryRepository;
+use wforum_backend::middleware::metrics::{self, Event, PROMETHEUS};
+use wforum_backend::service::metrics::MetricsRouter;
+
+
+// Function to start the application with the metrics layer and without a database.
+// It returns the server's base URL.
+async fn server() -> String {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| {
+        for (key, value) in [
+            ("JWT_SECRET", "test"),
+            ("DATABASE_URL", "postgresql://unused"),
+        ] {
+            std::env::set_var(key, value);
+        }
+    });
+    lazy_static::initialize(&PROMETHEUS);
+
+    let app = wforum_backend::service::Router::new()
+        .layer(axum::middleware::from_fn(metrics::track))
+        .merge(MetricsRouter::new())
+        .with_state(BeContext::new(Arc::new(MemoryRepository::new())));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    url
+}
+
+// Function to scrape the metrics and return the lines of a metric containing every label.
+async fn scrape(url: &str, name: &str, labels: &[&str]) -> Vec<String> {
+    let response = reqwest::get(format!("{}/metrics", url)).await.unwrap();
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+
+    response
+        .text()
+        .await
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with(&format!("{}{{", name)) || line.starts_with(&format!("{} ", name)))
+        .filter(|line| labels.iter().all(|label| line.contains(label)))
+        .map(String::from)
+        .collect()
+}
+
+
+#[tokio::test]
+async fn requests_are_labelled_by_matched_route() {
+    let url = server().await;
+
+    for path in ["/api/posts/abc", "/api/posts/def", "/no/such/route"] {
+        reqwest::get(format!("{}{}", url, path)).await.unwrap();
+    }
+
+    let matched = scrape(&url, "http_requests_total", &[
+        r#"method="GET""#, r#"route="/api/posts/:post_id""#, r#"status="400""#,
+    ]).await;
+    assert_eq!(matched.len(), 1, "{:?}", matched);
+
+    let unmatched = scrape(&url, "http_requests_total", &[r#"route="unmatched""#, r#"status="404""#]).await;
+    assert_eq!(unmatched.len(), 1, "{:?}", unmatched);
+
+    let latency = scrape(&url, "http_request_duration_seconds_bucket", &[r#"route="/api/posts/:post_id""#]).await;
+    assert!(!latency.is_empty());
+
+    // The raw paths never become labels.
+    assert!(scrape(&url, "http_requests_total", &["abc"]).await.is_empty());
+}
+
+#[tokio::test]
+async fn errors_are_counted_by_variant() {
+    let url = server().await;
+
+    reqwest::get(format!("{}/api/posts/abc", url)).await.unwrap();
+
+    let errors = scrape(&url, "wforum_errors_total", &[r#"variant="BadRequest""#]).await;
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
+
+#[tokio::test]
+async fn domain_events_are_counted() {
+    let url = server().await;
+
+    metrics::record(Event::PostCreated);
+    metrics::record(Event::Signup("password"));
+    metrics::record(Event::Login("oidc"));
+
+    assert_eq!(scrape(&url, "wforum_posts_created_total", &[]).await.len(), 1);
+    assert_eq!(scrape(&url, "wforum_signups_total", &[r#"method="password""#]).await.len(), 1);
+    assert_eq!(scrape(&url, "wforum_logins_total", &[r#"method="oidc""#]).await.len(), 1);
+}
@@ -0,0 +1,205 @@
+// Tests for the OpenID Connect client against a local mock provider.
+use axum::{routing::{get, post}, Form, Json};
+use jsonwebtoken::{EncodingKey, Header};
+use prisma_client_rust::chrono;
+use serde_json::json;
+use std::collections::HashMap;
+
+use wforum_backend::config::config::OidcProviderConfig;
+use wforum_backend::service::auth::oidc::{IdTokenClaims, OidcClient};
+
+
+// The client ID registered with the mock provider.
+const CLIENT_ID: &str = "wforum";
+// The client secret registered with the mock provider, also used to sign its ID tokens.
+const CLIENT_SECRET: &str = "mock-secret";
+// The secret used to sign the `state` parameter.
+const STATE_SECRET: &str = "state-secret";
+// The value of the cookie binding the login to the browser.
+const BINDING: &str = "browser-binding";
+// The authorization code the mock provider accepts.
+const CODE: &str = "valid-code";
+
+
+// Function to start a mock provider which issues ID tokens with the given nonce and audience.
+// It returns the provider's configuration.
+async fn mock_provider(nonce: &'static str, audience: &'static str) -> OidcProviderConfig {
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+    let discovery = json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{}/authorize", issuer),
+        "token_endpoint": format!("{}/token", issuer),
+        "jwks_uri": format!("{}/jwks", issuer),
+    });
+
+    let token_issuer = issuer.clone();
+    let app = axum::Router::new()
+        .route("/.well-known/openid-configuration", get(move || {
+            let discovery = discovery.clone();
+            async move { Json(discovery) }
+        }))
+        .route("/token", post(move |Form(form): Form<HashMap<String, String>>| {
+            let token_issuer = token_issuer.clone();
+            async move {
+                if form.get("code").map(String::as_str) != Some(CODE) {
+                    return (axum::http::StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_grant" })));
+                }
+
+                let claims = json!({
+                    "iss": token_issuer,
+                    "sub": "mock-user-1",
+                    "aud": audience,
+                    "exp": chrono::Utc::now().timestamp() + 300,
+                    "nonce": nonce,
+                    "email": "mock@example.com",
+                    "email_verified": true,
+                    "preferred_username": "mock user!",
+                });
+                let id_token = jsonwebtoken::encode(
+                    &Header::default(), &claims, &EncodingKey::from_secret(CLIENT_SECRET.as_ref()),
+                ).unwrap();
+
+                (axum::http::StatusCode::OK, Json(json!({ "id_token": id_token, "token_type": "Bearer" })))
+            }
+        }));
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    OidcProviderConfig {
+        name: String::from("mock"),
+        issuer,
+        client_id: CLIENT_ID.to_string(),
+        client_secret: CLIENT_SECRET.to_string(),
+        scopes: vec![String::from("openid"), String::from("email")],
+        redirect_uri: String::from("http://localhost/api/auth/oidc/mock/callback"),
+    }
+}
+
+// Function to extract a query parameter from a URL.
+fn query_param(url: &str, name: &str) -> String {
+    reqwest::Url::parse(url).unwrap()
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+
+#[tokio::test]
+async fn login_round_trip_returns_verified_claims() {
+    let provider = mock_provider("nonce-1", CLIENT_ID).await;
+    let client = OidcClient::new();
+
+    let metadata = client.discover(&provider).await.unwrap();
+    assert_eq!(metadata.token_endpoint, format!("{}/token", provider.issuer));
+
+    let url = OidcClient::authorization_url(&metadata, &provider, STATE_SECRET, BINDING).unwrap();
+    assert!(url.starts_with(&format!("{}/authorize?", provider.issuer)));
+    assert_eq!(query_param(&url, "client_id"), CLIENT_ID);
+    assert_eq!(query_param(&url, "scope"), "openid email");
+
+    let nonce = OidcClient::verify_state(&provider, &query_param(&url, "state"), STATE_SECRET, BINDING).unwrap();
+    assert_eq!(nonce, query_param(&url, "nonce"));
+
+    let claims = client.exchange_code(&metadata, &provider, CODE, "nonce-1").await.unwrap();
+    assert_eq!(claims.sub, "mock-user-1");
+    assert_eq!(claims.email.as_deref(), Some("mock@example.com"));
+    assert_eq!(claims.email_verified, Some(true));
+    assert_eq!(OidcClient::username_candidate(&claims, 3, 32), "mock_user");
+}
+
+#[tokio::test]
+async fn rejects_mismatched_nonce() {
+    let provider = mock_provider("nonce-2", CLIENT_ID).await;
+    let client = OidcClient::new();
+    let metadata = client.discover(&provider).await.unwrap();
+
+    assert!(client.exchange_code(&metadata, &provider, CODE, "other-nonce").await.is_err());
+}
+
+#[tokio::test]
+async fn rejects_wrong_audience() {
+    let provider = mock_provider("nonce-3", "another-client").await;
+    let client = OidcClient::new();
+    let metadata = client.discover(&provider).await.unwrap();
+
+    assert!(client.exchange_code(&metadata, &provider, CODE, "nonce-3").await.is_err());
+}
+
+#[tokio::test]
+async fn rejects_invalid_code() {
+    let provider = mock_provider("nonce-4", CLIENT_ID).await;
+    let client = OidcClient::new();
+    let metadata = client.discover(&provider).await.unwrap();
+
+    assert!(client.exchange_code(&metadata, &provider, "bad-code", "nonce-4").await.is_err());
+}
+
+#[tokio::test]
+async fn rejects_state_for_another_provider() {
+    let provider = mock_provider("nonce-5", CLIENT_ID).await;
+    let client = OidcClient::new();
+    let metadata = client.discover(&provider).await.unwrap();
+
+    let url = OidcClient::authorization_url(&metadata, &provider, STATE_SECRET, BINDING).unwrap();
+    let other = OidcProviderConfig { name: String::from("other"), ..provider.clone() };
+    let state = query_param(&url, "state");
+
+    assert!(OidcClient::verify_state(&other, &state, STATE_SECRET, BINDING).is_err());
+    assert!(OidcClient::verify_state(&provider, &state, "wrong-secret", BINDING).is_err());
+}
+
+#[tokio::test]
+async fn rejects_state_from_another_browser() {
+    let provider = mock_provider("nonce-6", CLIENT_ID).await;
+    let client = OidcClient::new();
+    let metadata = client.discover(&provider).await.unwrap();
+
+    let url = OidcClient::authorization_url(&metadata, &provider, STATE_SECRET, BINDING).unwrap();
+
+    assert!(OidcClient::verify_state(&provider, &query_param(&url, "state"), STATE_SECRET, "other-browser").is_err());
+}
+
+#[test]
+fn username_candidate_falls_back_to_email_and_default() {
+    let mut claims = IdTokenClaims {
+        iss: String::new(),
+        sub: String::from("1"),
+        nonce: None,
+        email: Some(String::from("jane.doe@example.com")),
+        email_verified: Some(true),
+        preferred_username: None,
+        name: None,
+        picture: None,
+    };
+    assert_eq!(OidcClient::username_candidate(&claims, 3, 32), "jane_doe");
+
+    claims.email = None;
+    assert_eq!(OidcClient::username_candidate(&claims, 3, 32), "user");
+}
+
+#[test]
+fn username_candidate_fits_the_username_limits() {
+    let mut claims = IdTokenClaims {
+        iss: String::new(),
+        sub: String::from("1"),
+        nonce: None,
+        email: None,
+        email_verified: Some(true),
+        preferred_username: Some(String::from("a")),
+        name: None,
+        picture: None,
+    };
+    let candidate = OidcClient::username_candidate(&claims, 3, 32);
+    assert_eq!(candidate.len(), 3);
+    assert!(candidate.starts_with('a') && candidate[1..].chars().all(|c| c.is_ascii_digit()));
+
+    claims.preferred_username = Some(String::from(".."));
+    assert_eq!(OidcClient::username_candidate(&claims, 2, 32), "__");
+
+    claims.preferred_username = Some(String::from("a.very.long.name"));
+    assert_eq!(OidcClient::username_candidate(&claims, 3, 8), "a_very_l");
+}
@@ -0,0 +1,127 @@
+// Tests that every route is described by the OpenAPI document.
+use std::collections::BTreeSet;
+use serde_json::Value;
+use utoipa::OpenApi;
+
+use wforum_backend::service::health::HealthRouter;
+use wforum_backend::service::metrics::MetricsRouter;
+use wforum_backend::service::openapi::ApiDoc;
+use wforum_backend::service::route::Route;
+use wforum_backend::service::Router;
+
+
+// The routes which are deliberately left out of the OpenAPI document.
+const UNDOCUMENTED: [(&str, &str); 7] = [
+    ("get", "/"),
+    ("get", "/api/openapi.json"),
+    ("get", "/api/docs"),
+    // The probes and the scrape endpoint are for orchestrators and Prometheus, not API clients.
+    ("get", "/healthz"),
+    ("get", "/readyz"),
+    ("get", "/version"),
+    ("get", "/metrics"),
+];
+
+
+// Function to list every route the application serves, from the same tables that build its routers.
+fn routes() -> Vec<Route> {
+    let mut routes = Router::routes();
+    routes.extend(HealthRouter::routes());
+    routes.extend(MetricsRouter::routes());
+    routes
+}
+
+// Function to convert a route to a `(method, OpenAPI path)` pair.
+fn openapi_route(route: &Route) -> (String, String) {
+    let path = route
+        .path
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(param) => format!("{{{}}}", param),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    (route.method.as_str().to_lowercase(), path)
+}
+
+// Function to collect the documented routes as `(method, OpenAPI path)` pairs.
+fn registered_routes() -> BTreeSet<(String, String)> {
+    routes().iter().filter(|route| route.documented).map(openapi_route).collect()
+}
+
+// Function to get the OpenAPI document as JSON.
+fn openapi() -> Value {
+    serde_json::to_value(ApiDoc::openapi()).unwrap()
+}
+
+// Function to collect every `$ref` in a JSON value.
+fn collect_refs(value: &Value, refs: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                refs.insert(reference.clone());
+            }
+            map.values().for_each(|value| collect_refs(value, refs));
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_refs(value, refs)),
+        _ => {}
+    }
+}
+
+
+#[test]
+fn every_route_is_documented() {
+    let openapi = openapi();
+    let undocumented: Vec<_> = registered_routes()
+        .into_iter()
+        .filter(|(method, path)| openapi["paths"][path][method].is_null())
+        .collect();
+
+    assert!(
+        undocumented.is_empty(),
+        "routes without a `#[utoipa::path]` in their service's `docs` module and in `ApiDoc`: {:?}", undocumented,
+    );
+}
+
+#[test]
+fn only_the_expected_routes_are_undocumented() {
+    let undocumented: BTreeSet<_> = routes().iter().filter(|route| !route.documented).map(openapi_route).collect();
+    let expected: BTreeSet<_> = UNDOCUMENTED
+        .iter()
+        .map(|(method, path)| (method.to_string(), path.to_string()))
+        .collect();
+
+    assert_eq!(undocumented, expected);
+}
+
+#[test]
+fn every_documented_route_exists() {
+    let routes = registered_routes();
+    let openapi = openapi();
+
+    for (path, item) in openapi["paths"].as_object().unwrap() {
+        for method in item.as_object().unwrap().keys() {
+            assert!(
+                routes.contains(&(method.clone(), path.clone())),
+                "documented route is not registered: {} {}", method, path,
+            );
+        }
+    }
+}
+
+#[test]
+fn every_referenced_schema_is_registered() {
+    let openapi = openapi();
+    let mut refs = BTreeSet::new();
+    collect_refs(&openapi, &mut refs);
+
+    for reference in refs {
+        let name = reference.strip_prefix("#/components/schemas/").unwrap();
+        assert!(
+            !openapi["components"]["schemas"][name].is_null(),
+            "schema is referenced but not registered in `ApiDoc`: {}", name,
+        );
+    }
+}
@@ -0,0 +1,98 @@
+// Tests for the server settings: CORS and the request body limit.
+use std::sync::{Arc, Once};
+use axum::extract::DefaultBodyLimit;
+use axum::routing::post;
+use serde_json::{json, Value};
+
+use wforum_backend::config::config::CorsConfig;
+use wforum_backend::config::{BeContext, CONFIG};
+use wforum_backend::extractor::validate::ValidJson;
+use wforum_backend::middleware::cors;
+use wforum_backend::repository::MemoryRepository;
+use wforum_backend::service::user::model::{CreateUserPost, UserBody};
+
+
+// Function to start a server whose single route accepts a JSON body, behind the given CORS settings.
+// It returns the server's base URL.
+async fn server(cors_config: CorsConfig) -> String {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| {
+        for (key, value) in [
+            ("JWT_SECRET", "test"),
+            ("DATABASE_URL", "postgresql://unused"),
+            ("BODY_LIMIT", "256"),
+        ] {
+            std::env::set_var(key, value);
+        }
+    });
+
+    let app = axum::Router::new()
+        .route("/users", post(|ValidJson(_): ValidJson<UserBody<CreateUserPost>>| async { "created" }))
+        .layer(DefaultBodyLimit::max(CONFIG.server_config.body_limit))
+        .layer(cors::layer(&cors_config))
+        .with_state(BeContext::new(Arc::new(MemoryRepository::new())));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    url
+}
+
+// Function to send a CORS preflight request from `origin` and return the response headers.
+async fn preflight(url: &str, origin: &str) -> reqwest::header::HeaderMap {
+    reqwest::Client::new()
+        .request(reqwest::Method::OPTIONS, format!("{}/users", url))
+        .header("origin", origin)
+        .header("access-control-request-method", "POST")
+        .header("access-control-request-headers", "authorization, content-type")
+        .send()
+        .await
+        .unwrap()
+        .headers()
+        .clone()
+}
+
+
+#[tokio::test]
+async fn any_origin_is_allowed_by_default() {
+    let url = server(CorsConfig { allowed_origins: vec![String::from("*")], allow_credentials: false }).await;
+
+    let headers = preflight(&url, "https://anywhere.example").await;
+    assert_eq!(headers["access-control-allow-origin"], "*");
+    assert!(headers.get("access-control-allow-credentials").is_none());
+}
+
+#[tokio::test]
+async fn only_listed_origins_are_allowed_with_credentials() {
+    let url = server(CorsConfig {
+        allowed_origins: vec![String::from("https://forum.example")],
+        allow_credentials: true,
+    }).await;
+
+    let headers = preflight(&url, "https://forum.example").await;
+    assert_eq!(headers["access-control-allow-origin"], "https://forum.example");
+    assert_eq!(headers["access-control-allow-credentials"], "true");
+    assert_eq!(headers["access-control-allow-headers"], "authorization, content-type");
+
+    let headers = preflight(&url, "https://evil.example").await;
+    assert!(headers.get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn bodies_over_the_limit_are_rejected() {
+    let url = server(CorsConfig { allowed_origins: vec![String::from("*")], allow_credentials: false }).await;
+    let client = reqwest::Client::new();
+
+    let small = json!({ "user": { "email": "ann@example.com", "username": "ann", "password": "hunter22" } });
+    let response = client.post(format!("{}/users", url)).json(&small).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let large = json!({ "user": { "email": "ann@example.com", "username": "ann", "password": "a".repeat(300) } });
+    let response = client.post(format!("{}/users", url)).json(&large).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 413);
+
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "PAYLOAD_TOO_LARGE");
+    assert_eq!(body["details"]["limit"], 256);
+}
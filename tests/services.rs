@@ -0,0 +1,710 @@
+// Tests for the business rules of the services, against the in-memory repository.
+// Users are created in the repository and authenticated with access JWTs, so no database is needed.
+use std::sync::{Arc, Once};
+use prisma_client_rust::chrono::{self, DateTime, FixedOffset};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use wforum_backend::config::BeContext;
+use wforum_backend::extractor::extractor::AuthUser;
+use wforum_backend::repository::MemoryRepository;
+use wforum_backend::service::user::service::UsersService;
+
+
+// The `TestApp` struct which represents the application served against its own in-memory repository.
+struct TestApp {
+    // The base URL of the API.
+    base: String,
+    // The application context, to create users in the repository.
+    ctx: BeContext,
+    // The HTTP client.
+    client: reqwest::Client,
+}
+
+// The `TestUser` struct which represents a user created in the repository.
+struct TestUser {
+    // The ID of the user.
+    user_id: i32,
+    // The username of the user.
+    username: String,
+    // The access JWT of the user.
+    token: String,
+}
+
+
+// Implementation of the `TestApp` struct.
+impl TestApp {
+    // Function to start the application with an empty repository and cache.
+    async fn spawn() -> Self {
+        static ENV: Once = Once::new();
+        ENV.call_once(|| {
+            for (key, value) in [
+                ("RUST_LOG", "info"),
+                ("BACKEND_PORT", "8000"),
+                ("JWT_SECRET", "test"),
+                ("JWT_EXPIRATION_VALUE", "1"),
+                ("JWT_EXPIRATION_UNIT", "days"),
+                ("DATABASE_URL", "postgresql://unused"),
+            ] {
+                std::env::set_var(key, value);
+            }
+        });
+
+        // Every test has its own context, so post IDs restarting in every repository never meet a shared cache.
+        let ctx = BeContext::new(Arc::new(MemoryRepository::new()));
+
+        let app = wforum_backend::service::Router::new().with_state(ctx.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/api", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        Self { base, ctx, client: reqwest::Client::new() }
+    }
+
+    // Function to create a user in the repository and sign a token for them.
+    async fn user(&self, username: &str) -> TestUser {
+        let user = self.ctx.repository
+            .create_user(format!("{}@test.example", username), username.to_string(), String::from("unused"))
+            .await
+            .unwrap();
+
+        TestUser {
+            user_id: user.user_id,
+            username: user.username,
+            token: AuthUser { user_id: user.user_id }.gen_jwt(&self.ctx),
+        }
+    }
+
+    // Function to send a request and return the status and the JSON body.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        user: Option<&TestUser>,
+        body: Option<Value>,
+    ) -> (u16, Value) {
+        let mut request = self.client.request(method, format!("{}{}", self.base, path));
+        if let Some(user) = user {
+            request = request.bearer_auth(&user.token);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request.send().await.unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+
+    // Function to create a post as a user and return its ID.
+    async fn create_post(&self, user: &TestUser, title: &str) -> i64 {
+        let (status, body) = self.send(reqwest::Method::POST, "/posts", Some(user), Some(json!({
+            "post": { "title": title, "description": "A description", "content": "Some content" },
+        }))).await;
+        assert_eq!(status, 200, "{}", body);
+
+        body["post"]["postId"].as_i64().unwrap()
+    }
+
+    // Function to create an API token with the given scopes for a user,
+    // and return the user authenticating with it and the token's ID.
+    async fn api_token(&self, user: &TestUser, scopes: &[&str]) -> (TestUser, i64) {
+        let (status, body) = self.send(reqwest::Method::POST, "/users/tokens", Some(user), Some(json!({
+            "apiToken": { "name": "script", "scopes": scopes },
+        }))).await;
+        assert_eq!(status, 200, "{}", body);
+
+        let token = TestUser {
+            user_id: user.user_id,
+            username: user.username.clone(),
+            token: body["apiToken"]["token"].as_str().unwrap().to_string(),
+        };
+        (token, body["apiToken"]["tokenId"].as_i64().unwrap())
+    }
+
+    // Function to enroll a user in two-factor authentication and return their recovery codes.
+    async fn enable_two_factor(&self, user: &TestUser) -> Vec<String> {
+        let (status, body) = self.send(reqwest::Method::POST, "/auth/2fa/enroll", Some(user), None).await;
+        assert_eq!(status, 200, "{}", body);
+
+        let secret = Secret::Encoded(body["twoFactor"]["secret"].as_str().unwrap().to_string());
+        let totp = TOTP::new(Algorithm::SHA1, 6, 1, 30, secret.to_bytes().unwrap(), None, user.username.clone())
+            .unwrap();
+
+        let (status, body) = self.send(reqwest::Method::POST, "/auth/2fa/confirm", Some(user), Some(json!({
+            "twoFactor": { "code": totp.generate_current().unwrap() },
+        }))).await;
+        assert_eq!(status, 200, "{}", body);
+
+        serde_json::from_value(body["twoFactor"]["recoveryCodes"].clone()).unwrap()
+    }
+}
+
+
+#[tokio::test]
+async fn users_cannot_follow_themselves() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+
+    let (status, body) = app.send(
+        reqwest::Method::POST, &format!("/profiles/{}/follow", alice.username), Some(&alice), None,
+    ).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "CANNOT_TARGET_SELF");
+}
+
+#[tokio::test]
+async fn blocking_removes_follows_both_ways() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/follow", alice.username), Some(&bob), None).await;
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/follow", bob.username), Some(&alice), None).await;
+
+    let (status, body) = app.send(
+        reqwest::Method::POST, &format!("/profiles/{}/block", bob.username), Some(&alice), None,
+    ).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["profile"]["blocking"], true);
+    assert_eq!(body["profile"]["following"], false);
+
+    let (_, body) = app.send(
+        reqwest::Method::GET, &format!("/profiles/{}", alice.username), Some(&bob), None,
+    ).await;
+    assert_eq!(body["profile"]["following"], false);
+    assert_eq!(body["profile"]["followed"], false);
+    assert_eq!(body["profile"]["blocked"], true);
+
+    let (status, body) = app.send(
+        reqwest::Method::POST, &format!("/profiles/{}/follow", alice.username), Some(&bob), None,
+    ).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "BLOCKED_BY_USER");
+}
+
+#[tokio::test]
+async fn blocked_users_cannot_like_or_comment() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let post_id = app.create_post(&alice, "Not for Bob").await;
+
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/block", bob.username), Some(&alice), None).await;
+
+    let (status, body) = app.send(
+        reqwest::Method::POST, &format!("/posts/{}/like", post_id), Some(&bob), None,
+    ).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "BLOCKED_BY_AUTHOR");
+
+    let (status, body) = app.send(
+        reqwest::Method::POST, &format!("/posts/{}/comments", post_id), Some(&bob),
+        Some(json!({ "comment": { "content": "Let me in" } })),
+    ).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "BLOCKED_BY_AUTHOR");
+}
+
+#[tokio::test]
+async fn likes_are_counted_once() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let like = format!("/posts/{}/like", app.create_post(&alice, "Worth a like").await);
+
+    let (status, body) = app.send(reqwest::Method::POST, &like, Some(&bob), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["post"]["likedCount"], 1);
+
+    let (status, body) = app.send(reqwest::Method::POST, &like, Some(&bob), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "ALREADY_LIKED");
+
+    let (status, body) = app.send(reqwest::Method::DELETE, &like, Some(&bob), None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["post"]["likedCount"], 0);
+}
+
+#[tokio::test]
+async fn usernames_are_unique() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+
+    let (status, body) = app.send(reqwest::Method::POST, "/users/create", None, Some(json!({
+        "user": { "email": "another@test.example", "username": alice.username, "password": "password123" },
+    }))).await;
+    assert_eq!(status, 409);
+    assert_eq!(body["code"], "CONFLICT");
+}
+
+#[tokio::test]
+async fn muted_users_are_hidden_from_the_muter_only() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let carol = app.user("carol").await;
+    let post_id = app.create_post(&alice, "Alice speaks").await;
+    app.create_post(&bob, "Bob speaks").await;
+    let comments = format!("/posts/{}/comments", post_id);
+    let comment = json!({ "comment": { "content": "Hi" } });
+    app.send(reqwest::Method::POST, &comments, Some(&bob), Some(comment)).await;
+
+    let mute = format!("/profiles/{}/mute", bob.username);
+    let (status, body) = app.send(reqwest::Method::POST, &mute, Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["mute"]["profile"]["username"], bob.username.as_str());
+    assert_eq!(body["mute"]["expiresAt"], Value::Null);
+
+    let (_, body) = app.send(reqwest::Method::GET, "/posts", Some(&alice), None).await;
+    assert_eq!(body["posts"].as_array().unwrap().len(), 1);
+    assert_eq!(body["posts"][0]["title"], "Alice speaks");
+    let (_, body) = app.send(reqwest::Method::GET, &comments, Some(&alice), None).await;
+    assert_eq!(body["comments"], json!([]));
+
+    // Everyone else, Bob included, still sees Bob's posts and comments.
+    for user in [&bob, &carol] {
+        let (_, body) = app.send(reqwest::Method::GET, "/posts", Some(user), None).await;
+        assert_eq!(body["posts"].as_array().unwrap().len(), 2);
+        let (_, body) = app.send(reqwest::Method::GET, &comments, Some(user), None).await;
+        assert_eq!(body["comments"].as_array().unwrap().len(), 1);
+    }
+
+    let (_, body) = app.send(reqwest::Method::GET, "/users/mutes", Some(&alice), None).await;
+    assert_eq!(body["mutes"][0]["profile"]["username"], bob.username.as_str());
+    let (_, body) = app.send(reqwest::Method::GET, "/users/mutes", Some(&bob), None).await;
+    assert_eq!(body["mutes"], json!([]));
+
+    let (status, _) = app.send(reqwest::Method::DELETE, &mute, Some(&alice), None).await;
+    assert_eq!(status, 200);
+    let (_, body) = app.send(reqwest::Method::GET, "/posts", Some(&alice), None).await;
+    assert_eq!(body["posts"].as_array().unwrap().len(), 2);
+
+    let (status, body) = app.send(reqwest::Method::DELETE, &mute, Some(&alice), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "NOT_MUTING");
+}
+
+#[tokio::test]
+async fn mutes_expire() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    app.create_post(&bob, "Back soon").await;
+
+    let mute = format!("/profiles/{}/mute?expiresInDays=7", bob.username);
+    let (status, body) = app.send(reqwest::Method::POST, &mute, Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert!(body["mute"]["expiresAt"].is_string());
+
+    let (_, body) = app.send(reqwest::Method::GET, "/posts", Some(&alice), None).await;
+    assert_eq!(body["posts"], json!([]));
+
+    // A mute which has run out no longer hides anything.
+    let expired: DateTime<FixedOffset> = (chrono::Utc::now() - chrono::Duration::minutes(1)).into();
+    app.ctx.repository.mute(alice.user_id, bob.user_id, Some(expired)).await.unwrap();
+
+    let (_, body) = app.send(reqwest::Method::GET, "/posts", Some(&alice), None).await;
+    assert_eq!(body["posts"].as_array().unwrap().len(), 1);
+    let (_, body) = app.send(reqwest::Method::GET, "/users/mutes", Some(&alice), None).await;
+    assert_eq!(body["mutes"], json!([]));
+}
+
+#[tokio::test]
+async fn mutes_are_checked() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+
+    let (status, body) = app.send(
+        reqwest::Method::POST, &format!("/profiles/{}/mute", alice.username), Some(&alice), None,
+    ).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "CANNOT_TARGET_SELF");
+
+    let mute = format!("/profiles/{}/mute?expiresInDays=0", bob.username);
+    let (status, body) = app.send(reqwest::Method::POST, &mute, Some(&alice), None).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+
+    let mute = "/profiles/nobody_by_this_name/mute";
+    assert_eq!(app.send(reqwest::Method::POST, mute, Some(&alice), None).await.0, 404);
+}
+
+#[tokio::test]
+async fn blocks_hide_content_both_ways() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let carol = app.user("carol").await;
+    let alice_post = format!("/posts/{}", app.create_post(&alice, "Alice speaks").await);
+    let bob_post = format!("/posts/{}", app.create_post(&bob, "Bob speaks").await);
+    let comments = format!("/posts/{}/comments", app.create_post(&carol, "Carol speaks").await);
+    for user in [&alice, &bob] {
+        let comment = json!({ "comment": { "content": format!("Hi from {}", user.username) } });
+        app.send(reqwest::Method::POST, &comments, Some(user), Some(comment)).await;
+    }
+    let intro = json!({ "user": { "intro": "Hello" } });
+    app.send(reqwest::Method::PUT, "/users", Some(&alice), Some(intro)).await;
+    app.send(reqwest::Method::GET, &alice_post, Some(&bob), None).await;
+
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/block", bob.username), Some(&alice), None).await;
+
+    // Neither side sees the other's posts, everyone else sees them all.
+    for (user, count) in [(&alice, 2), (&bob, 2), (&carol, 3)] {
+        let (_, body) = app.send(reqwest::Method::GET, "/posts", Some(user), None).await;
+        assert_eq!(body["posts"].as_array().unwrap().len(), count, "{}", user.username);
+    }
+
+    let (_, body) = app.send(reqwest::Method::GET, "/posts/history", Some(&bob), None).await;
+    assert_eq!(body["posts"], json!([]));
+
+    let (_, body) = app.send(reqwest::Method::GET, &comments, Some(&alice), None).await;
+    let (own, blocked) = (&body["comments"][0], &body["comments"][1]);
+    assert_eq!((&own["collapsed"], &own["content"]), (&json!(false), &json!("Hi from alice")));
+    assert_eq!((&blocked["collapsed"], &blocked["content"]), (&json!(true), &json!("")));
+    assert_eq!(body["comments"][1]["user"]["username"], bob.username.as_str());
+    let (_, body) = app.send(reqwest::Method::GET, &comments, Some(&carol), None).await;
+    assert_eq!(body["comments"][1]["collapsed"], false);
+
+    // The blocked user cannot read the blocker's posts or profile; the blocker still can read theirs.
+    let (status, body) = app.send(reqwest::Method::GET, &alice_post, Some(&bob), None).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "BLOCKED_BY_AUTHOR");
+    assert_eq!(app.send(reqwest::Method::GET, &bob_post, Some(&alice), None).await.0, 200);
+
+    let profile = format!("/profiles/{}", alice.username);
+    let (_, body) = app.send(reqwest::Method::GET, &profile, Some(&bob), None).await;
+    assert_eq!((&body["profile"]["blocked"], &body["profile"]["intro"]), (&json!(true), &Value::Null));
+    let (_, body) = app.send(reqwest::Method::GET, &profile, Some(&carol), None).await;
+    assert_eq!(body["profile"]["intro"], "Hello");
+}
+
+#[tokio::test]
+async fn follows_are_listed_and_counted() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let carol = app.user("carol").await;
+    let profile = format!("/profiles/{}", alice.username);
+
+    // An anonymous read is cached, following must drop it.
+    let (_, body) = app.send(reqwest::Method::GET, &profile, None, None).await;
+    assert_eq!(body["profile"]["followerCount"], 0);
+
+    for user in [&carol, &bob] {
+        let (status, _) = app.send(reqwest::Method::POST, &format!("{}/follow", profile), Some(user), None).await;
+        assert_eq!(status, 200);
+    }
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/follow", bob.username), Some(&alice), None).await;
+    app.create_post(&alice, "First").await;
+
+    let (_, body) = app.send(reqwest::Method::GET, &profile, None, None).await;
+    let profile_body = &body["profile"];
+    let counts = (&profile_body["followerCount"], &profile_body["followingCount"], &profile_body["postCount"]);
+    assert_eq!(counts, (&json!(2), &json!(1), &json!(1)));
+
+    // Followers are listed by username, with the viewer's flags.
+    let followers = format!("{}/followers", profile);
+    let (status, body) = app.send(reqwest::Method::GET, &followers, Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["profileCount"], 2);
+    let usernames: Vec<&Value> = body["profiles"].as_array().unwrap().iter().map(|p| &p["username"]).collect();
+    assert_eq!(usernames, [&json!("bob"), &json!("carol")]);
+    assert_eq!((&body["profiles"][0]["followed"], &body["profiles"][0]["following"]), (&json!(true), &json!(true)));
+    assert_eq!(body["profiles"][0].get("followerCount"), None);
+
+    let (_, body) = app.send(reqwest::Method::GET, &format!("{}?limit=1&offset=1", followers), None, None).await;
+    assert_eq!((&body["profileCount"], &body["profiles"][0]["username"]), (&json!(2), &json!("carol")));
+
+    let (_, body) = app.send(reqwest::Method::GET, &format!("{}/following", profile), None, None).await;
+    assert_eq!((&body["profileCount"], &body["profiles"][0]["username"]), (&json!(1), &json!("bob")));
+
+    let (status, _) = app.send(reqwest::Method::GET, &format!("{}?limit=0", followers), None, None).await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn blocks_are_listed_privately() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/block", bob.username), Some(&alice), None).await;
+
+    let (status, body) = app.send(reqwest::Method::GET, "/users/blocks", Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!((&body["profileCount"], &body["profiles"][0]["username"]), (&json!(1), &json!("bob")));
+    assert_eq!(body["profiles"][0]["blocking"], true);
+
+    let (_, body) = app.send(reqwest::Method::GET, "/users/blocks", Some(&bob), None).await;
+    assert_eq!(body["profiles"], json!([]));
+    assert_eq!(app.send(reqwest::Method::GET, "/users/blocks", None, None).await.0, 401);
+
+    // The blocked user cannot list the blocker's follows either.
+    let path = format!("/profiles/{}/followers", alice.username);
+    let (status, body) = app.send(reqwest::Method::GET, &path, Some(&bob), None).await;
+    assert_eq!((status, &body["code"]), (403, &json!("BLOCKED_BY_USER")));
+}
+
+#[tokio::test]
+async fn private_accounts_approve_their_followers() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let carol = app.user("carol").await;
+    let follow = format!("/profiles/{}/follow", alice.username);
+
+    let private = json!({ "user": { "private": true } });
+    let (status, body) = app.send(reqwest::Method::PUT, "/users", Some(&alice), Some(private)).await;
+    assert_eq!((status, &body["user"]["private"]), (200, &json!(true)));
+    let post = format!("/posts/{}", app.create_post(&alice, "Private").await);
+
+    // Following asks instead, and the posts stay hidden until the request is accepted.
+    let (status, body) = app.send(reqwest::Method::POST, &follow, Some(&bob), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!((&body["profile"]["following"], &body["profile"]["requesting"]), (&json!(false), &json!(true)));
+    let (status, body) = app.send(reqwest::Method::POST, &follow, Some(&bob), None).await;
+    assert_eq!((status, &body["code"]), (400, &json!("ALREADY_REQUESTED")));
+
+    for user in [None, Some(&bob)] {
+        let (status, body) = app.send(reqwest::Method::GET, &post, user, None).await;
+        assert_eq!((status, &body["code"]), (403, &json!("PRIVATE_PROFILE")));
+        let (_, body) = app.send(reqwest::Method::GET, "/posts", user, None).await;
+        assert_eq!(body["posts"], json!([]));
+    }
+    assert_eq!(app.send(reqwest::Method::GET, &post, Some(&alice), None).await.0, 200);
+
+    let (_, body) = app.send(reqwest::Method::GET, "/users/follow-requests", Some(&alice), None).await;
+    assert_eq!(body["requests"][0]["profile"]["username"], "bob");
+    assert_eq!(body["requests"][0]["profile"]["requested"], true);
+
+    let (status, body) = app.send(reqwest::Method::POST, "/users/follow-requests/bob", Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!((&body["profile"]["followed"], &body["profile"]["requested"]), (&json!(true), &json!(false)));
+    assert_eq!(app.send(reqwest::Method::GET, &post, Some(&bob), None).await.0, 200);
+    let (_, body) = app.send(reqwest::Method::GET, "/posts", Some(&bob), None).await;
+    assert_eq!(body["posts"].as_array().unwrap().len(), 1);
+
+    // Rejected requests are dropped; going public lets everyone still waiting in.
+    app.send(reqwest::Method::POST, &follow, Some(&carol), None).await;
+    let (status, body) = app.send(reqwest::Method::DELETE, "/users/follow-requests/carol", Some(&alice), None).await;
+    assert_eq!((status, &body["profile"]["requested"]), (200, &json!(false)));
+    let (status, body) = app.send(reqwest::Method::DELETE, "/users/follow-requests/carol", Some(&alice), None).await;
+    assert_eq!((status, &body["code"]), (400, &json!("NOT_REQUESTED")));
+
+    app.send(reqwest::Method::POST, &follow, Some(&carol), None).await;
+    let public = json!({ "user": { "private": false } });
+    app.send(reqwest::Method::PUT, "/users", Some(&alice), Some(public)).await;
+    let profile = format!("/profiles/{}", alice.username);
+    let (_, body) = app.send(reqwest::Method::GET, &profile, Some(&carol), None).await;
+    assert_eq!((&body["profile"]["following"], &body["profile"]["requesting"]), (&json!(true), &json!(false)));
+}
+
+#[tokio::test]
+async fn suspended_users_lose_their_sessions() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+
+    let (status, body) = app.send(reqwest::Method::GET, "/users", Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+
+    app.ctx.repository.set_suspended(alice.user_id, Some(chrono::Utc::now().into())).await.unwrap();
+
+    let (status, body) = app.send(reqwest::Method::GET, "/users", Some(&alice), None).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "ACCOUNT_SUSPENDED");
+
+    // Reading without logging in would hide the suspension instead of enforcing it.
+    let (status, body) = app.send(reqwest::Method::GET, "/posts", Some(&alice), None).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "ACCOUNT_SUSPENDED");
+
+    app.ctx.repository.set_suspended(alice.user_id, None).await.unwrap();
+
+    let (status, body) = app.send(reqwest::Method::GET, "/users", Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+}
+
+
+#[tokio::test]
+async fn post_listings_carry_no_last_modified() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let post_id = app.create_post(&alice, "A post").await;
+
+    // Deleting the newest post would move a listing's date backwards, so only single posts are dated.
+    let response = app.client.get(format!("{}/posts", app.base)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("last-modified").is_none());
+
+    let response = app.client.get(format!("{}/posts/{}", app.base, post_id)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("last-modified").is_some());
+}
+
+
+#[tokio::test]
+async fn comments_are_paged_with_the_viewers_relations() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let carol = app.user("carol").await;
+    let comments = format!("/posts/{}/comments", app.create_post(&alice, "Discuss").await);
+    for user in [&bob, &carol, &bob] {
+        let comment = json!({ "comment": { "content": format!("Hi from {}", user.username) } });
+        app.send(reqwest::Method::POST, &comments, Some(user), Some(comment)).await;
+    }
+
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/follow", bob.username), Some(&alice), None).await;
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/follow", alice.username), Some(&carol), None).await;
+
+    let (status, body) = app.send(reqwest::Method::GET, &format!("{}?limit=2", comments), Some(&alice), None).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["commentCount"], 3);
+    assert_eq!(body["comments"].as_array().unwrap().len(), 2);
+    assert_eq!(body["comments"][0]["user"]["following"], true);
+    assert_eq!(body["comments"][1]["user"]["following"], false);
+    assert_eq!(body["comments"][1]["user"]["followed"], true);
+
+    let (_, body) = app.send(reqwest::Method::GET, &format!("{}?offset=2", comments), Some(&alice), None).await;
+    assert_eq!(body["comments"][0]["user"]["username"], bob.username.as_str());
+
+    // Muted users' comments are left out of the count as well as the page.
+    app.send(reqwest::Method::POST, &format!("/profiles/{}/mute", bob.username), Some(&alice), None).await;
+    let (_, body) = app.send(reqwest::Method::GET, &comments, Some(&alice), None).await;
+    assert_eq!(body["commentCount"], 1);
+    assert_eq!(body["comments"][0]["user"]["username"], carol.username.as_str());
+
+    let (status, body) = app.send(reqwest::Method::GET, &format!("{}?limit=0", comments), None, None).await;
+    assert_eq!(status, 400, "{}", body);
+}
+
+
+#[tokio::test]
+async fn locked_accounts_are_notified_once() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let login = json!({ "user": { "email": "alice@test.example", "password": "wrong password" } });
+
+    for _ in 0..5 {
+        let (status, body) = app.send(reqwest::Method::POST, "/users", None, Some(login.clone())).await;
+        assert_eq!(status, 401, "{}", body);
+    }
+
+    let (status, body) = app.send(reqwest::Method::POST, "/users", None, Some(login)).await;
+    assert_eq!(status, 429, "{}", body);
+
+    let (_, body) = app.send(reqwest::Method::GET, "/users/notifications", Some(&alice), None).await;
+    assert_eq!(body["notifications"].as_array().unwrap().len(), 1);
+}
+
+
+#[tokio::test]
+async fn api_tokens_are_limited_to_their_scopes() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    let (token, _) = app.api_token(&alice, &["read"]).await;
+
+    let (status, body) = app.send(reqwest::Method::GET, "/posts", Some(&token), None).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, body) = app.send(reqwest::Method::POST, "/posts", Some(&token), Some(json!({
+        "post": { "title": "A title", "description": "A description", "content": "Some content" },
+    }))).await;
+    assert_eq!(status, 403);
+    assert_eq!(body["code"], "MISSING_SCOPE");
+
+    // Routes declaring no scope, such as account management, never accept API tokens.
+    for path in ["/users", "/users/tokens"] {
+        let (status, body) = app.send(reqwest::Method::GET, path, Some(&token), None).await;
+        assert_eq!(status, 403, "{}", path);
+        assert_eq!(body["code"], "TOKEN_NOT_ALLOWED");
+    }
+}
+
+#[tokio::test]
+async fn revoked_and_expired_api_tokens_are_refused() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+
+    let (token, token_id) = app.api_token(&alice, &["read"]).await;
+    let revoke = format!("/users/tokens/{}", token_id);
+    assert_eq!(app.send(reqwest::Method::DELETE, &revoke, Some(&alice), None).await.0, 200);
+
+    let (status, body) = app.send(reqwest::Method::GET, "/posts", Some(&token), None).await;
+    assert_eq!(status, 401);
+    assert_eq!(body["code"], "INVALID_TOKEN");
+
+    // Tokens cannot be created already expired, so one is stored expiring an hour ago.
+    let secret = "wfp_expiredexpiredexpiredexpiredexpiredexpi";
+    let hash: String = Sha256::digest(secret.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+    let expired_at = chrono::Utc::now() - chrono::Duration::hours(1);
+    app.ctx.repository
+        .create_api_token(
+            alice.user_id, String::from("old"), hash, vec![String::from("read")], Some(expired_at.into()),
+        )
+        .await
+        .unwrap();
+
+    let expired = TestUser { token: secret.to_string(), ..alice };
+    let (status, body) = app.send(reqwest::Method::GET, "/posts", Some(&expired), None).await;
+    assert_eq!(status, 401);
+    assert_eq!(body["code"], "TOKEN_EXPIRED");
+}
+
+
+#[tokio::test]
+async fn recovery_codes_are_redeemed_once() {
+    let app = TestApp::spawn().await;
+    let user = app.ctx.repository
+        .create_user(
+            String::from("alice@test.example"),
+            String::from("alice"),
+            UsersService::hash_password("password").unwrap(),
+        )
+        .await
+        .unwrap();
+    let alice = TestUser {
+        user_id: user.user_id,
+        username: user.username,
+        token: AuthUser { user_id: user.user_id }.gen_jwt(&app.ctx),
+    };
+    let codes = app.enable_two_factor(&alice).await;
+    assert_eq!(codes.len(), 10);
+
+    let login = json!({ "user": { "email": "alice@test.example", "password": "password" } });
+    let (_, body) = app.send(reqwest::Method::POST, "/users", None, Some(login)).await;
+    let token = body["challenge"]["token"].clone();
+    let verify = |recovery_code: &str| Some(json!({
+        "twoFactor": { "token": token, "recoveryCode": recovery_code },
+    }));
+
+    let recovery_code = codes[0].to_uppercase();
+    let (status, body) = app.send(reqwest::Method::POST, "/auth/2fa/verify", None, verify(&recovery_code)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, _) = app.send(reqwest::Method::POST, "/auth/2fa/verify", None, verify(&recovery_code)).await;
+    assert_eq!(status, 401);
+}
+
+
+#[tokio::test]
+async fn disabling_two_factor_is_throttled() {
+    let app = TestApp::spawn().await;
+    let alice = app.user("alice").await;
+    app.enable_two_factor(&alice).await;
+    let disable = json!({ "twoFactor": { "code": "abcdef" } });
+
+    for _ in 0..5 {
+        let (status, body) = app
+            .send(reqwest::Method::DELETE, "/auth/2fa", Some(&alice), Some(disable.clone()))
+            .await;
+        assert_eq!(status, 400, "{}", body);
+    }
+
+    let (status, body) = app.send(reqwest::Method::DELETE, "/auth/2fa", Some(&alice), Some(disable)).await;
+    assert_eq!(status, 429, "{}", body);
+}
@@ -0,0 +1,121 @@
+// Tests for serving HTTPS, reloading renewed certificates and redirecting plain HTTP to HTTPS.
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use axum::http::Uri;
+use axum::routing::get;
+
+use wforum_backend::config::config::TlsConfig;
+use wforum_backend::tls;
+
+
+// Function to copy a fixture certificate and key to a directory of their own, so they can be replaced.
+// It returns the TLS configuration pointing at the copies.
+fn certificate(test: &str, fixture: &str) -> TlsConfig {
+    let directory: PathBuf = std::env::temp_dir().join(format!("wforum-tls-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let config = TlsConfig {
+        cert_file: directory.join("cert.pem").to_string_lossy().into_owned(),
+        key_file: directory.join("key.pem").to_string_lossy().into_owned(),
+        reload_interval: 1,
+        redirect_port: None,
+    };
+    replace(&config, fixture);
+    config
+}
+
+// Function to overwrite the certificate and key with those of another fixture.
+fn replace(config: &TlsConfig, fixture: &str) {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    std::fs::copy(fixtures.join(format!("tls-{}.pem", fixture)), &config.cert_file).unwrap();
+    std::fs::copy(fixtures.join(format!("tls-{}.key.pem", fixture)), &config.key_file).unwrap();
+}
+
+
+#[test]
+fn redirects_keep_the_host_and_path() {
+    let uri: Uri = "/api/posts?page=2".parse().unwrap();
+
+    assert_eq!(
+        tls::redirect_target("forum.example:8080", &uri, 8443).as_deref(),
+        Some("https://forum.example:8443/api/posts?page=2"),
+    );
+    assert_eq!(
+        tls::redirect_target("forum.example", &uri, 443).as_deref(),
+        Some("https://forum.example/api/posts?page=2"),
+    );
+    assert_eq!(
+        tls::redirect_target("[::1]:80", &Uri::from_static("/"), 443).as_deref(),
+        Some("https://[::1]/"),
+    );
+
+    for invalid in ["", "evil.example/path", "user@forum.example", "forum example"] {
+        assert_eq!(tls::redirect_target(invalid, &uri, 443), None, "{:?}", invalid);
+    }
+}
+
+#[tokio::test]
+async fn plain_http_is_redirected_permanently() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, tls::redirect_router(8443)).await.unwrap() });
+
+    let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+    let response = client.post(format!("{}/api/users?x=1", url)).send().await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 308);
+    assert_eq!(response.headers()["location"], "https://127.0.0.1:8443/api/users?x=1");
+}
+
+#[tokio::test]
+async fn https_is_served_with_http2_offered() {
+    let config = certificate("serve", "old");
+    let rustls = tls::load(&config).await.unwrap();
+    assert_eq!(rustls.get_inner().alpn_protocols, [b"h2".to_vec(), b"http/1.1".to_vec()]);
+
+    let handle = axum_server::Handle::new();
+    let server = axum_server::bind_rustls("127.0.0.1:0".parse().unwrap(), rustls)
+        .handle(handle.clone())
+        .serve(axum::Router::new().route("/", get(|| async { "secure" })).into_make_service());
+    tokio::spawn(async move { server.await.unwrap() });
+    let address = handle.listening().await.unwrap();
+
+    let fixture = std::fs::read(&config.cert_file).unwrap();
+    let client = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(&fixture).unwrap())
+        .build()
+        .unwrap();
+    let response = client.get(format!("https://localhost:{}/", address.port())).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "secure");
+}
+
+#[tokio::test]
+async fn renewed_certificates_are_reloaded() {
+    let config = certificate("reload", "old");
+    let rustls = tls::load(&config).await.unwrap();
+    let original = rustls.get_inner();
+
+    let (shutdown_sender, shutdown_receiver) = tokio::sync::watch::channel(false);
+    let task = tls::watch(config.clone(), rustls.clone(), shutdown_receiver);
+
+    // A broken certificate is not loaded, the old one stays in use.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    std::fs::write(&config.cert_file, "not a certificate").unwrap();
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(Arc::ptr_eq(&original, &rustls.get_inner()));
+
+    replace(&config, "new");
+    let mut reloaded = false;
+    for _ in 0..30 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if !Arc::ptr_eq(&original, &rustls.get_inner()) {
+            reloaded = true;
+            break;
+        }
+    }
+    assert!(reloaded, "the renewed certificate was not loaded");
+
+    shutdown_sender.send(true).unwrap();
+    task.await.unwrap();
+}
@@ -0,0 +1,180 @@
+// Tests for the validation of request bodies.
+use axum::response::IntoResponse;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use wforum_backend::config::config::ValidationConfig;
+use wforum_backend::error::EError;
+use wforum_backend::extractor::validate::{Validate, Validator};
+use wforum_backend::service::post::model::{
+    CommentContent, CommentCreateInput, CreatePostPost, PostContent, UpdatePostPost,
+};
+use wforum_backend::service::profile::model::MuteQuery;
+use wforum_backend::service::user::model::{CreateUserPost, UpdateUserPost, UserBody};
+
+
+// Function to get small limits which are easy to exceed.
+fn limits() -> ValidationConfig {
+    ValidationConfig {
+        username_min: 3,
+        username_max: 12,
+        password_min: 8,
+        password_max: 64,
+        email_max: 40,
+        intro_max: 20,
+        avatar_max: 60,
+        title_max: 10,
+        description_max: 20,
+        content_max: 30,
+        comment_max: 15,
+        page_max: 50,
+        mute_days_max: 30,
+    }
+}
+
+// Function to parse a JSON body and validate it.
+// It returns the `(field, code)` pairs of every error.
+fn errors<T: DeserializeOwned + Validate>(body: Value) -> Vec<(String, String)> {
+    let limits = limits();
+    let body: T = serde_json::from_value(body).unwrap();
+    let mut validator = Validator::new(&limits);
+    body.validate(&mut validator);
+
+    match validator.finish() {
+        Ok(()) => Vec::new(),
+        Err(EError::Validation(fields)) => fields
+            .into_iter()
+            .map(|error| (error.field, error.code.to_string()))
+            .collect(),
+        Err(error) => panic!("unexpected error: {:?}", error),
+    }
+}
+
+// Function to build the expected `(field, code)` pairs.
+fn expected(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(field, code)| (field.to_string(), code.to_string())).collect()
+}
+
+
+#[test]
+fn valid_users_pass() {
+    let body = json!({ "user": { "email": "ann@example.com", "username": "ann_1-a", "password": "hunter22" } });
+    assert!(errors::<UserBody<CreateUserPost>>(body).is_empty());
+
+    let body = json!({ "user": { "avatar": "https://cdn.example.com/a.png", "intro": "" } });
+    assert!(errors::<UserBody<UpdateUserPost>>(body).is_empty());
+}
+
+#[test]
+fn invalid_users_report_every_field() {
+    let body = json!({ "user": { "email": "not an email", "username": "a/b", "password": "short" } });
+    assert_eq!(errors::<UserBody<CreateUserPost>>(body), expected(&[
+        ("user.email", "invalid_email"),
+        ("user.username", "invalid_username"),
+        ("user.password", "too_short"),
+    ]));
+
+    let body = json!({ "user": { "email": "", "username": "white space", "password": "x".repeat(65) } });
+    assert_eq!(errors::<UserBody<CreateUserPost>>(body), expected(&[
+        ("user.email", "required"),
+        ("user.username", "invalid_username"),
+        ("user.password", "too_long"),
+    ]));
+}
+
+#[test]
+fn emails_need_a_local_part_and_a_domain() {
+    for email in ["@example.com", "ann@", "ann@example", "ann@@example.com", "ann@exa mple.com", "ann@.com"] {
+        let body = json!({ "user": { "email": email } });
+        assert_eq!(
+            errors::<UserBody<UpdateUserPost>>(body), expected(&[("user.email", "invalid_email")]), "{}", email,
+        );
+    }
+}
+
+#[test]
+fn avatars_must_be_web_urls() {
+    for avatar in ["javascript:alert(1)", "ftp://example.com/a.png", "not a url", "/relative.png"] {
+        let body = json!({ "user": { "avatar": avatar } });
+        assert_eq!(
+            errors::<UserBody<UpdateUserPost>>(body), expected(&[("user.avatar", "invalid_url")]), "{}", avatar,
+        );
+    }
+}
+
+#[test]
+fn updated_user_fields_are_checked_when_present() {
+    let body = json!({ "user": { "intro": "x".repeat(21), "username": "ab" } });
+    assert_eq!(errors::<UserBody<UpdateUserPost>>(body), expected(&[
+        ("user.intro", "too_long"),
+        ("user.username", "too_short"),
+    ]));
+
+    assert!(errors::<UserBody<UpdateUserPost>>(json!({ "user": {} })).is_empty());
+}
+
+#[test]
+fn posts_need_a_title_and_content() {
+    let body = json!({ "post": { "title": "Hello", "description": "", "content": "World" } });
+    assert!(errors::<PostContent<CreatePostPost>>(body).is_empty());
+
+    let body = json!({ "post": { "title": "   ", "description": "x".repeat(21), "content": "" } });
+    assert_eq!(errors::<PostContent<CreatePostPost>>(body), expected(&[
+        ("post.title", "required"),
+        ("post.description", "too_long"),
+        ("post.content", "required"),
+    ]));
+}
+
+#[test]
+fn updated_posts_cannot_clear_their_title() {
+    let body = json!({ "post": { "title": "", "content": "x".repeat(31) } });
+    assert_eq!(errors::<PostContent<UpdatePostPost>>(body), expected(&[
+        ("post.title", "required"),
+        ("post.content", "too_long"),
+    ]));
+}
+
+#[test]
+fn comment_length_is_limited() {
+    let body = json!({ "comment": { "content": "Nice post" } });
+    assert!(errors::<CommentContent<CommentCreateInput>>(body).is_empty());
+
+    let body = json!({ "comment": { "content": "x".repeat(16) } });
+    assert_eq!(errors::<CommentContent<CommentCreateInput>>(body), expected(&[("comment.content", "too_long")]));
+
+    // Lengths are counted in characters, not bytes.
+    let body = json!({ "comment": { "content": "é".repeat(15) } });
+    assert!(errors::<CommentContent<CommentCreateInput>>(body).is_empty());
+}
+
+#[test]
+fn mutes_last_a_bounded_number_of_days() {
+    assert!(errors::<MuteQuery>(json!({})).is_empty());
+    assert!(errors::<MuteQuery>(json!({ "expiresInDays": 30 })).is_empty());
+
+    for days in [0, -1, 31] {
+        assert_eq!(
+            errors::<MuteQuery>(json!({ "expiresInDays": days })), expected(&[("expiresInDays", "out_of_range")]),
+        );
+    }
+}
+
+#[tokio::test]
+async fn validation_errors_list_fields_in_the_envelope() {
+    let limits = limits();
+    let body: CommentContent<CommentCreateInput> =
+        serde_json::from_value(json!({ "comment": { "content": "" } })).unwrap();
+    let mut validator = Validator::new(&limits);
+    body.validate(&mut validator);
+
+    let response = validator.finish().unwrap_err().into_response();
+    assert_eq!(response.status(), 400);
+
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+    assert_eq!(body["details"]["fields"], json!([
+        { "field": "comment.content", "code": "required", "message": "is required" },
+    ]));
+}